@@ -0,0 +1,209 @@
+//! Pure CCR protocol functions: confidential inputs hash, signing payload
+//! and transaction hash. No I/O, no global state and no allocation beyond
+//! `alloc`, so CCR construction can be proven inside a zkVM guest.
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Address, TxKind, B256, U256};
+use alloy_rlp::{BufMut, Encodable, Header};
+
+
+/// Type prefix of the signed record (and the signing payload).
+pub const RECORD_TYPE: u8 = 0x42;
+/// Type prefix of the request envelope.
+pub const REQUEST_TYPE: u8 = 0x43;
+
+/// Pricing of a record. Dynamic fee records have both fees in place of the
+/// gas price, in EIP-1559 order; kettles without dynamic fee support only
+/// accept `Legacy` records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fees {
+    Legacy { gas_price: u128 },
+    Dynamic { max_priority_fee_per_gas: u128, max_fee_per_gas: u128 },
+}
+
+impl Fees {
+
+    /// Number of RLP fields taken by the fees.
+    pub const fn field_count(&self) -> usize {
+        match self {
+            Fees::Legacy { .. } => 1,
+            Fees::Dynamic { .. } => 2,
+        }
+    }
+
+    fn push_fields<'a>(&'a self, fields: &mut Vec<&'a dyn Encodable>) {
+        match self {
+            Fees::Legacy { gas_price } => fields.push(gas_price),
+            Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas } => {
+                fields.push(max_priority_fee_per_gas);
+                fields.push(max_fee_per_gas);
+            }
+        }
+    }
+
+}
+
+/// Record fields covered by the signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordFields<'a> {
+    pub nonce: u64,
+    pub fees: Fees,
+    pub gas: u128,
+    /// `Create` for contract deployments, encoded as an empty string.
+    pub to: TxKind,
+    pub value: U256,
+    pub input: &'a [u8],
+    /// RLP encoded EIP-2930 access list, following the input in records
+    /// that carry one; records without keep the original layout.
+    pub access_list: Option<&'a [u8]>,
+    pub kettle_address: Address,
+    pub confidential_inputs_hash: B256,
+}
+
+/// Keccak of the confidential inputs, committed in the record.
+pub fn confidential_inputs_hash(confidential_inputs: &[u8]) -> B256 {
+    keccak256(confidential_inputs)
+}
+
+/// 0x42 prefixed RLP of the hash params, the payload signed (before any
+/// signing domain wrapping).
+pub fn signing_payload(record: &RecordFields<'_>) -> Vec<u8> {
+    let access_list = record.access_list.map(Encoded);
+    encode_list(RECORD_TYPE, &signing_fields(record, &access_list))
+}
+
+/// Length of `signing_payload`, computed without encoding it, eg. to
+/// preallocate the buffer it is written to.
+pub fn signing_payload_len(record: &RecordFields<'_>) -> usize {
+    let access_list = record.access_list.map(Encoded);
+    list_len(&signing_fields(record, &access_list))
+}
+
+/// Hash signed by the sender (suave-geth signs the bare payload).
+pub fn signing_hash(record: &RecordFields<'_>) -> B256 {
+    keccak256(signing_payload(record))
+}
+
+/// Hash of the signed record as reported by the node. Confidential inputs
+/// are not part of it.
+pub fn tx_hash(record: &RecordFields<'_>, chain_id: u64, v: u8, r: U256, s: U256) -> B256 {
+    let mut fields: Vec<&dyn Encodable> = Vec::with_capacity(14);
+    fields.push(&record.nonce);
+    record.fees.push_fields(&mut fields);
+    fields.extend([&record.gas as &dyn Encodable, &record.to, &record.value, &record.input]);
+    let access_list = record.access_list.map(Encoded);
+    fields.extend(access_list.as_ref().map(|list| list as &dyn Encodable));
+    fields.extend([
+        &record.kettle_address as &dyn Encodable,
+        &record.confidential_inputs_hash,
+        &chain_id,
+        &v,
+        &r,
+        &s,
+    ]);
+    keccak256(encode_list(RECORD_TYPE, &fields))
+}
+
+fn signing_fields<'a>(record: &'a RecordFields<'_>, access_list: &'a Option<Encoded<'_>>) -> Vec<&'a dyn Encodable> {
+    let mut fields: Vec<&dyn Encodable> = Vec::with_capacity(9);
+    fields.extend([&record.kettle_address as &dyn Encodable, &record.confidential_inputs_hash, &record.nonce]);
+    record.fees.push_fields(&mut fields);
+    fields.extend([&record.gas as &dyn Encodable, &record.to, &record.value, &record.input]);
+    fields.extend(access_list.as_ref().map(|list| list as &dyn Encodable));
+    fields
+}
+
+/// Item that is already RLP encoded, written as is.
+struct Encoded<'a>(&'a [u8]);
+
+impl Encodable for Encoded<'_> {
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_slice(self.0);
+    }
+
+    fn length(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Length of the prefixed list `encode_list` writes.
+fn list_len(fields: &[&dyn Encodable]) -> usize {
+    let payload_length: usize = fields.iter().map(|field| field.length()).sum();
+    1 + Header { list: true, payload_length }.length() + payload_length
+}
+
+fn encode_list(prefix: u8, fields: &[&dyn Encodable]) -> Vec<u8> {
+    let payload_length = fields.iter().map(|field| field.length()).sum();
+    let header = Header { list: true, payload_length };
+    let mut out = Vec::with_capacity(list_len(fields));
+    out.push(prefix);
+    header.encode(&mut out);
+    for field in fields {
+        field.encode(&mut out);
+    }
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{address, b256, hex};
+    use super::*;
+
+    #[test]
+    fn test_signing_hash() {
+        let input = hex!("236eb5a70000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000001000000000000000000000000772092ff73c43883a547bea1e1e007ec0d33478e0000000000000000000000000000000000000000000000000000000000000000");
+        let cinputs = hex!("000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000001ea7b22747873223a5b7b2274797065223a22307830222c226e6f6e6365223a22307830222c22746f223a22307838626265386333346637396433353534666631626236643932313733613237666661356237313233222c22676173223a22307835323038222c226761735072696365223a22307864222c226d61785072696f72697479466565506572476173223a6e756c6c2c226d6178466565506572476173223a6e756c6c2c2276616c7565223a223078336538222c22696e707574223a223078222c2276223a2230786366323837222c2272223a22307862396433643236643135633630376237653537353235333761336163326432363330643161653036386163353138616539393862613439313236323134383135222c2273223a22307835636534666439613565376533373138656630613731386533633462333135306538373036376533373361333439323538643962333330353930396332303565222c2268617368223a22307863633934626637386463366631373963663331376638643839353438393364393730303366333266353332623530623865333861626631333939353364643664227d5d2c2270657263656e74223a31302c224d617463684964223a5b302c302c302c302c302c302c302c302c302c302c302c302c302c302c302c305d7d00000000000000000000000000000000000000000000");
+        let record = RecordFields {
+            nonce: 0x18,
+            fees: Fees::Legacy { gas_price: 0x3b9aca00 },
+            gas: 0x0f4240,
+            to: TxKind::Call(address!("772092ff73c43883a547bea1e1e007ec0d33478e")),
+            value: U256::ZERO,
+            input: &input,
+            access_list: None,
+            kettle_address: address!("7d83e42b214b75bf1f3e57adc3415da573d97bff"),
+            confidential_inputs_hash: confidential_inputs_hash(&cinputs),
+        };
+        assert_eq!(signing_payload(&record)[0], RECORD_TYPE);
+        assert_eq!(signing_payload_len(&record), signing_payload(&record).len());
+        assert_eq!(signing_hash(&record), b256!("72ffab40c5116931200ca87052360787559871297b3615a8c2ff28be738ac59f"));
+
+        let dynamic = RecordFields {
+            fees: Fees::Dynamic { max_priority_fee_per_gas: 0x3b9aca00, max_fee_per_gas: 0x3b9aca00 },
+            ..record
+        };
+        assert_eq!(signing_payload(&dynamic).len(), signing_payload(&record).len() + 5);
+        assert_ne!(signing_hash(&dynamic), signing_hash(&record));
+
+        // Empty string in place of the 20 byte address
+        let create = RecordFields { to: TxKind::Create, ..record };
+        assert_eq!(signing_payload(&create).len(), signing_payload(&record).len() - 20);
+
+        // Empty access list, an RLP list header right after the input
+        let with_access_list = RecordFields { access_list: Some(&[0xc0]), ..record };
+        let payload = signing_payload(&with_access_list);
+        assert_eq!(payload.len(), signing_payload(&record).len() + 1);
+        assert_ne!(signing_hash(&with_access_list), signing_hash(&record));
+        for variant in [&dynamic, &create, &with_access_list] {
+            assert_eq!(signing_payload_len(variant), signing_payload(variant).len());
+        }
+        // Long header once the payload passes 55 bytes and 255 bytes
+        for len in [0, 1, 55, 56, 255, 256, 70_000] {
+            let input = alloc::vec![0xffu8; len];
+            let record = RecordFields { input: &input, ..record };
+            assert_eq!(signing_payload_len(&record), signing_payload(&record).len(), "input of {len} bytes");
+        }
+        assert_eq!(tx_hash(&with_access_list, 1, 0, U256::ZERO, U256::ZERO), keccak256(
+            encode_list(RECORD_TYPE, &[
+                &record.nonce as &dyn Encodable, &0x3b9aca00u128, &record.gas, &record.to, &record.value,
+                &record.input, &Encoded(&[0xc0]), &record.kettle_address, &record.confidential_inputs_hash,
+                &1u64, &0u8, &U256::ZERO, &U256::ZERO,
+            ])
+        ));
+    }
+
+}
@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use alloy::{primitives::Bytes, signers::wallet::LocalWallet};
+use suave_alloy_network::SuaveSigner;
+use suave_alloy_types::{fixtures, ConfidentialComputeRequest};
+
+
+fn request_with_inputs(inputs_len: usize) -> ConfidentialComputeRequest {
+    let mut record = fixtures::unsigned_record();
+    record.input = Bytes::from(vec![0xcd; inputs_len]);
+    ConfidentialComputeRequest::new(record, Some(Bytes::from(vec![0xab; inputs_len])))
+}
+
+fn bench_sign(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+    let signer = SuaveSigner::new(wallet);
+    let ccr = request_with_inputs(1 << 20);
+
+    let mut group = c.benchmark_group("sign_1mb");
+    group.bench_function("sign_transaction", |b| b.iter_batched(
+        || ccr.clone(),
+        |mut tx| rt.block_on(signer.sign_transaction(&mut tx)).unwrap(),
+        BatchSize::LargeInput,
+    ));
+    group.bench_function("sign_in_place", |b| b.iter_batched(
+        || ccr.clone(),
+        |mut tx| rt.block_on(signer.sign_in_place(&mut tx)).unwrap(),
+        BatchSize::LargeInput,
+    ));
+    group.bench_function("sign_owned", |b| b.iter_batched(
+        || ccr.clone(),
+        |tx| rt.block_on(signer.sign_owned(tx)).unwrap(),
+        BatchSize::LargeInput,
+    ));
+    group.finish();
+}
+
+criterion_group!(benches, bench_sign);
+criterion_main!(benches);
@@ -0,0 +1,215 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+use alloy::{
+    primitives::U256,
+    rpc::types::eth::TransactionReceipt,
+};
+use suave_alloy_types::{fee_to_usd, format_fee, ConfidentialComputeRequest, UsdOracle};
+
+
+/// Maximum spend (fees + value, in wei) allowed within a sliding window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetCap {
+    pub max_spend: U256,
+    pub window: Duration,
+}
+
+impl BudgetCap {
+    pub fn new(max_spend: U256, window: Duration) -> Self {
+        Self { max_spend, window }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    /// Already spent within the window.
+    pub spent: U256,
+    /// Worst-case cost of the rejected submission.
+    pub requested: U256,
+    pub cap: BudgetCap,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "Budget exceeded: spent {} + requested {} > cap {} per {:?}",
+            format_fee(self.spent), format_fee(self.requested), format_fee(self.cap.max_spend), self.cap.window
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Tracks realized fees and value sent per tag (strategy) and blocks new
+/// submissions once a tag's cap would be exceeded within its window.
+/// Tags without a cap (and no default cap) are tracked but never blocked;
+/// only their total is kept. Spends older than a tag's window are dropped
+/// whenever the tag is recorded or checked.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    caps: HashMap<String, BudgetCap>,
+    default_cap: Option<BudgetCap>,
+    spends: HashMap<String, Spends>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Spends {
+    /// Spends within the window, capped tags only.
+    window: VecDeque<(Instant, U256)>,
+    /// All-time total.
+    total: U256,
+}
+
+impl Spends {
+
+    fn prune(&mut self, window: Duration, now: Instant) {
+        while self.window.front().is_some_and(|(at, _)| now.saturating_duration_since(*at) >= window) {
+            self.window.pop_front();
+        }
+    }
+
+}
+
+impl Budget {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cap(mut self, tag: impl Into<String>, cap: BudgetCap) -> Self {
+        self.caps.insert(tag.into(), cap);
+        self
+    }
+
+    /// Cap applied to tags without their own cap.
+    pub fn with_default_cap(mut self, cap: BudgetCap) -> Self {
+        self.default_cap = Some(cap);
+        self
+    }
+
+    pub fn cap(&self, tag: &str) -> Option<BudgetCap> {
+        self.caps.get(tag).copied().or(self.default_cap)
+    }
+
+    pub fn record(&mut self, tag: &str, amount: U256) {
+        self.record_at(tag, amount, Instant::now());
+    }
+
+    pub fn record_at(&mut self, tag: &str, amount: U256, at: Instant) {
+        let cap = self.cap(tag);
+        let spends = self.spends.entry(tag.to_string()).or_default();
+        spends.total = spends.total.saturating_add(amount);
+        if let Some(cap) = cap {
+            spends.window.push_back((at, amount));
+            spends.prune(cap.window, at);
+        }
+    }
+
+    /// Record the realized fee of a receipt plus the value sent by `ccr`.
+    pub fn record_receipt(&mut self, tag: &str, ccr: &ConfidentialComputeRequest, receipt: &TransactionReceipt) {
+        let fee = U256::from(receipt.gas_used.unwrap_or_default())
+            * U256::from(receipt.effective_gas_price);
+        self.record(tag, fee + ccr.confidential_compute_record.value);
+    }
+
+    /// Spent within the tag's window (all time if uncapped).
+    pub fn spent(&mut self, tag: &str) -> U256 {
+        self.spent_at(tag, Instant::now())
+    }
+
+    pub fn spent_at(&mut self, tag: &str, now: Instant) -> U256 {
+        let cap = self.cap(tag);
+        let Some(spends) = self.spends.get_mut(tag) else {
+            return U256::ZERO;
+        };
+        let Some(cap) = cap else {
+            return spends.total;
+        };
+        spends.prune(cap.window, now);
+        spends.window.iter().map(|(_, amount)| *amount).fold(U256::ZERO, |acc, a| acc.saturating_add(a))
+    }
+
+    /// `spent` in USD at the oracle's price, for reporting.
+    pub fn spent_usd(&mut self, tag: &str, oracle: &dyn UsdOracle) -> Option<f64> {
+        fee_to_usd(self.spent(tag), oracle)
+    }
+
+    pub fn check(&mut self, tag: &str, requested: U256) -> Result<(), BudgetExceeded> {
+        self.check_at(tag, requested, Instant::now())
+    }
+
+    pub fn check_at(&mut self, tag: &str, requested: U256, now: Instant) -> Result<(), BudgetExceeded> {
+        let Some(cap) = self.cap(tag) else {
+            return Ok(());
+        };
+        let spent = self.spent_at(tag, now);
+        if spent.saturating_add(requested) > cap.max_spend {
+            return Err(BudgetExceeded { spent, requested, cap });
+        }
+        Ok(())
+    }
+
+    /// Check the worst-case cost of `ccr` (gas * gas price + value).
+    pub fn check_ccr(&mut self, tag: &str, ccr: &ConfidentialComputeRequest) -> Result<(), BudgetExceeded> {
+        self.check(tag, max_cost(ccr))
+    }
+
+}
+
+fn max_cost(ccr: &ConfidentialComputeRequest) -> U256 {
+    let record = &ccr.confidential_compute_record;
+    let fee = U256::from(record.gas.unwrap_or_default()) * U256::from(record.max_gas_price().unwrap_or_default());
+    fee.saturating_add(record.value)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_window() {
+        let cap = BudgetCap::new(U256::from(100), Duration::from_secs(60));
+        let mut budget = Budget::new().with_cap("arb", cap);
+        let start = Instant::now();
+
+        budget.record_at("arb", U256::from(70), start);
+        assert_eq!(budget.check_at("arb", U256::from(30), start), Ok(()));
+        let err = budget.check_at("arb", U256::from(31), start).unwrap_err();
+        assert_eq!(err, BudgetExceeded { spent: U256::from(70), requested: U256::from(31), cap });
+        assert!(err.to_string().starts_with("Budget exceeded: spent 0.00000000000000007 SETH"));
+
+        // Spend leaves the window
+        let later = start + Duration::from_secs(60);
+        assert_eq!(budget.spent_at("arb", later), U256::ZERO);
+        assert!(budget.check_at("arb", U256::from(100), later).is_ok());
+
+        // Uncapped tags are tracked but never blocked, without keeping history
+        budget.record_at("other", U256::from(5), start);
+        budget.record_at("other", U256::MAX, start);
+        assert!(budget.check_at("other", U256::MAX, start).is_ok());
+        assert_eq!(budget.spent_at("other", later), U256::MAX);
+        assert!(budget.spends["other"].window.is_empty());
+
+        // Recording prunes the window, even if the tag is never checked
+        for secs in 0..10 {
+            budget.record_at("arb", U256::from(1), later + Duration::from_secs(secs * 30));
+        }
+        assert_eq!(budget.spends["arb"].window.len(), 2);
+    }
+
+    #[test]
+    fn test_check_ccr() {
+        let mut budget = Budget::new()
+            .with_default_cap(BudgetCap::new(U256::from(1_000_000), Duration::from_secs(60)));
+        let mut ccr = ConfidentialComputeRequest::default();
+        ccr.confidential_compute_record.gas = Some(21_000);
+        ccr.confidential_compute_record.gas_price = Some(40);
+        assert!(budget.check_ccr("any", &ccr).is_ok());
+        ccr.confidential_compute_record.gas_price = Some(50);
+        assert!(budget.check_ccr("any", &ccr).is_err());
+    }
+
+}
@@ -0,0 +1,287 @@
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::{json, Value};
+use tower::{Service, ServiceExt};
+use alloy::{
+    consensus::SignableTransaction,
+    network::TxSigner,
+    primitives::{Address, Signature},
+    providers::ProviderBuilder,
+    rpc::{
+        client::RpcClient,
+        json_rpc::{RequestPacket, Response, ResponsePacket, SerializedRequest},
+    },
+    signers::Result as SignerResult,
+    transports::{http::Http, Transport, TransportError, TransportErrorKind, TransportFut},
+};
+use reqwest::Client as ReqwestClient;
+use super::{SuaveNetwork, SuaveProvider};
+
+
+/// Probabilities (0 to 1) of the injected failures. Seeded configs inject
+/// the same failures on every run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaosConfig {
+    /// Requests stalling for `timeout` and then failing.
+    pub timeout_rate: f64,
+    pub timeout: Duration,
+    /// Requests answered with a malformed result (eg. non-hex quantities),
+    /// failing when the caller deserializes it.
+    pub malformed_rate: f64,
+    /// Signatures delayed by `signing_delay`, like a remote signer or HSM
+    /// under load.
+    pub signing_delay_rate: f64,
+    pub signing_delay: Duration,
+    pub seed: Option<u64>,
+}
+
+impl Default for ChaosConfig {
+    /// No failures.
+    fn default() -> Self {
+        Self {
+            timeout_rate: 0.0,
+            timeout: Duration::from_secs(1),
+            malformed_rate: 0.0,
+            signing_delay_rate: 0.0,
+            signing_delay: Duration::from_secs(1),
+            seed: None,
+        }
+    }
+}
+
+impl ChaosConfig {
+
+    pub fn with_timeouts(mut self, rate: f64, timeout: Duration) -> Self {
+        self.timeout_rate = rate;
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_malformed_responses(mut self, rate: f64) -> Self {
+        self.malformed_rate = rate;
+        self
+    }
+
+    pub fn with_signing_delays(mut self, rate: f64, delay: Duration) -> Self {
+        self.signing_delay_rate = rate;
+        self.signing_delay = delay;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    fn rng(&self) -> Arc<Mutex<StdRng>> {
+        let rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Arc::new(Mutex::new(rng))
+    }
+
+}
+
+/// Counts of the failures injected so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    pub requests: u64,
+    pub timeouts: u64,
+    pub malformed: u64,
+    pub signatures: u64,
+    pub signing_delays: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    Timeout,
+    Malformed,
+}
+
+/// Transport injecting timeouts and malformed responses in front of
+/// `inner`, to test retry and failover logic against a flaky kettle.
+#[derive(Clone)]
+pub struct ChaosTransport<T> {
+    inner: T,
+    config: ChaosConfig,
+    rng: Arc<Mutex<StdRng>>,
+    stats: Arc<Mutex<ChaosStats>>,
+}
+
+impl<T> ChaosTransport<T>
+    where T: Transport + Clone
+{
+
+    pub fn new(inner: T, config: ChaosConfig) -> Self {
+        Self { inner, rng: config.rng(), config, stats: Default::default() }
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        *self.stats.lock().unwrap()
+    }
+
+    fn roll(&self) -> Option<Fault> {
+        let roll: f64 = self.rng.lock().unwrap().gen();
+        let mut stats = self.stats.lock().unwrap();
+        stats.requests += 1;
+        if roll < self.config.timeout_rate {
+            stats.timeouts += 1;
+            Some(Fault::Timeout)
+        } else if roll < self.config.timeout_rate + self.config.malformed_rate {
+            stats.malformed += 1;
+            Some(Fault::Malformed)
+        } else {
+            None
+        }
+    }
+
+    async fn handle(self, packet: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        match self.roll() {
+            Some(Fault::Timeout) => {
+                tokio::time::sleep(self.config.timeout).await;
+                Err(TransportErrorKind::custom_str("Request timed out (injected)"))
+            }
+            Some(Fault::Malformed) => malformed(&packet),
+            None => self.inner.clone().ready().await?.call(packet).await,
+        }
+    }
+
+}
+
+/// Responses whose result is not valid for any typed method.
+fn malformed(packet: &RequestPacket) -> Result<ResponsePacket, TransportError> {
+    let response = |req: &SerializedRequest| -> Result<Response, TransportError> {
+        let id = serde_json::to_value(req.id()).map_err(TransportErrorKind::custom)?;
+        let json: Value = json!({ "jsonrpc": "2.0", "id": id, "result": "0xnot-hex" });
+        serde_json::from_value(json).map_err(TransportErrorKind::custom)
+    };
+    Ok(match packet {
+        RequestPacket::Single(req) => ResponsePacket::Single(response(req)?),
+        RequestPacket::Batch(reqs) => ResponsePacket::Batch(reqs.iter().map(response).collect::<Result<_, _>>()?),
+    })
+}
+
+impl<T> Service<RequestPacket> for ChaosTransport<T>
+    where T: Transport + Clone
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, packet: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().handle(packet))
+    }
+}
+
+impl SuaveProvider<ChaosTransport<Http<ReqwestClient>>> {
+
+    /// HTTP provider with failures injected as configured.
+    pub fn chaos(url: url::Url, config: ChaosConfig) -> Self {
+        let transport = ChaosTransport::new(Http::new(url), config);
+        let root_provider = ProviderBuilder::<_, _, SuaveNetwork>::default()
+            .on_client(RpcClient::new(transport, false));
+        Self::new(root_provider)
+    }
+
+}
+
+/// Signer delaying signatures of `inner`, eg. to register it with a
+/// `SuaveSigner` and exercise deadlines on the signing path.
+pub struct ChaosSigner<S> {
+    inner: S,
+    config: ChaosConfig,
+    rng: Arc<Mutex<StdRng>>,
+    stats: Arc<Mutex<ChaosStats>>,
+}
+
+impl<S> ChaosSigner<S> {
+
+    pub fn new(inner: S, config: ChaosConfig) -> Self {
+        Self { inner, rng: config.rng(), config, stats: Default::default() }
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        *self.stats.lock().unwrap()
+    }
+
+    fn roll_delay(&self) -> bool {
+        let roll: f64 = self.rng.lock().unwrap().gen();
+        let mut stats = self.stats.lock().unwrap();
+        stats.signatures += 1;
+        let delayed = roll < self.config.signing_delay_rate;
+        if delayed {
+            stats.signing_delays += 1;
+        }
+        delayed
+    }
+
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S> TxSigner<Signature> for ChaosSigner<S>
+    where S: TxSigner<Signature> + Send + Sync
+{
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+
+    async fn sign_transaction(&self, tx: &mut dyn SignableTransaction<Signature>) -> SignerResult<Signature> {
+        if self.roll_delay() {
+            tokio::time::sleep(self.config.signing_delay).await;
+        }
+        self.inner.sign_transaction(tx).await
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+    use alloy::{primitives::U64, signers::wallet::LocalWallet};
+    use suave_alloy_types::fixtures;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_injected_transport_faults() {
+        let url = "http://localhost:0".parse().unwrap();
+        let malformed = SuaveProvider::chaos(url, ChaosConfig::default().with_malformed_responses(1.0));
+        let res: Result<U64, _> = malformed.client().request("eth_blockNumber", ()).await;
+        assert!(res.is_err());
+
+        let config = ChaosConfig::default().with_timeouts(0.5, Duration::from_millis(1)).with_seed(7);
+        let transport = ChaosTransport::new(Http::<ReqwestClient>::new("http://localhost:0".parse().unwrap()), config);
+        let faults = (0..100).map(|_| transport.roll()).collect::<Vec<_>>();
+        let stats = transport.stats();
+        assert_eq!((stats.requests, stats.malformed), (100, 0));
+        assert!(stats.timeouts > 20 && stats.timeouts < 80);
+        // Same seed, same faults
+        let replayed = ChaosTransport::new(transport.inner.clone(), config);
+        assert_eq!((0..100).map(|_| replayed.roll()).collect::<Vec<_>>(), faults);
+    }
+
+    #[tokio::test]
+    async fn test_signing_delay() {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let signer = ChaosSigner::new(wallet.clone(), ChaosConfig::default().with_signing_delays(1.0, Duration::from_millis(50)));
+        assert_eq!(signer.address(), wallet.address());
+
+        let mut ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        let started = Instant::now();
+        let sig = signer.sign_transaction(&mut ccr).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(sig, wallet.sign_transaction(&mut ccr).await.unwrap());
+        assert_eq!(signer.stats().signing_delays, 1);
+    }
+
+}
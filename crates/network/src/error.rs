@@ -0,0 +1,144 @@
+use std::str::FromStr;
+use alloy::{
+    primitives::Bytes,
+    transports::TransportError,
+};
+
+
+/// Errors reported by SUAVE nodes/kettles, mapped from JSON-RPC error
+/// codes and messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KettleError {
+    NonceTooLow(String),
+    NonceTooHigh(String),
+    ReplacementUnderpriced(String),
+    InsufficientFunds(String),
+    ConfidentialInputsTooLarge(String),
+    UnknownKettle(String),
+    ExecutionReverted { message: String, data: Option<Bytes> },
+    RateLimited(String),
+    /// Any other JSON-RPC error.
+    Rpc { code: i64, message: String },
+    /// Failure below the JSON-RPC layer (connection, timeout, decoding).
+    Transport(String),
+}
+
+/// JSON-RPC code geth uses for reverted execution.
+const EXECUTION_REVERTED_CODE: i64 = 3;
+/// JSON-RPC code for exceeded request limits.
+const LIMIT_EXCEEDED_CODE: i64 = -32005;
+
+impl KettleError {
+
+    pub fn from_rpc(code: i64, message: &str, data: Option<Bytes>) -> Self {
+        let msg = message.to_string();
+        let lower = message.to_lowercase();
+        if code == EXECUTION_REVERTED_CODE || lower.contains("execution reverted") {
+            KettleError::ExecutionReverted { message: msg, data }
+        } else if lower.contains("nonce too low") {
+            KettleError::NonceTooLow(msg)
+        } else if lower.contains("nonce too high") {
+            KettleError::NonceTooHigh(msg)
+        } else if lower.contains("replacement transaction underpriced") {
+            KettleError::ReplacementUnderpriced(msg)
+        } else if lower.contains("insufficient funds") {
+            KettleError::InsufficientFunds(msg)
+        } else if lower.contains("confidential inputs too large") || lower.contains("oversized data") {
+            KettleError::ConfidentialInputsTooLarge(msg)
+        } else if lower.contains("unknown kettle") || lower.contains("kettle not found")
+            || lower.contains("not the execution node") {
+            KettleError::UnknownKettle(msg)
+        } else if code == LIMIT_EXCEEDED_CODE || lower.contains("rate limit") {
+            KettleError::RateLimited(msg)
+        } else {
+            KettleError::Rpc { code, message: msg }
+        }
+    }
+
+    /// Whether resending the same signed request may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            KettleError::Transport(_) | KettleError::RateLimited(_) | KettleError::NonceTooHigh(_)
+        )
+    }
+
+    /// Whether the request may succeed after re-filling and re-signing
+    /// (new nonce or higher gas price).
+    pub fn needs_resign(&self) -> bool {
+        matches!(
+            self,
+            KettleError::NonceTooLow(_) | KettleError::ReplacementUnderpriced(_)
+        )
+    }
+
+}
+
+impl From<TransportError> for KettleError {
+    fn from(err: TransportError) -> Self {
+        match err.as_error_resp() {
+            Some(payload) => {
+                let data = payload.data.as_ref()
+                    .and_then(|data| Bytes::from_str(data.get().trim_matches('"')).ok());
+                KettleError::from_rpc(payload.code, &payload.message, data)
+            }
+            None => KettleError::Transport(err.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for KettleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KettleError::NonceTooLow(msg) => write!(f, "Nonce too low: {msg}"),
+            KettleError::NonceTooHigh(msg) => write!(f, "Nonce too high: {msg}"),
+            KettleError::ReplacementUnderpriced(msg) => write!(f, "Replacement underpriced: {msg}"),
+            KettleError::InsufficientFunds(msg) => write!(f, "Insufficient funds: {msg}"),
+            KettleError::ConfidentialInputsTooLarge(msg) => write!(f, "Confidential inputs too large: {msg}"),
+            KettleError::UnknownKettle(msg) => write!(f, "Unknown kettle: {msg}"),
+            KettleError::ExecutionReverted { message, data } => match data {
+                Some(data) => write!(f, "Execution reverted: {message} ({data})"),
+                None => write!(f, "Execution reverted: {message}"),
+            },
+            KettleError::RateLimited(msg) => write!(f, "Rate limited: {msg}"),
+            KettleError::Rpc { code, message } => write!(f, "RPC error {code}: {message}"),
+            KettleError::Transport(msg) => write!(f, "Transport error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for KettleError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_error_mapping() {
+        let err = KettleError::from_rpc(-32000, "nonce too low: next nonce 70, tx nonce 69", None);
+        assert!(matches!(err, KettleError::NonceTooLow(_)));
+        assert!(!err.is_retryable());
+        assert!(err.needs_resign());
+
+        let data = Bytes::from_static(&[0x08, 0xc3, 0x79, 0xa0]);
+        let err = KettleError::from_rpc(3, "execution reverted", Some(data.clone()));
+        assert_eq!(err, KettleError::ExecutionReverted { message: "execution reverted".into(), data: Some(data) });
+        assert!(!err.is_retryable());
+
+        assert!(KettleError::from_rpc(-32005, "limit exceeded", None).is_retryable());
+        assert!(matches!(
+            KettleError::from_rpc(-32000, "unknown kettle 0x03493869959c866713c33669ca118e774a30a0e5", None),
+            KettleError::UnknownKettle(_)
+        ));
+        assert!(matches!(
+            KettleError::from_rpc(-32000, "confidential inputs too large", None),
+            KettleError::ConfidentialInputsTooLarge(_)
+        ));
+        assert_eq!(
+            KettleError::from_rpc(-32601, "the method foo does not exist", None),
+            KettleError::Rpc { code: -32601, message: "the method foo does not exist".into() }
+        );
+    }
+
+}
@@ -0,0 +1,210 @@
+use std::time::{Duration, Instant};
+use alloy::{
+    primitives::TxHash,
+    providers::Provider,
+    rpc::types::eth::TransactionReceipt,
+    transports::{Transport, TransportErrorKind, TransportResult},
+};
+use suave_alloy_types::{ConfidentialComputeRequest, Fees};
+use super::{SuaveProvider, SuaveSigner};
+
+
+/// Minimum gas price bump (in percent) nodes accept for a replacement.
+pub const MIN_REPLACEMENT_BUMP_PERCENT: u128 = 10;
+
+/// Resubmits an unconfirmed CCR with the same nonce at geometrically
+/// increasing gas prices, up to `max_gas_price`, until one of the
+/// submissions is included or `timeout` passes. Dynamic fee records have
+/// both fees bumped, with `max_gas_price` capping the max fee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEscalator {
+    /// Gas price increase per step in percent; at least
+    /// `MIN_REPLACEMENT_BUMP_PERCENT`.
+    pub bump_percent: u128,
+    pub max_gas_price: u128,
+    /// Time to wait for inclusion before the next step.
+    pub step_interval: Duration,
+    pub poll_interval: Duration,
+    pub timeout: Duration,
+}
+
+/// One submission of an escalation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscalationStep {
+    /// Zero for the first submission.
+    pub step: usize,
+    pub fees: Fees,
+    pub tx_hash: TxHash,
+}
+
+#[derive(Debug, Clone)]
+pub struct EscalationOutcome {
+    pub receipt: TransactionReceipt,
+    /// Submission that got included.
+    pub included: EscalationStep,
+    pub steps: Vec<EscalationStep>,
+}
+
+impl GasEscalator {
+
+    pub fn new(max_gas_price: u128) -> Self {
+        Self {
+            bump_percent: 20,
+            max_gas_price,
+            step_interval: Duration::from_secs(12),
+            poll_interval: Duration::from_millis(500),
+            timeout: Duration::from_secs(120),
+        }
+    }
+
+    pub fn with_bump_percent(mut self, bump_percent: u128) -> Self {
+        self.bump_percent = bump_percent.max(MIN_REPLACEMENT_BUMP_PERCENT);
+        self
+    }
+
+    pub fn with_step_interval(mut self, step_interval: Duration) -> Self {
+        self.step_interval = step_interval;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Gas price of the step after `gas_price`, `None` once the cap is reached.
+    pub fn next_gas_price(&self, gas_price: u128) -> Option<u128> {
+        if gas_price >= self.max_gas_price {
+            return None;
+        }
+        let bumped = gas_price.saturating_mul(100 + self.bump_percent) / 100;
+        // Round up so tiny prices still increase
+        Some(bumped.max(gas_price + 1).min(self.max_gas_price))
+    }
+
+    /// Fees of the step after `fees`, `None` once the cap is reached. The
+    /// priority fee is bumped like the max fee (nodes require both for a
+    /// replacement) but never exceeds it.
+    pub fn next_fees(&self, fees: Fees) -> Option<Fees> {
+        match fees {
+            Fees::Legacy { gas_price } => self.next_gas_price(gas_price).map(|gas_price| Fees::Legacy { gas_price }),
+            Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas } => {
+                let max_fee_per_gas = self.next_gas_price(max_fee_per_gas)?;
+                let bumped = max_priority_fee_per_gas.saturating_mul(100 + self.bump_percent) / 100;
+                let max_priority_fee_per_gas = bumped.max(max_priority_fee_per_gas + 1).min(max_fee_per_gas);
+                Some(Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas })
+            }
+        }
+    }
+
+    /// All gas prices an escalation starting at `gas_price` goes through.
+    pub fn schedule(&self, gas_price: u128) -> Vec<u128> {
+        std::iter::successors(Some(gas_price), |price| self.next_gas_price(*price)).collect()
+    }
+
+    /// Submit `ccr` (nonce and fees filled) and escalate until
+    /// inclusion. `on_step` is called after every submission.
+    pub async fn escalate<T, F>(
+        &self,
+        provider: &SuaveProvider<T>,
+        signer: &SuaveSigner,
+        mut ccr: ConfidentialComputeRequest,
+        mut on_step: F,
+    ) -> TransportResult<EscalationOutcome>
+        where T: Transport + Clone, F: FnMut(&EscalationStep)
+    {
+        let record = &ccr.confidential_compute_record;
+        if record.nonce.is_none() {
+            return Err(TransportErrorKind::custom_str("Escalation needs a fixed nonce"));
+        }
+        let mut fees = record.fees()
+            .ok_or_else(|| TransportErrorKind::custom_str("Escalation needs an initial gas price"))?;
+        let start = Instant::now();
+        let mut steps: Vec<EscalationStep> = Vec::new();
+        let mut resubmit = true;
+        loop {
+            if resubmit {
+                ccr.confidential_compute_record.set_fees(fees);
+                let tx_hash = submit(provider, signer, &mut ccr).await?;
+                let step = EscalationStep { step: steps.len(), fees, tx_hash };
+                on_step(&step);
+                steps.push(step);
+            }
+
+            let step_start = Instant::now();
+            while step_start.elapsed() < self.step_interval {
+                // Any of the submissions may get included, newest is most likely
+                let submitted = steps.iter().rev().copied().collect::<Vec<_>>();
+                for step in submitted {
+                    let receipt: Option<TransactionReceipt> = provider.client()
+                        .request("eth_getTransactionReceipt", (step.tx_hash,)).await?;
+                    if let Some(receipt) = receipt {
+                        return Ok(EscalationOutcome { receipt, included: step, steps });
+                    }
+                }
+                if start.elapsed() >= self.timeout {
+                    return Err(TransportErrorKind::custom_str(&format!(
+                        "Not included after {} submissions in {:?}", steps.len(), self.timeout,
+                    )));
+                }
+                provider.sleep(self.poll_interval).await?;
+            }
+
+            match self.next_fees(fees) {
+                Some(next) => {
+                    fees = next;
+                    resubmit = true;
+                }
+                None => resubmit = false,
+            }
+        }
+    }
+
+}
+
+async fn submit<T>(
+    provider: &SuaveProvider<T>,
+    signer: &SuaveSigner,
+    ccr: &mut ConfidentialComputeRequest,
+) -> TransportResult<TxHash>
+    where T: Transport + Clone
+{
+    signer.sign_in_place(ccr).await.map_err(TransportErrorKind::custom)?;
+    let encoded = ccr.rlp_encode().map_err(TransportErrorKind::custom)?;
+    provider.send_raw_ccr(&encoded).await
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escalation_schedule() {
+        let escalator = GasEscalator::new(2_000);
+        assert_eq!(escalator.schedule(1_000), vec![1_000, 1_200, 1_440, 1_728, 2_000]);
+        assert_eq!(escalator.next_gas_price(2_000), None);
+        // Small prices still increase every step
+        let schedule = escalator.schedule(3);
+        assert_eq!(schedule[..3], [3, 4, 5]);
+        assert!(schedule.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(schedule.last(), Some(&2_000));
+
+        let low_bump = GasEscalator::new(2_000).with_bump_percent(1);
+        assert_eq!(low_bump.bump_percent, MIN_REPLACEMENT_BUMP_PERCENT);
+        assert_eq!(low_bump.next_gas_price(1_000), Some(1_100));
+    }
+
+    #[test]
+    fn test_escalation_dynamic_fees() {
+        let escalator = GasEscalator::new(2_000);
+        let dynamic = |max_priority_fee_per_gas, max_fee_per_gas| Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas };
+        assert_eq!(escalator.next_fees(Fees::Legacy { gas_price: 1_000 }), Some(Fees::Legacy { gas_price: 1_200 }));
+        assert_eq!(escalator.next_fees(dynamic(100, 1_000)), Some(dynamic(120, 1_200)));
+        // The tip follows the max fee but stays under it
+        assert_eq!(escalator.next_fees(dynamic(1_900, 1_900)), Some(dynamic(2_000, 2_000)));
+        assert_eq!(escalator.next_fees(dynamic(0, 1_000)), Some(dynamic(1, 1_200)));
+        assert_eq!(escalator.next_fees(dynamic(100, 2_000)), None);
+    }
+
+}
@@ -0,0 +1,552 @@
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use tokio::task::JoinError;
+use alloy::{
+    primitives::{Address, U64},
+    providers::Provider,
+    transports::{Transport, TransportResult},
+};
+use super::{ShutdownSignal, SuaveProvider};
+
+
+/// Thresholds a probed kettle has to meet to be considered healthy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthPolicy {
+    /// Reject kettles serving another chain.
+    pub expected_chain_id: Option<u64>,
+    /// Maximum age of the latest block.
+    pub max_block_age: Duration,
+    /// Time a probe may take before the endpoint is reported unhealthy.
+    pub probe_timeout: Duration,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            expected_chain_id: None,
+            max_block_age: Duration::from_secs(60),
+            probe_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Result of a single health probe. Failing checks leave their field
+/// `None` and record the first error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    pub url: String,
+    pub reachable: bool,
+    pub chain_id: Option<u64>,
+    pub kettle_address: Option<Address>,
+    pub latest_block_age: Option<Duration>,
+    /// Duration of the whole probe.
+    pub latency: Duration,
+    pub error: Option<String>,
+}
+
+impl HealthReport {
+
+    fn unreachable(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            reachable: false,
+            chain_id: None,
+            kettle_address: None,
+            latest_block_age: None,
+            latency: Duration::ZERO,
+            error: None,
+        }
+    }
+
+    pub fn is_healthy(&self, policy: &HealthPolicy) -> bool {
+        let chain_ok = match (policy.expected_chain_id, self.chain_id) {
+            (_, None) => false,
+            (Some(expected), Some(chain_id)) => expected == chain_id,
+            (None, Some(_)) => true,
+        };
+        let fresh = self.latest_block_age.is_some_and(|age| age <= policy.max_block_age);
+        self.reachable && chain_ok && self.kettle_address.is_some() && fresh
+    }
+
+}
+
+pub struct KettleHealth;
+
+impl KettleHealth {
+
+    /// Check RPC reachability, chain id, kettle address availability and
+    /// freshness of the latest block, giving up after the default
+    /// `HealthPolicy::probe_timeout`. Never fails; problems are reported.
+    pub async fn probe(url: &str) -> HealthReport {
+        Self::probe_with_timeout(url, HealthPolicy::default().probe_timeout).await
+    }
+
+    /// `probe` giving up after `timeout`; checks that didn't finish in time
+    /// are left `None`.
+    pub async fn probe_with_timeout(url: &str, timeout: Duration) -> HealthReport {
+        let start = Instant::now();
+        let mut report = HealthReport::unreachable(url);
+        match SuaveProvider::try_from(url) {
+            Ok(provider) => {
+                let probe = Self::probe_provider(&provider, &mut report);
+                if tokio::time::timeout(timeout, probe).await.is_err() {
+                    report.error.get_or_insert(format!("Probe timed out after {timeout:?}"));
+                }
+            }
+            Err(err) => report.error = Some(err.to_string()),
+        }
+        report.latency = start.elapsed();
+        report
+    }
+
+    async fn probe_provider<T>(provider: &SuaveProvider<T>, report: &mut HealthReport)
+        where T: Transport + Clone
+    {
+        let chain_id: TransportResult<U64> = provider.client().request("eth_chainId", ()).await;
+        match chain_id {
+            Ok(chain_id) => {
+                report.reachable = true;
+                report.chain_id = Some(chain_id.to());
+            }
+            Err(err) => {
+                report.error = Some(err.to_string());
+                return;
+            }
+        }
+        match provider.kettle_address().await {
+            Ok(kettle) => report.kettle_address = Some(kettle),
+            Err(err) => { report.error.get_or_insert(err.to_string()); }
+        }
+        match provider.latest_block_timestamp().await {
+            Ok(timestamp) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                report.latest_block_age = Some(Duration::from_secs(now.saturating_sub(timestamp)));
+            }
+            Err(err) => { report.error.get_or_insert(err.to_string()); }
+        }
+    }
+
+}
+
+/// Measures the round-trip to an endpoint. The default times an RPC call;
+/// custom probers can eg. resolve a hostname and ping its DNS records.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait LatencyProber {
+    /// `None` if the endpoint didn't answer.
+    async fn probe(&self, url: &str) -> Option<Duration>;
+}
+
+/// Times an `eth_blockNumber` round-trip.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RpcLatencyProber;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl LatencyProber for RpcLatencyProber {
+    async fn probe(&self, url: &str) -> Option<Duration> {
+        let provider = SuaveProvider::try_from(url).ok()?;
+        let start = Instant::now();
+        let _: U64 = provider.client().request("eth_blockNumber", ()).await.ok()?;
+        Some(start.elapsed())
+    }
+}
+
+/// Latency metrics of an endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Exponentially weighted moving average of the successful probes.
+    pub smoothed: Option<Duration>,
+    pub last: Option<Duration>,
+    pub samples: u64,
+    /// Failed probes since the last success.
+    pub failures: u64,
+}
+
+impl LatencyStats {
+
+    fn record(&mut self, sample: Option<Duration>, smoothing: f64) {
+        let Some(sample) = sample else {
+            self.failures += 1;
+            return;
+        };
+        self.smoothed = Some(match self.smoothed {
+            Some(smoothed) => smoothed.mul_f64(1.0 - smoothing) + sample.mul_f64(smoothing),
+            None => sample,
+        });
+        self.last = Some(sample);
+        self.samples += 1;
+        self.failures = 0;
+    }
+
+}
+
+/// How `KettlePool::select` routes submissions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutingPolicy {
+    /// Weight of the newest sample in the moving average.
+    pub smoothing: f64,
+    /// Relative improvement an endpoint needs over the selected one to take
+    /// over, so similar endpoints don't flap.
+    pub hysteresis: f64,
+    /// Consecutive failed probes after which an endpoint isn't routed to.
+    pub max_failures: u64,
+}
+
+impl Default for RoutingPolicy {
+    fn default() -> Self {
+        Self { smoothing: 0.3, hysteresis: 0.2, max_failures: 3 }
+    }
+}
+
+impl RoutingPolicy {
+
+    /// Smoothing and hysteresis are fractions; outside `0.0..=1.0` they
+    /// would make the latency math panic.
+    pub fn validate(&self) -> Result<(), InvalidRoutingPolicy> {
+        for (field, value) in [("smoothing", self.smoothing), ("hysteresis", self.hysteresis)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(InvalidRoutingPolicy { field, value });
+            }
+        }
+        Ok(())
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidRoutingPolicy {
+    pub field: &'static str,
+    pub value: f64,
+}
+
+impl std::fmt::Display for InvalidRoutingPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Routing {} {} is outside 0..=1", self.field, self.value)
+    }
+}
+
+impl std::error::Error for InvalidRoutingPolicy {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KettleEndpoint {
+    pub url: String,
+    pub last_report: Option<HealthReport>,
+    pub ejected: bool,
+    pub latency: LatencyStats,
+}
+
+impl KettleEndpoint {
+
+    fn is_routable(&self, policy: &RoutingPolicy) -> bool {
+        !self.ejected && self.latency.failures < policy.max_failures
+    }
+
+    /// Unprobed endpoints rank last.
+    fn rank(&self) -> Duration {
+        self.latency.smoothed.unwrap_or(Duration::MAX)
+    }
+
+}
+
+/// Set of kettle endpoints; unhealthy ones are ejected on health checks
+/// and re-admitted once they pass again. Submissions are routed to the
+/// fastest healthy endpoint by measured latency (see `select`).
+#[derive(Debug, Clone, Default)]
+pub struct KettlePool {
+    endpoints: Vec<KettleEndpoint>,
+    policy: HealthPolicy,
+    routing: RoutingPolicy,
+    selected: Option<usize>,
+}
+
+impl KettlePool {
+
+    pub fn new<I, S>(urls: I) -> Self
+        where I: IntoIterator<Item = S>, S: Into<String>
+    {
+        let endpoints = urls.into_iter()
+            .map(|url| KettleEndpoint {
+                url: url.into(),
+                last_report: None,
+                ejected: false,
+                latency: LatencyStats::default(),
+            })
+            .collect();
+        Self { endpoints, ..Default::default() }
+    }
+
+    pub fn with_policy(mut self, policy: HealthPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_routing(mut self, routing: RoutingPolicy) -> Result<Self, InvalidRoutingPolicy> {
+        routing.validate()?;
+        self.routing = routing;
+        Ok(self)
+    }
+
+    pub fn endpoints(&self) -> &[KettleEndpoint] {
+        &self.endpoints
+    }
+
+    /// Endpoints not ejected by the last health check.
+    pub fn healthy(&self) -> impl Iterator<Item = &KettleEndpoint> {
+        self.endpoints.iter().filter(|e| !e.ejected)
+    }
+
+    /// Probe every endpoint concurrently, each within the policy's probe
+    /// timeout, and eject the unhealthy ones.
+    pub async fn check_health(&mut self) {
+        let timeout = self.policy.probe_timeout;
+        let probes = self.endpoints.iter()
+            .map(|endpoint| {
+                let url = endpoint.url.clone();
+                let probe = tokio::spawn(async move { KettleHealth::probe_with_timeout(&url, timeout).await });
+                (endpoint.url.clone(), probe)
+            })
+            .collect::<Vec<_>>();
+        for (url, probe) in probes {
+            let result = probe.await;
+            self.apply_probe_result(&url, result);
+        }
+    }
+
+    /// Apply the report of a probe task; a panicked or cancelled probe
+    /// ejects the endpoint like a failed one.
+    fn apply_probe_result(&mut self, url: &str, result: Result<HealthReport, JoinError>) {
+        let report = result.unwrap_or_else(|err| HealthReport {
+            error: Some(format!("Probe failed: {err}")),
+            ..HealthReport::unreachable(url)
+        });
+        self.apply_report(report);
+    }
+
+    /// Update the endpoint the report belongs to.
+    pub fn apply_report(&mut self, report: HealthReport) {
+        let healthy = report.is_healthy(&self.policy);
+        if let Some(endpoint) = self.endpoints.iter_mut().find(|e| e.url == report.url) {
+            endpoint.ejected = !healthy;
+            endpoint.last_report = Some(report);
+        }
+    }
+
+    /// Per-endpoint latency metrics.
+    pub fn latencies(&self) -> impl Iterator<Item = (&str, &LatencyStats)> {
+        self.endpoints.iter().map(|e| (e.url.as_str(), &e.latency))
+    }
+
+    /// Record a latency sample (`None` for a failed probe).
+    pub fn record_latency(&mut self, url: &str, sample: Option<Duration>) {
+        let smoothing = self.routing.smoothing;
+        if let Some(endpoint) = self.endpoints.iter_mut().find(|e| e.url == url) {
+            endpoint.latency.record(sample, smoothing);
+        }
+    }
+
+    /// Probe the latency of every endpoint once.
+    pub async fn probe_latency<P: LatencyProber>(&mut self, prober: &P) {
+        let urls = self.endpoints.iter().map(|e| e.url.clone()).collect::<Vec<_>>();
+        for url in urls {
+            let sample = prober.probe(&url).await;
+            self.record_latency(&url, sample);
+        }
+    }
+
+    /// Endpoint to submit to: the fastest routable one, sticking with the
+    /// current selection unless it became unroutable or another endpoint is
+    /// faster by the hysteresis margin.
+    pub fn select(&mut self) -> Option<&KettleEndpoint> {
+        let routing = self.routing;
+        let fastest = self.endpoints.iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_routable(&routing))
+            .min_by_key(|(_, e)| e.rank())
+            .map(|(idx, _)| idx);
+        let current = self.selected.filter(|idx| self.endpoints[*idx].is_routable(&routing));
+        self.selected = match (current, fastest) {
+            (Some(current), Some(fastest)) => {
+                let threshold = match self.endpoints[current].latency.smoothed {
+                    Some(latency) => latency.mul_f64(1.0 - routing.hysteresis),
+                    None => Duration::MAX,
+                };
+                if self.endpoints[fastest].rank() < threshold { Some(fastest) } else { Some(current) }
+            }
+            (_, fastest) => fastest,
+        };
+        self.selected()
+    }
+
+    /// Endpoint chosen by the last `select`.
+    pub fn selected(&self) -> Option<&KettleEndpoint> {
+        self.selected.map(|idx| &self.endpoints[idx])
+    }
+
+    /// Probe latencies of a shared pool every `interval` until `shutdown`
+    /// fires, updating the selection after every round. The lock isn't
+    /// held while probing.
+    ///
+    /// ```ignore
+    /// let pool = Arc::new(RwLock::new(KettlePool::new(urls)));
+    /// let probes = KettlePool::run_latency_probes(pool.clone(), RpcLatencyProber, Duration::from_secs(10), provider.shutdown_signal());
+    /// provider.spawn(probes);
+    /// ```
+    pub async fn run_latency_probes<P: LatencyProber>(
+        pool: Arc<RwLock<Self>>,
+        prober: P,
+        interval: Duration,
+        mut shutdown: ShutdownSignal,
+    ) {
+        // A panic elsewhere while holding the pool can at worst leave one
+        // sample unrecorded; keep probing rather than dying with it
+        let read = || pool.read().unwrap_or_else(PoisonError::into_inner);
+        let write = || pool.write().unwrap_or_else(PoisonError::into_inner);
+        while !shutdown.is_shutdown() {
+            let urls = read().endpoints.iter().map(|e| e.url.clone()).collect::<Vec<_>>();
+            for url in urls {
+                let sample = prober.probe(&url).await;
+                write().record_latency(&url, sample);
+            }
+            write().select();
+            if !shutdown.sleep(interval).await {
+                break;
+            }
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(url: &str, chain_id: u64, block_age: u64) -> HealthReport {
+        HealthReport {
+            url: url.into(),
+            reachable: true,
+            chain_id: Some(chain_id),
+            kettle_address: Some(Address::repeat_byte(0x03)),
+            latest_block_age: Some(Duration::from_secs(block_age)),
+            latency: Duration::from_millis(20),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_health_policy() {
+        let policy = HealthPolicy { expected_chain_id: Some(0x1008c45), ..Default::default() };
+        assert!(report("a", 0x1008c45, 5).is_healthy(&policy));
+        assert!(!report("a", 1, 5).is_healthy(&policy));
+        assert!(!report("a", 0x1008c45, 600).is_healthy(&policy));
+        let mut no_kettle = report("a", 0x1008c45, 5);
+        no_kettle.kettle_address = None;
+        assert!(!no_kettle.is_healthy(&policy));
+    }
+
+    #[test]
+    fn test_pool_ejects_unhealthy() {
+        let mut pool = KettlePool::new(["a", "b"]);
+        pool.apply_report(report("a", 1, 5));
+        pool.apply_report(report("b", 1, 600));
+        assert_eq!(pool.healthy().map(|e| e.url.as_str()).collect::<Vec<_>>(), vec!["a"]);
+
+        pool.apply_report(report("b", 1, 1));
+        assert_eq!(pool.healthy().count(), 2);
+    }
+
+    #[test]
+    fn test_latency_routing_hysteresis() {
+        let ms = Duration::from_millis;
+        let routing = RoutingPolicy { smoothing: 1.0, ..Default::default() };
+        let mut pool = KettlePool::new(["a", "b", "c"]).with_routing(routing).unwrap();
+        assert_eq!(pool.select().map(|e| e.url.as_str()), Some("a"));
+
+        pool.record_latency("a", Some(ms(100)));
+        pool.record_latency("b", Some(ms(50)));
+        pool.record_latency("c", Some(ms(70)));
+        assert_eq!(pool.select().map(|e| e.url.as_str()), Some("b"));
+
+        // 10% faster isn't enough to switch
+        pool.record_latency("c", Some(ms(45)));
+        assert_eq!(pool.select().map(|e| e.url.as_str()), Some("b"));
+        pool.record_latency("c", Some(ms(30)));
+        assert_eq!(pool.select().map(|e| e.url.as_str()), Some("c"));
+
+        for _ in 0..3 {
+            pool.record_latency("c", None);
+        }
+        assert_eq!(pool.select().map(|e| e.url.as_str()), Some("b"));
+        let (_, stats) = pool.latencies().find(|(url, _)| *url == "c").unwrap();
+        assert_eq!((stats.samples, stats.failures, stats.last), (3, 3, Some(ms(30))));
+    }
+
+    #[test]
+    fn test_invalid_routing() {
+        let routing = RoutingPolicy { hysteresis: 1.5, ..Default::default() };
+        let err = KettlePool::new(["a"]).with_routing(routing).unwrap_err();
+        assert_eq!(err, InvalidRoutingPolicy { field: "hysteresis", value: 1.5 });
+        let routing = RoutingPolicy { smoothing: -0.1, ..Default::default() };
+        assert!(KettlePool::new(["a"]).with_routing(routing).is_err());
+        let routing = RoutingPolicy { smoothing: f64::NAN, ..Default::default() };
+        assert!(KettlePool::new(["a"]).with_routing(routing).is_err());
+    }
+
+    #[test]
+    fn test_latency_smoothing() {
+        let mut stats = LatencyStats::default();
+        stats.record(Some(Duration::from_millis(100)), 0.5);
+        stats.record(Some(Duration::from_millis(50)), 0.5);
+        assert_eq!(stats.smoothed, Some(Duration::from_millis(75)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_task_ejects() {
+        let mut pool = KettlePool::new(["a", "b"]);
+        pool.apply_report(report("a", 1, 5));
+        pool.apply_report(report("b", 1, 5));
+        let panicked = tokio::spawn(async { panic!("probe panicked") }).await;
+        pool.apply_probe_result("a", panicked.map(|()| report("a", 1, 5)));
+        assert_eq!(pool.healthy().map(|e| e.url.as_str()).collect::<Vec<_>>(), vec!["b"]);
+        let last = pool.endpoints()[0].last_report.as_ref().unwrap();
+        assert!(!last.reachable && last.error.as_ref().unwrap().starts_with("Probe failed"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_unreachable() {
+        let report = KettleHealth::probe("http://127.0.0.1:1").await;
+        assert!(!report.reachable);
+        assert!(report.error.is_some());
+        assert!(!report.is_healthy(&HealthPolicy::default()));
+    }
+
+    #[tokio::test]
+    async fn test_probe_timeout() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hang = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        let report = KettleHealth::probe_with_timeout(&url, Duration::from_millis(100)).await;
+        assert!(!report.reachable);
+        assert!(report.error.unwrap().contains("timed out"));
+
+        let policy = HealthPolicy { probe_timeout: Duration::from_millis(200), ..Default::default() };
+        let urls = ["a", "b", "c"].map(|path| format!("{url}/{path}"));
+        let mut pool = KettlePool::new(urls).with_policy(policy);
+        let start = Instant::now();
+        pool.check_health().await;
+        assert!(start.elapsed() < Duration::from_millis(550));
+        assert_eq!(pool.healthy().count(), 0);
+        assert!(pool.endpoints().iter().all(|e| e.last_report.is_some()));
+        hang.abort();
+    }
+
+}
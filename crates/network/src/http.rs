@@ -0,0 +1,104 @@
+use std::{
+    io::Write,
+    task::{Context, Poll},
+};
+use flate2::{write::GzEncoder, Compression};
+use reqwest::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    Client as ReqwestClient,
+};
+use tower::Service;
+use alloy::{
+    rpc::json_rpc::{RequestPacket, ResponsePacket},
+    transports::{http::Http, TransportError, TransportErrorKind, TransportFut, TransportResult},
+};
+use super::HttpCompression;
+
+
+/// Bodies smaller than this are sent uncompressed even with `Gzip`; the
+/// gzip header and the extra round of work outweigh the savings.
+pub const GZIP_MIN_BODY: usize = 1024;
+
+/// HTTP transport of providers built with `from_http_with_config`,
+/// compressing request bodies with the configured `HttpCompression`.
+#[derive(Debug, Clone)]
+pub struct CompressedHttp {
+    client: ReqwestClient,
+    url: url::Url,
+    compression: HttpCompression,
+    max_request_body: Option<usize>,
+}
+
+impl CompressedHttp {
+
+    pub fn new(client: ReqwestClient, url: url::Url, compression: HttpCompression) -> Self {
+        Self { client, url, compression, max_request_body: None }
+    }
+
+    /// Fail requests whose (uncompressed) body exceeds `max_request_body`
+    /// bytes instead of having the node reject (or drop) them.
+    pub fn with_max_request_body(mut self, max_request_body: usize) -> Self {
+        self.max_request_body = Some(max_request_body);
+        self
+    }
+
+    pub fn compression(&self) -> HttpCompression {
+        self.compression
+    }
+
+    pub fn url(&self) -> &url::Url {
+        &self.url
+    }
+
+    /// Whether the url points to the local machine, see `Http::guess_local`.
+    pub fn guess_local(&self) -> bool {
+        Http::with_client(self.client.clone(), self.url.clone()).guess_local()
+    }
+
+    async fn post(self, packet: RequestPacket) -> TransportResult<ResponsePacket> {
+        let body = serde_json::to_vec(&packet).map_err(TransportErrorKind::custom)?;
+        if let Some(max_request_body) = self.max_request_body.filter(|max| body.len() > *max) {
+            return Err(TransportErrorKind::custom_str(&format!(
+                "Request body of {} bytes exceeds the {max_request_body} bytes limit", body.len()
+            )));
+        }
+        let request = self.client.post(self.url).header(CONTENT_TYPE, "application/json");
+        let request = match self.compression {
+            HttpCompression::Gzip if body.len() >= GZIP_MIN_BODY => {
+                request.header(CONTENT_ENCODING, "gzip").body(gzip(&body)?)
+            }
+            _ => request.body(body),
+        };
+        let response = request.send().await.map_err(TransportErrorKind::custom)?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(TransportErrorKind::custom)?;
+        if !status.is_success() {
+            return Err(TransportErrorKind::custom_str(&format!(
+                "HTTP error {status} with body: {}", String::from_utf8_lossy(&body)
+            )));
+        }
+        serde_json::from_slice(&body)
+            .map_err(|err| TransportError::deser_err(err, String::from_utf8_lossy(&body)))
+    }
+
+}
+
+fn gzip(body: &[u8]) -> TransportResult<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(body.len() / 2), Compression::default());
+    encoder.write_all(body).map_err(TransportErrorKind::custom)?;
+    encoder.finish().map_err(TransportErrorKind::custom)
+}
+
+impl Service<RequestPacket> for CompressedHttp {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, packet: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().post(packet))
+    }
+}
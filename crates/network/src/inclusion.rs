@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use alloy::{
+    primitives::{Bytes, TxHash, B256, U64},
+    providers::Provider,
+    rpc::types::eth::TransactionReceipt,
+    transports::{Transport, TransportResult},
+};
+use super::SuaveProvider;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InclusionState {
+    Pending,
+    /// In a block that may still be reorged out.
+    Included { block_number: u64, block_hash: B256 },
+    /// Buried under the required number of confirmations; no longer polled.
+    Confirmed { block_number: u64, block_hash: B256 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InclusionEvent {
+    Included { tx_hash: TxHash, block_number: u64, block_hash: B256 },
+    /// The including block got orphaned; the request is pending again.
+    Reorged { tx_hash: TxHash, block_number: u64, orphaned: B256 },
+    /// The request was re-broadcast after a reorg.
+    Resubmitted { tx_hash: TxHash },
+    Confirmed { tx_hash: TxHash, block_number: u64, block_hash: B256 },
+}
+
+#[derive(Debug, Clone)]
+struct Tracked {
+    state: InclusionState,
+    /// Signed encoding, re-broadcast after a reorg if resubmission is on.
+    encoded: Option<Bytes>,
+}
+
+/// Reorg-aware inclusion tracking of submitted CCRs. Besides polling
+/// receipts, included requests are re-checked against the canonical block
+/// hash at their height until they have `confirmations` blocks on top; an
+/// orphaned inclusion reverts the request to pending (and optionally
+/// re-broadcasts it) instead of being reported as final.
+///
+/// ```ignore
+/// let mut tracker = InclusionTracker::new(3).with_resubmission(true);
+/// tracker.track_encoded(tx_hash, encoded);
+/// loop {
+///     for event in tracker.poll(&provider).await? {
+///         println!("{event:?}");
+///     }
+///     tokio::time::sleep(Duration::from_secs(1)).await;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InclusionTracker {
+    confirmations: u64,
+    resubmit: bool,
+    tracked: HashMap<TxHash, Tracked>,
+}
+
+impl InclusionTracker {
+
+    pub fn new(confirmations: u64) -> Self {
+        Self { confirmations: confirmations.max(1), resubmit: false, tracked: HashMap::new() }
+    }
+
+    /// Re-broadcast requests tracked with `track_encoded` after a reorg.
+    pub fn with_resubmission(mut self, resubmit: bool) -> Self {
+        self.resubmit = resubmit;
+        self
+    }
+
+    pub fn track(&mut self, tx_hash: TxHash) {
+        self.tracked.insert(tx_hash, Tracked { state: InclusionState::Pending, encoded: None });
+    }
+
+    pub fn track_encoded(&mut self, tx_hash: TxHash, encoded: Bytes) {
+        self.tracked.insert(tx_hash, Tracked { state: InclusionState::Pending, encoded: Some(encoded) });
+    }
+
+    pub fn untrack(&mut self, tx_hash: &TxHash) -> Option<InclusionState> {
+        self.tracked.remove(tx_hash).map(|tracked| tracked.state)
+    }
+
+    pub fn state(&self, tx_hash: &TxHash) -> Option<InclusionState> {
+        self.tracked.get(tx_hash).map(|tracked| tracked.state)
+    }
+
+    /// Tracked requests not confirmed yet.
+    pub fn unconfirmed(&self) -> impl Iterator<Item = TxHash> + '_ {
+        self.tracked.iter()
+            .filter(|(_, tracked)| !matches!(tracked.state, InclusionState::Confirmed { .. }))
+            .map(|(tx_hash, _)| *tx_hash)
+    }
+
+    /// Query receipts of pending requests and canonical hashes of included
+    /// ones, returning what changed.
+    pub async fn poll<T>(&mut self, provider: &SuaveProvider<T>) -> TransportResult<Vec<InclusionEvent>>
+        where T: Transport + Clone
+    {
+        let head: U64 = provider.client().request("eth_blockNumber", ()).await?;
+        let mut events = Vec::new();
+        for tx_hash in self.unconfirmed().collect::<Vec<_>>() {
+            match self.tracked[&tx_hash].state {
+                InclusionState::Pending => {
+                    let receipt: Option<TransactionReceipt> = provider.client()
+                        .request("eth_getTransactionReceipt", (tx_hash,)).await?;
+                    let inclusion = receipt.and_then(|r| Some((r.block_number?, r.block_hash?)));
+                    if let Some((block_number, block_hash)) = inclusion {
+                        events.push(self.on_inclusion(tx_hash, block_number, block_hash));
+                    }
+                }
+                InclusionState::Included { block_number, .. } => {
+                    let block: Option<BlockHash> = provider.client()
+                        .request("eth_getBlockByNumber", (U64::from(block_number), false)).await?;
+                    let event = self.on_canonical_hash(tx_hash, block.map(|b| b.hash), head.to());
+                    if let Some(event @ InclusionEvent::Reorged { .. }) = event {
+                        events.push(event);
+                        if let Some(encoded) = self.resubmission(&tx_hash) {
+                            provider.send_raw_ccr(&encoded).await?;
+                            events.push(InclusionEvent::Resubmitted { tx_hash });
+                        }
+                    } else {
+                        events.extend(event);
+                    }
+                }
+                InclusionState::Confirmed { .. } => {}
+            }
+        }
+        Ok(events)
+    }
+
+    fn on_inclusion(&mut self, tx_hash: TxHash, block_number: u64, block_hash: B256) -> InclusionEvent {
+        if let Some(tracked) = self.tracked.get_mut(&tx_hash) {
+            tracked.state = InclusionState::Included { block_number, block_hash };
+        }
+        InclusionEvent::Included { tx_hash, block_number, block_hash }
+    }
+
+    /// Compare an inclusion with the canonical hash at its height (`None`
+    /// if the chain is shorter now).
+    fn on_canonical_hash(&mut self, tx_hash: TxHash, canonical: Option<B256>, head: u64) -> Option<InclusionEvent> {
+        let tracked = self.tracked.get_mut(&tx_hash)?;
+        let InclusionState::Included { block_number, block_hash } = tracked.state else {
+            return None;
+        };
+        if canonical != Some(block_hash) {
+            tracked.state = InclusionState::Pending;
+            return Some(InclusionEvent::Reorged { tx_hash, block_number, orphaned: block_hash });
+        }
+        if head.saturating_sub(block_number) + 1 >= self.confirmations {
+            tracked.state = InclusionState::Confirmed { block_number, block_hash };
+            return Some(InclusionEvent::Confirmed { tx_hash, block_number, block_hash });
+        }
+        None
+    }
+
+    fn resubmission(&self, tx_hash: &TxHash) -> Option<Bytes> {
+        self.resubmit.then(|| self.tracked.get(tx_hash)?.encoded.clone()).flatten()
+    }
+
+}
+
+#[derive(Deserialize)]
+struct BlockHash {
+    hash: B256,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorg_reverts_to_pending() {
+        let tx_hash = TxHash::repeat_byte(0x01);
+        let (block_a, block_b) = (B256::repeat_byte(0xaa), B256::repeat_byte(0xbb));
+        let mut tracker = InclusionTracker::new(3).with_resubmission(true);
+        tracker.track_encoded(tx_hash, Bytes::from_static(&[0x43]));
+
+        tracker.on_inclusion(tx_hash, 10, block_a);
+        assert_eq!(tracker.on_canonical_hash(tx_hash, Some(block_a), 11), None);
+
+        // Block 10 got replaced
+        assert_eq!(
+            tracker.on_canonical_hash(tx_hash, Some(block_b), 11),
+            Some(InclusionEvent::Reorged { tx_hash, block_number: 10, orphaned: block_a })
+        );
+        assert_eq!(tracker.state(&tx_hash), Some(InclusionState::Pending));
+        assert_eq!(tracker.resubmission(&tx_hash), Some(Bytes::from_static(&[0x43])));
+
+        // Re-included in the new block 10 and buried
+        tracker.on_inclusion(tx_hash, 10, block_b);
+        assert_eq!(
+            tracker.on_canonical_hash(tx_hash, Some(block_b), 12),
+            Some(InclusionEvent::Confirmed { tx_hash, block_number: 10, block_hash: block_b })
+        );
+        assert_eq!(tracker.unconfirmed().count(), 0);
+    }
+
+    #[test]
+    fn test_shortened_chain_is_reorg() {
+        let tx_hash = TxHash::repeat_byte(0x01);
+        let mut tracker = InclusionTracker::new(1);
+        tracker.track(tx_hash);
+        tracker.on_inclusion(tx_hash, 10, B256::repeat_byte(0xaa));
+        assert!(matches!(tracker.on_canonical_hash(tx_hash, None, 9), Some(InclusionEvent::Reorged { .. })));
+        assert_eq!(tracker.resubmission(&tx_hash), None);
+    }
+
+}
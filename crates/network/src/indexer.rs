@@ -0,0 +1,205 @@
+use serde::Deserialize;
+use alloy::{
+    eips::eip2718::Decodable2718,
+    primitives::{Address, Bytes, TxHash, TxKind, B256, U64},
+    rpc::types::eth::TransactionReceipt,
+    transports::{Transport, TransportResult},
+};
+use suave_alloy_types::{ConfidentialComputeResult, SuaveTxEnvelope};
+use super::SuaveProvider;
+
+
+/// Block of a `BlockWalker` with its decoded transactions. Transactions of
+/// types this crate doesn't know are left out.
+#[derive(Debug, Clone)]
+pub struct WalkedBlock {
+    pub number: u64,
+    pub hash: B256,
+    pub transactions: Vec<(TxHash, SuaveTxEnvelope)>,
+}
+
+/// Walks blocks in order, fetching and decoding their transactions. It
+/// stops at the chain head (or `to_block`) and resumes from there on the
+/// next call, so it serves both for backfills and for following the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockWalker {
+    next: u64,
+    to_block: Option<u64>,
+}
+
+impl BlockWalker {
+
+    /// Walk from `from_block` up to `to_block` (inclusive), or without end.
+    pub fn new(from_block: u64, to_block: Option<u64>) -> Self {
+        Self { next: from_block, to_block }
+    }
+
+    /// Number of the block the next call returns.
+    pub fn next_number(&self) -> u64 {
+        self.next
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.to_block.is_some_and(|to_block| self.next > to_block)
+    }
+
+    /// The next block; `None` once past `to_block` or at the head.
+    pub async fn next_block<T>(&mut self, provider: &SuaveProvider<T>) -> TransportResult<Option<WalkedBlock>>
+        where T: Transport + Clone
+    {
+        if self.is_done() {
+            return Ok(None);
+        }
+        let block: Option<BlockTxHashes> = provider.client()
+            .request("eth_getBlockByNumber", (U64::from(self.next), false)).await?;
+        let Some(block) = block else {
+            return Ok(None);
+        };
+        let mut transactions = Vec::with_capacity(block.transactions.len());
+        for tx_hash in block.transactions {
+            let raw: Option<Bytes> = provider.client()
+                .request("eth_getRawTransactionByHash", (tx_hash,)).await?;
+            let decoded = raw.and_then(|raw| SuaveTxEnvelope::decode_2718(&mut raw.as_ref()).ok());
+            transactions.extend(decoded.map(|tx| (tx_hash, tx)));
+        }
+        self.next += 1;
+        Ok(Some(WalkedBlock { number: block.number.to(), hash: block.hash, transactions }))
+    }
+
+}
+
+#[derive(Deserialize)]
+struct BlockTxHashes {
+    number: U64,
+    hash: B256,
+    transactions: Vec<TxHash>,
+}
+
+/// Filter on the request records of kettle results; unset fields match
+/// anything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CcrQuery {
+    pub to: Option<Address>,
+    pub sender: Option<Address>,
+    pub kettle: Option<Address>,
+}
+
+impl CcrQuery {
+
+    /// Results of requests to the contract at `to`.
+    pub fn to(to: Address) -> Self {
+        Self { to: Some(to), ..Default::default() }
+    }
+
+    pub fn with_sender(mut self, sender: Address) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn with_kettle(mut self, kettle: Address) -> Self {
+        self.kettle = Some(kettle);
+        self
+    }
+
+    pub fn matches(&self, result: &ConfidentialComputeResult) -> bool {
+        let record = &result.request_record;
+        self.to.map_or(true, |to| record.to == TxKind::Call(to))
+            && self.kettle.map_or(true, |kettle| record.kettle_address == Some(kettle))
+            // Recovering is the costly part, so it goes last
+            && self.sender.map_or(true, |sender| result.request_sender().ok() == Some(sender))
+    }
+
+}
+
+/// Kettle result found by a query, with its receipt.
+#[derive(Debug, Clone)]
+pub struct IndexedCcr {
+    pub block_number: u64,
+    pub block_hash: B256,
+    pub tx_hash: TxHash,
+    pub result: ConfidentialComputeResult,
+    pub receipt: Option<TransactionReceipt>,
+}
+
+impl<T> SuaveProvider<T>
+    where T: Transport + Clone
+{
+
+    /// Kettle results of requests to `to` included between `from_block`
+    /// and `to_block` (inclusive), eg. to list the bids a contract got.
+    pub async fn find_ccrs(&self, to: Address, from_block: u64, to_block: u64) -> TransportResult<Vec<IndexedCcr>> {
+        self.query_ccrs(&CcrQuery::to(to), from_block, to_block).await
+    }
+
+    /// Kettle results matching `query` included between `from_block` and
+    /// `to_block` (inclusive). Blocks past the head are not waited for.
+    pub async fn query_ccrs(&self, query: &CcrQuery, from_block: u64, to_block: u64) -> TransportResult<Vec<IndexedCcr>> {
+        let mut walker = BlockWalker::new(from_block, Some(to_block));
+        let mut found = Vec::new();
+        while let Some(block) = walker.next_block(self).await? {
+            for (tx_hash, tx) in block.transactions {
+                let SuaveTxEnvelope::ConfidentialComputeResult(result) = tx else { continue };
+                if !query.matches(&result) {
+                    continue;
+                }
+                let receipt = self.client().request("eth_getTransactionReceipt", (tx_hash,)).await?;
+                found.push(IndexedCcr {
+                    block_number: block.number,
+                    block_hash: block.hash,
+                    tx_hash,
+                    result,
+                    receipt,
+                });
+            }
+        }
+        Ok(found)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Signature, U256};
+    use suave_alloy_types::fixtures;
+    use super::*;
+
+    fn result() -> (ConfidentialComputeResult, Address) {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let result = ConfidentialComputeResult {
+            request_record: fixture.request.confidential_compute_record,
+            confidential_compute_result: Bytes::new(),
+            chain_id: 0x1008c45,
+            signature: Signature::from_rs_and_parity(U256::from(1), U256::from(1), 0).unwrap(),
+        };
+        (result, fixture.signer)
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let (result, sender) = result();
+        let to = result.request_record.target().unwrap();
+        let kettle = result.kettle_address().unwrap();
+        let other = Address::repeat_byte(0xee);
+
+        assert!(CcrQuery::default().matches(&result));
+        assert!(CcrQuery::to(to).with_sender(sender).with_kettle(kettle).matches(&result));
+        assert!(!CcrQuery::to(other).matches(&result));
+        assert!(!CcrQuery::to(to).with_sender(other).matches(&result));
+        assert!(!CcrQuery::default().with_kettle(other).matches(&result));
+
+        let mut create = result.clone();
+        create.request_record.to = TxKind::Create;
+        assert!(!CcrQuery::to(to).matches(&create));
+    }
+
+    #[test]
+    fn test_walker_bounds() {
+        let mut walker = BlockWalker::new(5, Some(5));
+        assert!(!walker.is_done());
+        walker.next += 1;
+        assert!(walker.is_done());
+        assert!(!BlockWalker::new(5, None).is_done());
+    }
+
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+use alloy::{
+    primitives::Address,
+    transports::{Transport, TransportResult},
+};
+use super::{KettleFiller, SuaveProvider};
+
+
+/// Kettle address change observed by a `KettleWatcher`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KettleRotation {
+    pub previous: Address,
+    pub current: Address,
+}
+
+/// Periodically re-queries the kettle address of a provider and pushes
+/// rotations into the provider cache and the registered fillers, so newly
+/// built CCRs pick up the new kettle without restarting.
+///
+/// ```ignore
+/// let watcher = KettleWatcher::new(provider.clone(), Duration::from_secs(60))
+///     .with_filler(kettle_filler.clone());
+/// watcher.spawn(|rotation| println!("kettle rotated: {rotation:?}"));
+/// // ...
+/// provider.shutdown().await;
+/// ```
+#[derive(Clone)]
+pub struct KettleWatcher<T>
+    where T: Transport + Clone
+{
+    provider: SuaveProvider<T>,
+    fillers: Vec<KettleFiller>,
+    interval: Duration,
+}
+
+impl<T> KettleWatcher<T>
+    where T: Transport + Clone
+{
+
+    pub fn new(provider: SuaveProvider<T>, interval: Duration) -> Self {
+        Self { provider, fillers: Vec::new(), interval }
+    }
+
+    /// Update `filler` whenever the kettle address changes.
+    pub fn with_filler(mut self, filler: KettleFiller) -> Self {
+        self.fillers.push(filler);
+        self
+    }
+
+    /// Re-query the kettle address once, updating the fillers on change.
+    pub async fn poll(&self) -> TransportResult<Option<KettleRotation>> {
+        let rotation = self.provider.refresh_kettle_address().await?;
+        let current = self.provider.kettle_address().await?;
+        for filler in &self.fillers {
+            if filler.kettle_address() != Some(current) {
+                filler.set_kettle_address(current);
+            }
+        }
+        Ok(rotation)
+    }
+
+    /// Poll until the provider shuts down, calling `on_change` for every
+    /// rotation. Failed polls are skipped; the cached address stays in use
+    /// until the next success.
+    pub async fn run<F>(self, mut on_change: F)
+        where F: FnMut(KettleRotation)
+    {
+        let mut shutdown = self.provider.shutdown_signal();
+        while !shutdown.is_shutdown() {
+            if let Ok(Some(rotation)) = self.poll().await {
+                on_change(rotation);
+            }
+            if !shutdown.sleep(self.interval).await {
+                break;
+            }
+        }
+    }
+
+    /// `run` as a background task of the provider.
+    pub fn spawn<F>(self, on_change: F)
+        where F: FnMut(KettleRotation) + Send + 'static, T: Send + Sync + 'static
+    {
+        let provider = self.provider.clone();
+        provider.spawn(self.run(on_change));
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_poll_updates_fillers() {
+        let provider = SuaveProvider::try_from(crate::provider::tests::test_profile().rpc_url).unwrap();
+        let stale = Address::repeat_byte(0x01);
+        let filler = KettleFiller::new(Some(stale));
+        let watcher = KettleWatcher::new(provider.clone(), Duration::from_secs(1))
+            .with_filler(filler.clone());
+
+        // Nothing cached yet, so no rotation, but the filler gets the kettle
+        assert_eq!(watcher.poll().await.unwrap(), None);
+        let kettle = provider.kettle_address().await.unwrap();
+        assert_eq!(filler.kettle_address(), Some(kettle));
+        assert_eq!(watcher.poll().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_watcher() {
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        KettleWatcher::new(provider.clone(), Duration::from_secs(3600)).spawn(|_| {});
+        tokio::time::timeout(Duration::from_secs(5), provider.shutdown()).await.unwrap();
+        assert!(provider.is_shutdown());
+    }
+
+}
@@ -2,8 +2,67 @@ mod network;
 mod signer;
 mod provider;
 mod contract;
+pub mod methods;
+pub mod schema;
+mod simulation;
+mod timings;
+mod error;
+mod budget;
+mod health;
+mod presigned;
+mod wallet;
+pub mod pipeline;
+mod kettle_watcher;
+mod escalator;
+mod replay;
+mod nonce_audit;
+mod inclusion;
+#[cfg(feature = "experimental-upload")]
+mod upload;
+mod preflight;
+mod tasks;
+mod indexer;
+mod pricing;
+mod http;
+#[cfg(feature = "service")]
+mod service;
+#[cfg(feature = "service")]
+mod listener;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "chaos")]
+mod chaos;
 
 pub use network::SuaveNetwork;
 pub use signer::SuaveSigner;
-pub use provider::{SuaveProvider, SuaveFillProviderExt, KettleFiller};
-pub use contract::SuaveCallBuilderExt;
\ No newline at end of file
+pub use provider::{SuaveProvider, SuaveFillProviderExt, SuaveProviderExt, KettleFiller, SenderFiller, HttpCompression, HttpConfig};
+pub use contract::SuaveCallBuilderExt;
+pub use methods::RpcMethods;
+pub use simulation::{ExecutionOutcome, LocalSimulator, SimulationReport};
+pub use timings::{Timings, TimedSubmission};
+pub use error::KettleError;
+pub use budget::{Budget, BudgetCap, BudgetExceeded};
+pub use health::{HealthPolicy, HealthReport, InvalidRoutingPolicy, KettleEndpoint, KettleHealth, KettlePool, LatencyProber, LatencyStats, RoutingPolicy, RpcLatencyProber};
+pub use presigned::{PresignedEntry, PresignedPool};
+pub use wallet::{SuaveWallet, SUAVE_DERIVATION_PATH_PREFIX};
+pub use pipeline::{Pipeline, PipelineContext, PipelineError, PipelineOutcome, Stage};
+pub use kettle_watcher::{KettleRotation, KettleWatcher};
+pub use replay::{Interaction, ReplayMode, ReplayTransport, RECORD_ENV};
+pub use escalator::{EscalationOutcome, EscalationStep, GasEscalator, MIN_REPLACEMENT_BUMP_PERCENT};
+pub use nonce_audit::{NonceAuditor, NonceConflict, NonceJournal, NonceReport};
+pub use inclusion::{InclusionEvent, InclusionState, InclusionTracker};
+pub use preflight::{Preflight, PreflightError};
+pub use tasks::{ShutdownSignal, TaskScope};
+pub use indexer::{BlockWalker, CcrQuery, IndexedCcr, WalkedBlock};
+pub use pricing::InclusionEstimator;
+pub use http::{CompressedHttp, GZIP_MIN_BODY};
+#[cfg(feature = "service")]
+pub use service::{CcrParams, PolicyViolation, ServiceError, SignedCcr, SignerService, TenantPolicy};
+#[cfg(feature = "service")]
+pub use listener::{CcrHandler, CcrListener, ListenerError};
+#[cfg(feature = "otel")]
+pub use otel::{CcrLifecycle, ObservedInclusion};
+#[cfg(feature = "chaos")]
+pub use chaos::{ChaosConfig, ChaosSigner, ChaosStats, ChaosTransport};
+#[cfg(feature = "experimental-upload")]
+pub use upload::{UploadedInputs, DEFAULT_CHUNK_SIZE};
\ No newline at end of file
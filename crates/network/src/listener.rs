@@ -0,0 +1,248 @@
+use std::{net::SocketAddr, sync::Arc};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use alloy::primitives::{Address, Bytes, B256};
+use suave_alloy_types::{consts::ProtocolLimits, VerificationError, VerifiedCcr};
+use super::ShutdownSignal;
+
+
+/// Receives the requests that passed verification; an error is returned to
+/// the sender as the JSON-RPC error.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait CcrHandler: Send + Sync + 'static {
+    async fn handle(&self, ccr: VerifiedCcr) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenerError {
+    /// Body not a JSON-RPC request.
+    Parse(String),
+    UnsupportedMethod(String),
+    InvalidParams(String),
+    Verification(VerificationError),
+    Handler(String),
+}
+
+impl std::fmt::Display for ListenerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenerError::Parse(err) => write!(f, "Invalid JSON-RPC request: {err}"),
+            ListenerError::UnsupportedMethod(method) => write!(f, "Method `{method}` is not supported"),
+            ListenerError::InvalidParams(err) => write!(f, "Invalid params: {err}"),
+            ListenerError::Verification(err) => write!(f, "Rejected request: {err}"),
+            ListenerError::Handler(err) => write!(f, "Handler failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ListenerError {}
+
+impl ListenerError {
+
+    /// JSON-RPC error code.
+    pub fn code(&self) -> i64 {
+        match self {
+            ListenerError::Parse(_) => -32700,
+            ListenerError::UnsupportedMethod(_) => -32601,
+            ListenerError::InvalidParams(_) | ListenerError::Verification(_) => -32602,
+            ListenerError::Handler(_) => -32000,
+        }
+    }
+
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+/// Receive side of the protocol, for prototyping kettles: accepts raw CCRs
+/// sent to `kettle` with `eth_sendRawTransaction`, verifies them (see
+/// `VerifiedCcr::verify`) and hands them to the handler, answering with
+/// the tx hash.
+///
+/// - `POST /`: JSON-RPC over HTTP
+/// - `GET /ws`: the same JSON-RPC messages over a websocket
+pub struct CcrListener<H> {
+    kettle: Address,
+    limits: ProtocolLimits,
+    handler: H,
+}
+
+impl<H> CcrListener<H>
+    where H: CcrHandler
+{
+
+    pub fn new(kettle: Address, handler: H) -> Self {
+        Self { kettle, limits: ProtocolLimits::default(), handler }
+    }
+
+    pub fn with_limits(mut self, limits: ProtocolLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/", post(rpc_handler::<H>))
+            .route("/ws", get(ws_handler::<H>))
+            .with_state(Arc::new(self))
+    }
+
+    pub async fn serve(self, addr: SocketAddr) -> std::io::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, self.router()).await
+    }
+
+    /// `serve` until `shutdown` fires, letting in-flight requests finish.
+    pub async fn serve_with_shutdown(self, addr: SocketAddr, mut shutdown: ShutdownSignal) -> std::io::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, self.router())
+            .with_graceful_shutdown(async move { shutdown.wait().await })
+            .await
+    }
+
+    /// Verify a raw request and pass it to the handler.
+    pub async fn receive(&self, raw: &[u8]) -> Result<B256, ListenerError> {
+        let verified = VerifiedCcr::verify(raw, self.kettle, &self.limits)
+            .map_err(ListenerError::Verification)?;
+        let tx_hash = verified.tx_hash();
+        self.handler.handle(verified).await.map_err(ListenerError::Handler)?;
+        Ok(tx_hash)
+    }
+
+    /// JSON-RPC response to a request body.
+    async fn rpc(&self, body: &str) -> Value {
+        let request: RpcRequest = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(err) => return rpc_error(Value::Null, ListenerError::Parse(err.to_string())),
+        };
+        match self.send_raw_transaction(&request).await {
+            Ok(tx_hash) => json!({ "jsonrpc": "2.0", "id": request.id, "result": tx_hash }),
+            Err(err) => rpc_error(request.id, err),
+        }
+    }
+
+    async fn send_raw_transaction(&self, request: &RpcRequest) -> Result<B256, ListenerError> {
+        if request.method != "eth_sendRawTransaction" {
+            return Err(ListenerError::UnsupportedMethod(request.method.clone()));
+        }
+        let raw = match request.params.as_slice() {
+            [raw] => serde_json::from_value::<Bytes>(raw.clone())
+                .map_err(|e| ListenerError::InvalidParams(e.to_string()))?,
+            _ => return Err(ListenerError::InvalidParams("expected a single raw transaction".into())),
+        };
+        self.receive(&raw).await
+    }
+
+}
+
+fn rpc_error(id: Value, err: ListenerError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code(), "message": err.to_string() },
+    })
+}
+
+async fn rpc_handler<H>(State(listener): State<Arc<CcrListener<H>>>, body: String) -> Json<Value>
+    where H: CcrHandler
+{
+    Json(listener.rpc(&body).await)
+}
+
+async fn ws_handler<H>(State(listener): State<Arc<CcrListener<H>>>, ws: WebSocketUpgrade) -> Response
+    where H: CcrHandler
+{
+    ws.on_upgrade(move |socket| serve_socket(listener, socket))
+}
+
+async fn serve_socket<H>(listener: Arc<CcrListener<H>>, mut socket: WebSocket)
+    where H: CcrHandler
+{
+    while let Some(Ok(message)) = socket.recv().await {
+        let Message::Text(body) = message else { continue };
+        let response = listener.rpc(&body).await;
+        if socket.send(Message::Text(response.to_string())).await.is_err() {
+            break;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+    use suave_alloy_types::fixtures;
+    use super::*;
+
+    #[derive(Default)]
+    struct Collector(Arc<Mutex<Vec<VerifiedCcr>>>);
+
+    #[async_trait]
+    impl CcrHandler for Collector {
+        async fn handle(&self, ccr: VerifiedCcr) -> Result<(), String> {
+            self.0.lock().unwrap().push(ccr);
+            Ok(())
+        }
+    }
+
+    async fn send(router: Router, raw: &Bytes) -> Value {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_sendRawTransaction", "params": [raw] });
+        let request = Request::post("/")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_listener_verifies_and_hands_off() {
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let kettle = fixture.request.kettle_address().unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let router = CcrListener::new(kettle, Collector(received.clone())).router();
+
+        let response = send(router.clone(), &fixture.raw).await;
+        assert_eq!(response["result"], json!(fixture.tx_hash));
+        let verified = received.lock().unwrap().pop().unwrap();
+        assert_eq!(verified.sender(), fixture.signer);
+        assert_eq!(verified.request(), &fixture.request);
+
+        let other = CcrListener::new(Address::repeat_byte(0x03), Collector::default()).router();
+        assert_eq!(send(other, &fixture.raw).await["error"]["code"], -32602);
+        let truncated = Bytes::from(fixture.raw[..10].to_vec());
+        assert_eq!(send(router, &truncated).await["error"]["code"], -32602);
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rpc_errors() {
+        let listener = CcrListener::new(Address::ZERO, Collector::default());
+        assert_eq!(listener.rpc("not json").await["error"]["code"], -32700);
+        let response = listener.rpc(r#"{"jsonrpc":"2.0","id":7,"method":"eth_call","params":[]}"#).await;
+        assert_eq!((response["id"].clone(), response["error"]["code"].clone()), (json!(7), json!(-32601)));
+        let response = listener.rpc(r#"{"jsonrpc":"2.0","id":8,"method":"eth_sendRawTransaction","params":[]}"#).await;
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+}
@@ -0,0 +1,75 @@
+use std::borrow::Cow;
+
+
+/// Returns the list of kettle addresses served by the node.
+pub const KETTLE_ADDRESS: &str = "eth_kettleAddress";
+/// Submits a signed, 2718-encoded transaction.
+pub const SEND_RAW_TRANSACTION: &str = "eth_sendRawTransaction";
+/// Executes a (confidential) call without creating a transaction.
+pub const CALL: &str = "eth_call";
+/// Opens a streamed confidential inputs upload, returning its id.
+/// Experimental, not served by suave-geth (see `upload_inputs`).
+#[cfg(feature = "experimental-upload")]
+pub const BEGIN_INPUTS_UPLOAD: &str = "suavex_beginInputsUpload";
+/// Appends a chunk to a confidential inputs upload.
+#[cfg(feature = "experimental-upload")]
+pub const UPLOAD_INPUTS_CHUNK: &str = "suavex_uploadInputsChunk";
+/// Closes an upload; the kettle checks the hash and length and stores the
+/// inputs under their hash.
+#[cfg(feature = "experimental-upload")]
+pub const COMMIT_INPUTS_UPLOAD: &str = "suavex_commitInputsUpload";
+
+/// RPC method names used by `SuaveProvider` for SUAVE specific calls.
+/// Different suave-geth versions exposed these under different names,
+/// so they can be overridden per provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcMethods {
+    pub kettle_address: Cow<'static, str>,
+    pub send_raw_transaction: Cow<'static, str>,
+    pub call: Cow<'static, str>,
+}
+
+impl Default for RpcMethods {
+    fn default() -> Self {
+        Self {
+            kettle_address: Cow::Borrowed(KETTLE_ADDRESS),
+            send_raw_transaction: Cow::Borrowed(SEND_RAW_TRANSACTION),
+            call: Cow::Borrowed(CALL),
+        }
+    }
+}
+
+impl RpcMethods {
+
+    pub fn with_kettle_address(mut self, method: impl Into<Cow<'static, str>>) -> Self {
+        self.kettle_address = method.into();
+        self
+    }
+
+    pub fn with_send_raw_transaction(mut self, method: impl Into<Cow<'static, str>>) -> Self {
+        self.send_raw_transaction = method.into();
+        self
+    }
+
+    pub fn with_call(mut self, method: impl Into<Cow<'static, str>>) -> Self {
+        self.call = method.into();
+        self
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_overrides() {
+        let methods = RpcMethods::default()
+            .with_send_raw_transaction("suavex_sendConfidentialRequest");
+        assert_eq!(methods.kettle_address, KETTLE_ADDRESS);
+        assert_eq!(methods.send_raw_transaction, "suavex_sendConfidentialRequest");
+        assert_eq!(methods.call, CALL);
+    }
+
+}
@@ -22,6 +22,8 @@ pub enum SuaveTxType {
     Eip4844 = 3,
     /// SUAVE "transaction" type
     ConfidentialComputeRequest = 4,
+    /// Kettle result included in blocks
+    ConfidentialComputeResult = 0x50,
 }
 
 impl From<SuaveTxType> for u8 {
@@ -39,6 +41,7 @@ impl TryFrom<u8> for SuaveTxType {
             1 => SuaveTxType::Eip2930,
             2 => SuaveTxType::Eip1559,
             3 => SuaveTxType::Eip4844,
+            0x50 => SuaveTxType::ConfidentialComputeResult,
             _ => return Err(Eip2718Error::UnexpectedType(value)),
         })
     }
@@ -52,6 +55,7 @@ impl std::fmt::Display for SuaveTxType {
             SuaveTxType::Eip1559 => write!(f, "EIP-1559"),
             SuaveTxType::Eip4844 => write!(f, "EIP-4844"),
             SuaveTxType::ConfidentialComputeRequest => write!(f, "ConfidentialComputeRequest"),
+            SuaveTxType::ConfidentialComputeResult => write!(f, "ConfidentialComputeResult"),
         }
     }
 }
@@ -113,15 +117,11 @@ impl TransactionBuilder<SuaveNetwork> for ConfidentialComputeRequest {
     }
 
     fn to(&self) -> Option<TxKind> {
-        Some(TxKind::Call(self.confidential_compute_record.to))
+        Some(self.confidential_compute_record.to)
     }
 
     fn set_to(&mut self, to: TxKind) {
-        let new_address = match to {
-            TxKind::Call(addr) => addr,
-            TxKind::Create => Address::ZERO,
-        };
-        self.confidential_compute_record.to = new_address;
+        self.confidential_compute_record.to = to;
     }
 
     fn value(&self) -> Option<U256> {
@@ -141,19 +141,19 @@ impl TransactionBuilder<SuaveNetwork> for ConfidentialComputeRequest {
     }
 
     fn max_fee_per_gas(&self) -> Option<u128> {
-        None
+        self.confidential_compute_record.max_fee_per_gas
     }
 
-    fn set_max_fee_per_gas(&mut self, _max_fee_per_gas: u128) {
-        panic!("Cannot set max fee per gas for confidential compute request");
+    fn set_max_fee_per_gas(&mut self, max_fee_per_gas: u128) {
+        self.confidential_compute_record.max_fee_per_gas = Some(max_fee_per_gas);
     }
 
     fn max_priority_fee_per_gas(&self) -> Option<u128> {
-        None
+        self.confidential_compute_record.max_priority_fee_per_gas
     }
 
-    fn set_max_priority_fee_per_gas(&mut self, _max_priority_fee_per_gas: u128) {
-        panic!("Cannot set max priority fee per gas for confidential compute request");
+    fn set_max_priority_fee_per_gas(&mut self, max_priority_fee_per_gas: u128) {
+        self.confidential_compute_record.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
     }
 
     fn max_fee_per_blob_gas(&self) -> Option<u128> {
@@ -197,11 +197,11 @@ impl TransactionBuilder<SuaveNetwork> for ConfidentialComputeRequest {
     }
 
     fn access_list(&self) -> Option<&AccessList> {
-        None
+        self.confidential_compute_record.access_list.as_ref()
     }
 
-    fn set_access_list(&mut self, _access_list: AccessList) {
-        panic!("Cannot set access list for confidential compute request");
+    fn set_access_list(&mut self, access_list: AccessList) {
+        self.confidential_compute_record.access_list = Some(access_list);
     }
 
     fn blob_sidecar(&self) -> Option<&consensus::BlobTransactionSidecar> {
@@ -0,0 +1,217 @@
+use std::collections::{BTreeSet, HashMap};
+use alloy::{
+    primitives::Address,
+    transports::{Transport, TransportErrorKind, TransportResult},
+};
+use suave_alloy_types::ConfidentialComputeRequest;
+use super::{SuaveProvider, SuaveProviderExt};
+
+
+/// Nonces signed locally, per sender.
+#[derive(Debug, Clone, Default)]
+pub struct NonceJournal {
+    signed: HashMap<Address, BTreeSet<u64>>,
+}
+
+impl NonceJournal {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, sender: Address, nonce: u64) {
+        self.signed.entry(sender).or_default().insert(nonce);
+    }
+
+    /// Record a signed request; ignored if sender or nonce are missing.
+    pub fn record_ccr(&mut self, ccr: &ConfidentialComputeRequest) {
+        let record = &ccr.confidential_compute_record;
+        if let (Some(sender), Some(nonce)) = (record.from, record.nonce) {
+            self.record(sender, nonce);
+        }
+    }
+
+    pub fn nonces(&self, sender: Address) -> impl Iterator<Item = u64> + '_ {
+        self.signed.get(&sender).into_iter().flatten().copied()
+    }
+
+    /// Nonce after the highest one signed.
+    pub fn next_nonce(&self, sender: Address) -> Option<u64> {
+        self.signed.get(&sender)?.last().map(|nonce| nonce + 1)
+    }
+
+    /// Drop entries below `nonce`, eg. once they are confirmed.
+    pub fn forget_below(&mut self, sender: Address, nonce: u64) {
+        if let Some(nonces) = self.signed.get_mut(&sender) {
+            *nonces = nonces.split_off(&nonce);
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NonceConflict {
+    /// Nonces consumed on-chain that the journal never signed: the key is
+    /// used elsewhere (eg. a manual wallet).
+    ExternalUsage { sender: Address, nonces: Vec<u64> },
+    /// Nonces between the on-chain count and the highest signed one that
+    /// were never signed; everything above them is stuck.
+    Gap { sender: Address, missing: Vec<u64> },
+}
+
+impl std::fmt::Display for NonceConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NonceConflict::ExternalUsage { sender, nonces } =>
+                write!(f, "Nonces {nonces:?} of {sender} were used outside the journal"),
+            NonceConflict::Gap { sender, missing } =>
+                write!(f, "Nonces {missing:?} of {sender} were never signed; later ones are stuck"),
+        }
+    }
+}
+
+impl std::error::Error for NonceConflict {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceReport {
+    pub sender: Address,
+    /// Pending transaction count of the sender.
+    pub on_chain: u64,
+    /// Nonce after the highest journaled one.
+    pub local_next: Option<u64>,
+    /// Journaled nonces the chain hasn't consumed yet.
+    pub pending: Vec<u64>,
+    /// Number of journaled nonces.
+    pub signed: usize,
+    pub conflicts: Vec<NonceConflict>,
+}
+
+impl NonceReport {
+
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Nonce to sign with next.
+    pub fn next_nonce(&self) -> u64 {
+        self.local_next.map_or(self.on_chain, |local| local.max(self.on_chain))
+    }
+
+}
+
+/// Compares the journal of a bot with the on-chain transaction count and
+/// reports conflicts before the next signing, instead of the bot and a
+/// manual wallet silently replacing each other's transactions.
+///
+/// ```ignore
+/// let nonce = auditor.preflight(&provider, sender).await?;
+/// ccr.confidential_compute_record.nonce = Some(nonce);
+/// signer.sign_in_place(&mut ccr).await?;
+/// auditor.journal_mut().record_ccr(&ccr);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NonceAuditor {
+    journal: NonceJournal,
+}
+
+impl NonceAuditor {
+
+    pub fn new(journal: NonceJournal) -> Self {
+        Self { journal }
+    }
+
+    pub fn journal(&self) -> &NonceJournal {
+        &self.journal
+    }
+
+    pub fn journal_mut(&mut self) -> &mut NonceJournal {
+        &mut self.journal
+    }
+
+    /// Compare the journal of `sender` with its on-chain count.
+    pub fn report(&self, sender: Address, on_chain: u64) -> NonceReport {
+        let signed = self.journal.nonces(sender).collect::<BTreeSet<_>>();
+        let local_next = self.journal.next_nonce(sender);
+        let mut conflicts = Vec::new();
+
+        // Only nonces from the first journaled one on are attributable
+        if let Some(&first) = signed.first() {
+            let external = (first..on_chain).filter(|n| !signed.contains(n)).collect::<Vec<_>>();
+            if !external.is_empty() {
+                conflicts.push(NonceConflict::ExternalUsage { sender, nonces: external });
+            }
+        }
+        if let Some(local_next) = local_next {
+            let missing = (on_chain..local_next).filter(|n| !signed.contains(n)).collect::<Vec<_>>();
+            if !missing.is_empty() {
+                conflicts.push(NonceConflict::Gap { sender, missing });
+            }
+        }
+
+        NonceReport {
+            sender,
+            on_chain,
+            local_next,
+            pending: signed.range(on_chain..).copied().collect(),
+            signed: signed.len(),
+            conflicts,
+        }
+    }
+
+    pub async fn audit<T>(&self, provider: &SuaveProvider<T>, sender: Address) -> TransportResult<NonceReport>
+        where T: Transport + Clone
+    {
+        let on_chain = provider.transaction_count(sender).await?;
+        Ok(self.report(sender, on_chain))
+    }
+
+    /// Audit `sender` and return the nonce to sign with next, failing on
+    /// the first conflict.
+    pub async fn preflight<T>(&self, provider: &SuaveProvider<T>, sender: Address) -> TransportResult<u64>
+        where T: Transport + Clone
+    {
+        let report = self.audit(provider, sender).await?;
+        match report.conflicts.first() {
+            Some(conflict) => Err(TransportErrorKind::custom(conflict.clone())),
+            None => Ok(report.next_nonce()),
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonce_report() {
+        let sender = Address::repeat_byte(0x11);
+        let mut auditor = NonceAuditor::default();
+        assert!(auditor.report(sender, 4).is_clean());
+        assert_eq!(auditor.report(sender, 4).next_nonce(), 4);
+
+        for nonce in [4, 5, 6] {
+            auditor.journal_mut().record(sender, nonce);
+        }
+        let report = auditor.report(sender, 5);
+        assert!(report.is_clean());
+        assert_eq!(report.pending, vec![5, 6]);
+        assert_eq!(report.next_nonce(), 7);
+
+        // A manual wallet sent nonces 7 and 8
+        let report = auditor.report(sender, 9);
+        assert_eq!(report.conflicts, vec![NonceConflict::ExternalUsage { sender, nonces: vec![7, 8] }]);
+        assert_eq!(report.next_nonce(), 9);
+
+        // Nonces 7 and 8 were never signed, 9 is stuck behind them
+        auditor.journal_mut().record(sender, 9);
+        let report = auditor.report(sender, 7);
+        assert_eq!(report.conflicts, vec![NonceConflict::Gap { sender, missing: vec![7, 8] }]);
+        assert_eq!(report.pending, vec![9]);
+
+        auditor.journal_mut().forget_below(sender, 9);
+        assert_eq!(auditor.journal().nonces(sender).collect::<Vec<_>>(), vec![9]);
+    }
+
+}
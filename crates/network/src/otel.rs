@@ -0,0 +1,156 @@
+use std::time::{Duration, SystemTime};
+use opentelemetry::{
+    trace::{Span, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+use alloy::primitives::{Address, TxHash};
+use suave_alloy_types::ConfidentialComputeRequest;
+use super::{Timings, TimedSubmission};
+
+
+/// Lifecycle of a submitted CCR, exported as an OpenTelemetry trace: a
+/// `ccr` root span with `build` (filling), `sign` (signing and encoding),
+/// `submit` (RPC round-trip) and, once observed, `included` children.
+/// Spans go to whatever exporter the tracer is set up with, eg. OTLP.
+///
+/// ```ignore
+/// let mut submission = provider.send_ccr_timed(ccr.clone(), &signer).await?;
+/// let mut lifecycle = CcrLifecycle::new(&ccr, &submission);
+/// // ... once the receipt and result are in
+/// lifecycle.set_included(receipt.block_number.unwrap(), SystemTime::now(), Some(result.len()));
+/// lifecycle.emit(&opentelemetry::global::tracer("suave-bot"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CcrLifecycle {
+    pub tx_hash: TxHash,
+    pub kettle: Option<Address>,
+    pub gas: Option<u128>,
+    pub timings: Timings,
+    /// Wall clock time filling started.
+    pub started_at: SystemTime,
+    pub included: Option<ObservedInclusion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObservedInclusion {
+    pub block_number: u64,
+    /// When the inclusion was observed.
+    pub observed_at: SystemTime,
+    /// Length of the confidential compute result.
+    pub result_size: Option<usize>,
+}
+
+impl CcrLifecycle {
+
+    /// Lifecycle of the submission of `ccr` (as filled and signed).
+    pub fn new(ccr: &ConfidentialComputeRequest, submission: &TimedSubmission) -> Self {
+        let timings = submission.timings;
+        let submitted = timings.fill + timings.sign + timings.encode + timings.round_trip;
+        let sent_at = SystemTime::now() - submission.sent_at().elapsed();
+        let record = &ccr.confidential_compute_record;
+        Self {
+            tx_hash: submission.tx_hash,
+            kettle: record.kettle_address,
+            gas: record.gas,
+            timings,
+            started_at: sent_at - submitted,
+            included: None,
+        }
+    }
+
+    pub fn set_included(&mut self, block_number: u64, observed_at: SystemTime, result_size: Option<usize>) {
+        self.included = Some(ObservedInclusion { block_number, observed_at, result_size });
+    }
+
+    /// Name, start and end of every child span.
+    pub fn spans(&self) -> Vec<(&'static str, SystemTime, SystemTime)> {
+        let t = &self.timings;
+        let mut spans = Vec::with_capacity(4);
+        let mut start = self.started_at;
+        for (name, duration) in [("build", t.fill), ("sign", t.sign + t.encode), ("submit", t.round_trip)] {
+            spans.push((name, start, start + duration));
+            start += duration;
+        }
+        if let Some(included) = self.included {
+            spans.push(("included", start, included.observed_at.max(start)));
+        }
+        spans
+    }
+
+    /// Time from the start of filling to the end of the last span.
+    pub fn duration(&self) -> Duration {
+        self.finished_at().duration_since(self.started_at).unwrap_or_default()
+    }
+
+    fn finished_at(&self) -> SystemTime {
+        self.spans().last().map_or(self.started_at, |(_, _, end)| *end)
+    }
+
+    /// Emit the lifecycle trace through `tracer`.
+    pub fn emit<T>(&self, tracer: &T)
+        where T: Tracer, T::Span: Send + Sync + 'static
+    {
+        let root = tracer.span_builder("ccr")
+            .with_start_time(self.started_at)
+            .with_attributes(self.attributes())
+            .start(tracer);
+        let cx = Context::current_with_span(root);
+        for (name, start, end) in self.spans() {
+            let mut span = tracer.span_builder(name)
+                .with_start_time(start)
+                .start_with_context(tracer, &cx);
+            span.end_with_timestamp(end);
+        }
+        cx.span().end_with_timestamp(self.finished_at());
+    }
+
+    fn attributes(&self) -> Vec<KeyValue> {
+        let mut attributes = vec![KeyValue::new("ccr.tx_hash", self.tx_hash.to_string())];
+        if let Some(kettle) = self.kettle {
+            attributes.push(KeyValue::new("ccr.kettle", kettle.to_string()));
+        }
+        if let Some(gas) = self.gas {
+            attributes.push(KeyValue::new("ccr.gas", gas as i64));
+        }
+        if let Some(included) = self.included {
+            attributes.push(KeyValue::new("ccr.block_number", included.block_number as i64));
+            if let Some(size) = included.result_size {
+                attributes.push(KeyValue::new("ccr.result_size", size as i64));
+            }
+        }
+        attributes
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lifecycle_spans() {
+        let ms = Duration::from_millis;
+        let started_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut lifecycle = CcrLifecycle {
+            tx_hash: TxHash::repeat_byte(0x01),
+            kettle: Some(Address::repeat_byte(0x03)),
+            gas: Some(0x0f4240),
+            timings: Timings { fill: ms(3), sign: ms(1), encode: ms(1), round_trip: ms(40), first_receipt_poll: None },
+            started_at,
+            included: None,
+        };
+        let names = |l: &CcrLifecycle| l.spans().into_iter().map(|(name, ..)| name).collect::<Vec<_>>();
+        assert_eq!(names(&lifecycle), vec!["build", "sign", "submit"]);
+        assert_eq!(lifecycle.duration(), ms(45));
+
+        lifecycle.set_included(7, started_at + ms(2045), Some(32));
+        let spans = lifecycle.spans();
+        assert_eq!(spans[1], ("sign", started_at + ms(3), started_at + ms(5)));
+        assert_eq!(spans[3], ("included", started_at + ms(45), started_at + ms(2045)));
+        assert_eq!(lifecycle.attributes().len(), 5);
+
+        lifecycle.emit(&opentelemetry::trace::noop::NoopTracer::new());
+    }
+
+}
@@ -0,0 +1,500 @@
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use tracing::Instrument;
+use alloy::{
+    primitives::{Bytes, TxHash},
+    providers::Provider,
+    rpc::types::eth::TransactionReceipt,
+    transports::{Transport, TransportError, TransportErrorKind, TransportResult},
+};
+use suave_alloy_types::{consts::ProtocolLimits, explorer, ConfidentialCallResponse, ConfidentialComputeRequest};
+#[cfg(feature = "encryption")]
+use suave_alloy_types::{unix_now, Hpke, InputsEncryption, KettleKey};
+use super::{SuaveProvider, SuaveSigner};
+
+
+/// State a pipeline threads through its stages.
+pub struct PipelineContext<'a, T>
+    where T: Transport + Clone
+{
+    pub provider: &'a SuaveProvider<T>,
+    pub signer: &'a SuaveSigner,
+    pub ccr: ConfidentialComputeRequest,
+    /// 2718 encoding of the signed request.
+    pub encoded: Option<Bytes>,
+    pub tx_hash: Option<TxHash>,
+    pub receipt: Option<TransactionReceipt>,
+    pub response: Option<ConfidentialCallResponse>,
+}
+
+impl<T> PipelineContext<'_, T>
+    where T: Transport + Clone
+{
+
+    /// Give back the nonce the fill reserved, after a failure. Once the
+    /// request got encoded it may have reached the node, so the cached
+    /// nonce is re-fetched instead.
+    fn return_nonce(&self) {
+        let Some(nonce) = self.ccr.confidential_compute_record.nonce else { return };
+        if self.tx_hash.is_some() {
+            return;
+        }
+        let sender = self.signer.default_signer_address();
+        match self.encoded {
+            None => self.provider.release_nonce(sender, nonce),
+            Some(_) => self.provider.reset_nonce(sender),
+        }
+    }
+
+}
+
+/// A step of a submission pipeline. Implement it for custom steps (eg.
+/// encrypting confidential inputs or checking a budget) and add them with
+/// `Pipeline::stage`.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait Stage<T>: Send + Sync
+    where T: Transport + Clone
+{
+    fn name(&self) -> &str;
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageTiming {
+    pub stage: String,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct PipelineOutcome {
+    pub ccr: ConfidentialComputeRequest,
+    pub tx_hash: Option<TxHash>,
+    pub receipt: Option<TransactionReceipt>,
+    pub response: Option<ConfidentialCallResponse>,
+    /// Explorer link to the broadcast CCR, on chains with a known explorer.
+    pub explorer_url: Option<String>,
+    /// Timing of every completed stage, in execution order.
+    pub timings: Vec<StageTiming>,
+}
+
+#[derive(Debug)]
+pub struct PipelineError {
+    /// Name of the failed stage.
+    pub stage: String,
+    pub error: TransportError,
+    /// Timings of the stages completed before the failure.
+    pub timings: Vec<StageTiming>,
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Stage `{}` failed: {}", self.stage, self.error)
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// Declarative CCR submission: stages run in the order they were added,
+/// each one timed and run in a `ccr_pipeline_stage` tracing span.
+///
+/// ```ignore
+/// let outcome = Pipeline::standard().run(&provider, &signer, ccr).await?;
+/// ```
+pub struct Pipeline<T>
+    where T: Transport + Clone
+{
+    stages: Vec<Box<dyn Stage<T>>>,
+}
+
+impl<T> Default for Pipeline<T>
+    where T: Transport + Clone
+{
+    fn default() -> Self {
+        Self { stages: Vec::new() }
+    }
+}
+
+impl<T> Pipeline<T>
+    where T: Transport + Clone
+{
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// fill → validate → sign → broadcast → await receipt → decode.
+    pub fn standard() -> Self {
+        Self::new()
+            .fill()
+            .validate(ProtocolLimits::default())
+            .sign()
+            .broadcast()
+            .await_receipt(Duration::from_millis(500), Duration::from_secs(30))
+            .decode()
+    }
+
+    /// fill → encrypt → validate → sign → broadcast → await receipt →
+    /// decode, sealing the confidential inputs to `recipients`. Limits are
+    /// checked on the sealed inputs, as sent.
+    #[cfg(feature = "encryption")]
+    pub fn standard_encrypted(recipients: Vec<KettleKey>) -> Self {
+        Self::new()
+            .fill()
+            .encrypt(recipients)
+            .validate(ProtocolLimits::default())
+            .sign()
+            .broadcast()
+            .await_receipt(Duration::from_millis(500), Duration::from_secs(30))
+            .decode()
+    }
+
+    pub fn stage(mut self, stage: impl Stage<T> + 'static) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    pub fn fill(self) -> Self {
+        self.stage(Fill)
+    }
+
+    pub fn validate(self, limits: ProtocolLimits) -> Self {
+        self.stage(Validate(limits))
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn encrypt(self, recipients: Vec<KettleKey>) -> Self {
+        self.stage(Encrypt { scheme: Hpke, recipients })
+    }
+
+    pub fn sign(self) -> Self {
+        self.stage(Sign)
+    }
+
+    pub fn broadcast(self) -> Self {
+        self.stage(Broadcast)
+    }
+
+    pub fn await_receipt(self, poll_interval: Duration, timeout: Duration) -> Self {
+        self.stage(AwaitReceipt { poll_interval, timeout })
+    }
+
+    pub fn decode(self) -> Self {
+        self.stage(Decode)
+    }
+
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    pub async fn run(
+        &self,
+        provider: &SuaveProvider<T>,
+        signer: &SuaveSigner,
+        ccr: ConfidentialComputeRequest,
+    ) -> Result<PipelineOutcome, PipelineError> {
+        let reserves_nonce = ccr.confidential_compute_record.nonce.is_none();
+        let mut ctx = PipelineContext {
+            provider,
+            signer,
+            ccr,
+            encoded: None,
+            tx_hash: None,
+            receipt: None,
+            response: None,
+        };
+        let mut timings = Vec::with_capacity(self.stages.len());
+        for stage in &self.stages {
+            let start = Instant::now();
+            let span = tracing::debug_span!("ccr_pipeline_stage", stage = stage.name());
+            let result = stage.run(&mut ctx).instrument(span).await;
+            let elapsed = start.elapsed();
+            if let Err(error) = result {
+                tracing::warn!(stage = stage.name(), ?elapsed, %error, "CCR pipeline stage failed");
+                if reserves_nonce {
+                    ctx.return_nonce();
+                }
+                return Err(PipelineError { stage: stage.name().to_string(), error, timings });
+            }
+            tracing::debug!(stage = stage.name(), ?elapsed, "CCR pipeline stage done");
+            timings.push(StageTiming { stage: stage.name().to_string(), elapsed });
+        }
+        let explorer_url = ctx.tx_hash.zip(ctx.ccr.confidential_compute_record.chain_id)
+            .and_then(|(tx_hash, chain_id)| explorer::explorer_url(chain_id, tx_hash));
+        Ok(PipelineOutcome {
+            explorer_url,
+            ccr: ctx.ccr,
+            tx_hash: ctx.tx_hash,
+            receipt: ctx.receipt,
+            response: ctx.response,
+            timings,
+        })
+    }
+
+}
+
+fn missing(what: &str) -> TransportError {
+    TransportErrorKind::custom_str(&format!("Missing {what}; add the stage producing it first"))
+}
+
+/// Fill missing nonce, chain id, gas price and kettle address. Fails if
+/// the gas limit isn't set, as it can't be estimated.
+pub struct Fill;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> Stage<T> for Fill
+    where T: Transport + Clone
+{
+    fn name(&self) -> &str {
+        "fill"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+        ctx.provider.fill_ccr(&mut ctx.ccr, ctx.signer).await
+    }
+}
+
+/// Seal the confidential inputs to kettle keys with `scheme`.
+#[cfg(feature = "encryption")]
+pub struct Encrypt<E: InputsEncryption = Hpke> {
+    pub scheme: E,
+    pub recipients: Vec<E::RecipientKey>,
+}
+
+#[cfg(feature = "encryption")]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T, E> Stage<T> for Encrypt<E>
+    where T: Transport + Clone, E: InputsEncryption + Send + Sync, E::RecipientKey: Send + Sync
+{
+    fn name(&self) -> &str {
+        "encrypt"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+        let sealed = self.scheme.seal(&ctx.ccr.confidential_inputs, &self.recipients, unix_now())
+            .map_err(TransportErrorKind::custom)?;
+        ctx.ccr.set_confidential_inputs(sealed);
+        Ok(())
+    }
+}
+
+/// Check the confidential inputs hash and protocol limits.
+pub struct Validate(pub ProtocolLimits);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> Stage<T> for Validate
+    where T: Transport + Clone
+{
+    fn name(&self) -> &str {
+        "validate"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+        ctx.ccr.validate_inputs_hash().map_err(TransportErrorKind::custom)?;
+        ctx.ccr.validate_limits(&self.0).map_err(TransportErrorKind::custom)
+    }
+}
+
+/// Sign with the default signer and encode the request.
+pub struct Sign;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> Stage<T> for Sign
+    where T: Transport + Clone
+{
+    fn name(&self) -> &str {
+        "sign"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+        ctx.signer.sign_in_place(&mut ctx.ccr).await
+            .map_err(TransportErrorKind::custom)?;
+        ctx.encoded = Some(ctx.ccr.rlp_encode().map_err(TransportErrorKind::custom)?);
+        Ok(())
+    }
+}
+
+/// Submit the encoded request.
+pub struct Broadcast;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> Stage<T> for Broadcast
+    where T: Transport + Clone
+{
+    fn name(&self) -> &str {
+        "broadcast"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+        let encoded = ctx.encoded.as_ref().ok_or_else(|| missing("encoded request"))?;
+        ctx.tx_hash = Some(ctx.provider.send_raw_ccr(encoded).await?);
+        Ok(())
+    }
+}
+
+/// Poll the receipt until it's available or the timeout passes.
+pub struct AwaitReceipt {
+    pub poll_interval: Duration,
+    pub timeout: Duration,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> Stage<T> for AwaitReceipt
+    where T: Transport + Clone
+{
+    fn name(&self) -> &str {
+        "await_receipt"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+        let tx_hash = ctx.tx_hash.ok_or_else(|| missing("tx hash"))?;
+        let start = Instant::now();
+        loop {
+            let receipt: Option<TransactionReceipt> = ctx.provider.client()
+                .request("eth_getTransactionReceipt", (tx_hash,)).await?;
+            if receipt.is_some() {
+                ctx.receipt = receipt;
+                return Ok(());
+            }
+            if start.elapsed() >= self.timeout {
+                return Err(TransportErrorKind::custom_str(&format!("No receipt for {tx_hash} after {:?}", self.timeout)));
+            }
+            ctx.provider.sleep(self.poll_interval).await?;
+        }
+    }
+}
+
+/// Fetch the included request with the kettle's result.
+pub struct Decode;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T> Stage<T> for Decode
+    where T: Transport + Clone
+{
+    fn name(&self) -> &str {
+        "decode"
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+        let tx_hash = ctx.tx_hash.ok_or_else(|| missing("tx hash"))?;
+        let raw: Option<serde_json::Value> = ctx.provider.client()
+            .request("eth_getTransactionByHash", (tx_hash,)).await?;
+        let raw = raw.ok_or_else(|| missing("transaction on the node"))?;
+        ctx.response = Some(ConfidentialCallResponse::from_json(raw).map_err(TransportErrorKind::custom)?);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::wallet::LocalWallet;
+    use suave_alloy_types::fixtures;
+    use super::*;
+    use super::super::CompressedHttp;
+
+    struct Tag;
+
+    #[async_trait]
+    impl<T> Stage<T> for Tag
+        where T: Transport + Clone
+    {
+        fn name(&self) -> &str {
+            "tag"
+        }
+
+        async fn run(&self, ctx: &mut PipelineContext<'_, T>) -> TransportResult<()> {
+            ctx.ccr = ctx.ccr.clone().with_confidential_inputs(Bytes::from_static(b"tagged"));
+            Ok(())
+        }
+    }
+
+    fn filled_ccr() -> ConfidentialComputeRequest {
+        ConfidentialComputeRequest::new(fixtures::unsigned_record(), None)
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_stages() {
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let signer = SuaveSigner::new(wallet);
+
+        let pipeline = Pipeline::<CompressedHttp>::new()
+            .stage(Tag)
+            .validate(ProtocolLimits::default())
+            .sign();
+        assert_eq!(pipeline.stage_names(), vec!["tag", "validate", "sign"]);
+        let outcome = pipeline.run(&provider, &signer, filled_ccr()).await.unwrap();
+        assert_eq!(outcome.timings.len(), 3);
+        assert_eq!(outcome.explorer_url, None);
+        assert_eq!(outcome.ccr.confidential_inputs, Bytes::from_static(b"tagged"));
+        assert!(outcome.ccr.confidential_compute_record.signature.is_some());
+
+        // Broadcasting to an unreachable node fails in that stage
+        let err = Pipeline::<CompressedHttp>::new().sign().broadcast()
+            .run(&provider, &signer, filled_ccr()).await.unwrap_err();
+        assert_eq!(err.stage, "broadcast");
+        assert_eq!(err.timings.len(), 1);
+
+        let err = Pipeline::<CompressedHttp>::new().decode()
+            .run(&provider, &signer, filled_ccr()).await.unwrap_err();
+        assert_eq!(err.stage, "decode");
+
+        // No gas limit: the fill stage fails instead of the signer panicking
+        let mut no_gas = filled_ccr();
+        no_gas.confidential_compute_record.gas = None;
+        let err = Pipeline::<CompressedHttp>::standard().run(&provider, &signer, no_gas).await.unwrap_err();
+        assert_eq!(err.stage, "fill");
+        assert!(err.timings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failure_returns_nonce() {
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let signer = SuaveSigner::new(wallet);
+        let sender = signer.default_signer_address();
+        provider.set_next_nonce(sender, 5);
+
+        let mut ccr = filled_ccr();
+        ccr.confidential_compute_record.nonce = None;
+        let err = Pipeline::<CompressedHttp>::new().fill().decode()
+            .run(&provider, &signer, ccr).await.unwrap_err();
+        assert_eq!(err.stage, "decode");
+        assert_eq!(provider.next_nonce(sender).await.unwrap(), 5);
+
+        // A nonce set by the caller isn't the cache's to take back
+        let mut ccr = filled_ccr();
+        ccr.confidential_compute_record.nonce = Some(5);
+        let err = Pipeline::<CompressedHttp>::new().fill().decode()
+            .run(&provider, &signer, ccr).await.unwrap_err();
+        assert_eq!(err.stage, "decode");
+        assert_eq!(provider.next_nonce(sender).await.unwrap(), 6);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[tokio::test]
+    async fn test_pipeline_encrypt() {
+        use suave_alloy_types::{EncryptedInputs, KettleSecretKey};
+
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let kettle_key = KettleSecretKey::generate(0);
+        let ccr = filled_ccr().with_confidential_inputs(Bytes::from_static(b"secret"));
+
+        let pipeline = Pipeline::<CompressedHttp>::new().encrypt(vec![kettle_key.public_key().clone()]).sign();
+        let outcome = pipeline.run(&provider, &SuaveSigner::new(wallet), ccr).await.unwrap();
+        let sealed = &outcome.ccr.confidential_inputs;
+        assert_eq!(EncryptedInputs::decode(sealed).unwrap().decrypt(&kettle_key).unwrap(), Bytes::from_static(b"secret"));
+        outcome.ccr.validate_inputs_hash().unwrap();
+    }
+
+}
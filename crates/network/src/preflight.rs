@@ -0,0 +1,149 @@
+use serde_json::json;
+use alloy::{
+    json_abi::JsonAbi,
+    primitives::{keccak256, Address, Bytes, FixedBytes, B256},
+    providers::Provider,
+    transports::Transport,
+};
+use suave_alloy_types::ConfidentialComputeRequest;
+use super::{KettleError, SuaveNetwork, SuaveProviderExt};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightError {
+    /// Nothing deployed at the target, eg. a mistyped address or the wrong
+    /// chain.
+    NoCodeAtTarget(Address),
+    /// Contract creation, there is no target to check.
+    Create,
+    /// Selector of the calldata not in the target's ABI.
+    UnknownSelector { target: Address, selector: FixedBytes<4> },
+    /// Fetching the code or simulating the call failed (a reverted
+    /// simulation is `KettleError::ExecutionReverted`).
+    Node(KettleError),
+}
+
+impl std::fmt::Display for PreflightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreflightError::NoCodeAtTarget(target) => write!(f, "No contract deployed at {target}"),
+            PreflightError::Create => write!(f, "Contract creation has no target to check"),
+            PreflightError::UnknownSelector { target, selector } =>
+                write!(f, "Selector {selector} is not a function of {target}"),
+            PreflightError::Node(err) => write!(f, "Pre-flight failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PreflightError {}
+
+/// Optional checks of a CCR's target before it is signed, so a mistyped
+/// address or calldata fails early instead of burning a nonce. The target
+/// must have code; its calldata selector is checked against the ABI if
+/// one is given, and the call is simulated with `eth_call` if enabled.
+#[derive(Debug, Clone, Default)]
+pub struct Preflight {
+    abi: Option<JsonAbi>,
+    simulate: bool,
+}
+
+impl Preflight {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_abi(mut self, abi: JsonAbi) -> Self {
+        self.abi = Some(abi);
+        self
+    }
+
+    /// Also run the call with `eth_call`. Confidential precompiles don't
+    /// run outside a kettle, so only use this for targets whose entry
+    /// point doesn't need them.
+    pub fn with_simulation(mut self) -> Self {
+        self.simulate = true;
+        self
+    }
+
+    /// Run the checks; returns the code hash of the target.
+    pub async fn check<P, T>(&self, provider: &P, ccr: &ConfidentialComputeRequest) -> Result<B256, PreflightError>
+        where P: Provider<T, SuaveNetwork>, T: Transport + Clone
+    {
+        let record = &ccr.confidential_compute_record;
+        let target = record.target().ok_or(PreflightError::Create)?;
+        let code = provider.code(target).await.map_err(|e| PreflightError::Node(e.into()))?;
+        if code.is_empty() {
+            return Err(PreflightError::NoCodeAtTarget(target));
+        }
+        self.check_selector(target, &record.input)?;
+        if self.simulate {
+            let call = json!({ "to": target, "data": record.input, "value": record.value });
+            let _: Bytes = provider.client().request("eth_call", (call, "latest")).await
+                .map_err(|e| PreflightError::Node(e.into()))?;
+        }
+        Ok(keccak256(&code))
+    }
+
+    /// Check the selector of `input` against the ABI. Calls without a
+    /// selector (plain transfers, fallback) pass.
+    pub fn check_selector(&self, target: Address, input: &[u8]) -> Result<(), PreflightError> {
+        let (Some(abi), Some(selector)) = (&self.abi, input.get(..4)) else {
+            return Ok(());
+        };
+        let selector = FixedBytes::<4>::from_slice(selector);
+        if abi.functions().any(|function| function.selector() == selector) {
+            Ok(())
+        } else {
+            Err(PreflightError::UnknownSelector { target, selector })
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::TxKind;
+    use suave_alloy_types::fixtures;
+    use super::*;
+    use super::super::SuaveProvider;
+
+    fn abi() -> JsonAbi {
+        serde_json::from_str(r#"[{
+            "type": "function",
+            "name": "queryLatestPrice",
+            "inputs": [{"name": "ticker", "type": "string"}],
+            "outputs": [{"name": "price", "type": "uint256"}],
+            "stateMutability": "view"
+        }]"#).unwrap()
+    }
+
+    #[test]
+    fn test_selector_check() {
+        let ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        let record = &ccr.confidential_compute_record;
+        let target = record.target().unwrap();
+        let preflight = Preflight::new().with_abi(abi());
+        assert_eq!(preflight.check_selector(target, &record.input), Ok(()));
+        assert_eq!(preflight.check_selector(target, &[]), Ok(()));
+        assert_eq!(
+            preflight.check_selector(target, &[0xde, 0xad, 0xbe, 0xef]),
+            Err(PreflightError::UnknownSelector { target, selector: FixedBytes::from([0xde, 0xad, 0xbe, 0xef]) })
+        );
+        assert_eq!(Preflight::new().check_selector(target, &[0xde, 0xad, 0xbe, 0xef]), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_node() {
+        let ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        let err = Preflight::new().check(&provider, &ccr).await.unwrap_err();
+        assert!(matches!(err, PreflightError::Node(KettleError::Transport(_))));
+
+        let mut create = ccr;
+        create.confidential_compute_record.to = TxKind::Create;
+        assert_eq!(Preflight::new().check(&provider, &create).await, Err(PreflightError::Create));
+    }
+
+}
@@ -0,0 +1,279 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use tokio::{sync::Mutex, time::Instant};
+use alloy::{
+    primitives::Bytes,
+    signers::{Result as SignerResult, Error as SignerError},
+    transports::{Transport, TransportErrorKind, TransportResult},
+};
+use suave_alloy_types::ConfidentialComputeRequest;
+use super::{SuaveProvider, SuaveSigner};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresignedEntry {
+    pub ccr: ConfidentialComputeRequest,
+    /// 2718 encoding, ready for `send_raw_ccr`.
+    pub encoded: Bytes,
+    pub deadline: Instant,
+}
+
+impl PresignedEntry {
+    pub fn nonce(&self) -> u64 {
+        self.ccr.confidential_compute_record.nonce.unwrap_or_default()
+    }
+}
+
+/// CCRs signed ahead of time over a nonce range, so a trigger only pays for
+/// the submission round-trip.
+///
+/// `refill` tracks a nonce range given by the caller: entries whose nonce
+/// got consumed elsewhere or whose deadline passed are dropped, and the
+/// range is topped up with fresh signatures. `refresh` instead reserves
+/// nonces from the provider's nonce cache, and `spawn_refresh` runs it in
+/// the background to re-sign entries before they expire. Expired entries
+/// are never dropped by `take`/`take_next`: they hold a reserved nonce
+/// and stay until re-signed, so the sender's nonces have no gaps.
+#[derive(Debug, Clone)]
+pub struct PresignedPool {
+    /// Filled request (chain id, gas, gas price, kettle...) except the nonce.
+    template: ConfidentialComputeRequest,
+    signer: SuaveSigner,
+    depth: u64,
+    ttl: Duration,
+    entries: BTreeMap<u64, PresignedEntry>,
+}
+
+impl PresignedPool {
+
+    pub fn new(template: ConfidentialComputeRequest, signer: SuaveSigner, depth: u64, ttl: Duration) -> Self {
+        Self { template, signer, depth, ttl, entries: BTreeMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn nonces(&self) -> impl Iterator<Item = u64> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Replace the template; all entries signed from the old one are dropped.
+    pub fn set_template(&mut self, template: ConfidentialComputeRequest) {
+        self.template = template;
+        self.entries.clear();
+    }
+
+    /// Drop entries below `next_nonce` or expired, then sign entries for
+    /// every missing nonce in `next_nonce..next_nonce + depth`.
+    pub async fn refill(&mut self, next_nonce: u64) -> SignerResult<()> {
+        self.refill_at(next_nonce, Instant::now()).await
+    }
+
+    pub async fn refill_at(&mut self, next_nonce: u64, now: Instant) -> SignerResult<()> {
+        self.invalidate_below(next_nonce);
+        self.prune_expired_at(now);
+        for nonce in next_nonce..next_nonce + self.depth {
+            if self.entries.contains_key(&nonce) {
+                continue;
+            }
+            let entry = self.sign_entry(nonce, now).await?;
+            self.entries.insert(nonce, entry);
+        }
+        Ok(())
+    }
+
+    /// Re-sign expired entries and top the pool up to `depth` entries with
+    /// nonces reserved through `SuaveProvider::next_nonce`, so submissions
+    /// sharing the provider never reuse a presigned nonce.
+    pub async fn refresh<T>(&mut self, provider: &SuaveProvider<T>) -> TransportResult<()>
+        where T: Transport + Clone
+    {
+        self.refresh_until(provider, Instant::now()).await
+    }
+
+    /// `refresh`, also re-signing the entries that expire by `horizon`.
+    async fn refresh_until<T>(&mut self, provider: &SuaveProvider<T>, horizon: Instant) -> TransportResult<()>
+        where T: Transport + Clone
+    {
+        let expiring = self.entries.iter()
+            .filter(|(_, entry)| entry.deadline <= horizon)
+            .map(|(nonce, _)| *nonce)
+            .collect::<Vec<_>>();
+        for nonce in expiring {
+            let entry = self.sign_entry(nonce, Instant::now()).await.map_err(TransportErrorKind::custom)?;
+            self.entries.insert(nonce, entry);
+        }
+        let sender = self.signer.default_signer_address();
+        while (self.entries.len() as u64) < self.depth {
+            let nonce = provider.next_nonce(sender).await?;
+            let entry = self.sign_entry(nonce, Instant::now()).await.map_err(TransportErrorKind::custom)?;
+            self.entries.insert(nonce, entry);
+        }
+        Ok(())
+    }
+
+    /// `refresh` `pool` every `interval` until the provider shuts down,
+    /// re-signing entries that would expire before the next run. Failed
+    /// runs are retried on the next one.
+    pub fn spawn_refresh<T>(pool: Arc<Mutex<Self>>, provider: SuaveProvider<T>, interval: Duration)
+        where T: Transport + Clone + Send + Sync + 'static
+    {
+        let task_provider = provider.clone();
+        provider.spawn(async move {
+            let mut shutdown = task_provider.shutdown_signal();
+            while !shutdown.is_shutdown() {
+                let horizon = Instant::now() + interval;
+                if let Err(error) = pool.lock().await.refresh_until(&task_provider, horizon).await {
+                    tracing::warn!(%error, "Presigned pool refresh failed");
+                }
+                if !shutdown.sleep(interval).await {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Take the entry for `nonce` if it is still valid. An expired entry
+    /// stays in the pool until `refresh` (or `refill`) re-signs it.
+    pub fn take(&mut self, nonce: u64) -> Option<PresignedEntry> {
+        self.take_at(nonce, Instant::now())
+    }
+
+    pub fn take_at(&mut self, nonce: u64, now: Instant) -> Option<PresignedEntry> {
+        if self.entries.get(&nonce)?.deadline <= now {
+            return None;
+        }
+        self.entries.remove(&nonce)
+    }
+
+    /// Take the entry with the lowest nonce if it is still valid. Later
+    /// nonces aren't handed out while it waits to be re-signed, they
+    /// would be stuck behind it.
+    pub fn take_next(&mut self) -> Option<PresignedEntry> {
+        let nonce = *self.entries.keys().next()?;
+        self.take_at(nonce, Instant::now())
+    }
+
+    /// Drop entries whose nonce was consumed (by another submission).
+    pub fn invalidate_below(&mut self, next_nonce: u64) {
+        self.entries = self.entries.split_off(&next_nonce);
+    }
+
+    /// Drop expired entries. Only for pools tracking a caller-given range
+    /// (`refill` re-signs the dropped nonces); in `refresh` pools they are
+    /// reserved and would leave a gap.
+    pub fn prune_expired_at(&mut self, now: Instant) {
+        self.entries.retain(|_, entry| entry.deadline > now);
+    }
+
+    async fn sign_entry(&self, nonce: u64, now: Instant) -> SignerResult<PresignedEntry> {
+        let mut ccr = self.template.clone();
+        ccr.confidential_compute_record.nonce = Some(nonce);
+        self.signer.sign_in_place(&mut ccr).await?;
+        let encoded = ccr.rlp_encode().map_err(SignerError::other)?;
+        Ok(PresignedEntry { ccr, encoded, deadline: now + self.ttl })
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::{Address, TxKind}, signers::wallet::LocalWallet};
+    use suave_alloy_types::fixtures;
+    use super::*;
+
+    fn pool(depth: u64, ttl: Duration) -> PresignedPool {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let mut record = fixtures::unsigned_record();
+        record.nonce = None;
+        record.to = TxKind::Call(Address::repeat_byte(0xc8));
+        let template = ConfidentialComputeRequest::new(record, None);
+        PresignedPool::new(template, SuaveSigner::new(wallet), depth, ttl)
+    }
+
+    #[tokio::test]
+    async fn test_presigned_pool() {
+        let mut pool = pool(3, Duration::from_secs(12));
+        let start = Instant::now();
+        pool.refill_at(5, start).await.unwrap();
+        assert_eq!(pool.nonces().collect::<Vec<_>>(), vec![5, 6, 7]);
+
+        let entry = pool.take_at(5, start).unwrap();
+        assert_eq!(entry.nonce(), 5);
+        assert!(entry.ccr.confidential_compute_record.signature.is_some());
+        assert_eq!(entry.encoded[0], ConfidentialComputeRequest::TYPE);
+
+        // Nonce 6 got consumed elsewhere
+        pool.refill_at(7, start).await.unwrap();
+        assert_eq!(pool.nonces().collect::<Vec<_>>(), vec![7, 8, 9]);
+
+        // Everything expires and gets re-signed
+        let later = start + Duration::from_secs(12);
+        assert!(pool.take_at(7, later).is_none());
+        pool.refill_at(8, later).await.unwrap();
+        assert_eq!(pool.len(), 3);
+        assert!(pool.take_at(8, later).is_some_and(|e| e.deadline > later));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reserves_nonces() {
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        let mut pool = pool(3, Duration::from_secs(12));
+        let sender = pool.signer.default_signer_address();
+        provider.set_next_nonce(sender, 5);
+
+        pool.refresh(&provider).await.unwrap();
+        assert_eq!(pool.nonces().collect::<Vec<_>>(), vec![5, 6, 7]);
+        // Submissions through the provider skip the presigned nonces
+        assert_eq!(provider.next_nonce(sender).await.unwrap(), 8);
+
+        assert_eq!(pool.take_next().unwrap().nonce(), 5);
+        pool.refresh(&provider).await.unwrap();
+        assert_eq!(pool.nonces().collect::<Vec<_>>(), vec![6, 7, 9]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_entries_keep_nonces() {
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        let ttl = Duration::from_secs(12);
+        let mut pool = pool(2, ttl);
+        let sender = pool.signer.default_signer_address();
+        provider.set_next_nonce(sender, 5);
+        pool.refresh(&provider).await.unwrap();
+
+        tokio::time::advance(ttl).await;
+        assert!(pool.take_next().is_none());
+        assert!(pool.take(6).is_none());
+        assert_eq!(pool.nonces().collect::<Vec<_>>(), vec![5, 6]);
+
+        pool.refresh(&provider).await.unwrap();
+        assert_eq!(pool.take_next().unwrap().nonce(), 5);
+        assert_eq!(pool.take_next().unwrap().nonce(), 6);
+        assert_eq!(provider.next_nonce(sender).await.unwrap(), 7);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_background_refresh() {
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        let ttl = Duration::from_millis(50);
+        let pool = pool(2, ttl);
+        provider.set_next_nonce(pool.signer.default_signer_address(), 0);
+        let pool = Arc::new(Mutex::new(pool));
+
+        let start = Instant::now();
+        PresignedPool::spawn_refresh(pool.clone(), provider.clone(), Duration::from_millis(10));
+        tokio::time::sleep(3 * ttl).await;
+        provider.shutdown().await;
+
+        // Re-signed by every run before expiring, never topped up past depth
+        let pool = pool.lock().await;
+        assert_eq!(pool.nonces().collect::<Vec<_>>(), vec![0, 1]);
+        assert!(pool.entries.values().all(|entry| entry.deadline > start + 3 * ttl));
+    }
+
+}
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+use alloy::transports::{Transport, TransportResult};
+use suave_alloy_types::SuaveTxEnvelope;
+use super::{BlockWalker, SuaveProvider, WalkedBlock};
+
+
+/// Lowest gas price (max fee for dynamic fee records) a CCR was included
+/// at in a block, ie. what it took to make it in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BlockFloor {
+    number: u64,
+    price: u128,
+}
+
+/// Estimates how likely a CCR is to be included at a gas price, from the
+/// prices of the CCRs included in the last `window` blocks that had any.
+/// A price clears a block if it is at least the block's lowest included
+/// one; the probability is the share of sampled blocks it clears. Rough,
+/// as it ignores block fullness and what got dropped, but cheap to keep
+/// up to date.
+///
+/// ```ignore
+/// let head = provider.get_block_number().await?;
+/// let mut estimator = InclusionEstimator::new(50, head.saturating_sub(200));
+/// estimator.poll(&provider).await?;
+/// let gas_price = estimator.suggest_price(0.9).unwrap_or(fallback);
+/// ```
+#[derive(Debug, Clone)]
+pub struct InclusionEstimator {
+    window: usize,
+    walker: BlockWalker,
+    floors: VecDeque<BlockFloor>,
+}
+
+impl InclusionEstimator {
+
+    /// Sample the last `window` blocks with CCRs, walking from `from_block`.
+    pub fn new(window: usize, from_block: u64) -> Self {
+        Self { window: window.max(1), walker: BlockWalker::new(from_block, None), floors: VecDeque::new() }
+    }
+
+    /// Walk the blocks produced since the last call. Returns the number of
+    /// blocks read.
+    pub async fn poll<T>(&mut self, provider: &SuaveProvider<T>) -> TransportResult<usize>
+        where T: Transport + Clone
+    {
+        let mut read = 0;
+        while let Some(block) = self.walker.next_block(provider).await? {
+            self.record_block(&block);
+            read += 1;
+        }
+        Ok(read)
+    }
+
+    pub fn record_block(&mut self, block: &WalkedBlock) {
+        let prices = block.transactions.iter().filter_map(|(_, tx)| match tx {
+            SuaveTxEnvelope::ConfidentialComputeResult(result) => result.request_record.max_gas_price(),
+            _ => None,
+        });
+        self.record_prices(block.number, prices);
+    }
+
+    /// Record the gas prices of the CCRs included in block `number`;
+    /// blocks without any tell nothing and are skipped.
+    pub fn record_prices(&mut self, number: u64, prices: impl IntoIterator<Item = u128>) {
+        let Some(price) = prices.into_iter().min() else { return };
+        self.floors.push_back(BlockFloor { number, price });
+        while self.floors.len() > self.window {
+            self.floors.pop_front();
+        }
+    }
+
+    /// Number of blocks sampled.
+    pub fn samples(&self) -> usize {
+        self.floors.len()
+    }
+
+    /// Latest block sampled.
+    pub fn last_block(&self) -> Option<u64> {
+        self.floors.back().map(|floor| floor.number)
+    }
+
+    /// Share of sampled blocks `gas_price` would have made it into; `None`
+    /// without samples.
+    pub fn inclusion_probability(&self, gas_price: u128) -> Option<f64> {
+        if self.floors.is_empty() {
+            return None;
+        }
+        let cleared = self.floors.iter().filter(|floor| floor.price <= gas_price).count();
+        Some(cleared as f64 / self.floors.len() as f64)
+    }
+
+    /// Lowest gas price with an inclusion probability of at least `target`
+    /// (clamped to 0..=1); `None` without samples.
+    pub fn suggest_price(&self, target: f64) -> Option<u128> {
+        let mut prices = self.floors.iter().map(|floor| floor.price).collect::<Vec<_>>();
+        prices.sort_unstable();
+        let needed = (target.clamp(0.0, 1.0) * prices.len() as f64).ceil() as usize;
+        prices.get(needed.max(1) - 1).copied()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimates() {
+        let mut estimator = InclusionEstimator::new(4, 0);
+        assert_eq!((estimator.inclusion_probability(1), estimator.suggest_price(0.5)), (None, None));
+
+        estimator.record_prices(1, [50, 10, 30]);
+        estimator.record_prices(2, []);
+        estimator.record_prices(3, [20]);
+        estimator.record_prices(4, [40, 45]);
+        estimator.record_prices(5, [30]);
+        assert_eq!((estimator.samples(), estimator.last_block()), (4, Some(5)));
+
+        // Floors 10, 20, 40, 30
+        assert_eq!(estimator.inclusion_probability(9), Some(0.0));
+        assert_eq!(estimator.inclusion_probability(20), Some(0.5));
+        assert_eq!(estimator.inclusion_probability(40), Some(1.0));
+        assert_eq!(estimator.suggest_price(0.5), Some(20));
+        assert_eq!(estimator.suggest_price(0.6), Some(30));
+        assert_eq!(estimator.suggest_price(1.0), Some(40));
+        assert_eq!(estimator.suggest_price(0.0), Some(10));
+
+        // Block 1 leaves the window
+        estimator.record_prices(6, [35]);
+        assert_eq!(estimator.inclusion_probability(10), Some(0.0));
+    }
+
+}
@@ -1,50 +1,320 @@
 use reqwest::Client as ReqwestClient;
-use std::sync::{Arc, OnceLock};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock, RwLockWriteGuard};
 use std::str::FromStr;
+use std::time::Duration;
+use async_trait::async_trait;
+use serde::Deserialize;
 use alloy::{
-    transports::{http::Http, Transport, TransportResult},
+    transports::{Transport, TransportError, TransportErrorKind, TransportResult},
     providers::{
         fillers::{FillProvider, FillerControlFlow, TxFiller}, 
-        Provider, ProviderBuilder, RootProvider, SendableTx,
+        PendingTransactionBuilder, Provider, ProviderBuilder, RootProvider, SendableTx,
     },
-    rpc::client::ClientRef, 
-    primitives::Address, 
-    network::Network, 
+    rpc::{client::{ClientRef, RpcClient}, types::eth::TransactionReceipt},
+    primitives::{Address, Bytes, TxHash, TxKind, U128, U256, U64}, 
+    network::{Network, TransactionBuilder},
+    consensus::{SignableTransaction, TxEnvelope, TxLegacy},
+    eips::eip2718::Encodable2718,
+};
+use suave_alloy_types::{
+    CallbackLog, CallbackProof, CallbackTx, ClockSkewGuard, ConfidentialCallResponse, ConfidentialComputeResult,
 };
 use super::network::SuaveNetwork;
-
-
+use super::signer::SuaveSigner;
+use super::methods::{self, RpcMethods};
+use super::kettle_watcher::KettleRotation;
+use super::tasks::{ShutdownSignal, TaskScope};
+use super::http::CompressedHttp;
+
+
+/// Provider for SUAVE nodes.
+///
+/// Cloning is cheap: clones share the underlying client (connection pool)
+/// and the kettle address, chain id and nonce caches. The provider is
+/// `Send + Sync` for `Send + Sync` transports (eg. HTTP), so multi-task
+/// bots can hand out clones instead of wrapping it in a mutex.
+///
+/// Background tasks (kettle watchers, latency probes, ...) can be tied to
+/// the provider with `spawn`; `shutdown` stops them together with the
+/// provider's polling loops.
 #[derive(Clone)]
 pub struct SuaveProvider<T> 
     where T: Transport + Clone
 {
     root_provider: RootProvider<T, SuaveNetwork>,
+    methods: RpcMethods,
+    cache: Arc<ProviderCache>,
+    tasks: Arc<TaskScope>,
+}
+
+#[derive(Debug, Default)]
+struct ProviderCache {
+    /// Replaced on `refresh_kettle_address`.
+    kettle_address: RwLock<Option<Address>>,
+    chain_id: OnceLock<u64>,
+    /// Next nonce per sender.
+    nonces: Mutex<HashMap<Address, u64>>,
 }
 
 impl<T> SuaveProvider<T> 
     where T: Transport + Clone
 {
     pub fn new(root_provider: RootProvider<T, SuaveNetwork>) -> Self {
-        Self { root_provider }
+        Self {
+            root_provider,
+            methods: RpcMethods::default(),
+            cache: Arc::default(),
+            tasks: Arc::default(),
+        }
+    }
+
+    pub fn with_rpc_methods(mut self, methods: RpcMethods) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    pub fn rpc_methods(&self) -> &RpcMethods {
+        &self.methods
+    }
+
+    /// Run a background task until it returns; it should stop once
+    /// `shutdown_signal` fires.
+    pub fn spawn<F>(&self, task: F)
+        where F: Future<Output = ()> + Send + 'static
+    {
+        self.tasks.spawn(task);
+    }
+
+    pub fn shutdown_signal(&self) -> ShutdownSignal {
+        self.tasks.signal()
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.tasks.is_shutdown()
+    }
+
+    /// Stop the background tasks of all clones of the provider and wait for
+    /// them to return. Polling loops (receipt waits, gas escalation) return
+    /// an error from then on.
+    pub async fn shutdown(&self) {
+        self.tasks.shutdown().await;
+    }
+
+    /// Sleep between polls, failing if the provider shuts down first.
+    pub(crate) async fn sleep(&self, duration: Duration) -> TransportResult<()> {
+        if self.shutdown_signal().sleep(duration).await {
+            Ok(())
+        } else {
+            Err(TransportErrorKind::custom_str("Provider shut down"))
+        }
     }
 
+    /// Kettle address of the node, fetched once and cached.
     pub async fn kettle_address(&self) -> TransportResult<Address> {
-        kettle_address(self.client()).await
+        if let Some(kettle) = read_kettle(&self.cache.kettle_address) {
+            return Ok(kettle);
+        }
+        let kettle = kettle_address_with(self.client(), self.methods.kettle_address.clone()).await?;
+        Ok(*write_kettle(&self.cache.kettle_address).get_or_insert(kettle))
+    }
+
+    /// Re-query the kettle address and replace the cached one. Returns the
+    /// rotation if a previously cached address changed.
+    pub async fn refresh_kettle_address(&self) -> TransportResult<Option<KettleRotation>> {
+        let current = kettle_address_with(self.client(), self.methods.kettle_address.clone()).await?;
+        let previous = write_kettle(&self.cache.kettle_address).replace(current);
+        Ok(previous
+            .filter(|previous| *previous != current)
+            .map(|previous| KettleRotation { previous, current }))
+    }
+
+    /// Chain id of the node, fetched once and cached.
+    pub async fn cached_chain_id(&self) -> TransportResult<u64> {
+        if let Some(chain_id) = self.cache.chain_id.get() {
+            return Ok(*chain_id);
+        }
+        let chain_id: U64 = self.client().request("eth_chainId", ()).await?;
+        Ok(*self.cache.chain_id.get_or_init(|| chain_id.to()))
+    }
+
+    /// Reserve the next nonce of `sender`. The pending nonce is fetched on
+    /// first use, after that nonces are handed out locally so concurrent
+    /// tasks sharing the provider never reuse one.
+    pub async fn next_nonce(&self, sender: Address) -> TransportResult<u64> {
+        if let Some(nonce) = self.reserve_cached_nonce(sender) {
+            return Ok(nonce);
+        }
+        let pending: U64 = self.client().request("eth_getTransactionCount", (sender, "pending")).await?;
+        let mut nonces = self.lock_nonces();
+        let next = nonces.entry(sender).or_insert(pending.to());
+        let nonce = *next;
+        *next += 1;
+        Ok(nonce)
+    }
+
+    /// `SuaveProviderExt::fund` taking the nonce from the local nonce cache
+    /// (see `next_nonce`) instead of the node.
+    pub async fn fund(&self, signer: &SuaveSigner, to: Address, amount: U256) -> TransportResult<TxHash> {
+        let from = signer.default_signer_address();
+        let transfer = Transfer { nonce: self.next_nonce(from).await?, chain_id: self.cached_chain_id().await?, to, amount };
+        let sent = send_transfer(self, signer, transfer).await;
+        if sent.is_err() {
+            // The nonce may or may not have been taken
+            self.reset_nonce(from);
+        }
+        sent
+    }
+
+    /// Set the next nonce of `sender`, eg. after a nonce error.
+    pub fn set_next_nonce(&self, sender: Address, nonce: u64) {
+        self.lock_nonces().insert(sender, nonce);
+    }
+
+    /// Give back `nonce`, reserved with `next_nonce` but never submitted.
+    /// Only rewinds if no later nonce was reserved since, otherwise that
+    /// one would be handed out twice.
+    pub fn release_nonce(&self, sender: Address, nonce: u64) {
+        let mut nonces = self.lock_nonces();
+        if let Some(next) = nonces.get_mut(&sender).filter(|next| **next == nonce + 1) {
+            *next = nonce;
+        }
+    }
+
+    /// Forget the cached nonce of `sender`; the next call re-fetches it.
+    pub fn reset_nonce(&self, sender: Address) {
+        self.lock_nonces().remove(&sender);
+    }
+
+    // Every update leaves the map consistent, so a panic while holding the
+    // lock doesn't stop the other clones from handing out nonces.
+    fn lock_nonces(&self) -> MutexGuard<'_, HashMap<Address, u64>> {
+        self.cache.nonces.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn reserve_cached_nonce(&self, sender: Address) -> Option<u64> {
+        let mut nonces = self.lock_nonces();
+        let next = nonces.get_mut(&sender)?;
+        let nonce = *next;
+        *next += 1;
+        Some(nonce)
+    }
+
+    /// Submit an already signed and 2718-encoded CCR using the configured
+    /// submission method.
+    pub async fn send_raw_ccr(&self, encoded_ccr: &[u8]) -> TransportResult<TxHash> {
+        let encoded = alloy::primitives::hex::encode_prefixed(encoded_ccr);
+        self.client().request(self.methods.send_raw_transaction.clone(), (encoded,)).await
+    }
+
+    /// Timestamp (unix seconds) of the latest block.
+    pub async fn latest_block_timestamp(&self) -> TransportResult<u64> {
+        let block: BlockTimestamp = self.client()
+            .request("eth_getBlockByNumber", ("latest", false)).await?;
+        Ok(block.timestamp.to())
+    }
+
+    /// Compare the local clock with the latest block timestamp and fail if
+    /// the skew exceeds the guard's threshold. Returns the skew in seconds.
+    pub async fn check_clock_skew(&self, guard: &ClockSkewGuard) -> TransportResult<i64> {
+        let node_time = self.latest_block_timestamp().await?;
+        guard.check_now(node_time).map_err(TransportErrorKind::custom)
     }
 }
 
-type ReqwestHttp = Http<ReqwestClient>;
+#[derive(Deserialize)]
+struct BlockTimestamp {
+    timestamp: U64,
+}
 
-impl SuaveProvider<ReqwestHttp> {
+impl SuaveProvider<CompressedHttp> {
 
-    pub fn from_http(url: url::Url) -> SuaveProvider<ReqwestHttp> {
+    /// HTTP provider with the default `HttpConfig`, uncompressed.
+    pub fn from_http(url: url::Url) -> TransportResult<Self> {
+        Self::from_http_with_config(url, HttpConfig::default())
+    }
+
+    /// HTTP provider with the given compression and otherwise the default
+    /// `HttpConfig`.
+    pub fn from_http_with_compression(url: url::Url, compression: HttpCompression) -> TransportResult<Self> {
+        Self::from_http_with_config(url, HttpConfig { compression, ..Default::default() })
+    }
+
+    pub fn from_http_with_config(url: url::Url, config: HttpConfig) -> TransportResult<Self> {
+        let client = http_client(&config)?;
+        let transport = CompressedHttp::new(client, url, config.compression)
+            .with_max_request_body(config.max_request_body);
+        let is_local = transport.guess_local();
         let root_provider = ProviderBuilder::<_, _, SuaveNetwork>::default()
-            .on_http(url).expect("Failed to root provider for SuaveProvider");
-        Self { root_provider }
+            .on_client(RpcClient::new(transport, is_local));
+        Ok(Self::new(root_provider))
     }
 
 }
 
+fn http_client(config: &HttpConfig) -> TransportResult<ReqwestClient> {
+    ReqwestClient::builder()
+        .gzip(config.compression == HttpCompression::Gzip)
+        .timeout(config.timeout)
+        .connect_timeout(config.connect_timeout)
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(config.pool_idle_timeout)
+        .tcp_nodelay(config.tcp_nodelay)
+        .build()
+        .map_err(TransportErrorKind::custom)
+}
+
+/// Compression used by HTTP providers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpCompression {
+    #[default]
+    None,
+    /// Gzip request bodies of at least `GZIP_MIN_BODY` bytes
+    /// (`Content-Encoding: gzip`) and accept gzip responses. The node, or a
+    /// proxy in front of it, must inflate request bodies; geth's HTTP
+    /// server doesn't.
+    Gzip,
+}
+
+/// HTTP client tuning of providers built with `from_http_with_config`.
+/// The defaults are meant for CCRs carrying confidential inputs of a few
+/// hundred KB, which take longer to upload and execute than plain calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpConfig {
+    pub compression: HttpCompression,
+    /// Whole request, from connecting to reading the response.
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Largest request body sent, checked by the transport for every
+    /// request; geth rejects bodies over 5 MiB.
+    pub max_request_body: usize,
+    /// Idle connections kept per host. Bots submitting from many tasks
+    /// reuse them instead of paying a handshake per CCR.
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Option<Duration>,
+    /// Send small writes (eg. the tail of a large body) without waiting
+    /// for the previous segment to be acknowledged.
+    pub tcp_nodelay: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            compression: HttpCompression::None,
+            timeout: Duration::from_secs(60),
+            connect_timeout: Duration::from_secs(10),
+            max_request_body: 5 * 1024 * 1024,
+            pool_max_idle_per_host: 16,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tcp_nodelay: true,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl<T> Provider<T, SuaveNetwork> for SuaveProvider<T> 
     where T: Transport + Clone
 {
@@ -53,28 +323,45 @@ impl<T> Provider<T, SuaveNetwork> for SuaveProvider<T>
         &self.root_provider
     }
 
+    /// Submitted with the configured `send_raw_transaction` method (see
+    /// `send_raw_ccr`), which fill providers wrapping this one use to send
+    /// signed transactions as well.
+    async fn send_raw_transaction(
+        &self,
+        encoded_tx: &[u8],
+    ) -> TransportResult<PendingTransactionBuilder<'_, T, SuaveNetwork>> {
+        let tx_hash = self.send_raw_ccr(encoded_tx).await?;
+        Ok(PendingTransactionBuilder::new(self.root(), tx_hash))
+    }
+
 }
 
-impl TryFrom<&str> for SuaveProvider<ReqwestHttp> {
-    type Error = url::ParseError;
+impl TryFrom<&str> for SuaveProvider<CompressedHttp> {
+    type Error = TransportError;
 
     fn try_from(url: &str) -> Result<Self, Self::Error> {
-        Ok(SuaveProvider::from_http(url.parse()?))
+        SuaveProvider::from_http(url.parse().map_err(TransportErrorKind::custom)?)
     }
 
 }
 
-impl FromStr for SuaveProvider<ReqwestHttp> {
-    type Err = url::ParseError;
+impl FromStr for SuaveProvider<CompressedHttp> {
+    type Err = TransportError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(SuaveProvider::from_http(s.parse()?))
+        SuaveProvider::from_http(s.parse().map_err(TransportErrorKind::custom)?)
     }
 
 }
 
 pub trait SuaveFillProviderExt {
-    fn kettle_address(&self) -> impl std::future::Future<Output = TransportResult<Address>> + Send;
+    /// Kettle address of the node, queried with `methods.kettle_address`.
+    /// The fill provider doesn't expose the `SuaveProvider` it wraps, so
+    /// pass its methods (`SuaveProvider::rpc_methods`).
+    fn kettle_address(
+        &self,
+        methods: &RpcMethods,
+    ) -> impl std::future::Future<Output = TransportResult<Address>> + Send;
 }
 
 // todo: optimize for wasm
@@ -83,26 +370,195 @@ pub trait SuaveFillProviderExt {
 impl<S, T> SuaveFillProviderExt for FillProvider<S, SuaveProvider<T>, T, SuaveNetwork> 
     where S: TxFiller<SuaveNetwork>, T: Transport + Clone
 {
-    async fn kettle_address(&self) -> TransportResult<Address> {
-        kettle_address(self.client()).await
+    async fn kettle_address(&self, methods: &RpcMethods) -> TransportResult<Address> {
+        kettle_address_with(self.client(), methods.kettle_address.clone()).await
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct KettleFiller(Arc<OnceLock<Address>>);
+/// Gas limit of a plain value transfer.
+const TRANSFER_GAS: u128 = 21_000;
+
+pub trait SuaveProviderExt<T> {
+    /// Pending transaction count (next nonce) of `address`.
+    fn transaction_count(&self, address: Address) -> impl std::future::Future<Output = TransportResult<u64>> + Send;
+
+    /// Latest balance of `address` in wei.
+    fn balance(&self, address: Address) -> impl std::future::Future<Output = TransportResult<U256>> + Send;
+
+    /// Latest code deployed at `address`.
+    fn code(&self, address: Address) -> impl std::future::Future<Output = TransportResult<Bytes>> + Send;
+
+    /// Send `amount` wei from the signer's default address to `to` with a
+    /// plain (legacy) value transfer, eg. to fund throwaway test keys.
+    /// Submitted with `Provider::send_raw_transaction`, so `SuaveProvider`
+    /// uses its configured method.
+    /// `SuaveProvider::fund` also takes the nonce from its nonce cache.
+    fn fund(
+        &self, 
+        signer: &SuaveSigner, 
+        to: Address, 
+        amount: U256,
+    ) -> impl std::future::Future<Output = TransportResult<TxHash>> + Send;
+
+    /// Kettle result of the CCR `tx_hash`, `None` while it's pending.
+    fn get_ccr_result(
+        &self,
+        tx_hash: TxHash,
+    ) -> impl std::future::Future<Output = TransportResult<Option<ConfidentialComputeResult>>> + Send;
+
+    /// Check that `callback_hash` is the on-chain callback of the CCR
+    /// `request_hash` (see `ConfidentialComputeResult::verify_callback`).
+    fn verify_callback(
+        &self,
+        request_hash: TxHash,
+        callback_hash: TxHash,
+    ) -> impl std::future::Future<Output = TransportResult<CallbackProof>> + Send;
+}
+
+impl<P, T> SuaveProviderExt<T> for P 
+    where P: Provider<T, SuaveNetwork>, T: Transport + Clone
+{
+    async fn transaction_count(&self, address: Address) -> TransportResult<u64> {
+        let count: U64 = self.client().request("eth_getTransactionCount", (address, "pending")).await?;
+        Ok(count.to())
+    }
+
+    async fn balance(&self, address: Address) -> TransportResult<U256> {
+        self.client().request("eth_getBalance", (address, "latest")).await
+    }
+
+    async fn code(&self, address: Address) -> TransportResult<Bytes> {
+        self.client().request("eth_getCode", (address, "latest")).await
+    }
+
+    async fn fund(&self, signer: &SuaveSigner, to: Address, amount: U256) -> TransportResult<TxHash> {
+        let nonce = self.transaction_count(signer.default_signer_address()).await?;
+        let chain_id: U64 = self.client().request("eth_chainId", ()).await?;
+        let transfer = Transfer { nonce, chain_id: chain_id.to(), to, amount };
+        send_transfer(self, signer, transfer).await
+    }
+
+    async fn get_ccr_result(&self, tx_hash: TxHash) -> TransportResult<Option<ConfidentialComputeResult>> {
+        let client = self.client();
+        let receipt: Option<TransactionReceipt> = client.request("eth_getTransactionReceipt", (tx_hash,)).await?;
+        if receipt.is_none() {
+            return Ok(None);
+        }
+        let raw: Option<serde_json::Value> = client.request("eth_getTransactionByHash", (tx_hash,)).await?;
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+        let response = ConfidentialCallResponse::from_json(raw).map_err(TransportErrorKind::custom)?;
+        ConfidentialComputeResult::try_from(&response)
+            .map(Some)
+            .map_err(TransportErrorKind::custom)
+    }
+
+    async fn verify_callback(&self, request_hash: TxHash, callback_hash: TxHash) -> TransportResult<CallbackProof> {
+        let result = self.get_ccr_result(request_hash).await?
+            .ok_or_else(|| TransportErrorKind::custom_str("CCR has no result yet"))?;
+        let client = self.client();
+        let tx: Option<CallbackFields> = client.request("eth_getTransactionByHash", (callback_hash,)).await?;
+        let receipt: Option<CallbackReceipt> = client.request("eth_getTransactionReceipt", (callback_hash,)).await?;
+        let (Some(tx), Some(receipt)) = (tx, receipt) else {
+            return Err(TransportErrorKind::custom_str("Callback is not included"));
+        };
+        let callback = CallbackTx {
+            hash: callback_hash,
+            to: tx.to,
+            input: tx.input,
+            block_number: receipt.block_number.to(),
+            logs: receipt.logs,
+        };
+        result.verify_callback(request_hash, &callback).map_err(TransportErrorKind::custom)
+    }
+}
+
+#[derive(Deserialize)]
+struct CallbackFields {
+    to: Option<Address>,
+    input: Bytes,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallbackReceipt {
+    block_number: U64,
+    logs: Vec<CallbackLog>,
+}
+
+/// Fills the kettle address, fetched once and shared by clones. The
+/// address can be replaced (eg. by a `KettleWatcher`) after a rotation.
+///
+/// The address is fetched with `eth_kettleAddress` unless another method
+/// is set; `KettleFiller::from(&provider)` takes the provider's
+/// `RpcMethods` (and its cached kettle address).
+#[derive(Clone, Debug)]
+pub struct KettleFiller {
+    kettle: Arc<RwLock<Option<Address>>>,
+    method: Cow<'static, str>,
+}
+
+impl Default for KettleFiller {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
 
 impl KettleFiller {
 
     pub fn new(kettle_address: Option<Address>) -> Self {
-        let lock = OnceLock::new();
-        if let Some(kettle_address) = kettle_address {
-            lock.set(kettle_address).expect("brand new");
+        Self {
+            kettle: Arc::new(RwLock::new(kettle_address)),
+            method: Cow::Borrowed(methods::KETTLE_ADDRESS),
         }
-        Self(Arc::new(lock))
+    }
+
+    /// Fetch the kettle address with `method` (see `RpcMethods::kettle_address`).
+    pub fn with_method(mut self, method: impl Into<Cow<'static, str>>) -> Self {
+        self.method = method.into();
+        self
+    }
+
+    pub fn kettle_address(&self) -> Option<Address> {
+        read_kettle(&self.kettle)
+    }
+
+    /// Use `kettle_address` for requests built from now on.
+    pub fn set_kettle_address(&self, kettle_address: Address) {
+        *write_kettle(&self.kettle) = Some(kettle_address);
     }
 
 }
 
+impl<T> From<&SuaveProvider<T>> for KettleFiller
+    where T: Transport + Clone
+{
+    fn from(provider: &SuaveProvider<T>) -> Self {
+        Self::new(read_kettle(&provider.cache.kettle_address))
+            .with_method(provider.methods.kettle_address.clone())
+    }
+}
+
+// The kettle address locks guard a `Copy` value that a panicking writer
+// can't leave half-updated, so poisoning is ignored instead of spreading
+// the panic to every provider clone, filler and watcher.
+fn read_kettle(lock: &RwLock<Option<Address>>) -> Option<Address> {
+    *lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write_kettle(lock: &RwLock<Option<Address>>) -> RwLockWriteGuard<'_, Option<Address>> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl PartialEq for KettleFiller {
+    fn eq(&self, other: &Self) -> bool {
+        self.kettle_address() == other.kettle_address() && self.method == other.method
+    }
+}
+
+impl Eq for KettleFiller {}
+
 impl TxFiller<SuaveNetwork> for KettleFiller {
     type Fillable = Address;
 
@@ -123,12 +579,11 @@ impl TxFiller<SuaveNetwork> for KettleFiller {
         P: Provider<T, SuaveNetwork>,
         T: Transport + Clone,
     {
-        match self.0.get().cloned() {
+        match self.kettle_address() {
             Some(kettle) => Ok(kettle),
             None => {
-                let kettle = kettle_address(&provider.client()).await?;
-                let kettle = *self.0.get_or_init(|| kettle);
-                Ok(kettle)
+                let kettle = kettle_address_with(provider.client(), self.method.clone()).await?;
+                Ok(*write_kettle(&self.kettle).get_or_insert(kettle))
             }
         }
     }
@@ -148,25 +603,383 @@ impl TxFiller<SuaveNetwork> for KettleFiller {
 
 }
 
-async fn kettle_address<'a, T>(client: ClientRef<'a , T>) -> TransportResult<Address> 
+/// Fills the sender with the signer's default address, so nonce filling
+/// works without setting `from` on every request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SenderFiller(Address);
+
+impl SenderFiller {
+
+    pub fn new(sender: Address) -> Self {
+        Self(sender)
+    }
+
+}
+
+impl From<&SuaveSigner> for SenderFiller {
+    fn from(signer: &SuaveSigner) -> Self {
+        Self(signer.default_signer_address())
+    }
+}
+
+impl TxFiller<SuaveNetwork> for SenderFiller {
+    type Fillable = Address;
+
+    fn status(&self, tx: &<SuaveNetwork as Network>::TransactionRequest) -> FillerControlFlow {
+        if TransactionBuilder::from(tx).is_some() {
+            FillerControlFlow::Finished
+        } else {
+            FillerControlFlow::Ready
+        }
+    }
+
+    async fn prepare<P, T>(
+        &self,
+        _provider: &P,
+        _tx: &<SuaveNetwork as Network>::TransactionRequest,
+    ) -> TransportResult<Self::Fillable>
+    where
+        P: Provider<T, SuaveNetwork>,
+        T: Transport + Clone,
+    {
+        Ok(self.0)
+    }
+
+    async fn fill(
+        &self,
+        fillable: Self::Fillable,
+        mut tx: SendableTx<SuaveNetwork>,
+    ) -> TransportResult<SendableTx<SuaveNetwork>> {
+        if let Some(builder) = tx.as_mut_builder() {
+            if TransactionBuilder::from(builder).is_none() {
+                builder.set_from(fillable)
+            }
+        };
+        Ok(tx)
+    }
+
+}
+
+struct Transfer {
+    nonce: u64,
+    chain_id: u64,
+    to: Address,
+    amount: U256,
+}
+
+/// Sign a plain value transfer from the signer's default address at the
+/// current gas price and submit it through the provider.
+async fn send_transfer<P, T>(
+    provider: &P,
+    signer: &SuaveSigner,
+    transfer: Transfer,
+) -> TransportResult<TxHash>
+    where P: Provider<T, SuaveNetwork>, T: Transport + Clone
+{
+    let gas_price: U128 = provider.client().request("eth_gasPrice", ()).await?;
+    let mut tx = TxLegacy {
+        chain_id: Some(transfer.chain_id),
+        nonce: transfer.nonce,
+        gas_price: gas_price.to(),
+        gas_limit: TRANSFER_GAS,
+        to: TxKind::Call(transfer.to),
+        value: transfer.amount,
+        input: Bytes::new(),
+    };
+    let signature = signer.sign_any_from(signer.default_signer_address(), &mut tx).await
+        .map_err(TransportErrorKind::custom)?;
+    let mut encoded = Vec::new();
+    TxEnvelope::Legacy(tx.into_signed(signature)).encode_2718(&mut encoded);
+    let pending = provider.send_raw_transaction(&encoded).await?;
+    Ok(*pending.tx_hash())
+}
+
+async fn kettle_address_with<'a, T>(
+    client: ClientRef<'a , T>, 
+    method: Cow<'static, str>,
+) -> TransportResult<Address> 
     where T: Transport + Clone
 {
-    client.request(String::from("eth_kettleAddress"), ()).await
-        .map(|ks: Vec<Address>| ks[0])
+    let kettles: Vec<Address> = client.request(method, ()).await?;
+    kettles.first().copied()
+        .ok_or_else(|| TransportErrorKind::custom_str("Node serves no kettle address"))
 }
 
 
 #[cfg(test)]
-mod tests {
-    use std::str::FromStr;
+pub(crate) mod tests {
     use eyre::Result;
+    use suave_alloy_types::{ChainProfile, RIGIL};
     use super::*;
 
+    /// Chain the live tests run against, Rigil unless `SUAVE_TEST_CHAIN`
+    /// names another known one (eg. `toliman`).
+    pub(crate) fn test_profile() -> &'static ChainProfile {
+        std::env::var("SUAVE_TEST_CHAIN").ok()
+            .map(|name| ChainProfile::by_name(&name).expect("Unknown SUAVE_TEST_CHAIN"))
+            .unwrap_or(&RIGIL)
+    }
+
     #[tokio::test]
     async fn test_suave_provider() -> Result<()> {
-        let provider = SuaveProvider::try_from("https://rpc.rigil.suave.flashbots.net")?;
+        let provider = SuaveProvider::try_from(test_profile().rpc_url)?;
         let kettle_address = provider.kettle_address().await.unwrap();
-        assert_eq!(kettle_address, Address::from_str("0x03493869959c866713c33669ca118e774a30a0e5").unwrap());
+        assert!(test_profile().kettle_addresses.contains(&kettle_address));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_account_queries() -> Result<()> {
+        let provider = SuaveProvider::try_from(test_profile().rpc_url)?;
+        let kettle_address = provider.kettle_address().await?;
+        // The kettle is an EOA signing results
+        assert!(provider.code(kettle_address).await?.is_empty());
+        provider.transaction_count(kettle_address).await?;
+        provider.balance(kettle_address).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pending_ccr_result() -> Result<()> {
+        let provider = SuaveProvider::try_from(test_profile().rpc_url)?;
+        assert_eq!(provider.get_ccr_result(TxHash::repeat_byte(0x11)).await?, None);
+        Ok(())
+    }
+
+    /// Request served by `serve_rpc`: `Content-Encoding` header, raw body
+    /// and the JSON-RPC request inflated.
+    pub(crate) type Served = (Option<String>, Vec<u8>, serde_json::Value);
+
+    /// Serve `count` JSON-RPC requests over HTTP, answering each with the
+    /// result `respond` gives for its method.
+    pub(crate) async fn serve_rpc(
+        listener: tokio::net::TcpListener,
+        count: usize,
+        respond: fn(&str) -> serde_json::Value,
+    ) -> Result<Vec<Served>> {
+        use std::io::Read;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut served = Vec::new();
+        let (mut stream, _) = listener.accept().await?;
+        let mut pending = Vec::new();
+        let mut buf = [0u8; 8192];
+        while served.len() < count {
+            let header_end = loop {
+                if let Some(pos) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+                let n = stream.read(&mut buf).await?;
+                if n == 0 {
+                    // The client closed the connection, it opens another one
+                    eyre::ensure!(pending.is_empty(), "Connection closed before the headers ended");
+                    stream = listener.accept().await?.0;
+                }
+                pending.extend_from_slice(&buf[..n]);
+            };
+            let headers = String::from_utf8_lossy(&pending[..header_end]).to_lowercase();
+            let header = |name: &str| headers.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(':').map(|v| v.trim().to_string()));
+            let content_len: usize = header("content-length").unwrap_or_default().parse()?;
+            let encoding = header("content-encoding");
+            while pending.len() < header_end + content_len {
+                let n = stream.read(&mut buf).await?;
+                eyre::ensure!(n > 0, "Connection closed before the body ended");
+                pending.extend_from_slice(&buf[..n]);
+            }
+            let body = pending.drain(..header_end + content_len).skip(header_end).collect::<Vec<_>>();
+
+            let mut json = Vec::new();
+            match encoding.as_deref() {
+                Some("gzip") => { flate2::read::GzDecoder::new(body.as_slice()).read_to_end(&mut json)?; }
+                _ => json.clone_from(&body),
+            }
+            let request: serde_json::Value = serde_json::from_slice(&json)?;
+            let result = respond(request["method"].as_str().unwrap_or_default());
+            let response = serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+            stream.write_all(format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{response}", response.len()
+            ).as_bytes()).await?;
+            served.push((encoding, body, request));
+        }
+        Ok(served)
+    }
+
+    #[tokio::test]
+    async fn test_gzip_request_body() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?).parse()?;
+        let provider = SuaveProvider::from_http_with_compression(url, HttpCompression::Gzip)?;
+        let tx_hash = TxHash::repeat_byte(0x11);
+        let server = tokio::spawn(serve_rpc(listener, 1, |_| serde_json::json!(TxHash::repeat_byte(0x11))));
+
+        let ccr = vec![0x43; 64 * 1024];
+        assert_eq!(provider.send_raw_ccr(&ccr).await?, tx_hash);
+        let (encoding, body, request) = server.await??.remove(0);
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert!(body.len() * 10 < ccr.len());
+        assert_eq!(request["method"], "eth_sendRawTransaction");
+        assert_eq!(request["params"][0], alloy::primitives::hex::encode_prefixed(&ccr));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fund_configured_method() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let methods = RpcMethods::default().with_send_raw_transaction("suavex_sendRawTransaction");
+        let provider = SuaveProvider::try_from(url.as_str())?.with_rpc_methods(methods);
+        let server = tokio::spawn(serve_rpc(listener, 7, |method| match method {
+            "eth_chainId" => serde_json::json!("0x1008c45"),
+            "eth_gasPrice" => serde_json::json!("0x3b9aca00"),
+            "eth_getTransactionCount" => serde_json::json!("0x3"),
+            _ => serde_json::json!(TxHash::repeat_byte(0x11)),
+        }));
+
+        let wallet: alloy::signers::wallet::LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?;
+        let signer = SuaveSigner::new(wallet);
+        let from = signer.default_signer_address();
+        provider.set_next_nonce(from, 3);
+        let (to, amount) = (Address::repeat_byte(0x22), U256::from(1));
+        assert_eq!(provider.fund(&signer, to, amount).await?, TxHash::repeat_byte(0x11));
+        // The trait method asks the node for the nonce, and sends the same way
+        assert_eq!(SuaveProviderExt::fund(&provider, &signer, to, amount).await?, TxHash::repeat_byte(0x11));
+        let methods = server.await??.into_iter().map(|(_, _, request)| request["method"].clone()).collect::<Vec<_>>();
+        assert_eq!(methods, [
+            "eth_chainId", "eth_gasPrice", "suavex_sendRawTransaction",
+            "eth_getTransactionCount", "eth_chainId", "eth_gasPrice", "suavex_sendRawTransaction",
+        ]);
+        assert_eq!(provider.next_nonce(from).await?, 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_configured_methods() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let methods = RpcMethods::default()
+            .with_kettle_address("suavex_kettleAddress")
+            .with_send_raw_transaction("suavex_sendRawTransaction");
+        let provider = SuaveProvider::try_from(url.as_str())?.with_rpc_methods(methods);
+        let server = tokio::spawn(serve_rpc(listener, 2, |method| match method {
+            "suavex_kettleAddress" => serde_json::json!([Address::repeat_byte(0x22)]),
+            _ => serde_json::json!(TxHash::repeat_byte(0x11)),
+        }));
+
+        // Both the kettle filler and the alloy send path use the overrides
+        let filler = KettleFiller::from(&provider);
+        let ccr = suave_alloy_types::ConfidentialComputeRequest::default();
+        assert_eq!(filler.prepare(&provider, &ccr).await?, Address::repeat_byte(0x22));
+        let pending = provider.send_raw_transaction(&[0x43; 32]).await?;
+        assert_eq!(*pending.tx_hash(), TxHash::repeat_byte(0x11));
+        let methods = server.await??.into_iter().map(|(_, _, request)| request["method"].clone()).collect::<Vec<_>>();
+        assert_eq!(methods, ["suavex_kettleAddress", "suavex_sendRawTransaction"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_no_kettle_address() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let provider = SuaveProvider::try_from(url.as_str())?;
+        let server = tokio::spawn(serve_rpc(listener, 1, |_| serde_json::json!([])));
+        let err = provider.kettle_address().await.unwrap_err();
+        assert!(err.to_string().contains("Node serves no kettle address"));
+        server.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_request_body() -> Result<()> {
+        let config = HttpConfig { max_request_body: 1024, ..Default::default() };
+        let provider = SuaveProvider::from_http_with_config("http://localhost:0".parse()?, config)?;
+        let err = provider.send_raw_ccr(&[0x43; 512]).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds the 1024 bytes limit"));
+        // Every request goes through the transport, eg. simulations
+        let res: TransportResult<serde_json::Value> = provider.client().request("eth_call", (Bytes::from(vec![0u8; 512]),)).await;
+        assert!(res.unwrap_err().to_string().contains("exceeds the 1024 bytes limit"));
+        let err = provider.send_raw_transaction(&[0x02; 512]).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds the 1024 bytes limit"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_cache() -> Result<()> {
+        fn assert_send_sync<P: Send + Sync + Clone>() {}
+        assert_send_sync::<SuaveProvider<CompressedHttp>>();
+
+        let provider = SuaveProvider::try_from("http://localhost:0")?;
+        let clone = provider.clone();
+        let sender = Address::repeat_byte(0x11);
+        provider.set_next_nonce(sender, 7);
+        assert_eq!(clone.next_nonce(sender).await?, 7);
+        assert_eq!(provider.next_nonce(sender).await?, 8);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_release_nonce() -> Result<()> {
+        let provider = SuaveProvider::try_from("http://localhost:0")?;
+        let sender = Address::repeat_byte(0x11);
+        provider.set_next_nonce(sender, 7);
+        let (first, second) = (provider.next_nonce(sender).await?, provider.next_nonce(sender).await?);
+        // 8 is still out, giving 7 back would hand it out twice
+        provider.release_nonce(sender, first);
+        provider.release_nonce(sender, second);
+        assert_eq!(provider.next_nonce(sender).await?, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_kettle_filler_survives_poisoning() {
+        let filler = KettleFiller::new(Some(Address::repeat_byte(0x01)));
+        let poisoner = filler.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.kettle.write().unwrap();
+            panic!("poison the kettle lock");
+        }).join();
+        assert!(filler.kettle.is_poisoned());
+
+        assert_eq!(filler.kettle_address(), Some(Address::repeat_byte(0x01)));
+        filler.set_kettle_address(Address::repeat_byte(0x02));
+        assert_eq!(filler.kettle_address(), Some(Address::repeat_byte(0x02)));
+    }
+
+    #[tokio::test]
+    async fn test_nonces_survive_poisoning() -> Result<()> {
+        let provider = SuaveProvider::try_from("http://localhost:0")?;
+        let sender = Address::repeat_byte(0x11);
+        provider.set_next_nonce(sender, 3);
+        let poisoner = provider.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.cache.nonces.lock().unwrap();
+            panic!("poison the nonce lock");
+        }).join();
+        assert!(provider.cache.nonces.is_poisoned());
+
+        assert_eq!(provider.next_nonce(sender).await?, 3);
+        provider.release_nonce(sender, 3);
+        provider.reset_nonce(sender);
+        provider.set_next_nonce(sender, 9);
+        assert_eq!(provider.next_nonce(sender).await?, 9);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sender_filler() -> Result<()> {
+        let wallet: alloy::signers::wallet::LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?;
+        let signer = SuaveSigner::new(wallet);
+        let sender = signer.default_signer_address();
+        assert_eq!(signer.signer_addresses().collect::<Vec<_>>(), vec![sender]);
+
+        let filler = SenderFiller::from(&signer);
+        let ccr = suave_alloy_types::ConfidentialComputeRequest::default();
+        assert!(filler.status(&ccr).is_ready());
+
+        let filled = filler.fill(sender, SendableTx::Builder(ccr)).await?;
+        let ccr = filled.as_builder().unwrap();
+        assert_eq!(TransactionBuilder::from(ccr), Some(sender));
+        assert!(filler.status(ccr).is_finished());
         Ok(())
     }
 
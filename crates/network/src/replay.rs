@@ -0,0 +1,254 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tower::{Service, ServiceExt};
+use alloy::{
+    providers::ProviderBuilder,
+    rpc::{
+        client::RpcClient,
+        json_rpc::{RequestPacket, Response, ResponsePacket, SerializedRequest},
+    },
+    transports::{http::Http, Transport, TransportError, TransportErrorKind, TransportFut},
+};
+use reqwest::Client as ReqwestClient;
+use super::{SuaveNetwork, SuaveProvider};
+
+
+/// Environment variable forcing `ReplayTransport` to re-record cassettes.
+pub const RECORD_ENV: &str = "SUAVE_RECORD";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Forward requests to the inner transport and save the interactions.
+    Record,
+    /// Answer requests from the cassette only.
+    Replay,
+}
+
+/// Recorded JSON-RPC request (method and params, without id) and response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: Value,
+    pub response: Value,
+}
+
+#[derive(Debug, Default)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+impl Cassette {
+
+    /// First unused interaction for the request, or the last matching one
+    /// once all are used (eg. polling the same method).
+    fn find(&mut self, request: &Value) -> Option<Value> {
+        let matching = self.interactions.iter().enumerate()
+            .filter(|(_, i)| i.request == *request)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        let idx = matching.iter().find(|idx| !self.used[**idx]).or(matching.last())?;
+        self.used[*idx] = true;
+        Some(self.interactions[*idx].response.clone())
+    }
+
+}
+
+/// VCR-style transport: records JSON-RPC interactions to a cassette file on
+/// the first run and replays them afterwards, so integration tests run
+/// offline and deterministically. A missing cassette (or `SUAVE_RECORD`
+/// being set) records, an existing one replays.
+#[derive(Clone)]
+pub struct ReplayTransport<T> {
+    inner: T,
+    mode: ReplayMode,
+    path: PathBuf,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl<T> ReplayTransport<T>
+    where T: Transport + Clone
+{
+
+    pub fn new(inner: T, path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let path = path.as_ref().to_path_buf();
+        let mode = if path.exists() && std::env::var_os(RECORD_ENV).is_none() {
+            ReplayMode::Replay
+        } else {
+            ReplayMode::Record
+        };
+        Self::with_mode(inner, path, mode)
+    }
+
+    pub fn with_mode(inner: T, path: impl AsRef<Path>, mode: ReplayMode) -> Result<Self, TransportError> {
+        let path = path.as_ref().to_path_buf();
+        let interactions: Vec<Interaction> = match mode {
+            ReplayMode::Replay => {
+                let json = std::fs::read_to_string(&path).map_err(TransportErrorKind::custom)?;
+                serde_json::from_str(&json).map_err(TransportErrorKind::custom)?
+            }
+            ReplayMode::Record => Vec::new(),
+        };
+        let used = vec![false; interactions.len()];
+        let cassette = Cassette { interactions, used };
+        Ok(Self { inner, mode, path, cassette: Arc::new(Mutex::new(cassette)) })
+    }
+
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    async fn handle(self, packet: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        match self.mode {
+            ReplayMode::Replay => self.replay(&packet),
+            ReplayMode::Record => self.record(packet).await,
+        }
+    }
+
+    fn replay(&self, packet: &RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let mut cassette = self.cassette.lock().unwrap();
+        let mut responses = requests(packet).iter()
+            .map(|req| {
+                let mut response = cassette.find(&request_key(req)?)
+                    .ok_or_else(|| TransportErrorKind::custom_str(
+                        &format!("No recorded response for `{}`", req.method())
+                    ))?;
+                response["id"] = serde_json::to_value(req.id()).map_err(TransportErrorKind::custom)?;
+                serde_json::from_value::<Response>(response).map_err(TransportErrorKind::custom)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(match packet {
+            RequestPacket::Single(_) if responses.len() == 1 => ResponsePacket::Single(responses.remove(0)),
+            _ => ResponsePacket::Batch(responses),
+        })
+    }
+
+    async fn record(self, packet: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let keys = requests(&packet).iter()
+            .map(|req| Ok((req.id().clone(), request_key(req)?)))
+            .collect::<Result<Vec<_>, TransportError>>()?;
+        let response = self.inner.clone().ready().await?.call(packet).await?;
+        let responses = match &response {
+            ResponsePacket::Single(response) => std::slice::from_ref(response),
+            ResponsePacket::Batch(responses) => responses.as_slice(),
+        };
+
+        let mut cassette = self.cassette.lock().unwrap();
+        for response in responses {
+            let Some((_, request)) = keys.iter().find(|(id, _)| *id == response.id) else { continue };
+            let mut recorded = serde_json::to_value(response).map_err(TransportErrorKind::custom)?;
+            if let Some(object) = recorded.as_object_mut() {
+                object.remove("id");
+            }
+            cassette.interactions.push(Interaction { request: request.clone(), response: recorded });
+            cassette.used.push(true);
+        }
+        let json = serde_json::to_string_pretty(&cassette.interactions).map_err(TransportErrorKind::custom)?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(TransportErrorKind::custom)?;
+        }
+        std::fs::write(&self.path, json).map_err(TransportErrorKind::custom)?;
+        Ok(response)
+    }
+
+}
+
+fn requests(packet: &RequestPacket) -> &[SerializedRequest] {
+    match packet {
+        RequestPacket::Single(req) => std::slice::from_ref(req),
+        RequestPacket::Batch(reqs) => reqs.as_slice(),
+    }
+}
+
+/// Request identity used for matching: method and params, id excluded.
+/// Missing params are recorded as an empty list.
+fn request_key(req: &SerializedRequest) -> Result<Value, TransportError> {
+    let params = match req.params() {
+        Some(params) => serde_json::from_str(params.get()).map_err(TransportErrorKind::custom)?,
+        None => Value::Null,
+    };
+    let params = if params.is_null() { Value::Array(Vec::new()) } else { params };
+    Ok(serde_json::json!({ "method": req.method(), "params": params }))
+}
+
+impl<T> Service<RequestPacket> for ReplayTransport<T>
+    where T: Transport + Clone
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, packet: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().handle(packet))
+    }
+}
+
+impl SuaveProvider<ReplayTransport<Http<ReqwestClient>>> {
+
+    /// HTTP provider recording to / replaying from the cassette at `path`.
+    pub fn replay(url: url::Url, path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let transport = ReplayTransport::new(Http::new(url), path)?;
+        let root_provider = ProviderBuilder::<_, _, SuaveNetwork>::default()
+            .on_client(RpcClient::new(transport, false));
+        Ok(Self::new(root_provider))
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::{primitives::Address, providers::Provider, signers::wallet::LocalWallet};
+    use suave_alloy_types::fixtures;
+    use super::*;
+    use super::super::SuaveSigner;
+
+    fn cassette_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/cassettes").join(name)
+    }
+
+    #[tokio::test]
+    async fn test_replay_offline() {
+        // Recorded against rigil; the url is unreachable so only the cassette answers
+        let url = "http://localhost:0".parse().unwrap();
+        let provider = SuaveProvider::replay(url, cassette_path("kettle_address.json")).unwrap();
+        let kettle_address = provider.kettle_address().await.unwrap();
+        assert_eq!(kettle_address, Address::from_str("0x03493869959c866713c33669ca118e774a30a0e5").unwrap());
+        assert_eq!(provider.cached_chain_id().await.unwrap(), 0x1008c45);
+    }
+
+    #[tokio::test]
+    async fn test_send_tx_rigil() {
+        // The oracle CCR of the `empty_inputs` fixture, left to the provider to fill
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let mut ccr = fixture.request.clone();
+        let record = &mut ccr.confidential_compute_record;
+        (record.nonce, record.gas_price, record.chain_id, record.kettle_address) = (None, None, None, None);
+        record.signature = None;
+
+        let url = "http://localhost:0".parse().unwrap();
+        let provider = SuaveProvider::replay(url, cassette_path("send_tx_rigil.json")).unwrap();
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let submission = provider.send_ccr_timed(ccr, &SuaveSigner::new(wallet)).await.unwrap();
+        assert_eq!(submission.tx_hash, fixture.tx_hash);
+        assert_eq!(provider.next_nonce(fixture.signer).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_missing_interaction() {
+        let url = "http://localhost:0".parse().unwrap();
+        let provider = SuaveProvider::replay(url, cassette_path("kettle_address.json")).unwrap();
+        let res: Result<Value, _> = provider.client().request("eth_gasPrice", ()).await;
+        assert!(res.unwrap_err().to_string().contains("No recorded response for `eth_gasPrice`"));
+    }
+
+}
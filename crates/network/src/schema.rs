@@ -0,0 +1,97 @@
+//! OpenRPC description of the RPC methods `SuaveProvider` calls.
+
+use serde_json::{json, Value};
+use suave_alloy_types::schema;
+use super::RpcMethods;
+
+
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// OpenRPC document for the given method names, with CCR payloads
+/// described by the schemas in `suave_alloy_types::schema`.
+pub fn openrpc_document(methods: &RpcMethods) -> Value {
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "SUAVE CCR RPC",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": [
+            {
+                "name": methods.kettle_address,
+                "params": [],
+                "result": {
+                    "name": "kettleAddresses",
+                    "schema": { "type": "array", "items": { "$ref": "#/components/schemas/address" } }
+                }
+            },
+            {
+                "name": methods.send_raw_transaction,
+                "params": [{
+                    "name": "encodedCcr",
+                    "description": "2718 encoded signed ConfidentialComputeRequest (0x43 prefixed)",
+                    "required": true,
+                    "schema": { "$ref": "#/components/schemas/bytes" }
+                }],
+                "result": { "name": "txHash", "schema": { "$ref": "#/components/schemas/hash" } }
+            },
+            {
+                "name": methods.call,
+                "params": [
+                    {
+                        "name": "call",
+                        "required": true,
+                        "schema": { "type": "object" }
+                    },
+                    {
+                        "name": "block",
+                        "required": true,
+                        "schema": { "type": "string" }
+                    },
+                    {
+                        "name": "confidentialInputs",
+                        "schema": { "$ref": "#/components/schemas/bytes" }
+                    }
+                ],
+                "result": { "name": "output", "schema": { "$ref": "#/components/schemas/bytes" } }
+            },
+            {
+                "name": "eth_getTransactionByHash",
+                "params": [{
+                    "name": "txHash",
+                    "required": true,
+                    "schema": { "$ref": "#/components/schemas/hash" }
+                }],
+                "result": {
+                    "name": "transaction",
+                    "schema": { "$ref": "#/components/schemas/confidentialCallResponse" }
+                }
+            }
+        ],
+        "components": {
+            "schemas": schema::definitions_at("#/components/schemas/"),
+        }
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openrpc_document() {
+        let methods = RpcMethods::default().with_kettle_address("suavex_kettleAddress");
+        let doc = openrpc_document(&methods);
+        let names = doc["methods"].as_array().unwrap().iter()
+            .map(|m| m["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["suavex_kettleAddress", "eth_sendRawTransaction", "eth_call", "eth_getTransactionByHash"]);
+        assert!(doc["components"]["schemas"]["confidentialComputeRequest"].is_object());
+        assert_eq!(
+            doc["components"]["schemas"]["confidentialComputeRequest"]["properties"]["confidentialInputs"]["$ref"],
+            "#/components/schemas/bytes"
+        );
+    }
+
+}
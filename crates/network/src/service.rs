@@ -0,0 +1,462 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use axum::{
+    extract::{Path, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use alloy::{
+    primitives::{keccak256, Address, Bytes, TxKind, B256, U128, U256, U64},
+    transports::Transport,
+};
+use suave_alloy_types::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+use super::{SuaveProvider, SuaveSigner};
+
+
+/// Limits on what a tenant may sign. Empty allow-lists allow anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TenantPolicy {
+    pub allowed_targets: HashSet<Address>,
+    pub allowed_kettles: HashSet<Address>,
+    pub max_value: Option<U256>,
+    pub max_gas: Option<u128>,
+    pub max_gas_price: Option<u128>,
+    pub max_confidential_inputs_len: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyViolation {
+    Target(Address),
+    /// Contract creation while the allowed targets are restricted.
+    Create,
+    Kettle(Address),
+    Value { value: U256, max: U256 },
+    Gas { gas: u128, max: u128 },
+    GasPrice { gas_price: u128, max: u128 },
+    ConfidentialInputsLen { len: usize, max: usize },
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyViolation::Target(to) => write!(f, "Target {to} is not allowed"),
+            PolicyViolation::Create => write!(f, "Contract creation is not allowed"),
+            PolicyViolation::Kettle(kettle) => write!(f, "Kettle {kettle} is not allowed"),
+            PolicyViolation::Value { value, max } => write!(f, "Value {value} exceeds {max}"),
+            PolicyViolation::Gas { gas, max } => write!(f, "Gas {gas} exceeds {max}"),
+            PolicyViolation::GasPrice { gas_price, max } => write!(f, "Gas price {gas_price} exceeds {max}"),
+            PolicyViolation::ConfidentialInputsLen { len, max } =>
+                write!(f, "Confidential inputs of {len} bytes exceed {max} bytes"),
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+impl TenantPolicy {
+
+    pub fn with_allowed_target(mut self, target: Address) -> Self {
+        self.allowed_targets.insert(target);
+        self
+    }
+
+    pub fn with_allowed_kettle(mut self, kettle: Address) -> Self {
+        self.allowed_kettles.insert(kettle);
+        self
+    }
+
+    pub fn with_max_value(mut self, max_value: U256) -> Self {
+        self.max_value = Some(max_value);
+        self
+    }
+
+    pub fn with_max_gas(mut self, max_gas: u128) -> Self {
+        self.max_gas = Some(max_gas);
+        self
+    }
+
+    pub fn with_max_gas_price(mut self, max_gas_price: u128) -> Self {
+        self.max_gas_price = Some(max_gas_price);
+        self
+    }
+
+    pub fn with_max_confidential_inputs_len(mut self, max_len: usize) -> Self {
+        self.max_confidential_inputs_len = Some(max_len);
+        self
+    }
+
+    /// Check the set fields of `ccr`; unset ones are checked once filled.
+    pub fn check(&self, ccr: &ConfidentialComputeRequest) -> Result<(), PolicyViolation> {
+        let record = &ccr.confidential_compute_record;
+        if !self.allowed_targets.is_empty() {
+            match record.target() {
+                None => return Err(PolicyViolation::Create),
+                Some(to) if !self.allowed_targets.contains(&to) => return Err(PolicyViolation::Target(to)),
+                Some(_) => {}
+            }
+        }
+        if let Some(kettle) = record.kettle_address {
+            if !self.allowed_kettles.is_empty() && !self.allowed_kettles.contains(&kettle) {
+                return Err(PolicyViolation::Kettle(kettle));
+            }
+        }
+        if let Some(max) = self.max_value.filter(|max| record.value > *max) {
+            return Err(PolicyViolation::Value { value: record.value, max });
+        }
+        if let (Some(gas), Some(max)) = (record.gas, self.max_gas) {
+            if gas > max {
+                return Err(PolicyViolation::Gas { gas, max });
+            }
+        }
+        // The max fee for dynamic fee records
+        if let (Some(gas_price), Some(max)) = (record.max_gas_price(), self.max_gas_price) {
+            if gas_price > max {
+                return Err(PolicyViolation::GasPrice { gas_price, max });
+            }
+        }
+        let len = ccr.confidential_inputs.len();
+        if let Some(max) = self.max_confidential_inputs_len.filter(|max| len > *max) {
+            return Err(PolicyViolation::ConfidentialInputsLen { len, max });
+        }
+        Ok(())
+    }
+
+}
+
+struct Tenant {
+    api_key_hash: B256,
+    signer: SuaveSigner,
+    policy: TenantPolicy,
+    /// Held from filling to sending, so requests of a tenant take nonces
+    /// one at a time.
+    nonce_lock: Mutex<()>,
+}
+
+/// Request body of the sign and submit endpoints. Missing nonce, chain id,
+/// gas price and kettle address are filled from the node. Setting both
+/// dynamic fees sends a dynamic fee record instead of a gas price one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CcrParams {
+    pub to: Address,
+    #[serde(default)]
+    pub input: Bytes,
+    #[serde(default)]
+    pub value: U256,
+    pub gas: U64,
+    #[serde(default)]
+    pub gas_price: Option<U128>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<U128>,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<U128>,
+    #[serde(default)]
+    pub nonce: Option<U64>,
+    #[serde(default)]
+    pub chain_id: Option<U64>,
+    #[serde(default)]
+    pub kettle_address: Option<Address>,
+    #[serde(default)]
+    pub confidential_inputs: Bytes,
+}
+
+impl From<CcrParams> for ConfidentialComputeRequest {
+    fn from(params: CcrParams) -> Self {
+        let mut record = ConfidentialComputeRecord::default();
+        record.nonce = params.nonce.map(|n| n.to());
+        record.to = TxKind::Call(params.to);
+        record.gas = Some(params.gas.to());
+        record.gas_price = params.gas_price.map(|p| p.to());
+        record.max_priority_fee_per_gas = params.max_priority_fee_per_gas.map(|p| p.to());
+        record.max_fee_per_gas = params.max_fee_per_gas.map(|p| p.to());
+        record.value = params.value;
+        record.input = params.input;
+        record.kettle_address = params.kettle_address;
+        record.chain_id = params.chain_id.map(|c| c.to());
+        ConfidentialComputeRequest::new(record, Some(params.confidential_inputs))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedCcr {
+    /// 2718 encoded signed request.
+    pub raw: Bytes,
+    pub tx_hash: B256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceError {
+    UnknownTenant(String),
+    Unauthorized,
+    Policy(PolicyViolation),
+    Signing(String),
+    Node(String),
+}
+
+impl std::fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceError::UnknownTenant(tenant) => write!(f, "Unknown tenant `{tenant}`"),
+            ServiceError::Unauthorized => write!(f, "Invalid API key"),
+            ServiceError::Policy(violation) => write!(f, "Policy violation: {violation}"),
+            ServiceError::Signing(err) => write!(f, "Signing failed: {err}"),
+            ServiceError::Node(err) => write!(f, "Node error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl IntoResponse for ServiceError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            // Same status as a wrong key, so tenant names can't be probed
+            ServiceError::UnknownTenant(_) | ServiceError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ServiceError::Policy(_) => StatusCode::FORBIDDEN,
+            ServiceError::Signing(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ServiceError::Node(_) => StatusCode::BAD_GATEWAY,
+        };
+        (status, Json(serde_json::json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
+/// Embeddable HTTP service signing (and submitting) CCRs on behalf of
+/// tenants. Every tenant has its own signer, API key and policy; a request
+/// only ever reaches the keys of the tenant it authenticated as.
+///
+/// - `POST /tenants/{tenant}/sign`: fill, check and sign, returning the raw request
+/// - `POST /tenants/{tenant}/submit`: same, then broadcast it
+///
+/// Both take a `CcrParams` JSON body and an `Authorization: Bearer <api key>`
+/// header.
+///
+/// Requests of a tenant without an explicit nonce are filled, signed and
+/// sent one at a time: concurrent requests never share a nonce, and the
+/// nonce of a request failing before broadcast is given back instead of
+/// leaving a gap.
+pub struct SignerService<T>
+    where T: Transport + Clone
+{
+    provider: SuaveProvider<T>,
+    tenants: HashMap<String, Tenant>,
+}
+
+impl<T> SignerService<T>
+    where T: Transport + Clone + Send + Sync + 'static
+{
+
+    pub fn new(provider: SuaveProvider<T>) -> Self {
+        Self { provider, tenants: HashMap::new() }
+    }
+
+    /// Register a tenant. Only the hash of `api_key` is kept.
+    pub fn with_tenant(
+        mut self,
+        tenant: impl Into<String>,
+        api_key: &str,
+        signer: SuaveSigner,
+        policy: TenantPolicy,
+    ) -> Self {
+        let entry = Tenant { api_key_hash: keccak256(api_key), signer, policy, nonce_lock: Mutex::new(()) };
+        self.tenants.insert(tenant.into(), entry);
+        self
+    }
+
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/tenants/:tenant/sign", post(sign_handler::<T>))
+            .route("/tenants/:tenant/submit", post(submit_handler::<T>))
+            .with_state(Arc::new(self))
+    }
+
+    /// Serve until the provider shuts down, letting in-flight requests
+    /// finish.
+    pub async fn serve(self, addr: SocketAddr) -> std::io::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        let mut shutdown = self.provider.shutdown_signal();
+        axum::serve(listener, self.router())
+            .with_graceful_shutdown(async move { shutdown.wait().await })
+            .await
+    }
+
+    fn authenticate(&self, tenant: &str, headers: &HeaderMap) -> Result<&Tenant, ServiceError> {
+        let entry = self.tenants.get(tenant).ok_or_else(|| ServiceError::UnknownTenant(tenant.to_string()))?;
+        let api_key = headers.get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(ServiceError::Unauthorized)?;
+        if keccak256(api_key) != entry.api_key_hash {
+            return Err(ServiceError::Unauthorized);
+        }
+        Ok(entry)
+    }
+
+    async fn sign(
+        &self,
+        tenant: &str,
+        headers: &HeaderMap,
+        params: CcrParams,
+        submit: bool,
+    ) -> Result<SignedCcr, ServiceError> {
+        let tenant = self.authenticate(tenant, headers)?;
+        let mut ccr = ConfidentialComputeRequest::from(params);
+        // Reject early, then again once the node filled the rest
+        tenant.policy.check(&ccr).map_err(ServiceError::Policy)?;
+
+        // Serializes the tenant's submissions, explicit nonces included, so
+        // giving back a reserved nonce can't race another request
+        let _nonce_guard = tenant.nonce_lock.lock().await;
+        let reserved = ccr.confidential_compute_record.nonce.is_none();
+        let sender = tenant.signer.default_signer_address();
+        let result = self.sign_and_send(tenant, &mut ccr, submit).await;
+        match (&result, ccr.confidential_compute_record.nonce) {
+            // The node may or may not have taken the nonce
+            (Err(ServiceError::Node(_)), Some(_)) if submit && reserved => self.provider.reset_nonce(sender),
+            (Err(_), Some(nonce)) if reserved => self.provider.release_nonce(sender, nonce),
+            _ => {}
+        }
+        result
+    }
+
+    async fn sign_and_send(
+        &self,
+        tenant: &Tenant,
+        ccr: &mut ConfidentialComputeRequest,
+        submit: bool,
+    ) -> Result<SignedCcr, ServiceError> {
+        self.provider.fill_ccr(ccr, &tenant.signer).await
+            .map_err(|e| ServiceError::Node(e.to_string()))?;
+        tenant.policy.check(ccr).map_err(ServiceError::Policy)?;
+        tenant.signer.sign_in_place(ccr).await
+            .map_err(|e| ServiceError::Signing(e.to_string()))?;
+        let raw = ccr.rlp_encode().map_err(|e| ServiceError::Signing(e.to_string()))?;
+        let tx_hash = ccr.tx_hash().map_err(|e| ServiceError::Signing(e.to_string()))?;
+        if submit {
+            self.provider.send_raw_ccr(&raw).await
+                .map_err(|e| ServiceError::Node(e.to_string()))?;
+        }
+        Ok(SignedCcr { raw, tx_hash })
+    }
+
+}
+
+async fn sign_handler<T>(
+    State(service): State<Arc<SignerService<T>>>,
+    Path(tenant): Path<String>,
+    headers: HeaderMap,
+    Json(params): Json<CcrParams>,
+) -> Result<Json<SignedCcr>, ServiceError>
+    where T: Transport + Clone + Send + Sync + 'static
+{
+    service.sign(&tenant, &headers, params, false).await.map(Json)
+}
+
+async fn submit_handler<T>(
+    State(service): State<Arc<SignerService<T>>>,
+    Path(tenant): Path<String>,
+    headers: HeaderMap,
+    Json(params): Json<CcrParams>,
+) -> Result<Json<SignedCcr>, ServiceError>
+    where T: Transport + Clone + Send + Sync + 'static
+{
+    service.sign(&tenant, &headers, params, true).await.map(Json)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use alloy::signers::wallet::LocalWallet;
+    use tower::ServiceExt;
+    use super::*;
+
+    fn service() -> Router {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let policy = TenantPolicy::default().with_allowed_target(Address::repeat_byte(0xc8));
+        // Unreachable node: requests passing the policy fail at filling
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        SignerService::new(provider)
+            .with_tenant("bot", "secret", SuaveSigner::new(wallet), policy)
+            .router()
+    }
+
+    async fn post(router: Router, uri: &str, api_key: &str, to: Address) -> StatusCode {
+        let params = CcrParams { to, gas: U64::from(0x0f4240), ..Default::default() };
+        let request = Request::post(uri)
+            .header("content-type", "application/json")
+            .header(AUTHORIZATION, format!("Bearer {api_key}"))
+            .body(Body::from(serde_json::to_vec(&params).unwrap()))
+            .unwrap();
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_tenant_isolation_and_policy() {
+        let allowed = Address::repeat_byte(0xc8);
+        assert_eq!(post(service(), "/tenants/other/sign", "secret", allowed).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post(service(), "/tenants/bot/sign", "wrong", allowed).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post(service(), "/tenants/bot/submit", "secret", Address::ZERO).await, StatusCode::FORBIDDEN);
+        assert_eq!(post(service(), "/tenants/bot/sign", "secret", allowed).await, StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn test_failed_request_gives_nonce_back() {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let signer = SuaveSigner::new(wallet);
+        let sender = signer.default_signer_address();
+        let provider = SuaveProvider::try_from("http://localhost:0").unwrap();
+        provider.set_next_nonce(sender, 5);
+        let router = SignerService::new(provider.clone())
+            .with_tenant("bot", "secret", signer, TenantPolicy::default())
+            .router();
+
+        // Nonce 5 is reserved, then the unreachable node fails the fill
+        let mut requests = tokio::task::JoinSet::new();
+        for _ in 0..4 {
+            requests.spawn(post(router.clone(), "/tenants/bot/sign", "secret", Address::ZERO));
+        }
+        while let Some(status) = requests.join_next().await {
+            assert_eq!(status.unwrap(), StatusCode::BAD_GATEWAY);
+        }
+        assert_eq!(provider.next_nonce(sender).await.unwrap(), 5);
+    }
+
+    #[test]
+    fn test_policy_check() {
+        let policy = TenantPolicy::default()
+            .with_allowed_kettle(Address::repeat_byte(0x03))
+            .with_max_gas_price(100)
+            .with_max_confidential_inputs_len(4);
+        let mut ccr = ConfidentialComputeRequest::from(CcrParams { gas: U64::from(1), ..Default::default() });
+        assert_eq!(policy.check(&ccr), Ok(()));
+
+        ccr.confidential_compute_record.kettle_address = Some(Address::repeat_byte(0x04));
+        assert_eq!(policy.check(&ccr), Err(PolicyViolation::Kettle(Address::repeat_byte(0x04))));
+        ccr.confidential_compute_record.kettle_address = Some(Address::repeat_byte(0x03));
+        ccr.confidential_compute_record.gas_price = Some(101);
+        assert_eq!(policy.check(&ccr), Err(PolicyViolation::GasPrice { gas_price: 101, max: 100 }));
+        ccr.confidential_compute_record.gas_price = Some(100);
+        let dynamic = ConfidentialComputeRequest::from(CcrParams {
+            gas: U64::from(1),
+            max_priority_fee_per_gas: Some(U128::from(1)),
+            max_fee_per_gas: Some(U128::from(101)),
+            ..Default::default()
+        });
+        assert_eq!(policy.check(&dynamic), Err(PolicyViolation::GasPrice { gas_price: 101, max: 100 }));
+        ccr.set_confidential_inputs(Bytes::from_static(b"too long"));
+        assert_eq!(policy.check(&ccr), Err(PolicyViolation::ConfidentialInputsLen { len: 8, max: 4 }));
+
+        let targeted = TenantPolicy::default().with_allowed_target(Address::repeat_byte(0xc8));
+        ccr.confidential_compute_record.to = TxKind::Create;
+        assert_eq!(targeted.check(&ccr), Err(PolicyViolation::Create));
+    }
+
+}
@@ -7,11 +7,15 @@ use alloy::{
     signers::{Result as SignerResult, Error as SignerError},
     network::{TxSigner, NetworkSigner},
     primitives::{Address, Signature},
+    consensus::SignableTransaction,
 };
 use suave_alloy_types::ConfidentialComputeRequest;
 use crate::SuaveNetwork;
 
 
+/// Signs CCRs and plain transactions with secp256k1 `TxSigner`s. Requests
+/// of other `SignatureScheme`s are signed outside and attached with
+/// `ConfidentialComputeRequest::set_scheme_signature`.
 #[derive(Clone)]
 pub struct SuaveSigner {
     default_signer: Address,
@@ -36,22 +40,57 @@ impl SuaveSigner {
         self.signers.insert(signer.address(), signer);
     }
 
+    /// Address transactions are signed with unless another sender is set.
+    pub fn default_signer_address(&self) -> Address {
+        self.default_signer
+    }
+
+    pub fn signer_addresses(&self) -> impl Iterator<Item = Address> + '_ {
+        self.signers.keys().cloned()
+    }
+
+    /// Sign `tx` in place and return a copy of the signed request. Prefer
+    /// `sign_in_place` or `sign_owned` on hot paths, they don't copy it.
     pub async fn sign_transaction(&self, tx: &mut ConfidentialComputeRequest) -> SignerResult<ConfidentialComputeRequest> {
-        self.sign_transaction_from(self.default_signer, tx).await
+        self.sign_in_place(tx).await?;
+        Ok(tx.clone())
     }
 
-    async fn sign_transaction_from(
+    /// Sign `tx` with the default signer, setting its signature and sender.
+    /// Fails without calling the signer if a field signing needs is unset.
+    pub async fn sign_in_place(&self, tx: &mut ConfidentialComputeRequest) -> SignerResult<Signature> {
+        self.sign_in_place_from(self.default_signer, tx).await
+    }
+
+    pub async fn sign_in_place_from(
         &self,
         sender: Address,
         tx: &mut ConfidentialComputeRequest,
-    ) -> SignerResult<ConfidentialComputeRequest> {
+    ) -> SignerResult<Signature> {
+        let signer = self.signers.get(&sender).ok_or(SignerError::other("unknown signer"))?;
+        tx.signing_hash().map_err(SignerError::other)?;
+        let sig = signer.sign_transaction(tx).await?;
+        tx.confidential_compute_record.set_sig(sig);
+        tx.confidential_compute_record.from = Some(sender);
+        Ok(sig)
+    }
+
+    /// Sign `tx` with the default signer and return it.
+    pub async fn sign_owned(&self, mut tx: ConfidentialComputeRequest) -> SignerResult<ConfidentialComputeRequest> {
+        self.sign_in_place(&mut tx).await?;
+        Ok(tx)
+    }
+
+    /// Sign any (non-CCR) transaction, eg. a plain value transfer, with the
+    /// signer registered for `sender`.
+    pub async fn sign_any_from(
+        &self,
+        sender: Address,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> SignerResult<Signature> {
         self.signers.get(&sender)
             .ok_or(SignerError::other("unknown signer"))?
-            .sign_transaction(tx).await.map(|sig| {
-                tx.confidential_compute_record.set_sig(sig);
-                tx.confidential_compute_record.from = Some(sender);
-                tx.clone()
-            })
+            .sign_transaction(tx).await
     }
 }
 
@@ -60,7 +99,7 @@ impl SuaveSigner {
 impl NetworkSigner<SuaveNetwork> for SuaveSigner {
 
     fn default_signer_address(&self) -> Address {
-        self.default_signer
+        SuaveSigner::default_signer_address(self)
     }
 
     fn has_signer_for(&self,address: &Address) -> bool {
@@ -68,7 +107,7 @@ impl NetworkSigner<SuaveNetwork> for SuaveSigner {
     }
 
     fn signer_addresses(&self) -> impl Iterator<Item = Address> {
-        self.signers.keys().cloned()
+        SuaveSigner::signer_addresses(self)
     }
 
     async fn sign_transaction_from(
@@ -76,7 +115,8 @@ impl NetworkSigner<SuaveNetwork> for SuaveSigner {
         sender: Address,
         mut tx: ConfidentialComputeRequest,
     ) -> SignerResult<ConfidentialComputeRequest> {
-        self.sign_transaction_from(sender, &mut tx).await
+        self.sign_in_place_from(sender, &mut tx).await?;
+        Ok(tx)
     }
 
 }
@@ -99,3 +139,30 @@ where
         Self::new(signer)
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::Bytes, signers::wallet::LocalWallet};
+    use suave_alloy_types::fixtures;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign_in_place() {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let signer = SuaveSigner::new(wallet);
+        let mut ccr = ConfidentialComputeRequest::new(fixtures::unsigned_record(), Some(Bytes::from(vec![0xab; 64])));
+
+        let owned = signer.sign_owned(ccr.clone()).await.unwrap();
+        let sig = signer.sign_in_place(&mut ccr).await.unwrap();
+        assert_eq!(ccr, owned);
+        assert_eq!(ccr.confidential_compute_record.from, Some(signer.default_signer_address()));
+        assert_eq!(ccr.confidential_compute_record.signature, Some(sig));
+
+        ccr.confidential_compute_record.gas = None;
+        let err = signer.sign_in_place(&mut ccr).await.unwrap_err();
+        assert!(err.to_string().contains("gas"));
+        assert_eq!(ccr.confidential_compute_record.signature, Some(sig));
+    }
+
+}
@@ -0,0 +1,198 @@
+use std::str::FromStr;
+use serde::Serialize;
+use alloy::{
+    primitives::{Address, Bytes, U128, U256},
+    providers::Provider,
+    transports::{Transport, TransportResult},
+};
+use suave_alloy_types::{ConfidentialComputeRequest, Fees};
+use super::SuaveProvider;
+
+
+/// Result of executing a CCR, either locally or on a kettle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    Success(Bytes),
+    Revert(Bytes),
+    Error(String),
+}
+
+impl ExecutionOutcome {
+    pub fn output(&self) -> Option<&Bytes> {
+        match self {
+            ExecutionOutcome::Success(out) | ExecutionOutcome::Revert(out) => Some(out),
+            ExecutionOutcome::Error(_) => None,
+        }
+    }
+}
+
+/// Local execution backend (eg. revm with a forked state of the target
+/// contract) the kettle execution is compared against.
+pub trait LocalSimulator {
+    fn simulate(&self, ccr: &ConfidentialComputeRequest) -> ExecutionOutcome;
+}
+
+impl<F> LocalSimulator for F
+    where F: Fn(&ConfidentialComputeRequest) -> ExecutionOutcome
+{
+    fn simulate(&self, ccr: &ConfidentialComputeRequest) -> ExecutionOutcome {
+        self(ccr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    pub local: ExecutionOutcome,
+    pub kettle: ExecutionOutcome,
+}
+
+impl SimulationReport {
+
+    pub fn matches(&self) -> bool {
+        self.local == self.kettle
+    }
+
+    /// Offset of the first differing output byte, if both runs produced
+    /// output and it differs.
+    pub fn first_diff_offset(&self) -> Option<usize> {
+        let (local, kettle) = (self.local.output()?, self.kettle.output()?);
+        local.iter().zip(kettle.iter())
+            .position(|(a, b)| a != b)
+            .or_else(|| (local.len() != kettle.len()).then(|| local.len().min(kettle.len())))
+    }
+
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfidentialCallArgs<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<Address>,
+    /// `null` for contract creation.
+    to: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas: Option<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_price: Option<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_priority_fee_per_gas: Option<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_fee_per_gas: Option<U128>,
+    value: U256,
+    input: &'a Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kettle_address: Option<Address>,
+    confidential_inputs: &'a Bytes,
+    is_confidential: bool,
+}
+
+impl<'a> From<&'a ConfidentialComputeRequest> for ConfidentialCallArgs<'a> {
+    fn from(ccr: &'a ConfidentialComputeRequest) -> Self {
+        let record = &ccr.confidential_compute_record;
+        let (gas_price, max_priority_fee_per_gas, max_fee_per_gas) = match record.fees() {
+            Some(Fees::Legacy { gas_price }) => (Some(U128::from(gas_price)), None, None),
+            Some(Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas }) =>
+                (None, Some(U128::from(max_priority_fee_per_gas)), Some(U128::from(max_fee_per_gas))),
+            None => (None, None, None),
+        };
+        Self {
+            from: record.from,
+            to: record.target(),
+            gas: record.gas.map(U128::from),
+            gas_price,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+            value: record.value,
+            input: &record.input,
+            kettle_address: record.kettle_address,
+            confidential_inputs: &ccr.confidential_inputs,
+            is_confidential: true,
+        }
+    }
+}
+
+impl<T> SuaveProvider<T>
+    where T: Transport + Clone
+{
+
+    /// Execute `ccr` with a confidential `eth_call` on the kettle.
+    /// Reverts and execution errors are returned as outcomes; only transport
+    /// failures are returned as errors.
+    pub async fn confidential_call(&self, ccr: &ConfidentialComputeRequest) -> TransportResult<ExecutionOutcome> {
+        let args = ConfidentialCallArgs::from(ccr);
+        let res: TransportResult<Bytes> = self.client()
+            .request(self.rpc_methods().call.clone(), (args, "latest")).await;
+        match res {
+            Ok(out) => Ok(ExecutionOutcome::Success(out)),
+            Err(err) => match err.as_error_resp() {
+                Some(payload) => {
+                    let revert_data = payload.data.as_ref()
+                        .and_then(|data| Bytes::from_str(data.get().trim_matches('"')).ok());
+                    Ok(match revert_data {
+                        Some(data) => ExecutionOutcome::Revert(data),
+                        None => ExecutionOutcome::Error(payload.message.clone()),
+                    })
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    /// Run `ccr` through the local simulator and the kettle and report both
+    /// outcomes, to catch non-determinism before deploying a strategy.
+    pub async fn compare_simulation<S: LocalSimulator>(
+        &self,
+        simulator: &S,
+        ccr: &ConfidentialComputeRequest,
+    ) -> TransportResult<SimulationReport> {
+        let local = simulator.simulate(ccr);
+        let kettle = self.confidential_call(ccr).await?;
+        Ok(SimulationReport { local, kettle })
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_diff() {
+        let report = SimulationReport {
+            local: ExecutionOutcome::Success(Bytes::from_static(&[1, 2, 3])),
+            kettle: ExecutionOutcome::Success(Bytes::from_static(&[1, 9, 3])),
+        };
+        assert!(!report.matches());
+        assert_eq!(report.first_diff_offset(), Some(1));
+
+        let report = SimulationReport {
+            local: ExecutionOutcome::Success(Bytes::from_static(&[1, 2])),
+            kettle: ExecutionOutcome::Success(Bytes::from_static(&[1, 2, 3])),
+        };
+        assert_eq!(report.first_diff_offset(), Some(2));
+
+        let report = SimulationReport {
+            local: ExecutionOutcome::Revert(Bytes::new()),
+            kettle: ExecutionOutcome::Revert(Bytes::new()),
+        };
+        assert!(report.matches());
+        assert_eq!(report.first_diff_offset(), None);
+    }
+
+    #[test]
+    fn test_call_args_fees() {
+        let mut ccr = ConfidentialComputeRequest::default();
+        ccr.confidential_compute_record.set_fees(Fees::Dynamic { max_priority_fee_per_gas: 1, max_fee_per_gas: 2 });
+        let args = serde_json::to_value(ConfidentialCallArgs::from(&ccr)).unwrap();
+        assert_eq!(args["maxPriorityFeePerGas"], "0x1");
+        assert_eq!(args["maxFeePerGas"], "0x2");
+        assert!(args.get("gasPrice").is_none());
+
+        ccr.confidential_compute_record.set_fees(Fees::Legacy { gas_price: 3 });
+        let args = serde_json::to_value(ConfidentialCallArgs::from(&ccr)).unwrap();
+        assert_eq!(args["gasPrice"], "0x3");
+        assert!(args.get("maxFeePerGas").is_none());
+    }
+
+}
@@ -0,0 +1,141 @@
+use std::{future::Future, sync::Mutex, time::Duration};
+use tokio::{sync::watch, task::JoinSet};
+
+
+/// Fires when the `TaskScope` it came from shuts down (or is dropped).
+/// Background loops race their sleeps against it to stop promptly.
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+
+    pub fn is_shutdown(&self) -> bool {
+        *self.0.borrow() || self.0.has_changed().is_err()
+    }
+
+    /// Resolves once shutdown is requested.
+    pub async fn wait(&mut self) {
+        // An error means the scope is gone, which is a shutdown as well
+        let _ = self.0.wait_for(|shutdown| *shutdown).await;
+    }
+
+    /// Sleep for `duration`; `false` if shutdown was requested first.
+    pub async fn sleep(&mut self, duration: Duration) -> bool {
+        tokio::time::timeout(duration, self.wait()).await.is_err() && !self.is_shutdown()
+    }
+
+}
+
+/// Background tasks sharing a lifetime, eg. the watchers and probes of a
+/// provider. `shutdown` signals every task to stop and waits until all of
+/// them have returned, so embedding services can stop cleanly instead of
+/// leaking loops that poll the node forever.
+#[derive(Debug)]
+pub struct TaskScope {
+    shutdown: watch::Sender<bool>,
+    tasks: Mutex<JoinSet<()>>,
+}
+
+impl Default for TaskScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskScope {
+
+    pub fn new() -> Self {
+        Self { shutdown: watch::channel(false).0, tasks: Mutex::default() }
+    }
+
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.shutdown.subscribe())
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Run `task` on the current tokio runtime until it returns; it should
+    /// return once `signal` fires.
+    pub fn spawn<F>(&self, task: F)
+        where F: Future<Output = ()> + Send + 'static
+    {
+        let mut tasks = self.tasks.lock().unwrap();
+        // Reap finished tasks so long-lived scopes don't accumulate them
+        while tasks.try_join_next().is_some() {}
+        tasks.spawn(task);
+    }
+
+    /// Number of tasks not finished yet.
+    pub fn running(&self) -> usize {
+        let mut tasks = self.tasks.lock().unwrap();
+        while tasks.try_join_next().is_some() {}
+        tasks.len()
+    }
+
+    /// Signal shutdown and wait for every task to return. Tasks spawned
+    /// afterwards see the signal already fired.
+    pub async fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        while tasks.join_next().await.is_some() {}
+    }
+
+    /// `shutdown`, aborting the tasks still running after `grace`. Returns
+    /// whether all of them stopped on their own.
+    pub async fn shutdown_timeout(&self, grace: Duration) -> bool {
+        self.shutdown.send_replace(true);
+        let mut tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        let graceful = tokio::time::timeout(grace, async {
+            while tasks.join_next().await.is_some() {}
+        }).await.is_ok();
+        tasks.shutdown().await;
+        graceful
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_stops_tasks() {
+        let scope = TaskScope::new();
+        let ticks = Arc::new(AtomicUsize::new(0));
+        for _ in 0..3 {
+            let (mut signal, ticks) = (scope.signal(), ticks.clone());
+            scope.spawn(async move {
+                while signal.sleep(Duration::from_millis(1)).await {
+                    ticks.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(scope.running(), 3);
+
+        scope.shutdown().await;
+        assert_eq!(scope.running(), 0);
+        assert!(scope.signal().is_shutdown());
+        let stopped_at = ticks.load(Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(ticks.load(Ordering::Relaxed), stopped_at);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_timeout_aborts_stragglers() {
+        let scope = TaskScope::new();
+        scope.spawn(std::future::pending());
+        let mut signal = scope.signal();
+        scope.spawn(async move { signal.wait().await });
+        assert!(!scope.shutdown_timeout(Duration::from_millis(10)).await);
+        assert_eq!(scope.running(), 0);
+    }
+
+}
@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+use alloy::{
+    primitives::{TxHash, U128},
+    providers::Provider,
+    rpc::types::eth::TransactionReceipt,
+    transports::{Transport, TransportErrorKind, TransportResult},
+};
+use suave_alloy_types::ConfidentialComputeRequest;
+use super::{SuaveProvider, SuaveSigner};
+
+
+/// Durations of the stages of a CCR submission.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Filling missing nonce, chain id, gas price and kettle address.
+    pub fill: Duration,
+    pub sign: Duration,
+    pub encode: Duration,
+    /// HTTP round-trip of the raw submission.
+    pub round_trip: Duration,
+    /// Time from submission until the first receipt poll returned.
+    pub first_receipt_poll: Option<Duration>,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.fill + self.sign + self.encode + self.round_trip + self.first_receipt_poll.unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedSubmission {
+    pub tx_hash: TxHash,
+    pub timings: Timings,
+    sent_at: Instant,
+}
+
+impl TimedSubmission {
+    /// When the submission round-trip completed.
+    pub fn sent_at(&self) -> Instant {
+        self.sent_at
+    }
+}
+
+impl<T> SuaveProvider<T>
+    where T: Transport + Clone
+{
+
+    /// Fill, sign, encode and submit `ccr`, timing every stage.
+    pub async fn send_ccr_timed(
+        &self,
+        mut ccr: ConfidentialComputeRequest,
+        signer: &SuaveSigner,
+    ) -> TransportResult<TimedSubmission> {
+        let mut timings = Timings::default();
+
+        let start = Instant::now();
+        self.fill_ccr(&mut ccr, signer).await?;
+        timings.fill = start.elapsed();
+
+        let start = Instant::now();
+        signer.sign_in_place(&mut ccr).await
+            .map_err(TransportErrorKind::custom)?;
+        timings.sign = start.elapsed();
+
+        let start = Instant::now();
+        let encoded = ccr.rlp_encode().map_err(TransportErrorKind::custom)?;
+        timings.encode = start.elapsed();
+
+        let start = Instant::now();
+        let tx_hash = self.send_raw_ccr(&encoded).await?;
+        timings.round_trip = start.elapsed();
+
+        Ok(TimedSubmission { tx_hash, timings, sent_at: Instant::now() })
+    }
+
+    /// Poll the receipt of a timed submission once, recording the time of
+    /// the first poll.
+    pub async fn poll_receipt_timed(
+        &self,
+        submission: &mut TimedSubmission,
+    ) -> TransportResult<Option<TransactionReceipt>> {
+        let receipt = self.client()
+            .request("eth_getTransactionReceipt", (submission.tx_hash,)).await?;
+        submission.timings.first_receipt_poll.get_or_insert(submission.sent_at.elapsed());
+        Ok(receipt)
+    }
+
+    pub(crate) async fn fill_ccr(&self, ccr: &mut ConfidentialComputeRequest, signer: &SuaveSigner) -> TransportResult<()> {
+        let sender = signer.default_signer_address();
+        let record = &mut ccr.confidential_compute_record;
+        // Kettles execute CCRs confidentially, so the node can't estimate it
+        if record.gas.is_none() {
+            return Err(TransportErrorKind::custom_str("Missing gas limit; CCR gas can't be estimated, set it explicitly"));
+        }
+        if record.nonce.is_none() {
+            record.nonce = Some(self.next_nonce(sender).await?);
+        }
+        if record.chain_id.is_none() {
+            record.chain_id = Some(self.cached_chain_id().await?);
+        }
+        if record.fees().is_none() {
+            let gas_price: U128 = self.client().request("eth_gasPrice", ()).await?;
+            record.gas_price = Some(gas_price.to());
+        }
+        if record.kettle_address.is_none() {
+            record.kettle_address = Some(self.kettle_address().await?);
+        }
+        Ok(())
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_total() {
+        let timings = Timings {
+            fill: Duration::from_millis(3),
+            sign: Duration::from_millis(1),
+            encode: Duration::from_millis(1),
+            round_trip: Duration::from_millis(40),
+            first_receipt_poll: Some(Duration::from_millis(5)),
+        };
+        assert_eq!(timings.total(), Duration::from_millis(50));
+    }
+
+}
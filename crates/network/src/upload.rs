@@ -0,0 +1,200 @@
+//! Experimental chunked upload of confidential inputs, behind the
+//! `experimental-upload` feature.
+//!
+//! suave-geth (up to v0.2) only takes confidential inputs inline, in the
+//! `confidentialInputs` field of the 0x43 request; its RPC has no upload or
+//! inputs-by-hash method and the kettle rejects requests whose inputs don't
+//! hash to `confidentialInputsHash`. The `suavex_beginInputsUpload`,
+//! `suavex_uploadInputsChunk` and `suavex_commitInputsUpload` methods used
+//! here are a proposal for kettles (or proxies in front of them) resolving
+//! inputs by hash; nothing upstream implements them yet.
+//!
+//! Requests sent with `send_ccr_streamed` carry no inputs, only their hash.
+//! The decoders accept such hash-referenced requests (see
+//! `ConfidentialComputeRequest::references_inputs`).
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use serde::{Deserialize, Serialize};
+use alloy::{
+    primitives::{hex, Keccak256, TxHash, B256, U64},
+    providers::Provider,
+    transports::{Transport, TransportErrorKind, TransportResult},
+};
+use suave_alloy_types::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+use super::{SuaveProvider, SuaveSigner};
+use super::methods::{BEGIN_INPUTS_UPLOAD, COMMIT_INPUTS_UPLOAD, UPLOAD_INPUTS_CHUNK};
+
+
+/// Chunk size of `upload_inputs`.
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Confidential inputs stored on the kettle, referenced by hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedInputs {
+    pub hash: B256,
+    pub len: U64,
+}
+
+impl<T> SuaveProvider<T>
+    where T: Transport + Clone
+{
+
+    /// Stream confidential inputs to the kettle in chunks of
+    /// `DEFAULT_CHUNK_SIZE`, hashing them on the way. Only a single chunk is
+    /// held in memory, so multi-MB datasets can be read from disk directly.
+    /// Experimental, see the module docs.
+    pub async fn upload_inputs<R>(&self, inputs: R) -> TransportResult<UploadedInputs>
+        where R: AsyncRead + Unpin
+    {
+        self.upload_inputs_chunked(inputs, DEFAULT_CHUNK_SIZE).await
+    }
+
+    pub async fn upload_inputs_chunked<R>(&self, mut inputs: R, chunk_size: usize) -> TransportResult<UploadedInputs>
+        where R: AsyncRead + Unpin
+    {
+        let client = self.client();
+        let upload_id: String = client.request(BEGIN_INPUTS_UPLOAD, ()).await?;
+        let mut hasher = Keccak256::new();
+        let mut buf = vec![0u8; chunk_size.max(1)];
+        let mut len = 0u64;
+        for index in 0u64.. {
+            let read = read_chunk(&mut inputs, &mut buf).await
+                .map_err(TransportErrorKind::custom)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+            len += read as u64;
+            let chunk = hex::encode_prefixed(&buf[..read]);
+            let _: bool = client.request(UPLOAD_INPUTS_CHUNK, (upload_id.clone(), U64::from(index), chunk)).await?;
+        }
+        let uploaded = UploadedInputs { hash: hasher.finalize(), len: U64::from(len) };
+        let _: bool = client.request(COMMIT_INPUTS_UPLOAD, (upload_id, uploaded)).await?;
+        Ok(uploaded)
+    }
+
+    /// Upload `inputs` (see `upload_inputs`), then fill, sign and submit
+    /// `record` committing to their hash. The submitted request carries no
+    /// inputs; the kettle resolves them by hash.
+    pub async fn send_ccr_streamed<R>(
+        &self,
+        record: ConfidentialComputeRecord,
+        inputs: R,
+        signer: &SuaveSigner,
+    ) -> TransportResult<TxHash>
+        where R: AsyncRead + Unpin
+    {
+        let uploaded = self.upload_inputs(inputs).await?;
+        let mut ccr = ConfidentialComputeRequest::new(record, None);
+        ccr.confidential_compute_record.set_confidential_inputs_hash(uploaded.hash);
+        self.fill_ccr(&mut ccr, signer).await?;
+        signer.sign_in_place(&mut ccr).await
+            .map_err(TransportErrorKind::custom)?;
+        let encoded = ccr.rlp_encode().map_err(TransportErrorKind::custom)?;
+        self.send_raw_ccr(&encoded).await
+    }
+
+}
+
+/// Read until `buf` is full or the reader is exhausted.
+async fn read_chunk<R>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize>
+    where R: AsyncRead + Unpin
+{
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]).await? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::keccak256;
+    use suave_alloy_types::{fixtures, DecodeMode};
+    use super::*;
+    use super::super::provider::tests::serve_rpc;
+
+    fn respond(method: &str) -> serde_json::Value {
+        match method {
+            BEGIN_INPUTS_UPLOAD => serde_json::json!("0x01"),
+            UPLOAD_INPUTS_CHUNK | COMMIT_INPUTS_UPLOAD => serde_json::json!(true),
+            _ => serde_json::json!(TxHash::repeat_byte(0x11)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_inputs_mock() -> eyre::Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let provider = SuaveProvider::try_from(url.as_str())?;
+        let server = tokio::spawn(serve_rpc(listener, 5, respond));
+
+        let inputs = (0..600u32).map(|i| i as u8).collect::<Vec<_>>();
+        let uploaded = provider.upload_inputs_chunked(inputs.as_slice(), 256).await?;
+        assert_eq!(uploaded, UploadedInputs { hash: keccak256(&inputs), len: U64::from(600) });
+
+        let requests = server.await??.into_iter().map(|(_, _, request)| request).collect::<Vec<_>>();
+        let methods = requests.iter().map(|request| request["method"].clone()).collect::<Vec<_>>();
+        assert_eq!(methods, [
+            BEGIN_INPUTS_UPLOAD, UPLOAD_INPUTS_CHUNK, UPLOAD_INPUTS_CHUNK, UPLOAD_INPUTS_CHUNK, COMMIT_INPUTS_UPLOAD,
+        ]);
+        let chunks = requests[1..4].iter()
+            .map(|request| hex::decode(request["params"][2].as_str().unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(chunks.concat(), inputs);
+        assert_eq!(requests[3]["params"][1], "0x2");
+        assert_eq!(requests[4]["params"][1], serde_json::to_value(uploaded)?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_ccr_streamed_mock() -> eyre::Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let provider = SuaveProvider::try_from(url.as_str())?;
+        let server = tokio::spawn(serve_rpc(listener, 4, respond));
+
+        // Filled record, so only the upload and the submission hit the node
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let mut record = fixture.request.confidential_compute_record.clone();
+        record.signature = None;
+        let wallet: alloy::signers::wallet::LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?;
+        let inputs = vec![0x43; 1000];
+        let tx_hash = provider.send_ccr_streamed(record, inputs.as_slice(), &SuaveSigner::new(wallet)).await?;
+        assert_eq!(tx_hash, TxHash::repeat_byte(0x11));
+
+        let sent = server.await??.pop().unwrap().2;
+        let raw = hex::decode(sent["params"][0].as_str().unwrap())?;
+        let ccr = ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Strict)?;
+        assert!(ccr.references_inputs());
+        assert_eq!(ccr.confidential_compute_record.confidential_inputs_hash, Some(keccak256(&inputs)));
+        assert_eq!(ccr.recover_signer()?, fixture.signer);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chunked_hash_matches() {
+        let inputs = (0..1000u32).map(|i| i as u8).collect::<Vec<_>>();
+        // Reader yielding short reads, as sockets and files may
+        let mut reader = tokio::io::AsyncReadExt::chain(&inputs[..300], &inputs[300..]);
+        let mut buf = vec![0u8; 256];
+        let mut hasher = Keccak256::new();
+        let mut lens = Vec::new();
+        loop {
+            let read = read_chunk(&mut reader, &mut buf).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+            lens.push(read);
+        }
+        assert_eq!(lens, vec![256, 256, 256, 232]);
+        assert_eq!(hasher.finalize(), keccak256(&inputs));
+    }
+
+}
@@ -0,0 +1,99 @@
+use std::{ops::Range, sync::Arc};
+use alloy::signers::wallet::{coins_bip39::English, LocalWallet, MnemonicBuilder, WalletError};
+use super::SuaveSigner;
+
+
+/// BIP-44 path prefix of SUAVE accounts (same as Ethereum); the account
+/// index is appended.
+pub const SUAVE_DERIVATION_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
+
+/// Mnemonic (BIP-39/BIP-32) derivation of SUAVE signers.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SuaveWallet {
+    phrase: String,
+    path_prefix: String,
+}
+
+impl SuaveWallet {
+
+    pub fn new(phrase: impl Into<String>) -> Self {
+        Self { phrase: phrase.into(), path_prefix: SUAVE_DERIVATION_PATH_PREFIX.to_string() }
+    }
+
+    /// Use another derivation path prefix, eg. `m/44'/60'/1'/0/`.
+    pub fn with_path_prefix(mut self, path_prefix: impl Into<String>) -> Self {
+        self.path_prefix = path_prefix.into();
+        self
+    }
+
+    /// Signer of the account at `index` on the SUAVE path.
+    pub fn from_mnemonic(phrase: &str, index: u32) -> Result<SuaveSigner, WalletError> {
+        Self::new(phrase).signer(index)
+    }
+
+    pub fn wallet(&self, index: u32) -> Result<LocalWallet, WalletError> {
+        MnemonicBuilder::<English>::default()
+            .phrase(self.phrase.as_str())
+            .derivation_path(format!("{}{index}", self.path_prefix))?
+            .build()
+    }
+
+    pub fn signer(&self, index: u32) -> Result<SuaveSigner, WalletError> {
+        self.wallet(index).map(SuaveSigner::new)
+    }
+
+    /// Signers for every index in `indices`, eg. for bots rotating senders.
+    pub fn signers(&self, indices: Range<u32>) -> Result<Vec<SuaveSigner>, WalletError> {
+        indices.map(|index| self.signer(index)).collect()
+    }
+
+    /// Single signer holding all accounts in `indices`; `indices.start` is
+    /// the default sender (and always included), the others are picked with
+    /// `sign_transaction_from`.
+    pub fn multi_signer(&self, indices: Range<u32>) -> Result<SuaveSigner, WalletError> {
+        let mut signer = self.signer(indices.start)?;
+        for index in indices.skip(1) {
+            signer.register_signer(Arc::new(self.wallet(index)?));
+        }
+        Ok(signer)
+    }
+
+}
+
+impl std::fmt::Debug for SuaveWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuaveWallet")
+            .field("phrase", &"<redacted>")
+            .field("path_prefix", &self.path_prefix)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::primitives::Address;
+    use super::*;
+
+    const PHRASE: &str = "test test test test test test test test test test test junk";
+
+    #[test]
+    fn test_from_mnemonic() {
+        let signer = SuaveWallet::from_mnemonic(PHRASE, 0).unwrap();
+        assert_eq!(signer.default_signer_address(), Address::from_str("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266").unwrap());
+
+        let signers = SuaveWallet::new(PHRASE).signers(0..3).unwrap();
+        assert_eq!(signers.len(), 3);
+        assert_eq!(signers[1].default_signer_address(), Address::from_str("0x70997970C51812dc3A010C7d01b50e0d17dc79C8").unwrap());
+
+        let multi = SuaveWallet::new(PHRASE).multi_signer(0..3).unwrap();
+        assert_eq!(multi.signer_addresses().count(), 3);
+        assert_eq!(multi.default_signer_address(), signers[0].default_signer_address());
+
+        let other_path = SuaveWallet::new(PHRASE).with_path_prefix("m/44'/60'/1'/0/").signer(0).unwrap();
+        assert_ne!(other_path.default_signer_address(), signers[0].default_signer_address());
+        assert!(SuaveWallet::new(PHRASE).with_path_prefix("not a path").signer(0).is_err());
+    }
+
+}
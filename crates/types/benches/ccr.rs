@@ -0,0 +1,112 @@
+//! Encoding path benchmarks: build → hash → sign → encode at 1KB, 100KB
+//! and 1MB of confidential inputs.
+//!
+//! `cargo bench -p suave-alloy-types --bench ccr`
+//!
+//! With `--features flamegraph` every benchmark run in profiling mode
+//! writes a flamegraph to `target/criterion/<bench>/profile/flamegraph.svg`:
+//!
+//! `cargo bench -p suave-alloy-types --bench ccr --features flamegraph -- --profile-time 10`
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use alloy::{
+    primitives::{Address, Bytes, TxKind},
+    consensus::SignableTransaction,
+    eips::eip2718::Encodable2718,
+    network::TxSigner,
+    signers::wallet::LocalWallet,
+};
+use suave_alloy_types::{fixtures, ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+
+const INPUT_SIZES: [(&str, usize); 3] = [("1KB", 1 << 10), ("100KB", 100 << 10), ("1MB", 1 << 20)];
+
+fn record() -> ConfidentialComputeRecord {
+    let mut record = fixtures::unsigned_record();
+    record.to = TxKind::Call(Address::repeat_byte(0xc8));
+    record.input = Bytes::from_static(&[0x50, 0x72, 0x35, 0x53]);
+    record
+}
+
+fn wallet() -> LocalWallet {
+    "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap()
+}
+
+fn sign(rt: &tokio::runtime::Runtime, wallet: &LocalWallet, ccr: &mut ConfidentialComputeRequest) {
+    let sig = rt.block_on(wallet.sign_transaction(ccr)).unwrap();
+    ccr.confidential_compute_record.set_sig(sig);
+}
+
+/// Copy without the memoized signing hash: the cache is keyed on the
+/// calldata buffer, so a copy of it misses.
+fn uncached(ccr: &ConfidentialComputeRequest) -> ConfidentialComputeRequest {
+    let mut ccr = ccr.clone();
+    let record = &mut ccr.confidential_compute_record;
+    record.input = Bytes::copy_from_slice(&record.input);
+    ccr
+}
+
+fn bench_encoding_path(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let wallet = wallet();
+    let mut group = c.benchmark_group("ccr");
+
+    for (label, size) in INPUT_SIZES {
+        let inputs = Bytes::from(vec![0xab; size]);
+        let unsigned = ConfidentialComputeRequest::new(record(), Some(inputs.clone()));
+        let mut signed = unsigned.clone();
+        sign(&rt, &wallet, &mut signed);
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("build", label), &inputs, |b, inputs| {
+            b.iter(|| ConfidentialComputeRequest::new(record(), Some(inputs.clone())))
+        });
+        group.bench_with_input(BenchmarkId::new("signature_hash", label), &unsigned, |b, ccr| {
+            b.iter_batched(|| uncached(ccr), |ccr| ccr.signature_hash(), BatchSize::LargeInput)
+        });
+        group.bench_with_input(BenchmarkId::new("signature_hash_cached", label), &unsigned, |b, ccr| {
+            b.iter(|| ccr.signature_hash())
+        });
+        group.bench_with_input(BenchmarkId::new("sign", label), &unsigned, |b, ccr| {
+            b.iter_batched(|| uncached(ccr), |mut ccr| sign(&rt, &wallet, &mut ccr), BatchSize::LargeInput)
+        });
+        group.bench_with_input(BenchmarkId::new("encode", label), &signed, |b, ccr| {
+            b.iter(|| {
+                let mut encoded = Vec::with_capacity(ccr.encode_2718_len());
+                ccr.encode_2718(&mut encoded);
+                encoded
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("tx_hash", label), &signed, |b, ccr| {
+            b.iter(|| ccr.tx_hash().unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("end_to_end", label), &inputs, |b, inputs| {
+            b.iter(|| {
+                let mut ccr = ConfidentialComputeRequest::new(record(), Some(inputs.clone()));
+                sign(&rt, &wallet, &mut ccr);
+                let mut encoded = Vec::new();
+                ccr.encode_2718(&mut encoded);
+                encoded
+            })
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "flamegraph")]
+fn config() -> Criterion {
+    use pprof::criterion::{Output, PProfProfiler};
+    Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)))
+}
+
+#[cfg(not(feature = "flamegraph"))]
+fn config() -> Criterion {
+    Criterion::default()
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = bench_encoding_path
+}
+criterion_main!(benches);
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use alloy::{
+    primitives::{Address, Bytes, TxKind},
+    json_abi::{Function, JsonAbi},
+    dyn_abi::{DynSolType, DynSolValue, JsonAbiExt, Specifier},
+};
+use super::{CcrError, ConfidentialComputeRecord};
+
+
+/// Function call expression, e.g. `{"name": "queryLatestPrice", "args": ["ETHUSDT"]}`.
+///
+/// Arguments are JSON values matching the function inputs: strings are
+/// parsed with the solidity type (`"0x.."` addresses/bytes, `"1e18"`
+/// integers), arrays and tuples are JSON arrays.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub args: Vec<Value>,
+}
+
+impl FunctionCall {
+
+    pub fn new(name: impl Into<String>, args: Vec<Value>) -> Self {
+        Self { name: name.into(), args }
+    }
+
+    /// ABI encode the call (selector + arguments) against `abi`.
+    /// Overloads are resolved by the number of arguments.
+    pub fn encode(&self, abi: &JsonAbi) -> Result<Bytes, CcrError> {
+        let function = self.resolve(abi)?;
+        let values = function.inputs.iter()
+            .zip(&self.args)
+            .map(|(param, arg)| {
+                let ty = param.resolve().map_err(|e| CcrError::Abi(e.to_string()))?;
+                json_to_sol_value(&ty, arg)
+                    .map_err(|e| CcrError::Abi(format!("Invalid argument `{}` of {}: {e}", param.name, self.name)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let calldata = function.abi_encode_input(&values).map_err(|e| CcrError::Abi(e.to_string()))?;
+        Ok(calldata.into())
+    }
+
+    fn resolve<'a>(&self, abi: &'a JsonAbi) -> Result<&'a Function, CcrError> {
+        abi.function(&self.name)
+            .ok_or_else(|| CcrError::Abi(format!("Function {} not found in ABI", self.name)))?
+            .iter()
+            .find(|f| f.inputs.len() == self.args.len())
+            .ok_or_else(|| CcrError::Abi(format!("No overload of {} takes {} arguments", self.name, self.args.len())))
+    }
+
+}
+
+fn json_to_sol_value(ty: &DynSolType, value: &Value) -> Result<DynSolValue, String> {
+    match (ty, value) {
+        (DynSolType::Array(inner), Value::Array(items)) => {
+            let values = items.iter().map(|v| json_to_sol_value(inner, v)).collect::<Result<_, _>>()?;
+            Ok(DynSolValue::Array(values))
+        }
+        (DynSolType::FixedArray(inner, len), Value::Array(items)) => {
+            if items.len() != *len {
+                return Err(format!("Expected {len} items, got {}", items.len()));
+            }
+            let values = items.iter().map(|v| json_to_sol_value(inner, v)).collect::<Result<_, _>>()?;
+            Ok(DynSolValue::FixedArray(values))
+        }
+        (DynSolType::Tuple(types), Value::Array(items)) => {
+            if items.len() != types.len() {
+                return Err(format!("Expected {} tuple fields, got {}", types.len(), items.len()));
+            }
+            let values = types.iter().zip(items)
+                .map(|(ty, v)| json_to_sol_value(ty, v))
+                .collect::<Result<_, _>>()?;
+            Ok(DynSolValue::Tuple(values))
+        }
+        (_, Value::String(s)) => ty.coerce_str(s).map_err(|e| e.to_string()),
+        (_, Value::Number(_) | Value::Bool(_)) => ty.coerce_str(&value.to_string()).map_err(|e| e.to_string()),
+        _ => Err(format!("Cannot convert {value} to {ty}")),
+    }
+}
+
+impl ConfidentialComputeRecord {
+
+    /// Record calling `to` with calldata encoded from a JSON ABI and a JSON
+    /// function call, without compiled bindings. Gas, nonce etc. are left
+    /// for the caller/fillers.
+    pub fn from_abi_call(
+        abi_json: &str,
+        call_json: &str,
+        to: Address,
+        kettle_address: Address,
+    ) -> Result<Self, CcrError> {
+        let abi: JsonAbi = serde_json::from_str(abi_json)?;
+        let call: FunctionCall = serde_json::from_str(call_json)?;
+        Ok(Self {
+            to: TxKind::Call(to),
+            input: call.encode(&abi)?,
+            kettle_address: Some(kettle_address),
+            ..Default::default()
+        })
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use serde_json::json;
+    use eyre::Result;
+    use super::*;
+
+    const ORACLE_ABI: &str = r#"[
+        {"type":"function","name":"queryLatestPrice","stateMutability":"view",
+         "inputs":[{"name":"ticker","type":"string"}],"outputs":[{"name":"price","type":"uint256"}]},
+        {"type":"function","name":"setPrices","stateMutability":"nonpayable",
+         "inputs":[{"name":"tickers","type":"string[]"},{"name":"prices","type":"uint256[]"}],"outputs":[]},
+        {"type":"function","name":"setPrices","stateMutability":"nonpayable",
+         "inputs":[{"name":"ticker","type":"string"}],"outputs":[]}
+    ]"#;
+
+    #[test]
+    fn test_from_abi_call() -> Result<()> {
+        let to = Address::from_str("0xc803334c79650708Daf3a3462AC4B48296b1352a")?;
+        let kettle = Address::from_str("0x03493869959c866713c33669ca118e774a30a0e5")?;
+        let record = ConfidentialComputeRecord::from_abi_call(
+            ORACLE_ABI,
+            r#"{"name":"queryLatestPrice","args":["ETHUSDT"]}"#,
+            to,
+            kettle,
+        )?;
+        let expected = Bytes::from_str("0x50723553000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000074554485553445400000000000000000000000000000000000000000000000000")?;
+        assert_eq!(record.input, expected);
+        assert_eq!(record.to, TxKind::Call(to));
+        assert_eq!(record.kettle_address, Some(kettle));
+        assert_eq!(record.nonce, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_overloads_and_errors() -> Result<()> {
+        let abi: JsonAbi = serde_json::from_str(ORACLE_ABI)?;
+        let call = FunctionCall::new("setPrices", vec![json!(["ETHUSDT"]), json!([1, "0x10"])]);
+        let calldata = call.encode(&abi)?;
+        assert_eq!(&calldata[..4], abi.function("setPrices").unwrap()[0].selector().as_slice());
+
+        assert!(matches!(FunctionCall::new("missing", vec![]).encode(&abi), Err(CcrError::Abi(_))));
+        assert!(FunctionCall::new("queryLatestPrice", vec![]).encode(&abi).is_err());
+        assert!(FunctionCall::new("setPrices", vec![json!("ETHUSDT"), json!(["x"])]).encode(&abi).is_err());
+        Ok(())
+    }
+
+}
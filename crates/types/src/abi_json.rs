@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use serde_json::{json, Value};
+use alloy::{
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    json_abi::JsonAbi,
+    primitives::{hex, Address, FixedBytes},
+};
+use super::ConfidentialComputeRequest;
+
+
+/// ABIs of known SUAPPs by contract address, used to decode record inputs.
+#[derive(Debug, Clone, Default)]
+pub struct AbiRegistry {
+    abis: HashMap<Address, JsonAbi>,
+}
+
+impl AbiRegistry {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, address: Address, abi: JsonAbi) {
+        self.abis.insert(address, abi);
+    }
+
+    pub fn with(mut self, address: Address, abi: JsonAbi) -> Self {
+        self.register(address, abi);
+        self
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&JsonAbi> {
+        self.abis.get(address)
+    }
+
+    /// `input` of a call to `to` decoded as
+    /// `{"function", "signature", "args": [{"name", "type", "value"}]}`;
+    /// `None` if the ABI of `to` isn't registered or no function matches.
+    pub fn decode_input(&self, to: &Address, input: &[u8]) -> Option<Value> {
+        let selector = FixedBytes::<4>::from_slice(input.get(..4)?);
+        let function = self.get(to)?.functions().find(|f| f.selector() == selector)?;
+        let values = function.abi_decode_input(&input[4..], false).ok()?;
+        let args = function.inputs.iter().zip(&values).map(|(param, value)| json!({
+            "name": param.name,
+            "type": param.selector_type(),
+            "value": to_json(value),
+        })).collect::<Vec<_>>();
+        Some(json!({
+            "function": function.name,
+            "signature": function.signature(),
+            "args": args,
+        }))
+    }
+
+}
+
+/// Integers are written as decimal strings so no precision is lost.
+fn to_json(value: &DynSolValue) -> Value {
+    match value {
+        DynSolValue::Bool(b) => Value::Bool(*b),
+        DynSolValue::Int(i, _) => Value::String(i.to_string()),
+        DynSolValue::Uint(u, _) => Value::String(u.to_string()),
+        DynSolValue::Address(a) => Value::String(a.to_string()),
+        DynSolValue::FixedBytes(word, size) => Value::String(hex::encode_prefixed(&word[..*size])),
+        DynSolValue::Bytes(b) => Value::String(hex::encode_prefixed(b)),
+        DynSolValue::String(s) => Value::String(s.clone()),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) | DynSolValue::Tuple(items) => {
+            Value::Array(items.iter().map(to_json).collect())
+        }
+        other => Value::String(format!("{other:?}")),
+    }
+}
+
+impl ConfidentialComputeRequest {
+
+    /// JSON with the record input decoded next to the raw hex (as
+    /// `decodedInput`) if the target's ABI is registered, so logged or
+    /// stored requests are self-describing. The extra field is ignored when
+    /// deserializing.
+    pub fn to_json_enriched(&self, registry: &AbiRegistry) -> serde_json::Result<Value> {
+        let mut json = serde_json::to_value(self)?;
+        let record = &self.confidential_compute_record;
+        if let Some(decoded) = record.target().and_then(|to| registry.decode_input(&to, &record.input)) {
+            if let Some(Value::Object(record)) = json.get_mut("confidentialComputeRecord") {
+                record.insert("decodedInput".into(), decoded);
+            }
+        }
+        Ok(json)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_enriched_json() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let abi: JsonAbi = serde_json::from_str(r#"[{
+            "type": "function",
+            "name": "queryLatestPrice",
+            "inputs": [{"name": "ticker", "type": "string"}],
+            "outputs": [{"name": "price", "type": "uint256"}],
+            "stateMutability": "view"
+        }]"#).unwrap();
+        let to = fixture.request.confidential_compute_record.target().unwrap();
+
+        let json = fixture.request.to_json_enriched(&AbiRegistry::new().with(to, abi.clone())).unwrap();
+        let decoded = &json["confidentialComputeRecord"]["decodedInput"];
+        assert_eq!(decoded["signature"], "queryLatestPrice(string)");
+        assert_eq!(decoded["args"][0], json!({ "name": "ticker", "type": "string", "value": "ETHUSDT" }));
+        assert_eq!(json["confidentialComputeRecord"]["input"], json!(fixture.request.confidential_compute_record.input));
+        assert_eq!(serde_json::from_value::<ConfidentialComputeRequest>(json).unwrap(), fixture.request);
+
+        let other = AbiRegistry::new().with(Address::repeat_byte(0x01), abi);
+        assert_eq!(fixture.request.to_json_enriched(&other).unwrap(), serde_json::to_value(&fixture.request).unwrap());
+    }
+
+}
@@ -0,0 +1,210 @@
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
+
+use alloy::{
+    primitives::Bytes,
+    eips::eip2718::Decodable2718,
+};
+use super::{ConfidentialComputeRequest, ConfidentialComputeResult, SuaveTxEnvelope};
+
+
+/// Max number of leading bytes of a malformed item kept in the report.
+pub const SNIPPET_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    /// Item contained no bytes.
+    Empty,
+    /// Item is not a typed envelope or has an unsupported type byte.
+    UnexpectedType(u8),
+    /// RLP payload could not be decoded.
+    Rlp(alloy_rlp::Error),
+    /// Item decoded, but bytes were left after the RLP payload.
+    TrailingBytes(usize),
+}
+
+impl std::fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeErrorKind::Empty => write!(f, "empty item"),
+            DecodeErrorKind::UnexpectedType(ty) => write!(f, "unexpected type byte 0x{ty:02x}"),
+            DecodeErrorKind::Rlp(err) => write!(f, "rlp error: {err}"),
+            DecodeErrorKind::TrailingBytes(n) => write!(f, "{n} trailing bytes"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchDecodeError {
+    /// Position of the item within the batch.
+    pub index: usize,
+    pub kind: DecodeErrorKind,
+    /// Leading bytes of the raw item (at most `SNIPPET_LEN`).
+    pub snippet: Bytes,
+    /// Length of the raw item.
+    pub len: usize,
+}
+
+impl std::fmt::Display for BatchDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "item {} ({} bytes, starts with {}): {}", self.index, self.len, self.snippet, self.kind)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchDecodeReport {
+    /// Number of items in the batch.
+    pub total: usize,
+    pub errors: Vec<BatchDecodeError>,
+}
+
+impl BatchDecodeReport {
+
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn decoded(&self) -> usize {
+        self.total - self.errors.len()
+    }
+
+    pub fn failed_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.errors.iter().map(|e| e.index)
+    }
+
+}
+
+/// Decode every item of a batch (eg. raw txs of a block) into a SUAVE
+/// transaction: CCRs, their results and the plain legacy and EIP-1559
+/// transactions. Malformed items don't abort decoding; they are collected
+/// in the report and the successfully decoded transactions are returned
+/// with their batch index.
+pub fn decode_batch<I, B>(items: I) -> (Vec<(usize, SuaveTxEnvelope)>, BatchDecodeReport)
+    where I: IntoIterator<Item = B>, B: AsRef<[u8]>
+{
+    let mut decoded = Vec::new();
+    let mut report = BatchDecodeReport::default();
+    for (index, item) in items.into_iter().enumerate() {
+        let raw = item.as_ref();
+        report.total += 1;
+        match decode_item(raw) {
+            Ok(tx) => decoded.push((index, tx)),
+            Err(kind) => report.errors.push(BatchDecodeError {
+                index,
+                kind,
+                snippet: Bytes::copy_from_slice(&raw[..raw.len().min(SNIPPET_LEN)]),
+                len: raw.len(),
+            }),
+        }
+    }
+    (decoded, report)
+}
+
+fn decode_item(raw: &[u8]) -> Result<SuaveTxEnvelope, DecodeErrorKind> {
+    let ty = *raw.first().ok_or(DecodeErrorKind::Empty)?;
+    // Legacy transactions start with an RLP list header instead of a type
+    let known = [ConfidentialComputeRequest::TYPE, ConfidentialComputeResult::TYPE, 2];
+    if ty < 0xc0 && !known.contains(&ty) {
+        return Err(DecodeErrorKind::UnexpectedType(ty));
+    }
+    let mut buf = raw;
+    let tx = SuaveTxEnvelope::decode_2718(&mut buf).map_err(DecodeErrorKind::Rlp)?;
+    if !buf.is_empty() {
+        return Err(DecodeErrorKind::TrailingBytes(buf.len()));
+    }
+    Ok(tx)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{SignableTransaction, TxEip1559, TxLegacy},
+        eips::eip2718::Encodable2718,
+        network::TxSigner,
+        primitives::{Address, TxKind},
+        signers::wallet::LocalWallet,
+    };
+    use super::*;
+    use super::super::fixtures;
+
+    fn encoded_ccr() -> Bytes {
+        fixtures::load_fixture("bundle_inputs").unwrap().raw
+    }
+
+    #[test]
+    fn test_decode_batch_collects_errors() {
+        let valid = encoded_ccr();
+        let mut trailing = valid.to_vec();
+        trailing.push(0x00);
+        let items = vec![
+            valid.to_vec(),
+            Vec::new(),
+            vec![0x44, 0xc0],
+            valid[..40].to_vec(),
+            trailing,
+            valid.to_vec(),
+        ];
+
+        let (decoded, report) = decode_batch(&items);
+
+        assert_eq!(report.total, 6);
+        assert_eq!(report.decoded(), 2);
+        assert_eq!(decoded.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0, 5]);
+        assert_eq!(report.failed_indices().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(report.errors[0].kind, DecodeErrorKind::Empty);
+        assert_eq!(report.errors[1].kind, DecodeErrorKind::UnexpectedType(0x44));
+        assert!(matches!(report.errors[2].kind, DecodeErrorKind::Rlp(_)));
+        assert_eq!(report.errors[3].kind, DecodeErrorKind::TrailingBytes(1));
+        assert_eq!(report.errors[2].snippet.len(), SNIPPET_LEN);
+        assert_eq!(report.errors[2].len, 40);
+    }
+
+    #[test]
+    fn test_decode_batch_clean() {
+        let valid = encoded_ccr();
+        let (decoded, report) = decode_batch([valid.clone(), valid]);
+        assert!(report.is_clean());
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].1, decoded[1].1);
+    }
+
+    #[tokio::test]
+    async fn test_decode_block_transactions() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let record = fixture.request.confidential_compute_record.clone();
+        let result = ConfidentialComputeResult {
+            chain_id: record.chain_id.unwrap(),
+            signature: record.signature.unwrap(),
+            request_record: record,
+            confidential_compute_result: Bytes::from_static(&[0x01]),
+        };
+
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let to = TxKind::Call(Address::repeat_byte(0xc8));
+        let mut legacy = TxLegacy { chain_id: Some(0x1008c45), gas_price: 0x3b9aca00, gas_limit: 21_000, to, ..Default::default() };
+        let mut eip1559 = TxEip1559 { chain_id: 0x1008c45, max_fee_per_gas: 0x3b9aca00, gas_limit: 21_000, to, ..Default::default() };
+        let signature = wallet.sign_transaction(&mut legacy).await.unwrap();
+        let legacy = legacy.into_signed(signature);
+        let signature = wallet.sign_transaction(&mut eip1559).await.unwrap();
+        let eip1559 = eip1559.into_signed(signature);
+
+        let txs = vec![
+            SuaveTxEnvelope::from(fixture.request),
+            SuaveTxEnvelope::from(result),
+            SuaveTxEnvelope::from(legacy),
+            SuaveTxEnvelope::from(eip1559),
+        ];
+        let items = txs.iter()
+            .map(|tx| {
+                let mut out = Vec::new();
+                tx.encode_2718(&mut out);
+                out
+            })
+            .collect::<Vec<_>>();
+
+        let (decoded, report) = decode_batch(&items);
+        assert!(report.is_clean());
+        assert_eq!(decoded.into_iter().map(|(_, tx)| tx).collect::<Vec<_>>(), txs);
+    }
+
+}
@@ -0,0 +1,311 @@
+use alloy::{
+    eips::eip2930::AccessList,
+    primitives::{Address, Bytes, TxKind, U256},
+    sol_types::SolCall,
+};
+use super::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+
+/// Fluent construction of a (unsigned) record. Fields not set are left for
+/// the fillers.
+#[derive(Debug, Clone, Default)]
+pub struct ConfidentialComputeRecordBuilder {
+    record: ConfidentialComputeRecord,
+}
+
+impl ConfidentialComputeRecordBuilder {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `to` with the ABI encoded `sol!` call struct as input.
+    pub fn call<T: SolCall>(mut self, to: Address, call: &T) -> Self {
+        self.record.to = TxKind::Call(to);
+        self.record.input = call.abi_encode().into();
+        self
+    }
+
+    pub fn to(mut self, to: Address) -> Self {
+        self.record.to = TxKind::Call(to);
+        self
+    }
+
+    /// Deploy a contract with `init_code` (creation bytecode followed by
+    /// the ABI encoded constructor arguments) as input.
+    pub fn create(mut self, init_code: Bytes) -> Self {
+        self.record.to = TxKind::Create;
+        self.record.input = init_code;
+        self
+    }
+
+    pub fn input(mut self, input: Bytes) -> Self {
+        self.record.input = input;
+        self
+    }
+
+    pub fn value(mut self, value: U256) -> Self {
+        self.record.value = value;
+        self
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.record.nonce = Some(nonce);
+        self
+    }
+
+    pub fn gas(mut self, gas: u128) -> Self {
+        self.record.gas = Some(gas);
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: u128) -> Self {
+        self.record.gas_price = Some(gas_price);
+        self
+    }
+
+    /// Dynamic fees in place of the gas price (see `ConfidentialComputeRecord::fees`).
+    pub fn dynamic_fees(mut self, max_fee_per_gas: u128, max_priority_fee_per_gas: u128) -> Self {
+        self.record.max_fee_per_gas = Some(max_fee_per_gas);
+        self.record.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+        self
+    }
+
+    /// Slots to warm; see `ConfidentialComputeRecord::access_list`.
+    pub fn access_list(mut self, access_list: AccessList) -> Self {
+        self.record.access_list = Some(access_list);
+        self
+    }
+
+    pub fn kettle_address(mut self, kettle_address: Address) -> Self {
+        self.record.kettle_address = Some(kettle_address);
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.record.chain_id = Some(chain_id);
+        self
+    }
+
+    pub fn build(self) -> ConfidentialComputeRecord {
+        self.record
+    }
+
+}
+
+impl ConfidentialComputeRecord {
+
+    pub fn builder() -> ConfidentialComputeRecordBuilder {
+        ConfidentialComputeRecordBuilder::new()
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuilderError {
+    MissingField(&'static str),
+}
+
+impl std::fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuilderError::MissingField(field) => write!(f, "Missing {field} field"),
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+/// Fluent construction of a (unsigned) request. The target, gas limit and
+/// kettle are required (gas estimation doesn't work well with MEVM); nonce,
+/// gas price and chain id are left for the fillers if not set.
+#[derive(Debug, Clone, Default)]
+pub struct ConfidentialComputeRequestBuilder {
+    record: ConfidentialComputeRecordBuilder,
+    has_to: bool,
+    confidential_inputs: Option<Bytes>,
+}
+
+impl ConfidentialComputeRequestBuilder {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `to` with the ABI encoded `sol!` call struct as input.
+    pub fn call<T: SolCall>(mut self, to: Address, call: &T) -> Self {
+        self.record = self.record.call(to, call);
+        self.has_to = true;
+        self
+    }
+
+    pub fn to(mut self, to: Address) -> Self {
+        self.record = self.record.to(to);
+        self.has_to = true;
+        self
+    }
+
+    pub fn create(mut self, init_code: Bytes) -> Self {
+        self.record = self.record.create(init_code);
+        self.has_to = true;
+        self
+    }
+
+    pub fn input(mut self, input: Bytes) -> Self {
+        self.record = self.record.input(input);
+        self
+    }
+
+    pub fn value(mut self, value: U256) -> Self {
+        self.record = self.record.value(value);
+        self
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.record = self.record.nonce(nonce);
+        self
+    }
+
+    pub fn gas(mut self, gas: u128) -> Self {
+        self.record = self.record.gas(gas);
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: u128) -> Self {
+        self.record = self.record.gas_price(gas_price);
+        self
+    }
+
+    pub fn dynamic_fees(mut self, max_fee_per_gas: u128, max_priority_fee_per_gas: u128) -> Self {
+        self.record = self.record.dynamic_fees(max_fee_per_gas, max_priority_fee_per_gas);
+        self
+    }
+
+    pub fn access_list(mut self, access_list: AccessList) -> Self {
+        self.record = self.record.access_list(access_list);
+        self
+    }
+
+    pub fn kettle_address(mut self, kettle_address: Address) -> Self {
+        self.record = self.record.kettle_address(kettle_address);
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.record = self.record.chain_id(chain_id);
+        self
+    }
+
+    pub fn confidential_inputs(mut self, confidential_inputs: Bytes) -> Self {
+        self.confidential_inputs = Some(confidential_inputs);
+        self
+    }
+
+    /// ABI encoded `sol!` call struct as confidential inputs.
+    pub fn confidential_call<T: SolCall>(self, call: &T) -> Self {
+        self.confidential_inputs(call.abi_encode().into())
+    }
+
+    /// Request with the confidential inputs hash set.
+    pub fn build(self) -> Result<ConfidentialComputeRequest, BuilderError> {
+        let record = self.record.build();
+        if !self.has_to {
+            return Err(BuilderError::MissingField("to"));
+        }
+        if record.gas.is_none() {
+            return Err(BuilderError::MissingField("gas"));
+        }
+        if record.kettle_address.is_none() {
+            return Err(BuilderError::MissingField("kettle address"));
+        }
+        Ok(ConfidentialComputeRequest::new(record, self.confidential_inputs))
+    }
+
+}
+
+impl ConfidentialComputeRequest {
+
+    pub fn builder() -> ConfidentialComputeRequestBuilder {
+        ConfidentialComputeRequestBuilder::new()
+    }
+
+    /// Use the ABI encoded `sol!` call struct as confidential inputs, eg.
+    /// for SUAPPs decoding their inputs with `abi.decode`.
+    pub fn with_confidential_call<T: SolCall>(self, call: &T) -> Self {
+        self.with_confidential_inputs(call.abi_encode().into())
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::sol;
+    use super::*;
+
+    sol! {
+        function queryLatestPrice(string memory ticker) public view returns (uint price);
+        function submitBid(uint64 blockNumber, bytes bundle) external;
+    }
+
+    #[test]
+    fn test_record_from_sol_call() {
+        let to = Address::from_str("0xc803334c79650708Daf3a3462AC4B48296b1352a").unwrap();
+        let record = ConfidentialComputeRecord::builder()
+            .call(to, &queryLatestPriceCall { ticker: "ETHUSDT".into() })
+            .gas(0x0f4240)
+            .build();
+        let expected = Bytes::from_str("0x50723553000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000074554485553445400000000000000000000000000000000000000000000000000").unwrap();
+        assert_eq!(record.to, TxKind::Call(to));
+        assert_eq!(record.input, expected);
+        assert_eq!(record.gas, Some(0x0f4240));
+        assert_eq!(record.nonce, None);
+
+        let bid = submitBidCall { blockNumber: 7, bundle: Bytes::from_static(b"bundle") };
+        let ccr = ConfidentialComputeRequest::new(record, None).with_confidential_call(&bid);
+        let decoded = submitBidCall::abi_decode(&ccr.confidential_inputs, true).unwrap();
+        assert_eq!(decoded.blockNumber, 7);
+        assert_eq!(ccr.validate_inputs_hash(), Ok(()));
+    }
+
+    #[test]
+    fn test_request_builder() {
+        let to = Address::from_str("0xc803334c79650708Daf3a3462AC4B48296b1352a").unwrap();
+        let kettle = Address::from_str("0x03493869959c866713c33669ca118e774a30a0e5").unwrap();
+        let bid = submitBidCall { blockNumber: 7, bundle: Bytes::from_static(b"bundle") };
+        let ccr = ConfidentialComputeRequest::builder()
+            .call(to, &queryLatestPriceCall { ticker: "ETHUSDT".into() })
+            .nonce(0x45)
+            .gas(0x0f4240)
+            .gas_price(0x8c9aca00)
+            .kettle_address(kettle)
+            .confidential_call(&bid)
+            .build()
+            .unwrap();
+        let record = &ccr.confidential_compute_record;
+        assert_eq!((record.to, record.nonce, record.kettle_address), (TxKind::Call(to), Some(0x45), Some(kettle)));
+        assert_eq!(record.chain_id, None);
+        assert_eq!(ccr.validate_inputs_hash(), Ok(()));
+
+        let partial = ConfidentialComputeRequest::builder().gas(0x0f4240).kettle_address(kettle);
+        assert_eq!(partial.clone().build(), Err(BuilderError::MissingField("to")));
+        // The zero address is a valid target if set explicitly
+        assert!(partial.to(Address::ZERO).build().is_ok());
+        assert_eq!(
+            ConfidentialComputeRequest::builder().to(to).gas(0x0f4240).build(),
+            Err(BuilderError::MissingField("kettle address"))
+        );
+
+        let init_code = Bytes::from_static(&[0x60, 0x80, 0x60, 0x40, 0x52]);
+        let ccr = ConfidentialComputeRequest::builder()
+            .create(init_code.clone())
+            .gas(0x0f4240)
+            .kettle_address(kettle)
+            .build()
+            .unwrap();
+        let record = &ccr.confidential_compute_record;
+        assert_eq!((record.to, record.target(), &record.input), (TxKind::Create, None, &init_code));
+    }
+
+}
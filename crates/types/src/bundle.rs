@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use alloy::primitives::{Address, B256, U64};
+use super::DataId;
+
+
+/// Bundle as consumed by kettle builder SUAPPs (suave-geth `SBundle`),
+/// typically carried as confidential inputs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_block: Option<U64>,
+    pub txs: Vec<BundleTx>,
+    /// Txs allowed to revert without invalidating the bundle.
+    #[serde(default, rename = "revertingHashes", alias = "revertingTxHashes", skip_serializing_if = "Vec::is_empty")]
+    pub reverting_tx_hashes: Vec<B256>,
+    /// Txs that may be dropped (eg. already included) without invalidating
+    /// the bundle. Client side only: `SBundle` has no such field, so it is
+    /// checked by `validate` but never serialized.
+    #[serde(skip)]
+    pub dropping_tx_hashes: Vec<B256>,
+    #[serde(default, rename = "percent", skip_serializing_if = "Option::is_none")]
+    pub refund_percent: Option<u8>,
+    #[serde(default, rename = "MatchId", skip_serializing_if = "Option::is_none")]
+    pub match_id: Option<DataId>,
+}
+
+/// Signed transaction of a bundle in RPC JSON form. Only the hash is
+/// typed; the remaining fields are kept as they are.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleTx {
+    pub hash: B256,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleError {
+    /// A reverting tx hash not matching any tx of the bundle.
+    UnknownRevertingHash(B256),
+    /// A dropping tx hash not matching any tx of the bundle.
+    UnknownDroppingHash(B256),
+    DuplicateHash(B256),
+    Empty,
+    /// Two txs of a sender with the same nonce, only one of which can land.
+    ConflictingReplacement { sender: Address, nonce: u64, first: B256, second: B256 },
+    /// Txs of a sender not in consecutive nonce order.
+    NonceOutOfOrder { sender: Address, expected: u64, actual: u64 },
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleError::UnknownRevertingHash(hash) => write!(f, "Reverting tx hash {hash} is not in the bundle"),
+            BundleError::UnknownDroppingHash(hash) => write!(f, "Dropping tx hash {hash} is not in the bundle"),
+            BundleError::DuplicateHash(hash) => write!(f, "Tx hash {hash} is listed twice"),
+            BundleError::Empty => write!(f, "Bundle has no txs"),
+            BundleError::ConflictingReplacement { sender, nonce, first, second } =>
+                write!(f, "Txs {first} and {second} both use nonce {nonce} of {sender}"),
+            BundleError::NonceOutOfOrder { sender, expected, actual } =>
+                write!(f, "Tx of {sender} has nonce {actual}, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl BundleTx {
+
+    /// `from` field, if the tx carries one.
+    pub fn sender(&self) -> Option<Address> {
+        self.fields.get("from")?.as_str()?.parse().ok()
+    }
+
+    pub fn nonce(&self) -> Option<u64> {
+        let nonce = self.fields.get("nonce")?.as_str()?;
+        u64::from_str_radix(nonce.strip_prefix("0x")?, 16).ok()
+    }
+
+}
+
+impl Bundle {
+
+    pub fn tx_hashes(&self) -> impl Iterator<Item = B256> + '_ {
+        self.txs.iter().map(|tx| tx.hash)
+    }
+
+    /// Check the bundle has txs and that reverting/dropping hashes refer to
+    /// them; kettles drop bundles violating this.
+    pub fn validate(&self) -> Result<(), BundleError> {
+        if self.txs.is_empty() {
+            return Err(BundleError::Empty);
+        }
+        let tx_hashes = self.tx_hashes().collect::<Vec<_>>();
+        check_hashes(&tx_hashes, &self.reverting_tx_hashes, BundleError::UnknownRevertingHash)?;
+        check_hashes(&tx_hashes, &self.dropping_tx_hashes, BundleError::UnknownDroppingHash)
+    }
+
+    /// Drop repeated txs (keeping the first), then check the nonces of
+    /// every sender are consecutive without replacements and `validate`.
+    /// Meant to run before serializing, so malformed bundles are caught
+    /// here instead of silently failing in the kettle. Txs without `from`
+    /// and `nonce` fields are left out of the nonce checks.
+    /// Returns the number of txs dropped.
+    pub fn normalize(&mut self) -> Result<usize, BundleError> {
+        let len = self.txs.len();
+        let mut seen = Vec::with_capacity(len);
+        self.txs.retain(|tx| {
+            let repeated = seen.contains(&tx.hash);
+            seen.push(tx.hash);
+            !repeated
+        });
+        self.check_nonces()?;
+        self.validate()?;
+        Ok(len - self.txs.len())
+    }
+
+    fn check_nonces(&self) -> Result<(), BundleError> {
+        let mut last: Vec<(Address, u64, B256)> = Vec::new();
+        for tx in &self.txs {
+            let (Some(sender), Some(nonce)) = (tx.sender(), tx.nonce()) else { continue };
+            match last.iter_mut().find(|(s, _, _)| *s == sender) {
+                Some((_, previous, first)) if *previous == nonce => {
+                    return Err(BundleError::ConflictingReplacement { sender, nonce, first: *first, second: tx.hash });
+                }
+                Some((_, previous, _)) if *previous + 1 != nonce => {
+                    return Err(BundleError::NonceOutOfOrder { sender, expected: *previous + 1, actual: nonce });
+                }
+                Some(entry) => *entry = (sender, nonce, tx.hash),
+                None => last.push((sender, nonce, tx.hash)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+}
+
+fn check_hashes(
+    tx_hashes: &[B256],
+    listed: &[B256],
+    unknown: fn(B256) -> BundleError,
+) -> Result<(), BundleError> {
+    for (idx, hash) in listed.iter().enumerate() {
+        if !tx_hashes.contains(hash) {
+            return Err(unknown(*hash));
+        }
+        if listed[..idx].contains(hash) {
+            return Err(BundleError::DuplicateHash(*hash));
+        }
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+    use super::*;
+    use super::super::fixtures;
+
+    /// JSON bundle carried ABI encoded (as `bytes`) in the fixture inputs.
+    fn fixture_bundle_json() -> String {
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let inputs = &fixture.request.confidential_inputs;
+        let len = U256::from_be_slice(&inputs[32..64]).to::<usize>();
+        String::from_utf8(inputs[64..64 + len].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_bundle_from_fixture() {
+        let bundle = Bundle::from_json(&fixture_bundle_json()).unwrap();
+        assert_eq!(bundle.txs.len(), 1);
+        assert_eq!(bundle.refund_percent, Some(10));
+        assert_eq!(bundle.match_id, Some(DataId::ZERO));
+        assert_eq!(bundle.validate(), Ok(()));
+
+        let roundtrip = Bundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        assert_eq!(roundtrip, bundle);
+    }
+
+    #[test]
+    fn test_bundle_hash_validation() {
+        let mut bundle = Bundle::from_json(&fixture_bundle_json()).unwrap();
+        let included = bundle.txs[0].hash;
+        bundle.reverting_tx_hashes = vec![included];
+        bundle.dropping_tx_hashes = vec![included];
+        assert_eq!(bundle.validate(), Ok(()));
+        let json = bundle.to_json().unwrap();
+        assert!(json.contains("\"revertingHashes\""));
+        assert!(!json.contains("dropping"));
+
+        let other = B256::repeat_byte(0x11);
+        bundle.dropping_tx_hashes.push(other);
+        assert_eq!(bundle.validate(), Err(BundleError::UnknownDroppingHash(other)));
+        bundle.dropping_tx_hashes = vec![included, included];
+        assert_eq!(bundle.validate(), Err(BundleError::DuplicateHash(included)));
+
+        let legacy = r#"{"txs":[],"revertingTxHashes":["0x1111111111111111111111111111111111111111111111111111111111111111"]}"#;
+        let bundle = Bundle::from_json(legacy).unwrap();
+        assert_eq!(bundle.reverting_tx_hashes, vec![other]);
+        assert_eq!(bundle.validate(), Err(BundleError::Empty));
+    }
+
+    #[test]
+    fn test_normalize_bundle() {
+        let tx = |hash: u8, from: u8, nonce: &str| -> BundleTx {
+            serde_json::from_value(serde_json::json!({
+                "hash": B256::repeat_byte(hash),
+                "from": Address::repeat_byte(from),
+                "nonce": nonce,
+            })).unwrap()
+        };
+        let mut bundle = Bundle { txs: vec![tx(1, 0xa, "0x7"), tx(2, 0xb, "0x0"), tx(1, 0xa, "0x7"), tx(3, 0xa, "0x8")], ..Default::default() };
+        assert_eq!(bundle.txs[0].sender(), Some(Address::repeat_byte(0xa)));
+        assert_eq!(bundle.normalize(), Ok(1));
+        assert_eq!(bundle.tx_hashes().collect::<Vec<_>>(), [1, 2, 3].map(B256::repeat_byte));
+
+        bundle.txs.push(tx(4, 0xa, "0x8"));
+        assert_eq!(bundle.normalize(), Err(BundleError::ConflictingReplacement {
+            sender: Address::repeat_byte(0xa), nonce: 8, first: B256::repeat_byte(3), second: B256::repeat_byte(4),
+        }));
+        bundle.txs[3] = tx(4, 0xa, "0xa");
+        assert_eq!(bundle.normalize(), Err(BundleError::NonceOutOfOrder { sender: Address::repeat_byte(0xa), expected: 9, actual: 10 }));
+
+        // Fixture txs carry no sender
+        let mut fixture = Bundle::from_json(&fixture_bundle_json()).unwrap();
+        assert_eq!(fixture.normalize(), Ok(0));
+    }
+
+}
@@ -0,0 +1,97 @@
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use alloy::primitives::Bytes;
+use super::{ConfidentialComputeRequest, ConfidentialEnvelope, EnvelopeExtension};
+
+
+/// Name used in client tags produced by this crate.
+pub const CLIENT_NAME: &str = "suave-alloy";
+
+/// Opaque client attribution (client build plus an optional user label)
+/// carried as an envelope extension, so operators and kettle analytics can
+/// attribute flow per client build. It carries no authority and is not
+/// verified.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct ClientTag {
+    /// Client name and version, e.g. `suave-alloy/0.1.0`.
+    pub client: String,
+    pub label: String,
+}
+
+impl ClientTag {
+
+    /// Tag of this crate's build with a user label (may be empty).
+    pub fn current(label: impl Into<String>) -> Self {
+        Self {
+            client: format!("{CLIENT_NAME}/{}", env!("CARGO_PKG_VERSION")),
+            label: label.into(),
+        }
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut out = Vec::new();
+        Encodable::encode(self, &mut out);
+        out.into()
+    }
+
+    pub fn decode(mut data: &[u8]) -> alloy_rlp::Result<Self> {
+        <Self as Decodable>::decode(&mut data)
+    }
+
+    /// Value for an HTTP header (e.g. `x-suave-client`) on transports that
+    /// support custom headers.
+    pub fn header_value(&self) -> String {
+        if self.label.is_empty() {
+            self.client.clone()
+        } else {
+            format!("{} ({})", self.client, self.label)
+        }
+    }
+
+}
+
+impl ConfidentialComputeRequest {
+
+    pub fn with_client_tag(mut self, tag: &ClientTag) -> Self {
+        self.set_client_tag(tag);
+        self
+    }
+
+    pub fn set_client_tag(&mut self, tag: &ClientTag) {
+        let mut envelope = ConfidentialEnvelope::decode(&self.confidential_inputs)
+            .unwrap_or_else(|_| ConfidentialEnvelope::new(self.confidential_inputs.clone()));
+        envelope.set_extension(EnvelopeExtension::CLIENT_TAG, tag.encode());
+        self.set_confidential_inputs(envelope.encode());
+    }
+
+    pub fn client_tag(&self) -> Option<ClientTag> {
+        let envelope = ConfidentialEnvelope::decode(&self.confidential_inputs).ok()?;
+        let data = envelope.extension(EnvelopeExtension::CLIENT_TAG)?;
+        ClientTag::decode(data).ok()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_tag() {
+        let tag = ClientTag::current("arb-bot");
+        assert!(tag.client.starts_with("suave-alloy/"));
+        assert_eq!(tag.header_value(), format!("{} (arb-bot)", tag.client));
+        assert_eq!(ClientTag::decode(&tag.encode()).unwrap(), tag);
+
+        let ccr = ConfidentialComputeRequest::default()
+            .with_confidential_inputs(Bytes::from_static(b"bid"))
+            .with_client_tag(&tag);
+        assert_eq!(ccr.client_tag(), Some(tag));
+        let envelope = ConfidentialEnvelope::decode(&ccr.confidential_inputs).unwrap();
+        assert_eq!(envelope.payload, Bytes::from_static(b"bid"));
+        assert_eq!(ccr.validate_inputs_hash(), Ok(()));
+
+        assert_eq!(ConfidentialComputeRequest::default().client_tag(), None);
+    }
+
+}
@@ -0,0 +1,76 @@
+use alloy::primitives::{keccak256, Bytes, B256};
+use super::{ConfidentialComputeRequest, ConfidentialEnvelope, EnvelopeExtension};
+
+
+/// `keccak(salt || payload)`, the salted commitment to a payload.
+pub fn salted_commitment(salt: B256, payload: &[u8]) -> B256 {
+    let mut buf = Vec::with_capacity(32 + payload.len());
+    buf.extend_from_slice(salt.as_slice());
+    buf.extend_from_slice(payload);
+    keccak256(buf)
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Use `payload` as confidential inputs, salted so the inputs hash
+    /// exposed in the record can't be matched against a dictionary of
+    /// likely (low-entropy) payloads. The salt travels with the payload in
+    /// the confidential inputs envelope; the committed hash covers both, as
+    /// the node checks it against the full inputs. `salt` has to be fresh
+    /// random for every request.
+    pub fn with_salted_inputs(mut self, payload: Bytes, salt: B256) -> Self {
+        self.set_salted_inputs(payload, salt);
+        self
+    }
+
+    pub fn set_salted_inputs(&mut self, payload: Bytes, salt: B256) {
+        let mut envelope = ConfidentialEnvelope::decode(&self.confidential_inputs)
+            .unwrap_or_default();
+        envelope.payload = payload;
+        envelope.set_extension(EnvelopeExtension::SALT, Bytes::copy_from_slice(salt.as_slice()));
+        self.set_confidential_inputs(envelope.encode());
+    }
+
+    pub fn inputs_salt(&self) -> Option<B256> {
+        let envelope = ConfidentialEnvelope::decode(&self.confidential_inputs).ok()?;
+        let data = envelope.extension(EnvelopeExtension::SALT)?;
+        B256::try_from(data.as_ref()).ok()
+    }
+
+    /// `keccak(salt || payload)` for salted inputs, for SUAPPs committing to
+    /// the payload without the rest of the envelope.
+    pub fn salted_commitment(&self) -> Option<B256> {
+        let envelope = ConfidentialEnvelope::decode(&self.confidential_inputs).ok()?;
+        let salt = B256::try_from(envelope.extension(EnvelopeExtension::SALT)?.as_ref()).ok()?;
+        Some(salted_commitment(salt, &envelope.payload))
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salted_inputs() {
+        let payload = Bytes::from_static(b"yes");
+        let salt_a = B256::repeat_byte(0xaa);
+        let a = ConfidentialComputeRequest::default().with_salted_inputs(payload.clone(), salt_a);
+        let b = ConfidentialComputeRequest::default().with_salted_inputs(payload.clone(), B256::repeat_byte(0xbb));
+
+        let hash_a = a.confidential_compute_record.confidential_inputs_hash.unwrap();
+        assert_ne!(hash_a, keccak256(&payload));
+        assert_ne!(Some(hash_a), b.confidential_compute_record.confidential_inputs_hash);
+        assert_eq!(a.validate_inputs_hash(), Ok(()));
+
+        assert_eq!(a.inputs_salt(), Some(salt_a));
+        assert_eq!(ConfidentialEnvelope::decode(&a.confidential_inputs).unwrap().payload, payload);
+        assert_eq!(a.salted_commitment(), Some(keccak256([salt_a.as_slice(), b"yes"].concat())));
+
+        let plain = ConfidentialComputeRequest::default().with_confidential_inputs(payload);
+        assert_eq!(plain.inputs_salt(), None);
+        assert_eq!(plain.salted_commitment(), None);
+    }
+
+}
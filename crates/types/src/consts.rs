@@ -0,0 +1,171 @@
+//! SUAVE protocol constants, grouped per protocol version.
+
+use alloy::primitives::Bytes;
+
+/// Base gas charged for every transaction (incl. CCRs).
+pub const TX_GAS: u64 = 21_000;
+/// Gas charged per zero byte of calldata.
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+/// Gas charged per non-zero byte of calldata.
+pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
+/// Max size of an encoded transaction accepted by the node's pool.
+pub const MAX_TX_SIZE: usize = 4 * 32 * 1024;
+/// Max size of confidential inputs carried by a CCR.
+pub const MAX_CONFIDENTIAL_INPUTS_SIZE: usize = MAX_TX_SIZE;
+/// Max size of the record calldata.
+pub const MAX_CALLDATA_SIZE: usize = MAX_TX_SIZE;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProtocolVersion {
+    /// Rigil testnet era suave-geth.
+    #[default]
+    V1,
+}
+
+impl ProtocolVersion {
+    pub fn limits(&self) -> ProtocolLimits {
+        match self {
+            ProtocolVersion::V1 => ProtocolLimits {
+                tx_gas: TX_GAS,
+                tx_data_zero_gas: TX_DATA_ZERO_GAS,
+                tx_data_non_zero_gas: TX_DATA_NON_ZERO_GAS,
+                max_tx_size: MAX_TX_SIZE,
+                max_confidential_inputs_size: MAX_CONFIDENTIAL_INPUTS_SIZE,
+                max_calldata_size: MAX_CALLDATA_SIZE,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolLimits {
+    pub tx_gas: u64,
+    pub tx_data_zero_gas: u64,
+    pub tx_data_non_zero_gas: u64,
+    pub max_tx_size: usize,
+    pub max_confidential_inputs_size: usize,
+    pub max_calldata_size: usize,
+}
+
+impl Default for ProtocolLimits {
+    fn default() -> Self {
+        ProtocolVersion::default().limits()
+    }
+}
+
+impl ProtocolLimits {
+
+    /// Intrinsic gas of a CCR with the given calldata.
+    pub fn intrinsic_gas(&self, calldata: &[u8]) -> u64 {
+        let zeros = calldata.iter().filter(|b| **b == 0).count() as u64;
+        let non_zeros = calldata.len() as u64 - zeros;
+        self.tx_gas + zeros * self.tx_data_zero_gas + non_zeros * self.tx_data_non_zero_gas
+    }
+
+}
+
+/// Domain separation applied to the CCR signing payload (the 0x42 prefixed
+/// hash params) before hashing. suave-geth signs the bare payload; forks
+/// that adopted domain-separated signing to prevent cross-protocol
+/// signature reuse use one of the other variants.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SigningDomain {
+    #[default]
+    None,
+    /// EIP-191 personal message: `"\x19Ethereum Signed Message:\n" || len || payload`.
+    Eip191,
+    /// Custom domain separation tag prepended to the payload.
+    Custom(Bytes),
+    /// EIP-712 typed `ConfidentialRecord`, for browser wallets. Replaces the
+    /// payload instead of wrapping it (see `encode_eip712`).
+    Eip712,
+}
+
+impl SigningDomain {
+
+    pub fn wrap(&self, payload: &[u8]) -> Vec<u8> {
+        let prefix = match self {
+            SigningDomain::None | SigningDomain::Eip712 => return payload.to_vec(),
+            SigningDomain::Eip191 => format!("\x19Ethereum Signed Message:\n{}", payload.len()).into_bytes(),
+            SigningDomain::Custom(dst) => dst.to_vec(),
+        };
+        [prefix.as_slice(), payload].concat()
+    }
+
+    /// Length of `wrap` applied to a payload of `payload_len` bytes.
+    pub fn wrapped_len(&self, payload_len: usize) -> usize {
+        match self {
+            SigningDomain::None | SigningDomain::Eip712 => payload_len,
+            SigningDomain::Eip191 => "\x19Ethereum Signed Message:\n".len() + payload_len.to_string().len() + payload_len,
+            SigningDomain::Custom(dst) => dst.len() + payload_len,
+        }
+    }
+
+}
+
+/// JSON name of the record's kettle address. Nodes emit either one;
+/// deserialization accepts both, this picks the one written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KettleFieldName {
+    #[default]
+    KettleAddress,
+    ExecutionNode,
+}
+
+impl KettleFieldName {
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KettleFieldName::KettleAddress => "kettleAddress",
+            KettleFieldName::ExecutionNode => "executionNode",
+        }
+    }
+
+}
+
+/// Protocol parameters of the chain a client talks to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtocolConfig {
+    pub version: ProtocolVersion,
+    pub signing_domain: SigningDomain,
+    pub kettle_field: KettleFieldName,
+}
+
+impl ProtocolConfig {
+
+    pub fn with_signing_domain(mut self, signing_domain: SigningDomain) -> Self {
+        self.signing_domain = signing_domain;
+        self
+    }
+
+    pub fn with_kettle_field(mut self, kettle_field: KettleFieldName) -> Self {
+        self.kettle_field = kettle_field;
+        self
+    }
+
+    pub fn limits(&self) -> ProtocolLimits {
+        self.version.limits()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intrinsic_gas() {
+        let limits = ProtocolVersion::V1.limits();
+        assert_eq!(limits.intrinsic_gas(&[]), 21_000);
+        assert_eq!(limits.intrinsic_gas(&[0, 0, 1]), 21_000 + 2 * 4 + 16);
+    }
+
+    #[test]
+    fn test_signing_domain_wrap() {
+        assert_eq!(SigningDomain::None.wrap(b"ab"), b"ab");
+        assert_eq!(SigningDomain::Eip191.wrap(b"ab"), b"\x19Ethereum Signed Message:\n2ab");
+        assert_eq!(SigningDomain::Custom(Bytes::from_static(b"DST")).wrap(b"ab"), b"DSTab");
+    }
+
+}
@@ -1,29 +1,49 @@
+use std::{borrow::Cow, sync::Mutex};
 use serde::{Deserialize, Serialize};
-use alloy_rlp::{Encodable, RlpDecodable, RlpEncodable};
-use eyre::{Result, eyre};
+use alloy_rlp::{Decodable, Encodable, Header};
 use alloy::{
-    primitives::{self, Address, Bytes, FixedBytes, U256, Signature}, 
+    eips::eip2930::AccessList,
+    primitives::{self, Address, Bytes, FixedBytes, Signature, TxKind, U256}, 
     rpc::types::eth::TransactionRequest,
     serde as alloy_serde,
 };
+use suave_alloy_core::Fees;
+use super::consts::SigningDomain;
+use super::CcrError;
 
 
 pub const EMPTY_BYTES_HASH: FixedBytes<32> = FixedBytes([
     197,210,70,1,134,247,35,60,146,126,125,178,220,199,3,192,229,0,182,83,202,130,39,59,123,250,216,4,93,133,164,112
 ]);
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfidentialComputeRecord {
     #[serde(with = "alloy_serde::num::u64_hex_opt")]
     pub nonce: Option<u64>,
-    pub to: Address,
+    /// `Create` for confidential contract deployments (`null`, `""` or
+    /// `"0x"` in JSON). Required, so a dropped field isn't read as a
+    /// deployment.
+    #[serde(with = "tx_kind")]
+    pub to: TxKind,
     #[serde(with = "alloy_serde::num::u128_hex_or_decimal_opt")]
     pub gas: Option<u128>,
     #[serde(with = "alloy_serde::num::u128_hex_or_decimal_opt")]
     pub gas_price: Option<u128>,
+    /// With `max_priority_fee_per_gas`, makes the record a dynamic fee one
+    /// (see `fees`); not accepted by older kettles.
+    #[serde(default, with = "alloy_serde::num::u128_hex_or_decimal_opt", skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<u128>,
+    #[serde(default, with = "alloy_serde::num::u128_hex_or_decimal_opt", skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<u128>,
     pub value: U256,
     pub input: Bytes,
+    /// EIP-2930 access list; records carrying one (even empty) are encoded
+    /// and signed with it after the input, which older kettles reject.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    /// `executionNode` on some node versions, see `JsonFormat::kettle_field`.
+    #[serde(alias = "executionNode")]
     pub kettle_address: Option<Address>,
     #[serde(with = "alloy_serde::num::u64_hex_opt")]
     pub chain_id: Option<u64>,
@@ -31,8 +51,137 @@ pub struct ConfidentialComputeRecord {
     pub confidential_inputs_hash: Option<FixedBytes<32>>,
     #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub signature: Option<Signature>,
+    /// Record fields after `s` added by a newer protocol version, each a
+    /// complete RLP item. Kept opaque and re-emitted as is, so tooling on
+    /// this crate doesn't corrupt such records; `signing_hash` only covers
+    /// the fields known here.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_fields: Vec<Bytes>,
     #[serde(skip)]
     pub from: Option<Address>,
+    /// Domain the signing payload is wrapped in; not part of the encoding,
+    /// so set from the chain's `ProtocolConfig` (`with_protocol_config`,
+    /// `decode_2718_with`) rather than per record.
+    #[serde(skip)]
+    pub(crate) signing_domain: SigningDomain,
+    /// Signing hash of the current fields, so signing large-calldata
+    /// records doesn't re-encode them for every hash.
+    #[serde(skip)]
+    pub(crate) signing_hash_cache: SigningHashCache,
+}
+
+/// Field-wise, ignoring the signing hash cache.
+impl PartialEq for ConfidentialComputeRecord {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            nonce, to, gas, gas_price, max_fee_per_gas, max_priority_fee_per_gas, value, input, access_list,
+            kettle_address, chain_id, confidential_inputs_hash, signature, extra_fields, from, signing_domain,
+            signing_hash_cache: _,
+        } = self;
+        *nonce == other.nonce && *to == other.to && *gas == other.gas && *gas_price == other.gas_price
+            && *max_fee_per_gas == other.max_fee_per_gas
+            && *max_priority_fee_per_gas == other.max_priority_fee_per_gas
+            && *value == other.value && *input == other.input && *access_list == other.access_list
+            && *kettle_address == other.kettle_address && *chain_id == other.chain_id
+            && *confidential_inputs_hash == other.confidential_inputs_hash && *signature == other.signature
+            && *extra_fields == other.extra_fields && *from == other.from && *signing_domain == other.signing_domain
+    }
+}
+
+/// Last signing hash computed for a record, reused while the fields it
+/// covers are unchanged; setting any field, even through the public
+/// fields, invalidates it.
+///
+/// Fields are compared by a keccak fingerprint of everything the signing
+/// hash depends on but the input. The input is compared by identity:
+/// `Bytes` is immutable and the cache holds a handle to it, so the same
+/// buffer means the same calldata and no comparison of its contents is
+/// needed. A contended lock skips the cache rather than waiting.
+#[derive(Default)]
+pub(crate) struct SigningHashCache(Mutex<Option<CachedHash>>);
+
+#[derive(Clone)]
+struct CachedHash {
+    fingerprint: FixedBytes<32>,
+    input: Bytes,
+    hash: FixedBytes<32>,
+}
+
+impl SigningHashCache {
+
+    pub(crate) fn get_or_compute(
+        &self,
+        record: &ConfidentialComputeRecord,
+        compute: impl FnOnce() -> Result<FixedBytes<32>, CcrError>,
+    ) -> Result<FixedBytes<32>, CcrError> {
+        let fingerprint = fingerprint(record);
+        let is_input = |input: &Bytes| input.as_ptr() == record.input.as_ptr() && input.len() == record.input.len();
+        if let Ok(cache) = self.0.try_lock() {
+            if let Some(cached) = cache.as_ref().filter(|c| c.fingerprint == fingerprint && is_input(&c.input)) {
+                return Ok(cached.hash);
+            }
+        }
+        let hash = compute()?;
+        if let Ok(mut cache) = self.0.try_lock() {
+            *cache = Some(CachedHash { fingerprint, input: record.input.clone(), hash });
+        }
+        Ok(hash)
+    }
+
+    fn cached(&self) -> Option<CachedHash> {
+        self.0.try_lock().ok()?.clone()
+    }
+
+}
+
+/// Keccak of the fields the signing hash depends on, but the input. Every
+/// field is written with a fixed width or a length prefix, so distinct
+/// records can't write the same bytes.
+fn fingerprint(record: &ConfidentialComputeRecord) -> FixedBytes<32> {
+    fn option(hasher: &mut primitives::Keccak256, value: Option<&[u8]>) {
+        match value {
+            Some(value) => {
+                hasher.update([1]);
+                hasher.update((value.len() as u64).to_be_bytes());
+                hasher.update(value);
+            }
+            None => hasher.update([0]),
+        }
+    }
+
+    let mut hasher = primitives::Keccak256::new();
+    option(&mut hasher, record.nonce.map(u64::to_be_bytes).as_ref().map(|v| &v[..]));
+    option(&mut hasher, record.to.to().map(|to| to.as_slice()));
+    for value in [record.gas, record.gas_price, record.max_fee_per_gas, record.max_priority_fee_per_gas] {
+        option(&mut hasher, value.map(u128::to_be_bytes).as_ref().map(|v| &v[..]));
+    }
+    hasher.update(record.value.to_be_bytes::<32>());
+    option(&mut hasher, record.access_list.as_ref().map(alloy_rlp::encode).as_deref());
+    option(&mut hasher, record.kettle_address.as_ref().map(|a| a.as_slice()));
+    option(&mut hasher, record.chain_id.map(u64::to_be_bytes).as_ref().map(|v| &v[..]));
+    option(&mut hasher, record.confidential_inputs_hash.as_ref().map(|h| h.as_slice()));
+    let (tag, dst): (u8, &[u8]) = match &record.signing_domain {
+        SigningDomain::None => (0, &[]),
+        SigningDomain::Eip191 => (1, &[]),
+        SigningDomain::Custom(dst) => (2, dst),
+        SigningDomain::Eip712 => (3, &[]),
+    };
+    hasher.update([tag]);
+    option(&mut hasher, Some(dst));
+    hasher.finalize()
+}
+
+/// Copies the last hash; a copy of a record starts out cached.
+impl Clone for SigningHashCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.cached()))
+    }
+}
+
+impl std::fmt::Debug for SigningHashCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SigningHashCache").field(&self.cached().map(|cached| cached.hash)).finish()
+    }
 }
 
 impl ConfidentialComputeRecord {
@@ -41,22 +190,38 @@ impl ConfidentialComputeRecord {
     pub fn from_tx_request(
         tx_req: TransactionRequest, 
         kettle_address: Address, 
-    ) -> Result<Self> {
+    ) -> Result<Self, CcrError> {
         Ok(Self {
             input: tx_req.input.input.unwrap_or(Bytes::new()),
             gas_price: tx_req.gas_price,
+            max_fee_per_gas: tx_req.max_fee_per_gas,
+            max_priority_fee_per_gas: tx_req.max_priority_fee_per_gas,
             value: tx_req.value.unwrap_or(U256::ZERO),
-            to: tx_req.to.unwrap_or(Address::ZERO),
+            to: tx_req.to.map_or(TxKind::Create, TxKind::Call),
+            access_list: tx_req.access_list,
             nonce: tx_req.nonce,
             kettle_address: Some(kettle_address),
             chain_id: tx_req.chain_id,
             gas: tx_req.gas,
             confidential_inputs_hash: None,
             signature: None,
+            extra_fields: Vec::new(),
             from: None,
+            signing_domain: SigningDomain::None,
+            signing_hash_cache: SigningHashCache::default(),
         })
     }
 
+    /// Domain the signing payload is wrapped in, see `SigningDomain`.
+    pub fn signing_domain(&self) -> &SigningDomain {
+        &self.signing_domain
+    }
+
+    /// Called contract, `None` for contract creation.
+    pub fn target(&self) -> Option<Address> {
+        self.to.to().copied()
+    }
+
     pub fn set_confidential_inputs_hash(&mut self, confidential_inputs_hash: FixedBytes<32>) {
         self.confidential_inputs_hash = Some(confidential_inputs_hash);
     }
@@ -73,89 +238,298 @@ impl ConfidentialComputeRecord {
         self.signature = Some(signature);
     }
 
+    /// Dynamic fees if both are set, the legacy gas price otherwise.
+    pub fn fees(&self) -> Option<Fees> {
+        match (self.max_priority_fee_per_gas, self.max_fee_per_gas) {
+            (Some(max_priority_fee_per_gas), Some(max_fee_per_gas)) =>
+                Some(Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas }),
+            _ => self.gas_price.map(|gas_price| Fees::Legacy { gas_price }),
+        }
+    }
+
+    /// Most the sender can pay per gas.
+    pub fn max_gas_price(&self) -> Option<u128> {
+        self.fees().map(|fees| match fees {
+            Fees::Legacy { gas_price } => gas_price,
+            Fees::Dynamic { max_fee_per_gas, .. } => max_fee_per_gas,
+        })
+    }
+
+    /// Set the fees, clearing those of the other pricing so `fees` returns
+    /// them.
+    pub fn set_fees(&mut self, fees: Fees) {
+        let (gas_price, max_priority_fee_per_gas, max_fee_per_gas) = match fees {
+            Fees::Legacy { gas_price } => (Some(gas_price), None, None),
+            Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas } =>
+                (None, Some(max_priority_fee_per_gas), Some(max_fee_per_gas)),
+        };
+        self.gas_price = gas_price;
+        self.max_priority_fee_per_gas = max_priority_fee_per_gas;
+        self.max_fee_per_gas = max_fee_per_gas;
+    }
+
 }
 
 
-#[derive(Debug, RlpEncodable, RlpDecodable, PartialEq)]
-pub struct CRecordRLP {
+/// Signed record as encoded: 12 fields, plus one with dynamic fees and one
+/// with an access list, followed by the fields of newer versions. Borrows
+/// the variable-size fields of the record it is encoded from.
+#[derive(Debug, PartialEq)]
+pub struct CRecordRLP<'a> {
     nonce: u64,
-    gas_price: u128,
+    fees: Fees,
     gas: u128,
-    to: Address,
+    to: TxKind,
     value: U256,
-    input: Bytes,
+    input: Cow<'a, Bytes>,
+    access_list: Option<Cow<'a, AccessList>>,
     kettle_address: Address,
     confidential_inputs_hash: FixedBytes<32>,
     chain_id: u64,
     v: u8,
     r: U256,
     s: U256,
+    /// Complete RLP items, written as is after `s`.
+    extra_fields: Cow<'a, [Bytes]>,
 }
 
-impl CRecordRLP {
+/// RLP item kept as encoded.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RawItem(pub(crate) Bytes);
+
+impl Encodable for RawItem {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        out.put_slice(&self.0);
+    }
+
+    fn length(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Where the fields of a signed record are, told apart by their shape
+/// (the kettle address and inputs hash following the input, or the access
+/// list) rather than by count, so fields appended by newer versions don't
+/// make the layout ambiguous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RecordLayout {
+    pub dynamic: bool,
+    pub access_list: bool,
+    /// Number of fields before the extra ones.
+    pub known_fields: usize,
+}
+
+impl RecordLayout {
+
+    /// Layout of a record from the (is list, payload length) of its items.
+    pub(crate) fn detect(items: &[(bool, usize)]) -> Option<Self> {
+        [false, true].into_iter().find_map(|dynamic| {
+            // Index of the field after the input
+            let after_input = if dynamic { 7 } else { 6 };
+            let access_list = items.get(after_input)?.0;
+            let kettle_address = after_input + usize::from(access_list);
+            // kettle address, inputs hash, chain id, v, r, s
+            let known_fields = kettle_address + 6;
+            let matches = items.get(kettle_address) == Some(&(false, 20))
+                && items.get(kettle_address + 1) == Some(&(false, 32))
+                && items.len() >= known_fields;
+            matches.then_some(Self { dynamic, access_list, known_fields })
+        })
+    }
+
+}
+
+impl CRecordRLP<'_> {
+
+    /// Fields in encoding order, but for the extra ones; `encode` and
+    /// `fields_len` both go through this so the payload length can't drift
+    /// from what is written.
+    fn fields(&self) -> Vec<&dyn Encodable> {
+        let mut fields: Vec<&dyn Encodable> = vec![&self.nonce];
+        match &self.fees {
+            Fees::Legacy { gas_price } => fields.push(gas_price),
+            Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas } => {
+                fields.push(max_priority_fee_per_gas);
+                fields.push(max_fee_per_gas);
+            }
+        }
+        fields.extend([
+            &self.gas as &dyn Encodable,
+            &self.to,
+            &self.value,
+            &*self.input,
+        ]);
+        fields.extend(self.access_list.as_deref().map(|list| list as &dyn Encodable));
+        fields.extend([
+            &self.kettle_address as &dyn Encodable,
+            &self.confidential_inputs_hash,
+            &self.chain_id,
+            &self.v,
+            &self.r,
+            &self.s,
+        ]);
+        fields
+    }
+
     pub fn fields_len(&self) -> usize {
-        let mut len = 0;
-        len += self.nonce.length();
-        len += self.gas_price.length();
-        len += self.gas.length();
-        len += self.to.length();
-        len += self.value.length();
-        len += self.input.0.length();
-        len += self.kettle_address.length();
-        len += self.confidential_inputs_hash.length();
-        len += self.chain_id.length();
-        len += self.v.length();
-        len += self.r.length();
-        len += self.s.length();
-        len
-    }
-}
-
-impl TryFrom<&ConfidentialComputeRecord> for CRecordRLP {
-    type Error = eyre::Error; // todo: implement custom errors
-
-    fn try_from(ccr: &ConfidentialComputeRecord) -> Result<Self> {
-        let sig = ccr.signature
-            .expect("Missing signature field");
+        let extra_len: usize = self.extra_fields.iter().map(|field| field.len()).sum();
+        self.fields().iter().map(|field| field.length()).sum::<usize>() + extra_len
+    }
+
+}
+
+impl Encodable for CRecordRLP<'_> {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        Header { list: true, payload_length: self.fields_len() }.encode(out);
+        for field in self.fields() {
+            field.encode(out);
+        }
+        for field in self.extra_fields.iter() {
+            out.put_slice(field);
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.fields_len();
+        payload_length + alloy_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for CRecordRLP<'_> {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut fields = Header::decode_bytes(buf, true)?;
+        let layout = RecordLayout::detect(&item_shapes(fields)?)
+            .ok_or(alloy_rlp::Error::Custom("Unknown record layout"))?;
+        let dynamic = layout.dynamic;
+        let nonce = u64::decode(&mut fields)?;
+        let fees = if dynamic {
+            Fees::Dynamic {
+                max_priority_fee_per_gas: u128::decode(&mut fields)?,
+                max_fee_per_gas: u128::decode(&mut fields)?,
+            }
+        } else {
+            Fees::Legacy { gas_price: u128::decode(&mut fields)? }
+        };
+        Ok(Self {
+            nonce,
+            fees,
+            gas: u128::decode(&mut fields)?,
+            to: TxKind::decode(&mut fields)?,
+            value: U256::decode(&mut fields)?,
+            input: Cow::Owned(Bytes::decode(&mut fields)?),
+            access_list: layout.access_list.then(|| AccessList::decode(&mut fields).map(Cow::Owned)).transpose()?,
+            kettle_address: Address::decode(&mut fields)?,
+            confidential_inputs_hash: FixedBytes::decode(&mut fields)?,
+            chain_id: u64::decode(&mut fields)?,
+            v: u8::decode(&mut fields)?,
+            r: U256::decode(&mut fields)?,
+            s: U256::decode(&mut fields)?,
+            extra_fields: split_items(fields)?.into_iter()
+                .map(Bytes::copy_from_slice)
+                .collect(),
+        })
+    }
+}
+
+/// Whether each item of an RLP list payload is a list, and its payload
+/// length.
+fn item_shapes(mut payload: &[u8]) -> alloy_rlp::Result<Vec<(bool, usize)>> {
+    let mut shapes = Vec::new();
+    while !payload.is_empty() {
+        let header = Header::decode(&mut payload)?;
+        payload = payload.get(header.payload_length..).ok_or(alloy_rlp::Error::InputTooShort)?;
+        shapes.push((header.list, header.payload_length));
+    }
+    Ok(shapes)
+}
+
+/// Items of an RLP list payload, each with its header.
+fn split_items(mut payload: &[u8]) -> alloy_rlp::Result<Vec<&[u8]>> {
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let start = payload;
+        let header = Header::decode(&mut payload)?;
+        payload = payload.get(header.payload_length..).ok_or(alloy_rlp::Error::InputTooShort)?;
+        items.push(&start[..start.len() - payload.len()]);
+    }
+    Ok(items)
+}
+
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+impl<'a> TryFrom<&'a ConfidentialComputeRecord> for CRecordRLP<'a> {
+    type Error = CcrError;
+
+    fn try_from(ccr: &'a ConfidentialComputeRecord) -> Result<Self, CcrError> {
+        let sig = ccr.signature.ok_or(CcrError::MissingSignature)?;
         let (v, r, s) = signature_to_vrs(sig);
         let cinputs_hash = ccr.confidential_inputs_hash.unwrap_or(EMPTY_BYTES_HASH);
 
         Ok(Self {
-            nonce: ccr.nonce.ok_or_else(|| eyre!("Missing nonce field"))?,
-            gas_price: ccr.gas_price.ok_or_else(|| eyre!("Missing gas price field"))?,
-            gas: ccr.gas.ok_or_else(|| eyre!("Missing gas field"))?,
+            nonce: ccr.nonce.ok_or(CcrError::MissingField("nonce"))?,
+            fees: ccr.fees().ok_or(CcrError::MissingField("gas price"))?,
+            gas: ccr.gas.ok_or(CcrError::MissingField("gas"))?,
             to: ccr.to,
             value: ccr.value,
-            input: ccr.input.clone(),
-            kettle_address: ccr.kettle_address.ok_or_else(|| eyre!("Missing kettle address field"))?,
+            input: Cow::Borrowed(&ccr.input),
+            access_list: ccr.access_list.as_ref().map(Cow::Borrowed),
+            kettle_address: ccr.kettle_address.ok_or(CcrError::MissingField("kettle address"))?,
             confidential_inputs_hash: cinputs_hash,
-            chain_id: ccr.chain_id.ok_or_else(|| eyre!("Missing chain id field"))?,
-            v, r, s
+            chain_id: ccr.chain_id.ok_or(CcrError::MissingField("chain id"))?,
+            v, r, s,
+            extra_fields: Cow::Borrowed(&ccr.extra_fields),
         })
     }
 }
 
-impl Into<ConfidentialComputeRecord> for CRecordRLP {
-    fn into(self) -> ConfidentialComputeRecord {
-        let sig = Signature::from_rs_and_parity(self.r, self.s, self.v as u64)
-            .expect("Invalid signature");
-        ConfidentialComputeRecord {
-            nonce: Some(self.nonce),
-            gas_price: Some(self.gas_price),
-            gas: Some(self.gas),
-            to: self.to,
-            value: self.value,
-            input: self.input,
-            kettle_address: Some(self.kettle_address),
-            chain_id: Some(self.chain_id),
-            confidential_inputs_hash: Some(self.confidential_inputs_hash),
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+impl TryFrom<CRecordRLP<'_>> for ConfidentialComputeRecord {
+    type Error = alloy_rlp::Error;
+
+    fn try_from(rlp: CRecordRLP<'_>) -> alloy_rlp::Result<Self> {
+        let sig = Signature::from_rs_and_parity(rlp.r, rlp.s, rlp.v as u64)
+            .map_err(|_| alloy_rlp::Error::Custom("Invalid signature"))?;
+        let mut record = ConfidentialComputeRecord {
+            nonce: Some(rlp.nonce),
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            gas: Some(rlp.gas),
+            to: rlp.to,
+            value: rlp.value,
+            input: rlp.input.into_owned(),
+            access_list: rlp.access_list.map(Cow::into_owned),
+            kettle_address: Some(rlp.kettle_address),
+            chain_id: Some(rlp.chain_id),
+            confidential_inputs_hash: Some(rlp.confidential_inputs_hash),
             signature: Some(sig),
+            extra_fields: rlp.extra_fields.into_owned(),
             from: None, // todo: retrieve from signature and prehash
-        }
+            signing_domain: SigningDomain::None,
+            signing_hash_cache: SigningHashCache::default(),
+        };
+        record.set_fees(rlp.fees);
+        Ok(record)
     }
 
 }
 
+/// `to` as an address, or `null` for contract creation.
+mod tx_kind {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use alloy::primitives::{Address, TxKind};
+
+    pub fn serialize<S: Serializer>(to: &TxKind, serializer: S) -> Result<S::Ok, S::Error> {
+        to.to().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TxKind, D::Error> {
+        match Option::<String>::deserialize(deserializer)?.as_deref() {
+            None | Some("") | Some("0x") => Ok(TxKind::Create),
+            Some(to) => to.parse::<Address>().map(TxKind::Call).map_err(D::Error::custom),
+        }
+    }
+}
+
 pub(crate) fn signature_to_vrs(sig: Signature) -> (u8, U256, U256) {
     let v = sig.v().recid().to_byte();
     let r = sig.r();
@@ -165,15 +539,136 @@ pub(crate) fn signature_to_vrs(sig: Signature) -> (u8, U256, U256) {
 
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::str::FromStr;
 
+    use eyre::Result;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use super::*;
     use alloy::{
+        eips::eip2930::AccessListItem,
         network::TransactionBuilder, 
+        primitives::B256,
         rpc::types::eth::TransactionRequest, 
     };
 
+    /// Signed record with every field either an edge case (zero, length
+    /// prefix boundaries, max) or random.
+    pub(crate) fn random_record(rng: &mut StdRng) -> ConfidentialComputeRecord {
+        fn pick<T: Copy>(rng: &mut StdRng, edges: &[T], random: T) -> T {
+            if rng.gen_bool(0.5) { edges[rng.gen_range(0..edges.len())] } else { random }
+        }
+        let u64_edges = [0, 1, 0x7f, 0x80, u64::MAX];
+        let u128_edges = [0, 0x7f, 0x80, u64::MAX as u128, u128::MAX];
+        let u256_edges = [U256::ZERO, U256::from(0x80), U256::MAX];
+        let address_edges = [Address::ZERO, Address::repeat_byte(0xff)];
+
+        let random = rng.gen();
+        let nonce = pick(rng, &u64_edges, random);
+        let random = rng.gen();
+        let gas = pick(rng, &u128_edges, random);
+        let random = rng.gen();
+        let fee = pick(rng, &u128_edges, random);
+        let random = Address::from(rng.gen::<[u8; 20]>());
+        let to = match rng.gen_bool(0.1) {
+            true => TxKind::Create,
+            false => TxKind::Call(pick(rng, &address_edges, random)),
+        };
+        let random = U256::from_limbs(rng.gen());
+        let value = pick(rng, &u256_edges, random);
+        let random = rng.gen_range(0..300);
+        let input_len = pick(rng, &[0, 1, 55, 56, 1024], random);
+        let mut input = vec![0u8; input_len];
+        rng.fill(&mut input[..]);
+        let random = rng.gen();
+        let chain_id = pick(rng, &u64_edges, random);
+        let (r, s) = (U256::from_limbs(rng.gen()), U256::from_limbs(rng.gen()));
+        let signature = Signature::from_rs_and_parity(r, s, rng.gen_range(0u64..=1)).unwrap();
+
+        let mut record = ConfidentialComputeRecord {
+            nonce: Some(nonce),
+            to,
+            gas: Some(gas),
+            value,
+            input: input.into(),
+            access_list: rng.gen_bool(0.2).then(|| AccessList(vec![AccessListItem {
+                address: Address::from(rng.gen::<[u8; 20]>()),
+                storage_keys: vec![B256::from(rng.gen::<[u8; 32]>())],
+            }])),
+            kettle_address: Some(Address::from(rng.gen::<[u8; 20]>())),
+            chain_id: Some(chain_id),
+            confidential_inputs_hash: Some(FixedBytes::from(rng.gen::<[u8; 32]>())),
+            signature: Some(signature),
+            extra_fields: match rng.gen_bool(0.1) {
+                // A string and an empty list appended by a newer version
+                true => vec![Bytes::from_static(&[0x01]), Bytes::from_static(&[0xc0])],
+                false => Vec::new(),
+            },
+            ..Default::default()
+        };
+        record.set_fees(if rng.gen_bool(0.5) {
+            Fees::Legacy { gas_price: fee }
+        } else {
+            Fees::Dynamic { max_priority_fee_per_gas: rng.gen(), max_fee_per_gas: fee }
+        });
+        record
+    }
+
+    #[test]
+    fn test_fields_len_matches_encoding() {
+        let mut rng = StdRng::seed_from_u64(0x42);
+        for _ in 0..512 {
+            let rlp = CRecordRLP::try_from(&random_record(&mut rng)).unwrap();
+            let mut out = Vec::new();
+            rlp.encode(&mut out);
+            assert_eq!(out.len(), rlp.length());
+            assert_eq!(Header::decode(&mut out.as_slice()).unwrap().payload_length, rlp.fields_len());
+            assert_eq!(CRecordRLP::decode(&mut out.as_slice()).unwrap(), rlp);
+        }
+    }
+
+    #[test]
+    fn test_layout_detection() {
+        let legacy = [(false, 1), (false, 4), (false, 3), (false, 20), (false, 0), (false, 20)];
+        let signed = [(false, 20), (false, 32), (false, 3), (false, 1), (false, 32), (false, 32)];
+        let items = [&legacy[..], &signed[..]].concat();
+        assert_eq!(RecordLayout::detect(&items), Some(RecordLayout { dynamic: false, access_list: false, known_fields: 12 }));
+
+        // 20 byte input followed by a newer version's fields
+        let dynamic = [&legacy[..3], &[(false, 4)][..], &legacy[3..], &signed[..], &[(false, 20), (true, 32)][..]].concat();
+        assert_eq!(RecordLayout::detect(&dynamic), Some(RecordLayout { dynamic: true, access_list: false, known_fields: 13 }));
+
+        let with_access_list = [&legacy[..], &[(true, 0)][..], &signed[..]].concat();
+        assert_eq!(RecordLayout::detect(&with_access_list), Some(RecordLayout { dynamic: false, access_list: true, known_fields: 13 }));
+        assert_eq!(RecordLayout::detect(&items[..11]), None);
+    }
+
+    #[test]
+    fn test_create_record() {
+        let mut record = random_record(&mut StdRng::seed_from_u64(0x43));
+        record.to = TxKind::Create;
+        let rlp = CRecordRLP::try_from(&record).unwrap();
+        let mut out = Vec::new();
+        rlp.encode(&mut out);
+        let decoded = ConfidentialComputeRecord::try_from(CRecordRLP::decode(&mut out.as_slice()).unwrap()).unwrap();
+        assert_eq!((decoded.to, decoded.target()), (TxKind::Create, None));
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert!(json["to"].is_null());
+        let decoded: ConfidentialComputeRecord = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(decoded.to, TxKind::Create);
+
+        for to in [serde_json::json!(""), serde_json::json!("0x")] {
+            let mut json = json.clone();
+            json["to"] = to;
+            let decoded: ConfidentialComputeRecord = serde_json::from_value(json).unwrap();
+            assert_eq!(decoded.to, TxKind::Create);
+        }
+        let mut missing = json;
+        missing.as_object_mut().unwrap().remove("to");
+        let err = serde_json::from_value::<ConfidentialComputeRecord>(missing).unwrap_err();
+        assert!(err.to_string().contains("missing field `to`"));
+    }
 
     #[test]
     fn test_ccr_rlp_encode() -> Result<()> {
@@ -192,7 +687,7 @@ mod tests {
         
         let cc_record = ConfidentialComputeRecord::from_tx_request(tx.clone(), kettle_address)?;
         assert_eq!(cc_record.kettle_address, Some(kettle_address));
-        assert_eq!(cc_record.to, to_add);
+        assert_eq!(cc_record.to, TxKind::Call(to_add));
         assert_eq!(cc_record.gas, tx.gas);
         assert_eq!(cc_record.gas_price, tx.gas_price);
         assert_eq!(cc_record.chain_id, Some(chain_id));
@@ -201,6 +696,11 @@ mod tests {
         assert_eq!(cc_record.value, tx.value.unwrap());
         assert!(cc_record.confidential_inputs_hash.is_none());
         assert!(cc_record.signature.is_none());
+        assert_eq!(cc_record.access_list, None);
+
+        let access_list = AccessList(vec![AccessListItem { address: to_add, storage_keys: vec![B256::ZERO] }]);
+        let cc_record = ConfidentialComputeRecord::from_tx_request(TransactionRequest::default().with_access_list(access_list.clone()), kettle_address)?;
+        assert_eq!(cc_record.access_list, Some(access_list));
 
         Ok(())
     }
@@ -215,7 +715,7 @@ mod tests {
         
         let cc_record = ConfidentialComputeRecord::from_tx_request(tx.clone(), kettle_address)?;
         assert_eq!(cc_record.kettle_address, Some(kettle_address));
-        assert_eq!(cc_record.to, Address::ZERO);
+        assert_eq!(cc_record.to, TxKind::Create);
         assert_eq!(cc_record.gas, tx.gas);
         assert_eq!(cc_record.gas_price, None);
         assert_eq!(cc_record.chain_id, Some(chain_id));
@@ -224,6 +724,11 @@ mod tests {
         assert_eq!(cc_record.value, U256::ZERO);
         assert!(cc_record.confidential_inputs_hash.is_none());
         assert!(cc_record.signature.is_none());
+        assert_eq!(CRecordRLP::try_from(&cc_record), Err(CcrError::MissingSignature));
+
+        let mut signed = crate::fixtures::load_fixture("empty_inputs").unwrap().request.confidential_compute_record;
+        signed.nonce = None;
+        assert_eq!(CRecordRLP::try_from(&signed), Err(CcrError::MissingField("nonce")));
 
         Ok(())
     }
@@ -243,4 +748,38 @@ mod tests {
         assert!(cc_record_res.is_err());
     }
 
+    #[tokio::test]
+    async fn test_dynamic_fee_record() -> Result<()> {
+        use alloy::{consensus::SignableTransaction, eips::eip2718::{Decodable2718, Encodable2718}, network::TxSigner, signers::wallet::LocalWallet};
+        use crate::{fixtures, ConfidentialComputeRequest, DecodeMode};
+
+        let legacy = fixtures::load_fixture("empty_inputs").unwrap().request;
+        assert!(serde_json::to_value(&legacy)?["confidentialComputeRecord"].get("maxFeePerGas").is_none());
+
+        let mut ccr = legacy.clone();
+        let record = &mut ccr.confidential_compute_record;
+        record.gas_price = None;
+        record.max_fee_per_gas = Some(0x3b9aca00);
+        record.max_priority_fee_per_gas = Some(0x01);
+        assert_eq!(record.fees(), Some(Fees::Dynamic { max_priority_fee_per_gas: 0x01, max_fee_per_gas: 0x3b9aca00 }));
+        assert_eq!(record.max_gas_price(), Some(0x3b9aca00));
+        assert_ne!(ccr.signature_hash(), legacy.signature_hash());
+
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?;
+        let sig = wallet.sign_transaction(&mut ccr).await?;
+        ccr.confidential_compute_record.set_sig(sig);
+        let mut raw = Vec::new();
+        ccr.encode_2718(&mut raw);
+        assert_eq!(ConfidentialComputeRequest::decode_2718(&mut raw.as_slice())?, ccr);
+        for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+            assert_eq!(ConfidentialComputeRequest::decode_2718_with_mode(&raw, mode)?, ccr);
+        }
+        assert_eq!(sig.recover_address_from_prehash(&ccr.signature_hash())?, wallet.address());
+        assert_ne!(ccr.tx_hash()?, legacy.tx_hash()?);
+
+        let json = serde_json::to_string(&ccr)?;
+        assert_eq!(serde_json::from_str::<ConfidentialComputeRequest>(&json)?, ccr);
+        Ok(())
+    }
+
 }
@@ -1,12 +1,15 @@
-use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use std::borrow::Cow;
+use alloy_rlp::{Decodable, Encodable, Header};
 use serde::{Deserialize, Serialize};
-use eyre::{eyre, Result};
 use alloy::{
     primitives::{self, Address, Bytes, FixedBytes, U256, ChainId, Signature, TxKind}, 
     consensus::{SignableTransaction, Signed, Transaction}, 
     eips::eip2718::{Decodable2718, Encodable2718}
 };
-use super::crecord::{ConfidentialComputeRecord, CRecordRLP, EMPTY_BYTES_HASH};
+use suave_alloy_core::RecordFields;
+use super::crecord::{signature_to_vrs, ConfidentialComputeRecord, CRecordRLP, EMPTY_BYTES_HASH};
+use super::{CcrError, DecodeMode};
+use super::consts::{ProtocolConfig, SigningDomain};
 
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
@@ -32,7 +35,7 @@ impl ConfidentialComputeRequest {
         }
     }
 
-    pub fn rlp_encode(&self) -> Result<Bytes> {
+    pub fn rlp_encode(&self) -> Result<Bytes, CcrError> {
         let rlp_encoded = encode_with_prefix(
             ConfidentialComputeRequest::TYPE, 
             CRequestRLP::try_from(self)?
@@ -41,6 +44,20 @@ impl ConfidentialComputeRequest {
         Ok(rlp_encoded)
     }
 
+    /// Length of `rlp_encode`, computed without encoding.
+    pub fn encoded_len(&self) -> Result<usize, CcrError> {
+        Ok(1 + CRequestRLP::try_from(self)?.length())
+    }
+
+    /// `SignableTransaction::into_signed` without panicking on missing
+    /// fields.
+    pub fn try_into_signed(mut self, signature: Signature) -> Result<Signed<Self, Signature>, CcrError> {
+        let signature = signature.with_parity_bool();
+        self.confidential_compute_record.set_sig(signature);
+        let hash = self.tx_hash()?;
+        Ok(Signed::new_unchecked(self, signature, hash))
+    }
+
     pub fn kettle_address(&self) -> Option<Address> {
         self.confidential_compute_record.kettle_address
     }
@@ -54,6 +71,19 @@ impl ConfidentialComputeRequest {
         self.confidential_compute_record.kettle_address = Some(kettle_address);
     }
 
+    /// Unsigned copy for another chain and kettle, eg. to replay a
+    /// production CCR on a local devnet. The signature and sender are
+    /// cleared; everything else (incl. the nonce) is kept.
+    pub fn retarget(&self, chain_id: u64, kettle_address: Address) -> Self {
+        let mut ccr = self.clone();
+        let record = &mut ccr.confidential_compute_record;
+        record.chain_id = Some(chain_id);
+        record.kettle_address = Some(kettle_address);
+        record.signature = None;
+        record.from = None;
+        ccr
+    }
+
     pub fn with_confidential_inputs(mut self, confidential_inputs: Bytes) -> Self {
         self.set_confidential_inputs(confidential_inputs);
         self
@@ -69,13 +99,92 @@ impl ConfidentialComputeRequest {
         self.confidential_inputs.clone()
     }
 
-    fn hash(&self) -> FixedBytes<32> {
-        let rlp_encoded = encode_with_prefix(
-            ConfidentialComputeRecord::TYPE, 
-            CRequestHashParams::try_from(self).unwrap()
-        );
-        let hash = primitives::keccak256(&rlp_encoded);
-        hash
+    /// Recompute keccak over the confidential inputs and compare it to the
+    /// hash committed in the record (empty-bytes hash if none is set).
+    pub fn validate_inputs_hash(&self) -> Result<(), InputsHashMismatch> {
+        let committed = self.confidential_compute_record
+            .confidential_inputs_hash
+            .unwrap_or(EMPTY_BYTES_HASH);
+        let computed = primitives::keccak256(&self.confidential_inputs);
+        if committed != computed {
+            return Err(InputsHashMismatch { committed, computed, inputs_len: self.confidential_inputs.len() });
+        }
+        Ok(())
+    }
+
+    /// Whether the request carries no confidential inputs but commits to
+    /// non-empty ones, uploaded to the kettle beforehand and resolved there
+    /// by hash. Such requests can't be checked with `validate_inputs_hash`.
+    pub fn references_inputs(&self) -> bool {
+        self.confidential_inputs.is_empty() && self.confidential_compute_record
+            .confidential_inputs_hash
+            .is_some_and(|hash| hash != EMPTY_BYTES_HASH)
+    }
+
+    /// Transaction hash as reported by the node (`eth_sendRawTransaction`,
+    /// `eth_getTransactionByHash`): keccak of the 0x42 prefixed signed
+    /// record. Confidential inputs are not part of it, so the hash doesn't
+    /// leak them and stays the same across the envelope they travel in.
+    pub fn tx_hash(&self) -> Result<FixedBytes<32>, CcrError> {
+        let record = &self.confidential_compute_record;
+        if !record.extra_fields.is_empty() {
+            // Fields unknown to the core: hash the record as encoded
+            let mut out = vec![ConfidentialComputeRecord::TYPE];
+            CRecordRLP::try_from(record)?.encode(&mut out);
+            return Ok(primitives::keccak256(out));
+        }
+        let sig = record.signature.ok_or(CcrError::MissingSignature)?;
+        let (v, r, s) = signature_to_vrs(sig);
+        let chain_id = record.chain_id.ok_or(CcrError::MissingField("chain id"))?;
+        let access_list = record.access_list.as_ref().map(alloy_rlp::encode);
+        Ok(suave_alloy_core::tx_hash(&record_fields(record, &access_list)?, chain_id, v, r, s))
+    }
+
+    /// Sign in the signing domain of `config` (see `SigningDomain`).
+    pub fn with_protocol_config(mut self, config: &ProtocolConfig) -> Self {
+        self.confidential_compute_record.signing_domain = config.signing_domain.clone();
+        self
+    }
+
+    /// Address that signed the request, recovered from its signature over
+    /// the signing hash (in the record's signing domain), eg. to know who
+    /// sent raw CCR bytes before forwarding them to a kettle. Secp256k1
+    /// only, see `recover_signer_with` for other `SignatureScheme`s.
+    pub fn recover_signer(&self) -> Result<Address, CcrError> {
+        let sig = self.confidential_compute_record.signature.ok_or(CcrError::MissingSignature)?;
+        recover(&sig, self)
+    }
+
+    /// Hash the sender signs (`signature_hash` of `SignableTransaction`,
+    /// without needing the trait or panicking on missing fields). Unlike
+    /// `tx_hash` it is known before signing, eg. to correlate logs of a
+    /// request across its signing and submission.
+    pub fn signing_hash(&self) -> Result<FixedBytes<32>, CcrError> {
+        let record = &self.confidential_compute_record;
+        record.signing_hash_cache.get_or_compute(record, || self.signing_payload().map(primitives::keccak256))
+    }
+
+    /// 0x42 prefixed hash params wrapped in the record's signing domain.
+    fn signing_payload(&self) -> Result<Vec<u8>, CcrError> {
+        let record = &self.confidential_compute_record;
+        if record.signing_domain == SigningDomain::Eip712 {
+            return self.encode_eip712();
+        }
+        let access_list = record.access_list.as_ref().map(alloy_rlp::encode);
+        let payload = suave_alloy_core::signing_payload(&record_fields(record, &access_list)?);
+        Ok(record.signing_domain.wrap(&payload))
+    }
+
+    /// Length of the signing payload, without encoding it (but for EIP-712,
+    /// whose payload is hashed anyway).
+    pub fn signing_payload_len(&self) -> Result<usize, CcrError> {
+        let record = &self.confidential_compute_record;
+        if record.signing_domain == SigningDomain::Eip712 {
+            return self.encode_eip712().map(|payload| payload.len());
+        }
+        let access_list = record.access_list.as_ref().map(alloy_rlp::encode);
+        let payload_len = suave_alloy_core::signing_payload_len(&record_fields(record, &access_list)?);
+        Ok(record.signing_domain.wrapped_len(payload_len))
     }
 
 }
@@ -87,7 +196,7 @@ impl Transaction for ConfidentialComputeRequest {
     }
 
     fn to(&self) -> TxKind {
-        TxKind::Call(self.confidential_compute_record.to)
+        self.confidential_compute_record.to
     }
 
     fn value(&self) -> U256 {
@@ -112,6 +221,9 @@ impl Transaction for ConfidentialComputeRequest {
 
 }
 
+/// The trait can't return errors: its methods panic on requests missing
+/// fields needed for signing. Prefer `signing_hash`, `signing_payload_len`
+/// and `try_into_signed`, which return them.
 impl SignableTransaction<Signature> for ConfidentialComputeRequest {
 
     fn set_chain_id(&mut self, chain_id: ChainId) {
@@ -119,28 +231,29 @@ impl SignableTransaction<Signature> for ConfidentialComputeRequest {
     }
 
     fn encode_for_signing(&self, out: &mut dyn alloy_rlp::BufMut) {
-        out.put_u8(ConfidentialComputeRecord::TYPE);
-        CRequestHashParams::try_from(self).unwrap().encode(out);
+        out.put_slice(&self.signing_payload().expect("Missing fields for signing"));
     }
 
     fn payload_len_for_signature(&self) -> usize {
-        let chain_id = self.confidential_compute_record.chain_id.expect("Chain ID not set");
-        CRequestHashParams::try_from(self).unwrap().fields_len() + chain_id as usize + 2
+        self.signing_payload_len().expect("Missing fields for signing")
+    }
+
+    fn signature_hash(&self) -> FixedBytes<32> {
+        self.signing_hash().expect("Missing fields for signing")
     }
 
     fn into_signed(self, signature: Signature) -> Signed<Self, Signature> where Self: Sized {
-        let hash = self.hash().into();
-        Signed::new_unchecked(self, signature.with_parity_bool(), hash)
+        self.try_into_signed(signature).expect("Missing fields for signed request")
     }
 
 }
 
+#[deny(clippy::unwrap_used, clippy::expect_used)]
 impl Decodable2718 for ConfidentialComputeRequest {
     fn typed_decode(ty: u8, buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
         match ty {
             ConfidentialComputeRequest::TYPE => {
-                let crequest_prerlp = CRequestRLP::decode(buf)?;
-                Ok(crequest_prerlp.into())
+                ConfidentialComputeRequest::decode_rlp_with_mode(buf, DecodeMode::default())
             }
             _ => Err(alloy_rlp::Error::Custom("Only ConfidentialComputeRequest"))
         }
@@ -151,103 +264,142 @@ impl Decodable2718 for ConfidentialComputeRequest {
     }
 }
 
+/// Panics on unsigned or incomplete requests, like `SignableTransaction`;
+/// `rlp_encode` and `encoded_len` return the error instead.
 impl Encodable2718 for ConfidentialComputeRequest {
     fn type_flag(&self) -> Option<u8> {
         Some(ConfidentialComputeRequest::TYPE)
     }
 
     fn encode_2718_len(&self) -> usize {
-        CRequestRLP::try_from(self).unwrap().fields_len()
+        self.encoded_len().expect("Missing fields for encoding")
     }
 
     fn encode_2718(&self, out: &mut dyn alloy_rlp::BufMut) {
         out.put_u8(ConfidentialComputeRequest::TYPE);
-        CRequestRLP::try_from(self).unwrap().encode(out);
+        CRequestRLP::try_from(self).expect("Missing fields for encoding").encode(out);
     }
 }
 
-#[derive(Debug, RlpEncodable, RlpDecodable, PartialEq)]
-struct CRequestRLP {
-    request: CRecordRLP,
-    confidential_inputs: Bytes,
+/// Helpers on requests signed through `SignableTransaction::into_signed`.
+pub trait SignedRequestExt {
+    /// Signer of the request (see `ConfidentialComputeRequest::recover_signer`).
+    fn recover(&self) -> Result<Address, CcrError>;
 }
 
-impl CRequestRLP {
-    fn fields_len(&self) -> usize {
-        let mut len = 0;
-        len += self.request.fields_len();
-        len += self.confidential_inputs.length();
-        len
+impl SignedRequestExt for Signed<ConfidentialComputeRequest, Signature> {
+    fn recover(&self) -> Result<Address, CcrError> {
+        recover(self.signature(), self.tx())
     }
 }
 
-impl TryFrom<&ConfidentialComputeRequest> for CRequestRLP {
-    type Error = eyre::Error;
+fn recover(sig: &Signature, ccr: &ConfidentialComputeRequest) -> Result<Address, CcrError> {
+    sig.recover_address_from_prehash(&ccr.signing_hash()?).map_err(|_| CcrError::InvalidSignature)
+}
 
-    fn try_from(ccr: &ConfidentialComputeRequest) -> Result<Self> {
-        Ok(Self {
-            request: (&ccr.confidential_compute_record).try_into()?,
-            confidential_inputs: ccr.confidential_inputs.clone(),
-        })
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputsHashMismatch {
+    /// Hash committed in the record.
+    pub committed: FixedBytes<32>,
+    /// Keccak of the carried confidential inputs.
+    pub computed: FixedBytes<32>,
+    pub inputs_len: usize,
 }
 
-impl Into<ConfidentialComputeRequest> for CRequestRLP {
-    fn into(self) -> ConfidentialComputeRequest {
-        let cc_record: ConfidentialComputeRecord = self.request.into();
-        ConfidentialComputeRequest {
-            confidential_compute_record: cc_record,
-            confidential_inputs: self.confidential_inputs,
-        }
+impl std::fmt::Display for InputsHashMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "Confidential inputs hash mismatch: record commits to {}, inputs ({} bytes) hash to {}",
+            self.committed, self.inputs_len, self.computed
+        )
     }
 }
 
-#[derive(Debug, RlpEncodable, PartialEq)]
-struct CRequestHashParams {
-    kettle_address: Address,
-    confidential_inputs_hash: FixedBytes<32>,
-    nonce: u64,
-    gas_price: u128,
-    gas: u128,
-    to: Address,
-    value: U256,
-    input: Bytes,
+impl std::error::Error for InputsHashMismatch {}
+
+/// Signed request as encoded, borrowing the request it is encoded from.
+#[derive(Debug, PartialEq)]
+pub(crate) struct CRequestRLP<'a> {
+    request: CRecordRLP<'a>,
+    confidential_inputs: Cow<'a, Bytes>,
 }
 
-impl CRequestHashParams {
+impl CRequestRLP<'_> {
     fn fields_len(&self) -> usize {
-        let mut len = 0;
-        len += self.kettle_address.length();
-        len += self.confidential_inputs_hash.length();
-        len += self.nonce.length();
-        len += self.gas_price.length();
-        len += self.to.length();
-        len += self.value.length();
-        len += self.input.0.length();
-        len
+        self.request.length() + self.confidential_inputs.length()
     }
 }
 
-impl TryFrom<&ConfidentialComputeRequest> for CRequestHashParams {
-    type Error = eyre::Error;
+impl Encodable for CRequestRLP<'_> {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        Header { list: true, payload_length: self.fields_len() }.encode(out);
+        self.request.encode(out);
+        self.confidential_inputs.encode(out);
+    }
 
-    fn try_from(ccr: &ConfidentialComputeRequest) -> Result<Self> {
-        let cinputs_hash = ccr.confidential_compute_record
-            .confidential_inputs_hash
-            .unwrap_or(EMPTY_BYTES_HASH);
+    fn length(&self) -> usize {
+        let payload_length = self.fields_len();
+        payload_length + alloy_rlp::length_of_length(payload_length)
+    }
+}
+
+impl Decodable for CRequestRLP<'_> {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut fields = Header::decode_bytes(buf, true)?;
+        let expected = fields.len();
+        let rlp = Self {
+            request: CRecordRLP::decode(&mut fields)?,
+            confidential_inputs: Cow::Owned(Bytes::decode(&mut fields)?),
+        };
+        if !fields.is_empty() {
+            return Err(alloy_rlp::Error::ListLengthMismatch { expected, got: expected - fields.len() });
+        }
+        Ok(rlp)
+    }
+}
+
+impl<'a> TryFrom<&'a ConfidentialComputeRequest> for CRequestRLP<'a> {
+    type Error = CcrError;
+
+    fn try_from(ccr: &'a ConfidentialComputeRequest) -> Result<Self, CcrError> {
         Ok(Self {
-            kettle_address: ccr.confidential_compute_record.kettle_address.ok_or_else(|| eyre!("Missing kettle address field"))?,
-            confidential_inputs_hash: cinputs_hash,
-            nonce: ccr.confidential_compute_record.nonce.ok_or_else(|| eyre!("Missing nonce field"))?,
-            gas_price: ccr.confidential_compute_record.gas_price.ok_or_else(|| eyre!("Missing gas price field"))?,
-            gas: ccr.confidential_compute_record.gas.ok_or_else(|| eyre!("Missing gas field"))?,
-            to: ccr.confidential_compute_record.to,
-            value: ccr.confidential_compute_record.value,
-            input: ccr.confidential_compute_record.input.clone(),
+            request: (&ccr.confidential_compute_record).try_into()?,
+            confidential_inputs: Cow::Borrowed(&ccr.confidential_inputs),
         })
     }
 }
 
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+impl TryFrom<CRequestRLP<'_>> for ConfidentialComputeRequest {
+    type Error = alloy_rlp::Error;
+
+    fn try_from(rlp: CRequestRLP<'_>) -> alloy_rlp::Result<Self> {
+        Ok(ConfidentialComputeRequest {
+            confidential_compute_record: rlp.request.try_into()?,
+            confidential_inputs: rlp.confidential_inputs.into_owned(),
+        })
+    }
+}
+
+/// Fields covered by the signature; all but the inputs hash are required.
+/// `access_list` is the RLP of the record's access list, if it has one.
+fn record_fields<'a>(
+    record: &'a ConfidentialComputeRecord,
+    access_list: &'a Option<Vec<u8>>,
+) -> Result<RecordFields<'a>, CcrError> {
+    Ok(RecordFields {
+        nonce: record.nonce.ok_or(CcrError::MissingField("nonce"))?,
+        fees: record.fees().ok_or(CcrError::MissingField("gas price"))?,
+        gas: record.gas.ok_or(CcrError::MissingField("gas"))?,
+        to: record.to,
+        value: record.value,
+        input: &record.input,
+        access_list: access_list.as_deref(),
+        kettle_address: record.kettle_address.ok_or(CcrError::MissingField("kettle address"))?,
+        confidential_inputs_hash: record.confidential_inputs_hash.unwrap_or(EMPTY_BYTES_HASH),
+    })
+}
+
 fn encode_with_prefix<T: Encodable>(prefix: u8, item: T) -> Bytes {
     let mut buffer = vec![prefix];
     item.encode(&mut buffer);
@@ -267,7 +419,9 @@ mod tests {
             TxSigner
         }, 
     };
-    use eyre::Ok;
+    use eyre::{Ok, Result};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use super::super::crecord::tests::random_record;
 
     #[test]
     fn test_ccr_rlp_encode() -> Result<()> {
@@ -302,31 +456,6 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn test_crequesthashparams_hash() {
-        let kettle_address = Address::from_str("0x7d83e42b214b75bf1f3e57adc3415da573d97bff").unwrap();
-        let to_add = Address::from_str("0x772092ff73c43883a547bea1e1e007ec0d33478e").unwrap();
-        let input = Bytes::from_str("0x236eb5a70000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000000000000000000000000000000000001000000000000000000000000772092ff73c43883a547bea1e1e007ec0d33478e0000000000000000000000000000000000000000000000000000000000000000").unwrap();
-        let cinputs = Bytes::from_str("0x000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000001ea7b22747873223a5b7b2274797065223a22307830222c226e6f6e6365223a22307830222c22746f223a22307838626265386333346637396433353534666631626236643932313733613237666661356237313233222c22676173223a22307835323038222c226761735072696365223a22307864222c226d61785072696f72697479466565506572476173223a6e756c6c2c226d6178466565506572476173223a6e756c6c2c2276616c7565223a223078336538222c22696e707574223a223078222c2276223a2230786366323837222c2272223a22307862396433643236643135633630376237653537353235333761336163326432363330643161653036386163353138616539393862613439313236323134383135222c2273223a22307835636534666439613565376533373138656630613731386533633462333135306538373036376533373361333439323538643962333330353930396332303565222c2268617368223a22307863633934626637386463366631373963663331376638643839353438393364393730303366333266353332623530623865333861626631333939353364643664227d5d2c2270657263656e74223a31302c224d617463684964223a5b302c302c302c302c302c302c302c302c302c302c302c302c302c302c302c305d7d00000000000000000000000000000000000000000000").unwrap();
-        let cinputs_hash = primitives::keccak256(&cinputs);
-
-        let hash_params = CRequestHashParams {
-            kettle_address,
-            confidential_inputs_hash: cinputs_hash,
-            nonce: 0x18,
-            gas_price: 0x3b9aca00,
-            gas: 0x0f4240,
-            to: to_add,
-            value: U256::ZERO,
-            input,
-        };
-        let encoded = encode_with_prefix(ConfidentialComputeRecord::TYPE, hash_params);
-        let hash = primitives::keccak256(&encoded);
-
-        let expected_hash = FixedBytes::from_str("0x72ffab40c5116931200ca87052360787559871297b3615a8c2ff28be738ac59f").unwrap();
-        assert_eq!(hash, expected_hash);
-    }
-
     #[test]
     fn test_crequest_hash() {
         let kettle_address = Address::from_str("0x7d83e42b214b75bf1f3e57adc3415da573d97bff").unwrap();
@@ -340,19 +469,26 @@ mod tests {
             confidential_inputs_hash: Some(cinputs_hash),
             nonce: Some(0x18),
             gas_price: Some(0x3b9aca00),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             gas: Some(0x0f4240),
-            to: to_add,
+            to: TxKind::Call(to_add),
             value: U256::ZERO,
             input,
+            access_list: None,
             signature: None,
+            extra_fields: Vec::new(),
             chain_id: Some(1),
             from: None,
+            signing_domain: SigningDomain::None,
+            signing_hash_cache: Default::default(),
         };
         let crequest = ConfidentialComputeRequest {
             confidential_compute_record: crecord,
             confidential_inputs: cinputs
         };
-        let hash = crequest.hash();
+        let hash = crequest.signing_hash().unwrap();
+        assert_eq!(hash, crequest.signature_hash());
 
         let expected_hash = FixedBytes::from_str("0x72ffab40c5116931200ca87052360787559871297b3615a8c2ff28be738ac59f").unwrap();
         assert_eq!(hash, expected_hash);
@@ -426,4 +562,183 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_signing_domain() -> Result<()> {
+        let fixture = super::super::fixtures::load_fixture("empty_inputs").unwrap();
+        assert_eq!(fixture.request.signature_hash(), fixture.signing_hash);
+
+        let mut payload = Vec::new();
+        fixture.request.encode_for_signing(&mut payload);
+        let config = ProtocolConfig::default().with_signing_domain(SigningDomain::Eip191);
+        let mut ccr = fixture.request.clone().with_protocol_config(&config);
+        assert_eq!(ccr.signature_hash(), primitives::eip191_hash_message(&payload));
+
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?;
+        let sig = wallet.sign_transaction(&mut ccr).await?;
+        assert_eq!(sig.recover_address_from_prehash(&ccr.signature_hash())?, wallet.address());
+        assert_ne!(sig.recover_address_from_prehash(&fixture.signing_hash)?, wallet.address());
+
+        // The domain comes from the config, not the encoding
+        ccr.confidential_compute_record.set_sig(sig);
+        let raw = ccr.rlp_encode()?;
+        let decoded = ConfidentialComputeRequest::decode_2718_with(&raw, &config)?;
+        assert_eq!(decoded.confidential_compute_record.signing_domain(), &SigningDomain::Eip191);
+        assert_eq!(decoded.recover_signer()?, wallet.address());
+        let mut buf = &raw[1..];
+        let typed = ConfidentialComputeRequest::typed_decode_with(ConfidentialComputeRequest::TYPE, &mut buf, &config)?;
+        assert_eq!(typed, decoded);
+        let plain = ConfidentialComputeRequest::decode_2718(&mut &raw[..])?;
+        assert_ne!(plain.recover_signer()?, wallet.address());
+
+        let dst = Bytes::from_static(b"MY_FORK_CCR_V1");
+        let custom = fixture.request.clone()
+            .with_protocol_config(&ProtocolConfig::default().with_signing_domain(SigningDomain::Custom(dst.clone())));
+        assert_eq!(custom.signature_hash(), primitives::keccak256([dst.as_ref(), &payload].concat()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_retarget() {
+        let fixture = super::super::fixtures::load_fixture("bundle_inputs").unwrap();
+        let kettle = Address::repeat_byte(0x03);
+        let ccr = fixture.request.retarget(0x1008c45, kettle);
+        let record = &ccr.confidential_compute_record;
+        assert_eq!(record.chain_id, Some(0x1008c45));
+        assert_eq!(record.kettle_address, Some(kettle));
+        assert_eq!(record.signature, None);
+        assert_eq!(ccr.tx_hash(), Err(CcrError::MissingSignature));
+        assert_eq!(record.nonce, fixture.request.confidential_compute_record.nonce);
+        assert_eq!(ccr.confidential_inputs, fixture.request.confidential_inputs);
+        assert_eq!(ccr.validate_inputs_hash(), std::result::Result::Ok(()));
+    }
+
+    #[test]
+    fn test_validate_inputs_hash() {
+        let cinputs = Bytes::from_static(b"confidential");
+        let mut cc_request = ConfidentialComputeRequest::new(ConfidentialComputeRecord::default(), Some(cinputs.clone()));
+        assert_eq!(cc_request.validate_inputs_hash(), std::result::Result::Ok(()));
+        assert_eq!(ConfidentialComputeRequest::default().validate_inputs_hash(), std::result::Result::Ok(()));
+
+        cc_request.confidential_inputs = Bytes::from_static(b"tampered");
+        let err = cc_request.validate_inputs_hash().unwrap_err();
+        assert_eq!(err.committed, primitives::keccak256(&cinputs));
+        assert_eq!(err.computed, primitives::keccak256(b"tampered"));
+        assert_eq!(err.inputs_len, 8);
+    }
+
+    #[test]
+    fn test_signing_hash_cache() -> Result<()> {
+        let fixture = super::super::fixtures::load_fixture("empty_inputs").unwrap();
+        let mut ccr = fixture.request.clone();
+        let uncached = |ccr: &ConfidentialComputeRequest| primitives::keccak256(ccr.signing_payload().unwrap());
+        assert_eq!(ccr.signing_hash()?, fixture.signing_hash);
+        assert_eq!(ccr.clone().signing_hash()?, fixture.signing_hash);
+        assert_eq!(ccr, fixture.request);
+
+        // Public fields changed without setters
+        ccr.confidential_compute_record.nonce = Some(1);
+        assert_eq!(ccr.signing_hash()?, uncached(&ccr));
+        assert_ne!(ccr.signing_hash()?, fixture.signing_hash);
+        ccr.confidential_compute_record.signing_domain = SigningDomain::Eip191;
+        assert_eq!(ccr.signing_hash()?, uncached(&ccr));
+        ccr.confidential_compute_record.signing_domain = SigningDomain::Custom(Bytes::from_static(b"A"));
+        assert_eq!(ccr.signing_hash()?, uncached(&ccr));
+        ccr.confidential_compute_record.signing_domain = SigningDomain::Custom(Bytes::from_static(b"B"));
+        assert_eq!(ccr.signing_hash()?, uncached(&ccr));
+
+        // Same length calldata in a new buffer
+        let mut input = ccr.confidential_compute_record.input.to_vec();
+        input[0] ^= 1;
+        ccr.confidential_compute_record.input = input.into();
+        assert_eq!(ccr.signature_hash(), uncached(&ccr));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tx_hash_matches_node() -> Result<()> {
+        // `requestRecord` as returned by `eth_getTransactionByHash` on Rigil
+        let record_str = r#"{"chainId":"0x1008c45","confidentialInputsHash":"0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470","gas":"0xf4240","gasPrice":"0x8c9aca00","hash":"0x3d753c496bb9053c7da2cdbbe170614d3e9408ee12ba521c72c2b21e151b7ab9","input":"0x50723553000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000074554485553445400","kettleAddress":"0x03493869959c866713c33669ca118e774a30a0e5","maxFeePerGas":null,"maxPriorityFeePerGas":null,"nonce":"0x45","r":"0xc1c5071f78c6f6b6380ebc4957dd4f6c74bdf5be742ad0d62d2d75f510e33660","s":"0x5de5c97f9c5ee5c5dad3bb0d591e581f48cd947e998d32500bb73de24dd7a6f9","to":"0xc803334c79650708daf3a3462ac4b48296b1352a","type":"0x42","v":"0x0","value":"0x0"}"#;
+        let node_hash = FixedBytes::<32>::from_str("0x3d753c496bb9053c7da2cdbbe170614d3e9408ee12ba521c72c2b21e151b7ab9")?;
+        let record: ConfidentialComputeRecord = serde_json::from_str(record_str)?;
+        let cc_request = ConfidentialComputeRequest::new(record, None);
+        assert_eq!(cc_request.tx_hash()?, node_hash);
+        assert_ne!(cc_request.signing_hash()?, node_hash);
+        assert_eq!(ConfidentialComputeRequest::default().signing_hash(), Err(CcrError::MissingField("nonce")));
+
+        let signature = cc_request.confidential_compute_record.signature.unwrap();
+        let mut unsigned = cc_request.clone();
+        unsigned.confidential_compute_record.signature = None;
+        let signed = unsigned.into_signed(signature);
+        assert_eq!(*signed.hash(), node_hash);
+
+        // Errors instead of the trait methods' panics
+        let mut incomplete = cc_request.clone();
+        incomplete.confidential_compute_record.chain_id = None;
+        assert_eq!(incomplete.encoded_len(), Err(CcrError::MissingField("chain id")));
+        assert_eq!(incomplete.try_into_signed(signature).err(), Some(CcrError::MissingField("chain id")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fields_len_matches_encoding() {
+        let mut rng = StdRng::seed_from_u64(0x43);
+        for _ in 0..512 {
+            let mut inputs = vec![0u8; rng.gen_range(0..600)];
+            rng.fill(&mut inputs[..]);
+            let ccr = ConfidentialComputeRequest::new(random_record(&mut rng), Some(inputs.into()));
+            let rlp = CRequestRLP::try_from(&ccr).unwrap();
+            assert!(matches!(rlp.confidential_inputs, Cow::Borrowed(_)));
+            let mut out = Vec::new();
+            rlp.encode(&mut out);
+            assert_eq!(alloy_rlp::Header::decode(&mut out.as_slice()).unwrap().payload_length, rlp.fields_len());
+            assert_eq!(ccr.encode_2718_len(), 1 + out.len());
+            assert_eq!(ccr.rlp_encode().unwrap().len(), ccr.encode_2718_len());
+        }
+    }
+
+    #[test]
+    fn test_payload_len_for_signature() {
+        let mut rng = StdRng::seed_from_u64(0x42);
+        let domains = [
+            SigningDomain::None,
+            SigningDomain::Eip191,
+            SigningDomain::Custom(Bytes::from_static(b"MY_FORK_CCR_V1")),
+            SigningDomain::Eip712,
+        ];
+        for i in 0..256 {
+            let mut record = random_record(&mut rng);
+            record.signing_domain = domains[i % domains.len()].clone();
+            let ccr = ConfidentialComputeRequest::new(record, None);
+            let Ok(len) = ccr.signing_payload_len() else {
+                // Records EIP-712 can't express (access list, dynamic fees, gas over u64)
+                assert!(ccr.signing_payload().is_err());
+                continue;
+            };
+            assert_eq!(ccr.payload_len_for_signature(), len);
+            // Written into a buffer preallocated from the reported length
+            let mut out = Vec::with_capacity(len);
+            ccr.encode_for_signing(&mut out);
+            assert_eq!(out.len(), len, "{:?}", ccr.confidential_compute_record.signing_domain);
+            assert_eq!(primitives::keccak256(&out), ccr.signature_hash());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recover_signer() -> Result<()> {
+        for fixture in crate::fixtures::load_fixtures() {
+            assert_eq!(fixture.request.recover_signer()?, fixture.signer, "{}", fixture.name);
+        }
+
+        let wallet = LocalWallet::from_str("0x1111111111111111111111111111111111111111111111111111111111111111")?;
+        let mut ccr = crate::fixtures::load_fixture("bundle_inputs").unwrap().request.retarget(0x1008c45, Address::repeat_byte(0x03));
+        assert_eq!(ccr.recover_signer(), Err(CcrError::MissingSignature));
+
+        let sig = wallet.sign_transaction(&mut ccr).await?;
+        let signed = ccr.into_signed(sig);
+        assert_eq!(signed.recover()?, wallet.address());
+        assert_eq!(signed.tx().recover_signer()?, wallet.address());
+        Ok(())
+    }
+
 }
\ No newline at end of file
@@ -1,9 +1,15 @@
 use alloy::{
     rpc::types::eth::Transaction,
-    primitives::Bytes, 
+    primitives::{Address, Bytes, Signature, B256},
+    serde as alloy_serde,
 };
 use serde::{Deserialize, Serialize};
-use super::ConfidentialComputeRecord;
+use serde_json::Value;
+use super::{ConfidentialComputeRecord, SuaveTransaction};
+
+
+/// Fields a node adds to CCR transactions on top of the Ethereum ones.
+const CCR_FIELDS: [&str; 2] = ["confidentialComputeResult", "requestRecord"];
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,23 +21,117 @@ pub struct ConfidentialCallResponse {
     pub request_record: ConfidentialComputeRecord,
 }
 
+/// Kettle result (type 0x50) as returned by `eth_getTransactionByHash` on
+/// suave-geth, without the Ethereum fields that don't apply to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuaveRpcTransaction {
+    pub hash: B256,
+    pub block_hash: Option<B256>,
+    #[serde(default, with = "alloy_serde::num::u64_hex_opt")]
+    pub block_number: Option<u64>,
+    #[serde(default, with = "alloy_serde::num::u64_hex_opt")]
+    pub transaction_index: Option<u64>,
+    /// Kettle that signed the result.
+    pub from: Address,
+    #[serde(with = "alloy_serde::num::u64_hex")]
+    pub chain_id: u64,
+    pub request_record: ConfidentialComputeRecord,
+    pub confidential_compute_result: Bytes,
+    /// Kettle signature.
+    #[serde(flatten)]
+    pub signature: Signature,
+}
+
+impl SuaveRpcTransaction {
+
+    pub fn kettle_address(&self) -> Option<Address> {
+        self.request_record.kettle_address
+    }
+
+}
+
+impl From<SuaveRpcTransaction> for SuaveTransaction {
+    fn from(tx: SuaveRpcTransaction) -> Self {
+        Self {
+            request_record: tx.request_record,
+            confidential_compute_result: tx.confidential_compute_result,
+            chain_id: tx.chain_id,
+            signature: tx.signature,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseError {
+    /// CCR fields missing, eg. the node isn't a SUAVE node or a proxy
+    /// stripped unknown fields. `raw` is the JSON as received.
+    NotACcrReceipt { missing: Vec<&'static str>, raw: Value },
+    /// CCR fields present but not decodable.
+    Malformed { error: String, raw: Value },
+}
+
+impl std::fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResponseError::NotACcrReceipt { missing, .. } =>
+                write!(f, "Not a CCR response, missing {}", missing.join(", ")),
+            ResponseError::Malformed { error, .. } => write!(f, "Malformed CCR response: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ResponseError {}
+
+impl ResponseError {
+
+    pub fn raw(&self) -> &Value {
+        match self {
+            ResponseError::NotACcrReceipt { raw, .. } | ResponseError::Malformed { raw, .. } => raw,
+        }
+    }
+
+}
+
+impl ConfidentialCallResponse {
+
+    /// Decode a transaction as returned by the node, failing with
+    /// `NotACcrReceipt` if the CCR fields are absent (or null).
+    pub fn from_json(raw: Value) -> Result<Self, ResponseError> {
+        let missing = CCR_FIELDS.iter()
+            .filter(|field| raw.get(**field).map_or(true, Value::is_null))
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(ResponseError::NotACcrReceipt { missing, raw });
+        }
+        serde_json::from_value(raw.clone())
+            .map_err(|e| ResponseError::Malformed { error: e.to_string(), raw })
+    }
+
+}
+
 impl TryFrom<Transaction> for ConfidentialCallResponse {
-    type Error = eyre::Error;
+    type Error = ResponseError;
 
     fn try_from(tx: Transaction) -> Result<Self, Self::Error> {
-        let confidential_compute_result = serde_json::from_value(
-            tx.other.get("confidentialComputeResult")
-                .ok_or(eyre::eyre!("Missing confidentialComputeResult"))?.clone()
-            )?;
-        let confidential_compute_record = serde_json::from_value(
-            tx.other.get("requestRecord")
-                .ok_or(eyre::eyre!("Missing requestRecord"))?.clone()
-            )?;
+        let raw = || serde_json::to_value(&tx).unwrap_or_default();
+        let missing = CCR_FIELDS.iter()
+            .filter(|field| tx.other.get(**field).map_or(true, Value::is_null))
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(ResponseError::NotACcrReceipt { missing, raw: raw() });
+        }
+        let decoded = serde_json::from_value(tx.other["confidentialComputeResult"].clone())
+            .and_then(|result| Ok((result, serde_json::from_value(tx.other["requestRecord"].clone())?)));
+        let (confidential_compute_result, request_record) = decoded
+            .map_err(|e| ResponseError::Malformed { error: e.to_string(), raw: raw() })?;
 
         Ok(Self {
             transaction: tx,
             confidential_compute_result,
-            request_record: confidential_compute_record,
+            request_record,
         })
     }
 }
@@ -43,10 +143,11 @@ mod tests {
     use super::*;
     use super::super::crecord::signature_to_vrs;
 
+    const RESPONSE: &str = r#"{"blockHash":null,"blockNumber":null,"chainId":"0x1008c45","confidentialComputeResult":"0x0000000000000000000000000000000000000000000000000000000001ccb310","from":"0x19e7e376e7c213b7e7e7e46cc70a5dd086daff2a","gas":"0xf4240","gasPrice":"0x8c9aca00","hash":"0x82f636c7bd91f9895f896b044e33528a2d116c65eea4c8e18c30c4577ae20ce2","input":"0x0000000000000000000000000000000000000000000000000000000001ccb310","nonce":"0x45","r":"0x85242d1876ce1d6a655fd485346628f3df18a051be0f8efa4bfa40b9e85a3dfe","requestRecord":{"chainId":"0x1008c45","confidentialInputsHash":"0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470","gas":"0xf4240","gasPrice":"0x8c9aca00","hash":"0x3d753c496bb9053c7da2cdbbe170614d3e9408ee12ba521c72c2b21e151b7ab9","input":"0x50723553000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000074554485553445400","kettleAddress":"0x03493869959c866713c33669ca118e774a30a0e5","maxFeePerGas":null,"maxPriorityFeePerGas":null,"nonce":"0x45","r":"0xc1c5071f78c6f6b6380ebc4957dd4f6c74bdf5be742ad0d62d2d75f510e33660","s":"0x5de5c97f9c5ee5c5dad3bb0d591e581f48cd947e998d32500bb73de24dd7a6f9","to":"0xc803334c79650708daf3a3462ac4b48296b1352a","type":"0x42","v":"0x0","value":"0x0"},"s":"0x4f0880f42d42b1de17f97c33749d60a46bd1f493c6547f08ac2bed0c6d111861","to":"0xc803334c79650708daf3a3462ac4b48296b1352a","transactionIndex":null,"type":"0x50","v":"0x1","value":"0x0"}"#;
+
     #[test]
     fn test_parse_response() {
-        let response_str = r#"{"blockHash":null,"blockNumber":null,"chainId":"0x1008c45","confidentialComputeResult":"0x0000000000000000000000000000000000000000000000000000000001ccb310","from":"0x19e7e376e7c213b7e7e7e46cc70a5dd086daff2a","gas":"0xf4240","gasPrice":"0x8c9aca00","hash":"0x82f636c7bd91f9895f896b044e33528a2d116c65eea4c8e18c30c4577ae20ce2","input":"0x0000000000000000000000000000000000000000000000000000000001ccb310","nonce":"0x45","r":"0x85242d1876ce1d6a655fd485346628f3df18a051be0f8efa4bfa40b9e85a3dfe","requestRecord":{"chainId":"0x1008c45","confidentialInputsHash":"0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470","gas":"0xf4240","gasPrice":"0x8c9aca00","hash":"0x3d753c496bb9053c7da2cdbbe170614d3e9408ee12ba521c72c2b21e151b7ab9","input":"0x50723553000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000074554485553445400","kettleAddress":"0x03493869959c866713c33669ca118e774a30a0e5","maxFeePerGas":null,"maxPriorityFeePerGas":null,"nonce":"0x45","r":"0xc1c5071f78c6f6b6380ebc4957dd4f6c74bdf5be742ad0d62d2d75f510e33660","s":"0x5de5c97f9c5ee5c5dad3bb0d591e581f48cd947e998d32500bb73de24dd7a6f9","to":"0xc803334c79650708daf3a3462ac4b48296b1352a","type":"0x42","v":"0x0","value":"0x0"},"s":"0x4f0880f42d42b1de17f97c33749d60a46bd1f493c6547f08ac2bed0c6d111861","to":"0xc803334c79650708daf3a3462ac4b48296b1352a","transactionIndex":null,"type":"0x50","v":"0x1","value":"0x0"}"#;
-        let response_tx: Transaction = serde_json::from_str(response_str).unwrap();
+        let response_tx: Transaction = serde_json::from_str(RESPONSE).unwrap();
         let response_cc: ConfidentialCallResponse = response_tx.clone().try_into().unwrap();
 
         assert_eq!(response_cc.transaction, response_tx);
@@ -57,7 +158,7 @@ mod tests {
         assert_eq!(response_cc.request_record.nonce, Some(0x45));
         assert_eq!(response_cc.request_record.input, Bytes::from_str("0x50723553000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000074554485553445400").unwrap());
         assert_eq!(response_cc.request_record.kettle_address, Address::from_str("0x03493869959c866713c33669ca118e774a30a0e5").ok());
-        assert_eq!(response_cc.request_record.to, Address::from_str("0xc803334c79650708daf3a3462ac4b48296b1352a").unwrap());
+        assert_eq!(response_cc.request_record.target(), Address::from_str("0xc803334c79650708daf3a3462ac4b48296b1352a").ok());
         assert_eq!(response_cc.request_record.confidential_inputs_hash, Some(FixedBytes::from_str("0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470").unwrap()));
         
         let sig = response_cc.request_record.signature.expect("No signature");
@@ -66,4 +167,39 @@ mod tests {
         assert_eq!(r, U256::from_str("0xc1c5071f78c6f6b6380ebc4957dd4f6c74bdf5be742ad0d62d2d75f510e33660").unwrap());
         assert_eq!(s, U256::from_str("0x5de5c97f9c5ee5c5dad3bb0d591e581f48cd947e998d32500bb73de24dd7a6f9").unwrap());    
     }
+
+    #[test]
+    fn test_rpc_transaction() {
+        let tx: SuaveRpcTransaction = serde_json::from_str(RESPONSE).unwrap();
+        assert_eq!(tx.hash, B256::from_str("0x82f636c7bd91f9895f896b044e33528a2d116c65eea4c8e18c30c4577ae20ce2").unwrap());
+        assert_eq!((tx.block_number, tx.chain_id), (None, 0x1008c45));
+        assert_eq!(tx.from, Address::from_str("0x19e7e376e7c213b7e7e7e46cc70a5dd086daff2a").unwrap());
+        assert_eq!(tx.kettle_address(), Address::from_str("0x03493869959c866713c33669ca118e774a30a0e5").ok());
+        assert_eq!(tx.request_record.nonce, Some(0x45));
+        assert_eq!(tx.signature.r(), U256::from_str("0x85242d1876ce1d6a655fd485346628f3df18a051be0f8efa4bfa40b9e85a3dfe").unwrap());
+
+        let response: ConfidentialCallResponse = serde_json::from_str(RESPONSE).unwrap();
+        let from_response = SuaveTransaction::try_from(&response).unwrap();
+        assert_eq!(SuaveTransaction::from(tx.clone()), from_response);
+
+        let json = serde_json::to_value(&tx).unwrap();
+        assert_eq!(serde_json::from_value::<SuaveRpcTransaction>(json).unwrap(), tx);
+    }
+
+    #[test]
+    fn test_not_a_ccr_response() {
+        let plain = r#"{"blockHash":null,"blockNumber":null,"chainId":"0x1","from":"0x19e7e376e7c213b7e7e7e46cc70a5dd086daff2a","gas":"0x5208","gasPrice":"0x3b9aca00","hash":"0x82f636c7bd91f9895f896b044e33528a2d116c65eea4c8e18c30c4577ae20ce2","input":"0x","nonce":"0x0","r":"0x85242d1876ce1d6a655fd485346628f3df18a051be0f8efa4bfa40b9e85a3dfe","s":"0x4f0880f42d42b1de17f97c33749d60a46bd1f493c6547f08ac2bed0c6d111861","to":"0xc803334c79650708daf3a3462ac4b48296b1352a","transactionIndex":null,"type":"0x0","v":"0x25","value":"0x0","requestRecord":null}"#;
+        let raw: Value = serde_json::from_str(plain).unwrap();
+        let err = ConfidentialCallResponse::from_json(raw.clone()).unwrap_err();
+        assert_eq!(err, ResponseError::NotACcrReceipt { missing: CCR_FIELDS.to_vec(), raw: raw.clone() });
+        assert_eq!(err.raw()["hash"], raw["hash"]);
+
+        let tx: Transaction = serde_json::from_value(raw).unwrap();
+        assert!(matches!(ConfidentialCallResponse::try_from(tx), Err(ResponseError::NotACcrReceipt { .. })));
+
+        let mut malformed: Value = serde_json::from_str(plain).unwrap();
+        malformed["confidentialComputeResult"] = Value::from("0x");
+        malformed["requestRecord"] = Value::from(42);
+        assert!(matches!(ConfidentialCallResponse::from_json(malformed), Err(ResponseError::Malformed { .. })));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,343 @@
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use alloy::{
+    primitives::{keccak256, Address, Bytes, Signature, B256, U256},
+    consensus::{Signed, TxEip1559, TxEnvelope, TxLegacy},
+    eips::eip2718::{Decodable2718, Encodable2718},
+};
+use super::{
+    crecord::{signature_to_vrs, CRecordRLP},
+    CcrError,
+    ConfidentialCallResponse,
+    ConfidentialComputeRecord,
+    ConfidentialComputeRequest,
+};
+
+
+/// Transaction a kettle includes in a block after executing a CCR: the
+/// signed request record, the confidential compute result and the
+/// kettle's signature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfidentialComputeResult {
+    pub request_record: ConfidentialComputeRecord,
+    pub confidential_compute_result: Bytes,
+    pub chain_id: u64,
+    pub signature: Signature,
+}
+
+impl ConfidentialComputeResult {
+    pub const TYPE: u8 = 0x50;
+
+    pub fn kettle_address(&self) -> Option<Address> {
+        self.request_record.kettle_address
+    }
+
+    /// Signer of the embedded request, recovered from the record signature.
+    pub fn request_sender(&self) -> Result<Address, CcrError> {
+        let request = ConfidentialComputeRequest {
+            confidential_compute_record: self.request_record.clone(),
+            confidential_inputs: Bytes::new(),
+        };
+        request.recover_signer()
+    }
+
+    /// Hash of the 2718 encoding, as the transaction is referenced on chain.
+    pub fn tx_hash(&self) -> B256 {
+        let mut out = Vec::new();
+        self.encode_2718(&mut out);
+        keccak256(out)
+    }
+
+}
+
+/// Name suave-geth uses for the 0x50 transaction.
+pub type SuaveTransaction = ConfidentialComputeResult;
+
+impl TryFrom<&ConfidentialCallResponse> for ConfidentialComputeResult {
+    type Error = CcrError;
+
+    fn try_from(response: &ConfidentialCallResponse) -> Result<Self, CcrError> {
+        let tx = &response.transaction;
+        let sig = tx.signature.as_ref().ok_or(CcrError::MissingSignature)?;
+        Ok(Self {
+            request_record: response.request_record.clone(),
+            confidential_compute_result: response.confidential_compute_result.clone(),
+            chain_id: tx.chain_id.ok_or(CcrError::MissingField("chain id"))?,
+            signature: Signature::from_rs_and_parity(sig.r, sig.s, sig.v.to::<u64>())
+                .map_err(|_| CcrError::InvalidSignature)?,
+        })
+    }
+}
+
+#[derive(Debug, RlpEncodable, RlpDecodable, PartialEq)]
+struct CResultRLP<'a> {
+    request: CRecordRLP<'a>,
+    confidential_compute_result: Bytes,
+    chain_id: u64,
+    v: u8,
+    r: U256,
+    s: U256,
+}
+
+impl<'a> TryFrom<&'a ConfidentialComputeResult> for CResultRLP<'a> {
+    type Error = CcrError;
+
+    fn try_from(result: &'a ConfidentialComputeResult) -> Result<Self, CcrError> {
+        let (v, r, s) = signature_to_vrs(result.signature);
+        Ok(Self {
+            request: (&result.request_record).try_into()?,
+            confidential_compute_result: result.confidential_compute_result.clone(),
+            chain_id: result.chain_id,
+            v, r, s,
+        })
+    }
+}
+
+impl TryFrom<CResultRLP<'_>> for ConfidentialComputeResult {
+    type Error = alloy_rlp::Error;
+
+    fn try_from(rlp: CResultRLP<'_>) -> alloy_rlp::Result<Self> {
+        let signature = Signature::from_rs_and_parity(rlp.r, rlp.s, rlp.v as u64)
+            .map_err(|_| alloy_rlp::Error::Custom("Invalid result signature"))?;
+        Ok(Self {
+            request_record: rlp.request.try_into()?,
+            confidential_compute_result: rlp.confidential_compute_result,
+            chain_id: rlp.chain_id,
+            signature,
+        })
+    }
+}
+
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+impl Decodable2718 for ConfidentialComputeResult {
+    fn typed_decode(ty: u8, buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        match ty {
+            ConfidentialComputeResult::TYPE => CResultRLP::decode(buf)?.try_into(),
+            _ => Err(alloy_rlp::Error::Custom("Only ConfidentialComputeResult"))
+        }
+    }
+
+    fn fallback_decode(_buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        Err(alloy_rlp::Error::Custom("Only ConfidentialComputeResult"))
+    }
+}
+
+impl Encodable2718 for ConfidentialComputeResult {
+    fn type_flag(&self) -> Option<u8> {
+        Some(ConfidentialComputeResult::TYPE)
+    }
+
+    fn encode_2718_len(&self) -> usize {
+        1 + CResultRLP::try_from(self).unwrap().length()
+    }
+
+    fn encode_2718(&self, out: &mut dyn alloy_rlp::BufMut) {
+        out.put_u8(ConfidentialComputeResult::TYPE);
+        CResultRLP::try_from(self).unwrap().encode(out);
+    }
+}
+
+/// Any transaction found in a SUAVE block: CCRs (0x43), their results
+/// (0x50) and the plain Ethereum transactions (legacy and EIP-1559) used
+/// for funding and callbacks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuaveTxEnvelope {
+    ConfidentialComputeRequest(ConfidentialComputeRequest),
+    ConfidentialComputeResult(ConfidentialComputeResult),
+    Legacy(Signed<TxLegacy>),
+    Eip1559(Signed<TxEip1559>),
+}
+
+impl SuaveTxEnvelope {
+
+    /// EIP-2718 type, 0 for legacy transactions.
+    pub fn tx_type(&self) -> u8 {
+        match self {
+            SuaveTxEnvelope::ConfidentialComputeRequest(_) => ConfidentialComputeRequest::TYPE,
+            SuaveTxEnvelope::ConfidentialComputeResult(_) => ConfidentialComputeResult::TYPE,
+            SuaveTxEnvelope::Legacy(_) => 0,
+            SuaveTxEnvelope::Eip1559(_) => 2,
+        }
+    }
+
+    /// Whether it is a CCR or a CCR result rather than a plain transaction.
+    pub fn is_confidential(&self) -> bool {
+        matches!(self, SuaveTxEnvelope::ConfidentialComputeRequest(_) | SuaveTxEnvelope::ConfidentialComputeResult(_))
+    }
+
+    /// 2718 encoding; the plain transactions go through alloy's envelope.
+    fn encoded(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            SuaveTxEnvelope::ConfidentialComputeRequest(ccr) => ccr.encode_2718(&mut out),
+            SuaveTxEnvelope::ConfidentialComputeResult(result) => result.encode_2718(&mut out),
+            SuaveTxEnvelope::Legacy(tx) => TxEnvelope::Legacy(tx.clone()).encode_2718(&mut out),
+            SuaveTxEnvelope::Eip1559(tx) => TxEnvelope::Eip1559(tx.clone()).encode_2718(&mut out),
+        }
+        out
+    }
+
+}
+
+impl From<ConfidentialComputeRequest> for SuaveTxEnvelope {
+    fn from(ccr: ConfidentialComputeRequest) -> Self {
+        SuaveTxEnvelope::ConfidentialComputeRequest(ccr)
+    }
+}
+
+impl From<ConfidentialComputeResult> for SuaveTxEnvelope {
+    fn from(result: ConfidentialComputeResult) -> Self {
+        SuaveTxEnvelope::ConfidentialComputeResult(result)
+    }
+}
+
+impl From<Signed<TxLegacy>> for SuaveTxEnvelope {
+    fn from(tx: Signed<TxLegacy>) -> Self {
+        SuaveTxEnvelope::Legacy(tx)
+    }
+}
+
+impl From<Signed<TxEip1559>> for SuaveTxEnvelope {
+    fn from(tx: Signed<TxEip1559>) -> Self {
+        SuaveTxEnvelope::Eip1559(tx)
+    }
+}
+
+#[deny(clippy::unwrap_used, clippy::expect_used)]
+impl Decodable2718 for SuaveTxEnvelope {
+    fn typed_decode(ty: u8, buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        match ty {
+            ConfidentialComputeRequest::TYPE => {
+                ConfidentialComputeRequest::typed_decode(ty, buf).map(Into::into)
+            }
+            ConfidentialComputeResult::TYPE => {
+                ConfidentialComputeResult::typed_decode(ty, buf).map(Into::into)
+            }
+            2 => match TxEnvelope::typed_decode(ty, buf)? {
+                TxEnvelope::Eip1559(tx) => Ok(tx.into()),
+                _ => Err(alloy_rlp::Error::Custom("Unexpected SUAVE transaction type")),
+            },
+            _ => Err(alloy_rlp::Error::Custom("Unexpected SUAVE transaction type")),
+        }
+    }
+
+    fn fallback_decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        match TxEnvelope::fallback_decode(buf)? {
+            TxEnvelope::Legacy(tx) => Ok(tx.into()),
+            _ => Err(alloy_rlp::Error::Custom("Expected a legacy transaction")),
+        }
+    }
+}
+
+impl Encodable2718 for SuaveTxEnvelope {
+    fn type_flag(&self) -> Option<u8> {
+        match self {
+            SuaveTxEnvelope::Legacy(_) => None,
+            _ => Some(self.tx_type()),
+        }
+    }
+
+    fn encode_2718_len(&self) -> usize {
+        self.encoded().len()
+    }
+
+    fn encode_2718(&self, out: &mut dyn alloy_rlp::BufMut) {
+        out.put_slice(&self.encoded());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::{
+        consensus::SignableTransaction,
+        network::TxSigner,
+        primitives::TxKind,
+        signers::wallet::LocalWallet,
+    };
+    use super::*;
+    use super::super::fixtures;
+
+    fn result_fixture() -> ConfidentialComputeResult {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let signature = Signature::from_rs_and_parity(
+            U256::from_str("0x85242d1876ce1d6a655fd485346628f3df18a051be0f8efa4bfa40b9e85a3dfe").unwrap(),
+            U256::from_str("0x4f0880f42d42b1de17f97c33749d60a46bd1f493c6547f08ac2bed0c6d111861").unwrap(),
+            1,
+        ).unwrap();
+        ConfidentialComputeResult {
+            request_record: fixture.request.confidential_compute_record,
+            confidential_compute_result: Bytes::from_str("0x0000000000000000000000000000000000000000000000000000000001ccb310").unwrap(),
+            chain_id: 0x1008c45,
+            signature,
+        }
+    }
+
+    fn encode(envelope: &SuaveTxEnvelope) -> Vec<u8> {
+        let mut out = Vec::new();
+        envelope.encode_2718(&mut out);
+        out
+    }
+
+    #[test]
+    fn test_result_roundtrip() {
+        let envelope = SuaveTxEnvelope::from(result_fixture());
+        let encoded = encode(&envelope);
+        assert_eq!(encoded[0], ConfidentialComputeResult::TYPE);
+        assert_eq!(encoded.len(), envelope.encode_2718_len());
+
+        let decoded = SuaveTxEnvelope::decode_2718(&mut encoded.as_slice()).unwrap();
+        assert_eq!(decoded, envelope);
+    }
+
+    #[test]
+    fn test_suave_transaction() {
+        let tx: SuaveTransaction = result_fixture();
+        let mut encoded = Vec::new();
+        tx.encode_2718(&mut encoded);
+        assert_eq!(encoded, encode(&SuaveTxEnvelope::from(tx.clone())));
+        assert_eq!(tx.tx_hash(), keccak256(&encoded));
+
+        let decoded = SuaveTransaction::decode_2718(&mut encoded.as_slice()).unwrap();
+        assert_eq!(decoded.request_record, tx.request_record);
+        assert_eq!(decoded.confidential_compute_result, tx.confidential_compute_result);
+        assert_eq!(decoded.signature, tx.signature);
+
+        assert_eq!(tx.request_sender().unwrap(), fixtures::load_fixture("empty_inputs").unwrap().signer);
+
+        let fixture = fixtures::load_fixture("value_transfer").unwrap();
+        assert!(SuaveTransaction::decode_2718(&mut fixture.raw.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_decode_request_and_unknown() {
+        let fixture = fixtures::load_fixture("value_transfer").unwrap();
+        let decoded = SuaveTxEnvelope::decode_2718(&mut fixture.raw.as_ref()).unwrap();
+        assert_eq!(decoded, SuaveTxEnvelope::ConfidentialComputeRequest(fixture.request));
+        assert_eq!(encode(&decoded), fixture.raw.to_vec());
+
+        let mut unknown = fixture.raw.to_vec();
+        unknown[0] = 0x44;
+        assert!(SuaveTxEnvelope::decode_2718(&mut unknown.as_slice()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_plain_transactions() {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let to = TxKind::Call(Address::repeat_byte(0xc8));
+        let mut legacy = TxLegacy { chain_id: Some(0x1008c45), gas_price: 0x3b9aca00, gas_limit: 21_000, to, value: U256::from(1), ..Default::default() };
+        let mut eip1559 = TxEip1559 { chain_id: 0x1008c45, max_fee_per_gas: 0x3b9aca00, gas_limit: 21_000, to, ..Default::default() };
+        let signature = wallet.sign_transaction(&mut legacy).await.unwrap();
+        let legacy = SuaveTxEnvelope::from(legacy.into_signed(signature));
+        let signature = wallet.sign_transaction(&mut eip1559).await.unwrap();
+        let eip1559 = SuaveTxEnvelope::from(eip1559.into_signed(signature));
+
+        for (envelope, type_flag) in [(legacy, None), (eip1559, Some(2))] {
+            let encoded = encode(&envelope);
+            assert_eq!((envelope.type_flag(), envelope.is_confidential()), (type_flag, false));
+            assert_eq!(encoded.len(), envelope.encode_2718_len());
+            assert_eq!(SuaveTxEnvelope::decode_2718(&mut encoded.as_slice()).unwrap(), envelope);
+        }
+    }
+
+}
@@ -0,0 +1,135 @@
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use rand::RngCore;
+use alloy::primitives::{hex, FixedBytes};
+
+
+/// 16 byte id of a confidential store data record (`Suave.DataId`, formerly
+/// `BidId`), eg. the `MatchId` of bundles. Serialized as an array of bytes
+/// like suave-geth does; hex strings are accepted as well.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DataId(pub [u8; 16]);
+
+/// Name of `DataId` in older SUAPPs and suave-geth versions.
+pub type BidId = DataId;
+
+impl DataId {
+    pub const ZERO: Self = Self([0; 16]);
+
+    pub const fn new(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    /// Random id, eg. for records created off-chain.
+    pub fn random() -> Self {
+        let mut bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+}
+
+impl std::fmt::Display for DataId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode_prefixed(self.0))
+    }
+}
+
+impl std::fmt::Debug for DataId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DataId({self})")
+    }
+}
+
+impl FromStr for DataId {
+    type Err = hex::FromHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode_to_array(s).map(Self)
+    }
+}
+
+impl AsRef<[u8]> for DataId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 16]> for DataId {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<DataId> for [u8; 16] {
+    fn from(id: DataId) -> Self {
+        id.0
+    }
+}
+
+/// `bytes16` in `sol!` calls.
+impl From<FixedBytes<16>> for DataId {
+    fn from(bytes: FixedBytes<16>) -> Self {
+        Self(bytes.0)
+    }
+}
+
+impl From<DataId> for FixedBytes<16> {
+    fn from(id: DataId) -> Self {
+        FixedBytes(id.0)
+    }
+}
+
+impl Serialize for DataId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DataId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Bytes([u8; 16]),
+            Hex(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Bytes(bytes) => Ok(Self(bytes)),
+            Repr::Hex(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_id_formats() {
+        let id = DataId::new([0xab; 16]);
+        assert_eq!(id.to_string(), "0xabababababababababababababababab");
+        assert_eq!(id.to_string().parse::<DataId>().unwrap(), id);
+        assert_eq!("abababababababababababababababab".parse::<DataId>().unwrap(), id);
+        assert!("0xabab".parse::<DataId>().is_err());
+        assert_eq!(DataId::from(FixedBytes::<16>::from(id)), id);
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!("[{}]", ["171"; 16].join(",")));
+        assert_eq!(serde_json::from_str::<DataId>(&json).unwrap(), id);
+        assert_eq!(serde_json::from_str::<DataId>("\"0xabababababababababababababababab\"").unwrap(), id);
+
+        assert_ne!(DataId::random(), DataId::random());
+        assert!(DataId::default().is_zero());
+    }
+
+}
@@ -0,0 +1,625 @@
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
+
+use alloy_rlp::Decodable;
+use alloy::{
+    eips::{eip2718::Decodable2718, eip2930::{AccessList, AccessListItem}},
+    primitives::{Address, Bytes, FixedBytes, Signature, TxKind, B256, U256},
+};
+use suave_alloy_core::Fees;
+use super::{
+    consts::{ProtocolConfig, SigningDomain},
+    crecord::{signature_to_vrs, CRecordRLP, RecordLayout},
+    scheme::{Secp256k1, SignatureScheme},
+    crequest::CRequestRLP,
+    CcrError,
+    ConfidentialComputeRecord,
+    ConfidentialComputeRequest,
+};
+
+
+/// Half of the secp256k1 curve order; canonical signatures have `s` at most this.
+pub const SECP256K1N_HALF: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0, 0x5d576e7357a4501d, 0xffffffffffffffff, 0x7fffffffffffffff,
+]);
+
+/// secp256k1 curve order; `r` and `s` must be below it.
+pub(crate) const SECP256K1N: U256 = U256::from_limbs([
+    0xbfd25e8cd0364141, 0xbaaedce6af48a03b, 0xfffffffffffffffe, 0xffffffffffffffff,
+]);
+
+/// How strictly untrusted CCR bytes/JSON are checked.
+///
+/// - `Strict` rejects trailing bytes, non-canonical RLP, invalid signatures
+///   (scalars out of range, high-s or not recoverable) and confidential
+///   inputs not matching the committed hash.
+/// - `Lenient` accepts historical quirks (non-canonical integers, trailing
+///   bytes) as long as the values are readable.
+///
+/// Both keep the record fields of newer protocol versions (see
+/// `ConfidentialComputeRecord::extra_fields`).
+///
+/// The default, used by `Decodable2718` and every decoder not taking a
+/// mode, is `Lenient`, or `Strict` with the `strict-decode` feature.
+/// Strict checking is otherwise opt-in through the `*_with_mode` decoders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeMode {
+    Strict,
+    Lenient,
+}
+
+impl Default for DecodeMode {
+    fn default() -> Self {
+        if cfg!(feature = "strict-decode") {
+            DecodeMode::Strict
+        } else {
+            DecodeMode::Lenient
+        }
+    }
+}
+
+impl ConfidentialComputeRecord {
+
+    /// Decode an RLP encoded signed record.
+    pub fn decode_signed_with_mode(mut buf: &[u8], mode: DecodeMode) -> alloy_rlp::Result<Self> {
+        match mode {
+            DecodeMode::Strict => {
+                let record: ConfidentialComputeRecord = CRecordRLP::decode(&mut buf)?.try_into()?;
+                if !buf.is_empty() {
+                    return Err(alloy_rlp::Error::Custom("Trailing bytes after record"));
+                }
+                record.check_strict().map_err(alloy_rlp::Error::Custom)?;
+                Ok(record)
+            }
+            DecodeMode::Lenient => lenient::decode_record(&mut buf),
+        }
+    }
+
+    pub fn from_json_with_mode(json: &str, mode: DecodeMode) -> Result<Self, CcrError> {
+        let record: Self = serde_json::from_str(json)?;
+        if mode == DecodeMode::Strict {
+            record.check_strict().map_err(CcrError::NonCanonical)?;
+        }
+        Ok(record)
+    }
+
+    pub(crate) fn check_strict(&self) -> Result<(), &'static str> {
+        self.check_strict_with(&Secp256k1)
+    }
+
+    /// Signature checks of `DecodeMode::Strict` under `scheme`: a valid
+    /// signature of the scheme, canonical (`SignatureScheme::check_canonical`)
+    /// and verifying against the signing hash.
+    pub(crate) fn check_strict_with<S: SignatureScheme>(&self, scheme: &S) -> Result<(), &'static str> {
+        let Some(sig) = self.signature else { return Ok(()) };
+        let (v, r, s) = signature_to_vrs(sig);
+        let signature = scheme.from_vrs(v, r, s).map_err(|_| "Invalid signature")?;
+        scheme.check_canonical(&signature)?;
+        let ccr = ConfidentialComputeRequest {
+            confidential_compute_record: self.clone(),
+            confidential_inputs: Bytes::new(),
+        };
+        let signing_hash = ccr.signing_hash().map_err(|_| "Unrecoverable signature")?;
+        scheme.signer(&signature, &signing_hash).map_err(|_| "Unrecoverable signature")?;
+        Ok(())
+    }
+
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Decode a 2718 encoded (0x43 prefixed) request.
+    pub fn decode_2718_with_mode(raw: &[u8], mode: DecodeMode) -> alloy_rlp::Result<Self> {
+        Self::decode_2718_with_scheme(raw, mode, &Secp256k1)
+    }
+
+    /// Decode a 2718 encoded request signed with `scheme`; `Strict` checks
+    /// its signature under the scheme instead of secp256k1.
+    pub fn decode_2718_with_scheme<S: SignatureScheme>(
+        raw: &[u8],
+        mode: DecodeMode,
+        scheme: &S,
+    ) -> alloy_rlp::Result<Self> {
+        let (ty, mut buf) = raw.split_first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        if *ty != ConfidentialComputeRequest::TYPE {
+            return Err(alloy_rlp::Error::Custom("Only ConfidentialComputeRequest"));
+        }
+        let ccr = Self::decode_rlp_with_scheme(&mut buf, mode, scheme)?;
+        if mode == DecodeMode::Strict && !buf.is_empty() {
+            return Err(alloy_rlp::Error::Custom("Trailing bytes after request"));
+        }
+        Ok(ccr)
+    }
+
+    /// Decode a 2718 encoded request of a chain with `config`. The signing
+    /// domain isn't part of the encoding, so requests of domain-separated
+    /// forks need it to recover their signer.
+    pub fn decode_2718_with(raw: &[u8], config: &ProtocolConfig) -> alloy_rlp::Result<Self> {
+        Self::decode_2718_with_mode(raw, DecodeMode::default()).map(|ccr| ccr.with_protocol_config(config))
+    }
+
+    /// `Decodable2718::typed_decode` for a chain with `config`.
+    pub fn typed_decode_with(ty: u8, buf: &mut &[u8], config: &ProtocolConfig) -> alloy_rlp::Result<Self> {
+        <Self as Decodable2718>::typed_decode(ty, buf).map(|ccr| ccr.with_protocol_config(config))
+    }
+
+    /// Decode an untrusted 2718 encoded request, rejecting it before
+    /// anything is copied if the record calldata or the confidential inputs
+    /// are longer than the given limits (eg. from `ProtocolLimits`).
+    pub fn decode_2718_bounded(
+        raw: &[u8],
+        max_inputs_len: usize,
+        max_calldata_len: usize,
+    ) -> alloy_rlp::Result<Self> {
+        let (ty, buf) = raw.split_first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        if *ty != ConfidentialComputeRequest::TYPE {
+            return Err(alloy_rlp::Error::Custom("Only ConfidentialComputeRequest"));
+        }
+        lenient::check_bounds(buf, max_inputs_len, max_calldata_len)?;
+        Self::decode_2718_with_mode(raw, DecodeMode::default())
+    }
+
+    pub fn from_json_with_mode(json: &str, mode: DecodeMode) -> Result<Self, CcrError> {
+        let ccr: Self = serde_json::from_str(json)?;
+        if mode == DecodeMode::Strict {
+            ccr.check_strict().map_err(CcrError::NonCanonical)?;
+        }
+        Ok(ccr)
+    }
+
+    pub(crate) fn decode_rlp_with_mode(buf: &mut &[u8], mode: DecodeMode) -> alloy_rlp::Result<Self> {
+        Self::decode_rlp_with_scheme(buf, mode, &Secp256k1)
+    }
+
+    fn decode_rlp_with_scheme<S: SignatureScheme>(
+        buf: &mut &[u8],
+        mode: DecodeMode,
+        scheme: &S,
+    ) -> alloy_rlp::Result<Self> {
+        match mode {
+            DecodeMode::Strict => {
+                let ccr: ConfidentialComputeRequest = CRequestRLP::decode(buf)?.try_into()?;
+                ccr.check_strict_with(scheme).map_err(alloy_rlp::Error::Custom)?;
+                Ok(ccr)
+            }
+            DecodeMode::Lenient => lenient::decode_request(buf),
+        }
+    }
+
+    fn check_strict(&self) -> Result<(), &'static str> {
+        self.check_strict_with(&Secp256k1)
+    }
+
+    fn check_strict_with<S: SignatureScheme>(&self, scheme: &S) -> Result<(), &'static str> {
+        self.confidential_compute_record.check_strict_with(scheme)?;
+        if self.references_inputs() {
+            // Inputs uploaded separately, the kettle checks them
+            return Ok(());
+        }
+        self.validate_inputs_hash().map_err(|_| "Confidential inputs hash mismatch")
+    }
+
+}
+
+/// Minimal RLP reader that accepts non-canonical encodings.
+mod lenient {
+    use alloy_rlp::{Error, Result};
+    use super::*;
+
+    pub(super) fn decode_request(buf: &mut &[u8]) -> Result<ConfidentialComputeRequest> {
+        let mut fields = read_list(buf)?;
+        let record = decode_record(&mut fields)?;
+        let confidential_inputs = Bytes::copy_from_slice(read_string(&mut fields)?);
+        Ok(ConfidentialComputeRequest { confidential_compute_record: record, confidential_inputs })
+    }
+
+    pub(super) fn decode_record(buf: &mut &[u8]) -> Result<ConfidentialComputeRecord> {
+        let mut fields = read_list(buf)?;
+        let layout = layout(fields)?;
+        let nonce = read_uint(&mut fields, 8)?.to::<u64>();
+        let fees = if layout.dynamic {
+            Fees::Dynamic {
+                max_priority_fee_per_gas: read_uint(&mut fields, 16)?.to::<u128>(),
+                max_fee_per_gas: read_uint(&mut fields, 16)?.to::<u128>(),
+            }
+        } else {
+            Fees::Legacy { gas_price: read_uint(&mut fields, 16)?.to::<u128>() }
+        };
+        let gas = read_uint(&mut fields, 16)?.to::<u128>();
+        let to = match read_string(&mut fields)? {
+            [] => TxKind::Create,
+            to => TxKind::Call(Address::try_from(to).map_err(|_| Error::UnexpectedLength)?),
+        };
+        let value = read_uint(&mut fields, 32)?;
+        let input = Bytes::copy_from_slice(read_string(&mut fields)?);
+        let access_list = match layout.access_list {
+            true => Some(read_access_list(&mut fields)?),
+            false => None,
+        };
+        let kettle_address = Address::from(read_fixed::<20>(&mut fields)?);
+        let confidential_inputs_hash = FixedBytes::from(read_fixed::<32>(&mut fields)?);
+        let chain_id = read_uint(&mut fields, 8)?.to::<u64>();
+        let v = read_uint(&mut fields, 8)?.to::<u64>();
+        let r = read_uint(&mut fields, 32)?;
+        let s = read_uint(&mut fields, 32)?;
+        let signature = Signature::from_rs_and_parity(r, s, v)
+            .map_err(|_| Error::Custom("Invalid signature"))?;
+        let mut extra_fields = Vec::new();
+        while !fields.is_empty() {
+            let item = fields;
+            read_item(&mut fields)?;
+            extra_fields.push(Bytes::copy_from_slice(&item[..item.len() - fields.len()]));
+        }
+        let mut record = ConfidentialComputeRecord {
+            nonce: Some(nonce),
+            gas: Some(gas),
+            to,
+            value,
+            input,
+            access_list,
+            kettle_address: Some(kettle_address),
+            chain_id: Some(chain_id),
+            confidential_inputs_hash: Some(confidential_inputs_hash),
+            signature: Some(signature),
+            extra_fields,
+            from: None,
+            signing_domain: SigningDomain::None,
+            ..Default::default()
+        };
+        record.set_fees(fees);
+        Ok(record)
+    }
+
+    fn layout(mut fields: &[u8]) -> Result<RecordLayout> {
+        let mut shapes = Vec::new();
+        while !fields.is_empty() {
+            let (list, payload) = read_item(&mut fields)?;
+            shapes.push((list, payload.len()));
+        }
+        RecordLayout::detect(&shapes).ok_or(Error::Custom("Unknown record layout"))
+    }
+
+    /// Check the declared lengths of the record calldata and confidential
+    /// inputs without copying them.
+    pub(super) fn check_bounds(mut buf: &[u8], max_inputs_len: usize, max_calldata_len: usize) -> Result<()> {
+        let mut fields = read_list(&mut buf)?;
+        let mut record = read_list(&mut fields)?;
+        // nonce, gas price (or both fees), gas, to, value
+        let skipped = if layout(record)?.dynamic { 6 } else { 5 };
+        for _ in 0..skipped {
+            read_item(&mut record)?;
+        }
+        if read_string(&mut record)?.len() > max_calldata_len {
+            return Err(Error::Custom("Calldata exceeds limit"));
+        }
+        if read_string(&mut fields)?.len() > max_inputs_len {
+            return Err(Error::Custom("Confidential inputs exceed limit"));
+        }
+        Ok(())
+    }
+
+    fn read_item<'a>(buf: &mut &'a [u8]) -> Result<(bool, &'a [u8])> {
+        let prefix = *buf.first().ok_or(Error::InputTooShort)?;
+        let (is_list, header_len, payload_len) = match prefix {
+            0x00..=0x7f => return Ok((false, take(buf, 1)?)),
+            0x80..=0xb7 => (false, 1, (prefix - 0x80) as usize),
+            0xb8..=0xbf => {
+                let len_of_len = (prefix - 0xb7) as usize;
+                (false, 1 + len_of_len, be_usize(buf.get(1..1 + len_of_len).ok_or(Error::InputTooShort)?)?)
+            }
+            0xc0..=0xf7 => (true, 1, (prefix - 0xc0) as usize),
+            0xf8..=0xff => {
+                let len_of_len = (prefix - 0xf7) as usize;
+                (true, 1 + len_of_len, be_usize(buf.get(1..1 + len_of_len).ok_or(Error::InputTooShort)?)?)
+            }
+        };
+        take(buf, header_len)?;
+        Ok((is_list, take(buf, payload_len)?))
+    }
+
+    fn read_list<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8]> {
+        match read_item(buf)? {
+            (true, payload) => Ok(payload),
+            (false, _) => Err(Error::UnexpectedString),
+        }
+    }
+
+    fn read_access_list(buf: &mut &[u8]) -> Result<AccessList> {
+        let mut items = read_list(buf)?;
+        let mut access_list = Vec::new();
+        while !items.is_empty() {
+            let mut item = read_list(&mut items)?;
+            let address = Address::from(read_fixed::<20>(&mut item)?);
+            let mut keys = read_list(&mut item)?;
+            let mut storage_keys = Vec::new();
+            while !keys.is_empty() {
+                storage_keys.push(B256::from(read_fixed::<32>(&mut keys)?));
+            }
+            access_list.push(AccessListItem { address, storage_keys });
+        }
+        Ok(AccessList(access_list))
+    }
+
+    fn read_string<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8]> {
+        match read_item(buf)? {
+            (false, payload) => Ok(payload),
+            (true, _) => Err(Error::UnexpectedList),
+        }
+    }
+
+    fn read_fixed<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N]> {
+        read_string(buf)?.try_into().map_err(|_| Error::UnexpectedLength)
+    }
+
+    /// Read an unsigned integer of at most `max_bytes` significant bytes,
+    /// ignoring leading zeros.
+    fn read_uint(buf: &mut &[u8], max_bytes: usize) -> Result<U256> {
+        let bytes = read_string(buf)?;
+        let first_non_zero = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        let significant = &bytes[first_non_zero..];
+        if significant.len() > max_bytes {
+            return Err(Error::Overflow);
+        }
+        U256::try_from_be_slice(significant).ok_or(Error::Overflow)
+    }
+
+    fn be_usize(bytes: &[u8]) -> Result<usize> {
+        if bytes.len() > std::mem::size_of::<usize>() {
+            return Err(Error::Overflow);
+        }
+        Ok(bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize))
+    }
+
+    fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+        if buf.len() < len {
+            return Err(Error::InputTooShort);
+        }
+        let (head, tail) = buf.split_at(len);
+        *buf = tail;
+        Ok(head)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::eips::eip2718::Encodable2718;
+    use super::*;
+    use super::super::fixtures;
+
+    /// `empty_inputs` fixture with the gas field encoded with a leading zero byte.
+    const NON_CANONICAL_GAS: &str = "0x43f8f0f8ed80843b9aca0084000f424094c803334c79650708daf3a3462ac4b48296b1352a80b84c507235530000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000745544855534454009403493869959c866713c33669ca118e774a30a0e5a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a4708401008c4501a0f415c9e428312c448967fad9d72bff1c9ed6d3aa3d2bc66fed4aa49abc0b34b6a02143f98c3bc8cf389f33550363f545cf4ff046c1f41c9744cfa939513562a22480";
+
+    fn high_s(ccr: &ConfidentialComputeRequest) -> ConfidentialComputeRequest {
+        let n = U256::from_str("0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap();
+        let sig = ccr.confidential_compute_record.signature.unwrap();
+        let flipped = Signature::from_rs_and_parity(sig.r(), n - sig.s(), !sig.v().y_parity()).unwrap();
+        let mut ccr = ccr.clone();
+        ccr.confidential_compute_record.signature = Some(flipped);
+        ccr
+    }
+
+    #[test]
+    fn test_both_modes_accept_fixtures() {
+        for fixture in fixtures::load_fixtures() {
+            for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+                let decoded = ConfidentialComputeRequest::decode_2718_with_mode(&fixture.raw, mode).unwrap();
+                assert_eq!(decoded, fixture.request, "{} {mode:?}", fixture.name);
+                let json = serde_json::to_string(&fixture.request).unwrap();
+                let parsed = ConfidentialComputeRequest::from_json_with_mode(&json, mode).unwrap();
+                assert_eq!(parsed, fixture.request, "{} {mode:?}", fixture.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let mut raw = fixture.raw.to_vec();
+        raw.extend_from_slice(&[0xc0]);
+        assert!(ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Strict).is_err());
+        let decoded = ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Lenient).unwrap();
+        assert_eq!(decoded, fixture.request);
+    }
+
+    #[test]
+    fn test_default_mode() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let raw = Bytes::from_str(NON_CANONICAL_GAS).unwrap();
+        let decoded = ConfidentialComputeRequest::decode_2718(&mut raw.as_ref());
+        let decoded_with = ConfidentialComputeRequest::decode_2718_with(&raw, &ProtocolConfig::default());
+        if cfg!(feature = "strict-decode") {
+            assert_eq!(DecodeMode::default(), DecodeMode::Strict);
+            assert!(decoded.is_err() && decoded_with.is_err());
+        } else {
+            assert_eq!(DecodeMode::default(), DecodeMode::Lenient);
+            assert_eq!(decoded.unwrap(), fixture.request);
+            assert_eq!(decoded_with.unwrap(), fixture.request);
+        }
+    }
+
+    #[test]
+    fn test_hash_referenced_inputs() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let mut ccr = fixture.request.clone();
+        ccr.confidential_compute_record.confidential_inputs_hash = Some(alloy::primitives::keccak256(b"uploaded"));
+        assert!(ccr.references_inputs());
+        let decoded = ConfidentialComputeRequest::decode_2718_with_mode(&ccr.encoded_2718(), DecodeMode::Strict).unwrap();
+        assert_eq!(decoded, ccr);
+
+        // Inputs carried in the request must still match the hash
+        ccr.confidential_inputs = Bytes::from_static(b"tampered");
+        assert!(!ccr.references_inputs());
+        assert!(ConfidentialComputeRequest::decode_2718_with_mode(&ccr.encoded_2718(), DecodeMode::Strict).is_err());
+    }
+
+    #[test]
+    fn test_non_canonical_integer() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let raw = Bytes::from_str(NON_CANONICAL_GAS).unwrap();
+        assert!(ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Strict).is_err());
+        let decoded = ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Lenient).unwrap();
+        assert_eq!(decoded, fixture.request);
+    }
+
+    #[test]
+    fn test_high_s_signature() {
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let ccr = high_s(&fixture.request);
+        let mut raw = Vec::new();
+        ccr.encode_2718(&mut raw);
+
+        assert!(ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Strict).is_err());
+        let decoded = ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Lenient).unwrap();
+        assert_eq!(decoded, ccr);
+
+        let json = serde_json::to_string(&ccr).unwrap();
+        assert_eq!(ConfidentialComputeRequest::from_json_with_mode(&json, DecodeMode::Strict), Err(CcrError::NonCanonical("High-s signature")));
+        assert!(ConfidentialComputeRequest::from_json_with_mode(&json, DecodeMode::Lenient).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_signature_scalars() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let sig = fixture.request.confidential_compute_record.signature.unwrap();
+        for (r, s) in [(U256::ZERO, sig.s()), (SECP256K1N, sig.s()), (sig.r(), U256::ZERO)] {
+            let mut ccr = fixture.request.clone();
+            ccr.confidential_compute_record.signature = Some(Signature::from_rs_and_parity(r, s, sig.v().y_parity()).unwrap());
+            let mut raw = Vec::new();
+            ccr.encode_2718(&mut raw);
+
+            assert_eq!(
+                ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Strict),
+                Err(alloy_rlp::Error::Custom("Signature scalar out of range"))
+            );
+            assert_eq!(ConfidentialComputeRequest::decode_2718_with_mode(&raw, DecodeMode::Lenient).unwrap(), ccr);
+        }
+    }
+
+    /// Truncated, bit-flipped and garbage inputs must error, never panic.
+    #[test]
+    fn test_adversarial_inputs_dont_panic() {
+        let mut inputs: Vec<Vec<u8>> = vec![
+            vec![],
+            vec![ConfidentialComputeRequest::TYPE],
+            vec![ConfidentialComputeRequest::TYPE, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            vec![ConfidentialComputeRequest::TYPE, 0xbf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ];
+        for fixture in fixtures::load_fixtures() {
+            let raw = fixture.raw.to_vec();
+            inputs.extend((0..raw.len()).map(|len| raw[..len].to_vec()));
+            for i in 0..raw.len() {
+                for flip in [0x01, 0x80, 0xff] {
+                    let mut mutated = raw.clone();
+                    mutated[i] ^= flip;
+                    inputs.push(mutated);
+                }
+            }
+        }
+
+        for input in &inputs {
+            for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+                let _ = ConfidentialComputeRequest::decode_2718_with_mode(input, mode);
+                let _ = ConfidentialComputeRecord::decode_signed_with_mode(input.get(1..).unwrap_or_default(), mode);
+            }
+            let _ = ConfidentialComputeRequest::decode_2718(&mut input.as_slice());
+            let _ = super::super::SuaveTxEnvelope::decode_2718(&mut input.as_slice());
+            let _ = super::super::ConfidentialEnvelope::decode(input);
+        }
+        let (_, report) = super::super::decode_batch(&inputs);
+        assert!(!report.errors.is_empty());
+    }
+
+    #[test]
+    fn test_bounded_decode() {
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let record = &fixture.request.confidential_compute_record;
+        let (inputs_len, calldata_len) = (fixture.request.confidential_inputs.len(), record.input.len());
+
+        let decoded = ConfidentialComputeRequest::decode_2718_bounded(&fixture.raw, inputs_len, calldata_len).unwrap();
+        assert_eq!(decoded, fixture.request);
+        assert!(ConfidentialComputeRequest::decode_2718_bounded(&fixture.raw, inputs_len - 1, calldata_len).is_err());
+        assert!(ConfidentialComputeRequest::decode_2718_bounded(&fixture.raw, inputs_len, calldata_len - 1).is_err());
+
+        // Length prefix claiming ~4GB of confidential inputs
+        let crafted = [
+            ConfidentialComputeRequest::TYPE, 0xcc,
+            0xc6, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80,
+            0xbb, 0xff, 0xff, 0xff, 0xff,
+        ];
+        assert!(ConfidentialComputeRequest::decode_2718_bounded(&crafted, 1024, 1024).is_err());
+    }
+
+    #[test]
+    fn test_invalid_v_is_an_error() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let mut raw = fixture.raw.to_vec();
+        // v is the single byte `01` right before the `a0`-prefixed r
+        let v_pos = raw.windows(2).rposition(|w| w == [0x01, 0xa0]).unwrap();
+        raw[v_pos] = 0x05;
+        for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+            assert!(ConfidentialComputeRequest::decode_2718_with_mode(&raw, mode).is_err());
+        }
+    }
+
+    #[test]
+    fn test_access_list() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let access_list = AccessList(vec![AccessListItem {
+            address: Address::repeat_byte(0xc8),
+            storage_keys: vec![B256::ZERO, B256::repeat_byte(0x01)],
+        }]);
+        for fees in [Fees::Legacy { gas_price: 0x3b9aca00 }, Fees::Dynamic { max_priority_fee_per_gas: 1, max_fee_per_gas: 0x3b9aca00 }] {
+            let mut ccr = fixture.request.clone();
+            ccr.confidential_compute_record.gas_price = None;
+            ccr.confidential_compute_record.set_fees(fees);
+            ccr.confidential_compute_record.access_list = Some(access_list.clone());
+            let mut raw = Vec::new();
+            ccr.encode_2718(&mut raw);
+            for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+                let decoded = ConfidentialComputeRequest::decode_2718_with_mode(&raw, mode).unwrap();
+                assert_eq!(decoded, ccr, "{fees:?} {mode:?}");
+            }
+            assert_ne!(ccr.signing_hash().unwrap(), fixture.request.signing_hash().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_extra_fields_round_trip() {
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let mut ccr = fixture.request.clone();
+        ccr.confidential_compute_record.extra_fields = vec![Bytes::from_static(&[0x82, 0x01, 0x02]), Bytes::from_static(&[0xc0])];
+        let mut raw = Vec::new();
+        ccr.encode_2718(&mut raw);
+        for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+            let decoded = ConfidentialComputeRequest::decode_2718_with_mode(&raw, mode).unwrap();
+            assert_eq!(decoded, ccr, "{mode:?}");
+            let mut reencoded = Vec::new();
+            decoded.encode_2718(&mut reencoded);
+            assert_eq!(reencoded, raw, "{mode:?}");
+        }
+        // The record hash covers them, the signature doesn't
+        assert_ne!(ccr.tx_hash().unwrap(), fixture.tx_hash);
+        assert_eq!(ccr.recover_signer().unwrap(), fixture.signer);
+
+        let json = serde_json::to_string(&ccr).unwrap();
+        assert_eq!(ConfidentialComputeRequest::from_json_with_mode(&json, DecodeMode::Lenient).unwrap(), ccr);
+    }
+
+    #[test]
+    fn test_record_decode() {
+        let fixture = fixtures::load_fixture("value_transfer").unwrap();
+        let mut buf = &fixture.raw[1..];
+        let mut fields = alloy_rlp::Header::decode_bytes(&mut buf, true).unwrap();
+        let record_start = fields;
+        CRecordRLP::decode(&mut fields).unwrap();
+        let record_rlp = &record_start[..record_start.len() - fields.len()];
+
+        for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+            let record = ConfidentialComputeRecord::decode_signed_with_mode(record_rlp, mode).unwrap();
+            assert_eq!(record, fixture.request.confidential_compute_record);
+        }
+    }
+
+}
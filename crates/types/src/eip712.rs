@@ -0,0 +1,150 @@
+use serde_json::json;
+use alloy::{
+    primitives::{keccak256, Address, B256, U256},
+    sol,
+    sol_types::{Eip712Domain, SolStruct},
+};
+use super::{crecord::EMPTY_BYTES_HASH, CcrError, ConfidentialComputeRequest};
+
+
+/// Name of the EIP-712 domain CCRs are signed in; the verifying contract
+/// is the kettle.
+pub const EIP712_DOMAIN_NAME: &str = "ConfidentialRecord";
+
+sol! {
+    /// Record fields signed as EIP-712 typed data (suave-geth's
+    /// `ConfidentialRecord` type).
+    #[derive(Debug, PartialEq, Eq)]
+    struct ConfidentialRecord {
+        uint64 nonce;
+        uint256 gasPrice;
+        uint64 gas;
+        address to;
+        uint256 value;
+        bytes data;
+        address kettleAddress;
+        bytes32 confidentialInputsHash;
+    }
+}
+
+pub fn eip712_domain(kettle_address: Address) -> Eip712Domain {
+    Eip712Domain {
+        name: Some(EIP712_DOMAIN_NAME.into()),
+        verifying_contract: Some(kettle_address),
+        ..Default::default()
+    }
+}
+
+/// EIP-712 signing for wallets that only sign typed data (eg. MetaMask via
+/// `eth_signTypedData_v4`). Selected with `SigningDomain::Eip712`; the
+/// encoding is the same, so the kettle has to be configured to verify
+/// EIP-712 signatures.
+impl ConfidentialComputeRequest {
+
+    pub fn eip712_record(&self) -> Result<ConfidentialRecord, CcrError> {
+        let record = &self.confidential_compute_record;
+        if record.access_list.is_some() {
+            return Err(CcrError::Unsupported("access list in EIP-712 typed data"));
+        }
+        let gas = record.gas.ok_or(CcrError::MissingField("gas"))?;
+        Ok(ConfidentialRecord {
+            nonce: record.nonce.ok_or(CcrError::MissingField("nonce"))?,
+            gasPrice: U256::from(record.gas_price.ok_or(CcrError::MissingField("gas price"))?),
+            gas: u64::try_from(gas).map_err(|_| CcrError::GasOverflow(gas))?,
+            // Typed data has no way to express a creation; signed as the zero address
+            to: record.target().unwrap_or_default(),
+            value: record.value,
+            data: record.input.clone(),
+            kettleAddress: record.kettle_address.ok_or(CcrError::MissingField("kettle address"))?,
+            confidentialInputsHash: record.confidential_inputs_hash.unwrap_or(EMPTY_BYTES_HASH),
+        })
+    }
+
+    /// `0x1901 || domainSeparator || hashStruct(record)`, the EIP-712
+    /// signing payload.
+    pub fn encode_eip712(&self) -> Result<Vec<u8>, CcrError> {
+        let record = self.eip712_record()?;
+        let domain = eip712_domain(record.kettleAddress);
+        Ok([&[0x19u8, 0x01][..], domain.separator().as_slice(), record.eip712_hash_struct().as_slice()].concat())
+    }
+
+    pub fn signing_hash_eip712(&self) -> Result<B256, CcrError> {
+        self.encode_eip712().map(keccak256)
+    }
+
+    /// Typed data as passed to `eth_signTypedData_v4`.
+    pub fn eip712_typed_data(&self) -> Result<serde_json::Value, CcrError> {
+        let record = self.eip712_record()?;
+        Ok(json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "verifyingContract", "type": "address" },
+                ],
+                "ConfidentialRecord": [
+                    { "name": "nonce", "type": "uint64" },
+                    { "name": "gasPrice", "type": "uint256" },
+                    { "name": "gas", "type": "uint64" },
+                    { "name": "to", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "data", "type": "bytes" },
+                    { "name": "kettleAddress", "type": "address" },
+                    { "name": "confidentialInputsHash", "type": "bytes32" },
+                ],
+            },
+            "primaryType": "ConfidentialRecord",
+            "domain": {
+                "name": EIP712_DOMAIN_NAME,
+                "verifyingContract": record.kettleAddress,
+            },
+            "message": {
+                "nonce": record.nonce,
+                "gasPrice": record.gasPrice.to_string(),
+                "gas": record.gas,
+                "to": record.to,
+                "value": record.value.to_string(),
+                "data": record.data,
+                "kettleAddress": record.kettleAddress,
+                "confidentialInputsHash": record.confidentialInputsHash,
+            },
+        }))
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use eyre::Result;
+    use alloy::{consensus::SignableTransaction, network::TxSigner, signers::wallet::LocalWallet};
+    use super::*;
+    use super::super::{consts::{ProtocolConfig, SigningDomain}, fixtures};
+
+    #[tokio::test]
+    async fn test_eip712_signing() -> Result<()> {
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let config = ProtocolConfig::default().with_signing_domain(SigningDomain::Eip712);
+        let mut ccr = fixture.request.clone().with_protocol_config(&config);
+
+        let record = ccr.eip712_record()?;
+        let domain = eip712_domain(record.kettleAddress);
+        let payload = ccr.encode_eip712()?;
+        assert_eq!(&payload[..2], &[0x19, 0x01]);
+        assert_eq!(ccr.signing_hash_eip712()?, record.eip712_signing_hash(&domain));
+        assert_eq!(ccr.signature_hash(), ccr.signing_hash_eip712()?);
+        assert_ne!(ccr.signature_hash(), fixture.signing_hash);
+
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?;
+        let sig = wallet.sign_transaction(&mut ccr).await?;
+        assert_eq!(sig.recover_address_from_prehash(&ccr.signing_hash_eip712()?)?, wallet.address());
+
+        let typed_data = ccr.eip712_typed_data()?;
+        assert_eq!(typed_data["primaryType"], "ConfidentialRecord");
+        assert_eq!(typed_data["message"]["nonce"], record.nonce);
+
+        ccr.confidential_compute_record.gas = Some(u64::MAX as u128 + 1);
+        assert_eq!(ccr.encode_eip712(), Err(CcrError::GasOverflow(u64::MAX as u128 + 1)));
+        Ok(())
+    }
+
+}
@@ -0,0 +1,250 @@
+use std::ops::Range;
+use alloy_rlp::{Encodable, Header};
+use alloy::primitives::hex;
+use super::{
+    crecord::{signature_to_vrs, RawItem, EMPTY_BYTES_HASH},
+    ConfidentialComputeRecord,
+    CcrError,
+    ConfidentialComputeRequest,
+    Fees,
+};
+
+
+/// Byte range one RLP item occupies in an encoding. For lists the range
+/// covers only the list header; the items follow as their own entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldRange {
+    pub name: String,
+    pub range: Range<usize>,
+}
+
+/// Encoding of a request together with the byte ranges of every field,
+/// for diagnosing encoding/hash mismatches with other implementations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeMap {
+    pub bytes: Vec<u8>,
+    pub fields: Vec<FieldRange>,
+}
+
+impl EncodeMap {
+
+    pub fn field(&self, name: &str) -> Option<&[u8]> {
+        self.fields.iter()
+            .find(|f| f.name == name)
+            .map(|f| &self.bytes[f.range.clone()])
+    }
+
+    /// One line per field: offset, length, name and the field's bytes.
+    pub fn annotated_hex(&self) -> String {
+        let width = self.fields.iter().map(|f| f.name.len()).max().unwrap_or(0);
+        self.fields.iter()
+            .map(|f| format!(
+                "{:>5} {:>5}  {:<width$}  {}",
+                f.range.start, f.range.len(), f.name, hex::encode(&self.bytes[f.range.clone()]),
+            ))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+}
+
+impl std::fmt::Display for EncodeMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.annotated_hex())
+    }
+}
+
+enum Node<'a> {
+    Field(&'static str, &'a dyn Encodable),
+    List(&'static str, Vec<Node<'a>>),
+}
+
+impl Node<'_> {
+
+    fn payload_length(&self) -> usize {
+        match self {
+            Node::Field(_, item) => item.length(),
+            Node::List(_, items) => items.iter().map(Node::length).sum(),
+        }
+    }
+
+    fn length(&self) -> usize {
+        match self {
+            Node::Field(_, item) => item.length(),
+            Node::List(..) => {
+                let payload_length = self.payload_length();
+                Header { list: true, payload_length }.length() + payload_length
+            }
+        }
+    }
+
+    fn write(&self, prefix: &str, map: &mut EncodeMap) {
+        let start = map.bytes.len();
+        match self {
+            Node::Field(name, item) => {
+                item.encode(&mut map.bytes);
+                map.fields.push(FieldRange { name: format!("{prefix}{name}"), range: start..map.bytes.len() });
+            }
+            Node::List(name, items) => {
+                Header { list: true, payload_length: self.payload_length() }.encode(&mut map.bytes);
+                let name = format!("{prefix}{name}");
+                map.fields.push(FieldRange { name: name.clone(), range: start..map.bytes.len() });
+                let prefix = format!("{name}.");
+                for item in items {
+                    item.write(&prefix, map);
+                }
+            }
+        }
+    }
+
+}
+
+fn encode_prefixed(ty: u8, node: Node<'_>) -> EncodeMap {
+    let mut map = EncodeMap { bytes: vec![ty], fields: vec![FieldRange { name: "type".into(), range: 0..1 }] };
+    node.write("", &mut map);
+    map
+}
+
+fn required<T: Copy>(value: Option<T>, name: &'static str) -> Result<T, CcrError> {
+    value.ok_or(CcrError::MissingField(name))
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Map of the 0x43 encoding (as produced by `encode_2718`).
+    pub fn encode_map(&self) -> Result<EncodeMap, CcrError> {
+        let record = &self.confidential_compute_record;
+        let signature = record.signature.ok_or(CcrError::MissingSignature)?;
+        let (v, r, s) = signature_to_vrs(signature);
+        let (nonce, fees, gas, kettle_address, chain_id) = required_fields(record)?;
+        let cinputs_hash = record.confidential_inputs_hash.unwrap_or(EMPTY_BYTES_HASH);
+
+        let mut record_nodes = vec![Node::Field("nonce", &nonce)];
+        record_nodes.extend(fee_nodes(&fees));
+        record_nodes.extend([
+            Node::Field("gas", &gas),
+            Node::Field("to", &record.to),
+            Node::Field("value", &record.value),
+            Node::Field("input", &record.input),
+        ]);
+        record_nodes.extend(record.access_list.as_ref().map(|list| Node::Field("access_list", list)));
+        record_nodes.extend([
+            Node::Field("kettle_address", &kettle_address),
+            Node::Field("confidential_inputs_hash", &cinputs_hash),
+            Node::Field("chain_id", &chain_id),
+            Node::Field("v", &v),
+            Node::Field("r", &r),
+            Node::Field("s", &s),
+        ]);
+        let extra_fields: Vec<_> = record.extra_fields.iter().cloned().map(RawItem).collect();
+        record_nodes.extend(extra_fields.iter().map(|field| Node::Field("extra_field", field)));
+
+        let node = Node::List("request", vec![
+            Node::List("record", record_nodes),
+            Node::Field("confidential_inputs", &self.confidential_inputs),
+        ]);
+        Ok(encode_prefixed(ConfidentialComputeRequest::TYPE, node))
+    }
+
+    /// Map of the 0x42 payload whose keccak is signed.
+    pub fn signing_encode_map(&self) -> Result<EncodeMap, CcrError> {
+        let record = &self.confidential_compute_record;
+        let (nonce, fees, gas, kettle_address, _) = required_fields(record)?;
+        let cinputs_hash = record.confidential_inputs_hash.unwrap_or(EMPTY_BYTES_HASH);
+
+        let mut nodes = vec![
+            Node::Field("kettle_address", &kettle_address),
+            Node::Field("confidential_inputs_hash", &cinputs_hash),
+            Node::Field("nonce", &nonce),
+        ];
+        nodes.extend(fee_nodes(&fees));
+        nodes.extend([
+            Node::Field("gas", &gas),
+            Node::Field("to", &record.to),
+            Node::Field("value", &record.value),
+            Node::Field("input", &record.input),
+        ]);
+        nodes.extend(record.access_list.as_ref().map(|list| Node::Field("access_list", list)));
+        let node = Node::List("signing_payload", nodes);
+        Ok(encode_prefixed(ConfidentialComputeRecord::TYPE, node))
+    }
+
+}
+
+fn required_fields(
+    record: &ConfidentialComputeRecord,
+) -> Result<(u64, Fees, u128, alloy::primitives::Address, u64), CcrError> {
+    Ok((
+        required(record.nonce, "nonce")?,
+        required(record.fees(), "gas price")?,
+        required(record.gas, "gas")?,
+        required(record.kettle_address, "kettle address")?,
+        required(record.chain_id, "chain id")?,
+    ))
+}
+
+/// Gas price, or both dynamic fees in EIP-1559 order.
+fn fee_nodes(fees: &Fees) -> Vec<Node<'_>> {
+    match fees {
+        Fees::Legacy { gas_price } => vec![Node::Field("gas_price", gas_price)],
+        Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas } => vec![
+            Node::Field("max_priority_fee_per_gas", max_priority_fee_per_gas),
+            Node::Field("max_fee_per_gas", max_fee_per_gas),
+        ],
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::keccak256,
+        eips::eip2718::Encodable2718,
+    };
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_encode_map_matches_encoding() {
+        for fixture in fixtures::load_fixtures() {
+            let map = fixture.request.encode_map().unwrap();
+            let mut encoded = Vec::new();
+            fixture.request.encode_2718(&mut encoded);
+            assert_eq!(map.bytes, encoded, "{}", fixture.name);
+            assert_eq!(map.fields.last().unwrap().range.end, encoded.len());
+
+            let signing = fixture.request.signing_encode_map().unwrap();
+            assert_eq!(keccak256(&signing.bytes), fixture.signing_hash, "{}", fixture.name);
+        }
+    }
+
+    #[test]
+    fn test_encode_map_fields() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let map = fixture.request.encode_map().unwrap();
+        // 0x0f4240 prefixed with its string header
+        assert_eq!(map.field("request.record.gas"), Some(&[0x83, 0x0f, 0x42, 0x40][..]));
+        assert_eq!(map.field("request.confidential_inputs"), Some(&[0x80][..]));
+        assert!(map.to_string().lines().any(|l| l.contains("request.record.kettle_address")));
+
+        let mut unsigned = fixture.request.clone();
+        unsigned.confidential_compute_record.signature = None;
+        assert!(unsigned.encode_map().is_err());
+        assert!(unsigned.signing_encode_map().is_ok());
+    }
+
+    #[test]
+    fn test_encode_map_dynamic_fees() {
+        let mut request = fixtures::load_fixture("empty_inputs").unwrap().request;
+        request.confidential_compute_record.set_fees(Fees::Dynamic { max_priority_fee_per_gas: 1, max_fee_per_gas: 2 });
+        let map = request.encode_map().unwrap();
+        assert_eq!(map.bytes, request.encoded_2718());
+        assert_eq!(map.field("request.record.max_priority_fee_per_gas"), Some(&[0x01][..]));
+        assert_eq!(map.field("request.record.max_fee_per_gas"), Some(&[0x02][..]));
+        assert_eq!(map.field("request.record.gas_price"), None);
+
+        let signing = request.signing_encode_map().unwrap();
+        assert_eq!(keccak256(&signing.bytes), request.signing_hash().unwrap());
+    }
+
+}
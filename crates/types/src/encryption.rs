@@ -0,0 +1,429 @@
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use alloy::primitives::{self, Bytes, FixedBytes, B256};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305 as PayloadCipher, Key, Nonce,
+};
+use hpke::{
+    aead::ChaCha20Poly1305, kdf::HkdfSha256, kem::X25519HkdfSha256,
+    Deserializable, Kem as KemTrait, OpModeR, OpModeS, Serializable,
+};
+use rand::{rngs::OsRng, RngCore};
+use zeroize::Zeroizing;
+use super::ConfidentialComputeRequest;
+
+
+type Kem = X25519HkdfSha256;
+
+/// Context string mixed into every HPKE key wrap.
+const HPKE_INFO: &[u8] = b"suave-ccr-confidential-inputs";
+
+/// Public key of a kettle with its rotation metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KettleKey {
+    /// X25519 public key.
+    pub public_key: FixedBytes<32>,
+    /// Rotation epoch the key belongs to.
+    pub epoch: u64,
+    /// Unix timestamp (seconds) after which the key must not be used.
+    pub not_after: Option<u64>,
+}
+
+impl KettleKey {
+
+    pub fn new(public_key: FixedBytes<32>, epoch: u64) -> Self {
+        Self { public_key, epoch, not_after: None }
+    }
+
+    pub fn with_not_after(mut self, not_after: u64) -> Self {
+        self.not_after = Some(not_after);
+        self
+    }
+
+    pub fn key_id(&self) -> B256 {
+        primitives::keccak256(self.public_key)
+    }
+
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        self.not_after.map_or(true, |not_after| now <= not_after)
+    }
+
+}
+
+/// Kettle side key pair used to open encrypted inputs. The secret is
+/// wiped from memory on drop.
+#[derive(Clone)]
+pub struct KettleSecretKey {
+    secret: Zeroizing<[u8; 32]>,
+    key: KettleKey,
+}
+
+impl KettleSecretKey {
+
+    pub fn generate(epoch: u64) -> Self {
+        let (sk, _) = Kem::gen_keypair(&mut OsRng);
+        let mut secret = Zeroizing::new([0u8; 32]);
+        secret.copy_from_slice(&sk.to_bytes());
+        Self::from_zeroizing(secret, epoch).expect("freshly generated key")
+    }
+
+    pub fn from_secret(secret: FixedBytes<32>, epoch: u64) -> Result<Self, EncryptionError> {
+        Self::from_zeroizing(Zeroizing::new(secret.0), epoch)
+    }
+
+    fn from_zeroizing(secret: Zeroizing<[u8; 32]>, epoch: u64) -> Result<Self, EncryptionError> {
+        let sk = <Kem as KemTrait>::PrivateKey::from_bytes(secret.as_slice())
+            .map_err(EncryptionError::Hpke)?;
+        let pk = Kem::sk_to_pk(&sk);
+        let key = KettleKey::new(FixedBytes::from_slice(&pk.to_bytes()), epoch);
+        Ok(Self { secret, key })
+    }
+
+    pub fn public_key(&self) -> &KettleKey {
+        &self.key
+    }
+
+}
+
+impl std::fmt::Debug for KettleSecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KettleSecretKey")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Content key wrapped for a single recipient.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct RecipientStanza {
+    pub key_id: B256,
+    pub epoch: u64,
+    pub encapped_key: Bytes,
+    pub wrapped_key: Bytes,
+}
+
+/// Confidential inputs encrypted once under a random content key, with
+/// the content key wrapped (HPKE) for every recipient kettle. Any kettle of
+/// a failover pool can open the payload without re-encryption.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct EncryptedInputs {
+    pub nonce: FixedBytes<12>,
+    pub recipients: Vec<RecipientStanza>,
+    pub ciphertext: Bytes,
+}
+
+impl EncryptedInputs {
+    /// "SCX" followed by the format version.
+    pub const MAGIC: [u8; 4] = [0x53, 0x43, 0x58, 0x01];
+
+    pub fn encrypt(plaintext: &[u8], recipients: &[KettleKey], now: u64) -> Result<Self, EncryptionError> {
+        if recipients.is_empty() {
+            return Err(EncryptionError::NoRecipients);
+        }
+        let mut content_key = Zeroizing::new([0u8; 32]);
+        let mut nonce = FixedBytes::<12>::ZERO;
+        OsRng.fill_bytes(content_key.as_mut_slice());
+        OsRng.fill_bytes(nonce.as_mut_slice());
+
+        let stanzas = recipients.iter()
+            .map(|recipient| wrap_key(content_key.as_slice(), recipient, now))
+            .collect::<Result<Vec<_>, _>>()?;
+        let ciphertext = PayloadCipher::new(Key::from_slice(content_key.as_slice()))
+            .encrypt(Nonce::from_slice(nonce.as_slice()), Payload { msg: plaintext, aad: &Self::MAGIC })
+            .map_err(|_| EncryptionError::Cipher)?;
+
+        Ok(Self { nonce, recipients: stanzas, ciphertext: ciphertext.into() })
+    }
+
+    pub fn is_encrypted(confidential_inputs: &[u8]) -> bool {
+        confidential_inputs.starts_with(&Self::MAGIC)
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut out = Self::MAGIC.to_vec();
+        Encodable::encode(self, &mut out);
+        out.into()
+    }
+
+    pub fn decode(confidential_inputs: &[u8]) -> Result<Self, EncryptionError> {
+        if !Self::is_encrypted(confidential_inputs) {
+            return Err(EncryptionError::NotEncrypted);
+        }
+        let mut buf = &confidential_inputs[Self::MAGIC.len()..];
+        let decoded = <Self as Decodable>::decode(&mut buf).map_err(EncryptionError::Rlp)?;
+        if !buf.is_empty() {
+            return Err(EncryptionError::Rlp(alloy_rlp::Error::Custom("Trailing bytes after encrypted inputs")));
+        }
+        Ok(decoded)
+    }
+
+    pub fn recipient_ids(&self) -> impl Iterator<Item = B256> + '_ {
+        self.recipients.iter().map(|stanza| stanza.key_id)
+    }
+
+    pub fn decrypt(&self, secret_key: &KettleSecretKey) -> Result<Bytes, EncryptionError> {
+        let key_id = secret_key.key.key_id();
+        let stanza = self.recipients.iter()
+            .find(|stanza| stanza.key_id == key_id)
+            .ok_or(EncryptionError::UnknownRecipient(key_id))?;
+        let content_key = unwrap_key(stanza, secret_key)?;
+        if content_key.len() != 32 {
+            return Err(EncryptionError::Cipher);
+        }
+        let plaintext = PayloadCipher::new(Key::from_slice(content_key.as_slice()))
+            .decrypt(Nonce::from_slice(self.nonce.as_slice()), Payload { msg: &self.ciphertext, aad: &Self::MAGIC })
+            .map_err(|_| EncryptionError::Cipher)?;
+        Ok(plaintext.into())
+    }
+
+    /// Decrypt with the first matching key of a key ring, eg. a kettle
+    /// holding keys of the current and the previous rotation epoch.
+    pub fn decrypt_with_any(&self, secret_keys: &[KettleSecretKey]) -> Result<Bytes, EncryptionError> {
+        let secret_key = secret_keys.iter()
+            .find(|sk| self.recipient_ids().any(|id| id == sk.key.key_id()))
+            .ok_or(EncryptionError::NoMatchingKey)?;
+        self.decrypt(secret_key)
+    }
+
+}
+
+/// Scheme sealing confidential inputs to kettles. `Hpke` (the
+/// `EncryptedInputs` format) is the default; schemes of other kettle TEEs
+/// (eg. SGX, TDX or Nitro attested keys) plug in as implementations without
+/// changes to CCR construction.
+pub trait InputsEncryption {
+    /// Public key, with its metadata, inputs are sealed to.
+    type RecipientKey;
+    /// Key opening sealed inputs.
+    type SecretKey;
+
+    fn seal(&self, plaintext: &[u8], recipients: &[Self::RecipientKey], now: u64) -> Result<Bytes, EncryptionError>;
+
+    fn open(&self, sealed: &[u8], secret_key: &Self::SecretKey) -> Result<Bytes, EncryptionError>;
+
+    /// Whether `confidential_inputs` are sealed by this scheme.
+    fn is_sealed(&self, confidential_inputs: &[u8]) -> bool;
+}
+
+/// Default scheme: HPKE (X25519, ChaCha20Poly1305) wrapped content key,
+/// encoded as `EncryptedInputs`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Hpke;
+
+impl InputsEncryption for Hpke {
+    type RecipientKey = KettleKey;
+    type SecretKey = KettleSecretKey;
+
+    fn seal(&self, plaintext: &[u8], recipients: &[KettleKey], now: u64) -> Result<Bytes, EncryptionError> {
+        EncryptedInputs::encrypt(plaintext, recipients, now).map(|encrypted| encrypted.encode())
+    }
+
+    fn open(&self, sealed: &[u8], secret_key: &KettleSecretKey) -> Result<Bytes, EncryptionError> {
+        EncryptedInputs::decode(sealed)?.decrypt(secret_key)
+    }
+
+    fn is_sealed(&self, confidential_inputs: &[u8]) -> bool {
+        EncryptedInputs::is_encrypted(confidential_inputs)
+    }
+}
+
+fn wrap_key(content_key: &[u8], recipient: &KettleKey, now: u64) -> Result<RecipientStanza, EncryptionError> {
+    let key_id = recipient.key_id();
+    if !recipient.is_valid_at(now) {
+        return Err(EncryptionError::ExpiredKey(key_id));
+    }
+    let pk = <Kem as KemTrait>::PublicKey::from_bytes(recipient.public_key.as_slice())
+        .map_err(EncryptionError::Hpke)?;
+    let (encapped_key, wrapped_key) = hpke::single_shot_seal::<ChaCha20Poly1305, HkdfSha256, Kem, _>(
+        &OpModeS::Base,
+        &pk,
+        HPKE_INFO,
+        content_key,
+        key_id.as_slice(),
+        &mut OsRng,
+    ).map_err(EncryptionError::Hpke)?;
+    Ok(RecipientStanza {
+        key_id,
+        epoch: recipient.epoch,
+        encapped_key: Bytes::copy_from_slice(&encapped_key.to_bytes()),
+        wrapped_key: wrapped_key.into(),
+    })
+}
+
+fn unwrap_key(stanza: &RecipientStanza, secret_key: &KettleSecretKey) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+    let sk = <Kem as KemTrait>::PrivateKey::from_bytes(secret_key.secret.as_slice())
+        .map_err(EncryptionError::Hpke)?;
+    let encapped_key = <Kem as KemTrait>::EncappedKey::from_bytes(&stanza.encapped_key)
+        .map_err(EncryptionError::Hpke)?;
+    hpke::single_shot_open::<ChaCha20Poly1305, HkdfSha256, Kem>(
+        &OpModeR::Base,
+        &sk,
+        &encapped_key,
+        HPKE_INFO,
+        &stanza.wrapped_key,
+        stanza.key_id.as_slice(),
+    ).map(Zeroizing::new).map_err(EncryptionError::Hpke)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptionError {
+    NoRecipients,
+    ExpiredKey(B256),
+    UnknownRecipient(B256),
+    NoMatchingKey,
+    NotEncrypted,
+    Cipher,
+    Hpke(hpke::HpkeError),
+    Rlp(alloy_rlp::Error),
+    /// Failure specific to an `InputsEncryption` implementation.
+    Scheme(String),
+}
+
+impl std::fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptionError::NoRecipients => write!(f, "No recipients"),
+            EncryptionError::ExpiredKey(id) => write!(f, "Recipient key {id} expired"),
+            EncryptionError::UnknownRecipient(id) => write!(f, "Key {id} is not a recipient"),
+            EncryptionError::NoMatchingKey => write!(f, "None of the keys is a recipient"),
+            EncryptionError::NotEncrypted => write!(f, "Inputs are not encrypted"),
+            EncryptionError::Cipher => write!(f, "Payload cipher failure"),
+            EncryptionError::Hpke(err) => write!(f, "HPKE error: {err}"),
+            EncryptionError::Rlp(err) => write!(f, "RLP error: {err}"),
+            EncryptionError::Scheme(err) => write!(f, "Encryption scheme error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+impl ConfidentialComputeRequest {
+
+    /// Encrypt the current confidential inputs to all `recipients` and
+    /// replace them (and their hash) with the encrypted form.
+    pub fn encrypt_confidential_inputs(&mut self, recipients: &[KettleKey], now: u64) -> Result<(), EncryptionError> {
+        self.encrypt_confidential_inputs_with(&Hpke, recipients, now)
+    }
+
+    /// Like `encrypt_confidential_inputs` with a custom scheme.
+    pub fn encrypt_confidential_inputs_with<E: InputsEncryption>(
+        &mut self,
+        scheme: &E,
+        recipients: &[E::RecipientKey],
+        now: u64,
+    ) -> Result<(), EncryptionError> {
+        let sealed = scheme.seal(&self.confidential_inputs, recipients, now)?;
+        self.set_confidential_inputs(sealed);
+        Ok(())
+    }
+
+    pub fn decrypt_confidential_inputs_with<E: InputsEncryption>(
+        &self,
+        scheme: &E,
+        secret_key: &E::SecretKey,
+    ) -> Result<Bytes, EncryptionError> {
+        if !scheme.is_sealed(&self.confidential_inputs) {
+            return Err(EncryptionError::NotEncrypted);
+        }
+        scheme.open(&self.confidential_inputs, secret_key)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_recipient_roundtrip() {
+        let kettle_a = KettleSecretKey::generate(1);
+        let kettle_b = KettleSecretKey::generate(1);
+        let outsider = KettleSecretKey::generate(1);
+        let plaintext = b"confidential bundle";
+
+        let recipients = [kettle_a.public_key().clone(), kettle_b.public_key().clone()];
+        let encrypted = EncryptedInputs::encrypt(plaintext, &recipients, 0).unwrap();
+        let decoded = EncryptedInputs::decode(&encrypted.encode()).unwrap();
+        assert_eq!(decoded, encrypted);
+
+        assert_eq!(decoded.decrypt(&kettle_a).unwrap(), Bytes::from_static(plaintext));
+        assert_eq!(decoded.decrypt(&kettle_b).unwrap(), Bytes::from_static(plaintext));
+        assert_eq!(
+            decoded.decrypt(&outsider),
+            Err(EncryptionError::UnknownRecipient(outsider.public_key().key_id()))
+        );
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old_key = KettleSecretKey::generate(1);
+        let new_key = KettleSecretKey::generate(2);
+        let expired = old_key.public_key().clone().with_not_after(100);
+
+        assert_eq!(
+            EncryptedInputs::encrypt(b"x", &[expired.clone()], 101),
+            Err(EncryptionError::ExpiredKey(expired.key_id()))
+        );
+
+        let encrypted = EncryptedInputs::encrypt(b"x", &[new_key.public_key().clone()], 101).unwrap();
+        assert_eq!(encrypted.recipients[0].epoch, 2);
+        let key_ring = [old_key, new_key];
+        assert_eq!(encrypted.decrypt_with_any(&key_ring).unwrap(), Bytes::from_static(b"x"));
+        assert_eq!(encrypted.decrypt_with_any(&key_ring[..1]), Err(EncryptionError::NoMatchingKey));
+    }
+
+    #[test]
+    fn test_encrypt_request_inputs() {
+        let kettle = KettleSecretKey::generate(1);
+        let mut ccr = ConfidentialComputeRequest::default()
+            .with_confidential_inputs(Bytes::from_static(b"secret"));
+        ccr.encrypt_confidential_inputs(&[kettle.public_key().clone()], 0).unwrap();
+
+        assert!(EncryptedInputs::is_encrypted(&ccr.confidential_inputs));
+        assert_eq!(
+            ccr.confidential_compute_record.confidential_inputs_hash,
+            Some(primitives::keccak256(&ccr.confidential_inputs))
+        );
+        let encrypted = EncryptedInputs::decode(&ccr.confidential_inputs).unwrap();
+        assert_eq!(encrypted.decrypt(&kettle).unwrap(), Bytes::from_static(b"secret"));
+        assert_eq!(ccr.decrypt_confidential_inputs_with(&Hpke, &kettle).unwrap(), Bytes::from_static(b"secret"));
+    }
+
+    /// Stand-in for another TEE scheme: XOR with a shared byte.
+    struct XorScheme;
+
+    impl InputsEncryption for XorScheme {
+        type RecipientKey = u8;
+        type SecretKey = u8;
+
+        fn seal(&self, plaintext: &[u8], recipients: &[u8], _now: u64) -> Result<Bytes, EncryptionError> {
+            let key = recipients.first().ok_or(EncryptionError::NoRecipients)?;
+            Ok(std::iter::once(0xff).chain(plaintext.iter().map(|b| b ^ key)).collect())
+        }
+
+        fn open(&self, sealed: &[u8], secret_key: &u8) -> Result<Bytes, EncryptionError> {
+            Ok(sealed[1..].iter().map(|b| b ^ secret_key).collect())
+        }
+
+        fn is_sealed(&self, confidential_inputs: &[u8]) -> bool {
+            confidential_inputs.first() == Some(&0xff)
+        }
+    }
+
+    #[test]
+    fn test_custom_scheme() {
+        let mut ccr = ConfidentialComputeRequest::default()
+            .with_confidential_inputs(Bytes::from_static(b"secret"));
+        assert_eq!(ccr.decrypt_confidential_inputs_with(&XorScheme, &0x2a), Err(EncryptionError::NotEncrypted));
+
+        ccr.encrypt_confidential_inputs_with(&XorScheme, &[0x2a], 0).unwrap();
+        assert!(!Hpke.is_sealed(&ccr.confidential_inputs));
+        assert_eq!(
+            ccr.confidential_compute_record.confidential_inputs_hash,
+            Some(primitives::keccak256(&ccr.confidential_inputs))
+        );
+        assert_eq!(ccr.decrypt_confidential_inputs_with(&XorScheme, &0x2a).unwrap(), Bytes::from_static(b"secret"));
+    }
+
+}
@@ -0,0 +1,164 @@
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
+
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use alloy::primitives::Bytes;
+
+
+/// Structured confidential inputs: the application payload plus typed
+/// extensions (fee vouchers, client metadata, ...) the payload consumer
+/// may need. Inputs without the envelope magic are treated as a bare
+/// payload, so existing SUAPPs keep receiving their bytes unchanged.
+///
+/// **The format is specific to this crate.** No SUAPP, kettle or other
+/// SUAVE client knows it: a SUAPP receiving enveloped inputs gets the
+/// magic and the RLP below instead of its payload, and has to decode them
+/// itself. Only attach extensions for SUAPPs written against this format.
+///
+/// Encoding: a bare payload unless there are extensions or the payload
+/// itself starts with `MAGIC`, otherwise `MAGIC` followed by the RLP list
+/// `[payload, [[kind, data], ...]]`. Payloads starting with `MAGIC` are
+/// always wrapped, so every input starting with it is an envelope.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfidentialEnvelope {
+    pub payload: Bytes,
+    pub extensions: Vec<EnvelopeExtension>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct EnvelopeExtension {
+    pub kind: u8,
+    pub data: Bytes,
+}
+
+impl EnvelopeExtension {
+    pub const FEE_VOUCHER: u8 = 0x01;
+    pub const TIMESTAMP: u8 = 0x02;
+    pub const CLIENT_TAG: u8 = 0x03;
+    pub const SALT: u8 = 0x04;
+    pub const APPROVALS: u8 = 0x05;
+
+    pub fn new(kind: u8, data: Bytes) -> Self {
+        Self { kind, data }
+    }
+}
+
+#[derive(RlpEncodable, RlpDecodable)]
+struct EnvelopeRLP {
+    payload: Bytes,
+    extensions: Vec<EnvelopeExtension>,
+}
+
+impl ConfidentialEnvelope {
+    /// "SCE" followed by the envelope version.
+    pub const MAGIC: [u8; 4] = [0x53, 0x43, 0x45, 0x01];
+
+    pub fn new(payload: Bytes) -> Self {
+        Self { payload, extensions: Vec::new() }
+    }
+
+    pub fn is_envelope(confidential_inputs: &[u8]) -> bool {
+        confidential_inputs.starts_with(&Self::MAGIC)
+    }
+
+    /// Encode the envelope. Without extensions the bare payload is
+    /// returned, unless it could be mistaken for an envelope.
+    pub fn encode(&self) -> Bytes {
+        if self.extensions.is_empty() && !Self::is_envelope(&self.payload) {
+            return self.payload.clone();
+        }
+        let rlp = EnvelopeRLP {
+            payload: self.payload.clone(),
+            extensions: self.extensions.clone(),
+        };
+        let mut out = Self::MAGIC.to_vec();
+        rlp.encode(&mut out);
+        out.into()
+    }
+
+    pub fn decode(confidential_inputs: &[u8]) -> alloy_rlp::Result<Self> {
+        if !Self::is_envelope(confidential_inputs) {
+            return Ok(Self::new(Bytes::copy_from_slice(confidential_inputs)));
+        }
+        let mut buf = &confidential_inputs[Self::MAGIC.len()..];
+        let rlp = EnvelopeRLP::decode(&mut buf)?;
+        if !buf.is_empty() {
+            return Err(alloy_rlp::Error::Custom("Trailing bytes after envelope"));
+        }
+        Ok(Self { payload: rlp.payload, extensions: rlp.extensions })
+    }
+
+    pub fn extension(&self, kind: u8) -> Option<&Bytes> {
+        self.extensions.iter()
+            .find(|ext| ext.kind == kind)
+            .map(|ext| &ext.data)
+    }
+
+    /// Set extension data, replacing an existing extension of the same kind.
+    pub fn set_extension(&mut self, kind: u8, data: Bytes) {
+        match self.extensions.iter_mut().find(|ext| ext.kind == kind) {
+            Some(ext) => ext.data = data,
+            None => self.extensions.push(EnvelopeExtension::new(kind, data)),
+        }
+    }
+
+    pub fn with_extension(mut self, kind: u8, data: Bytes) -> Self {
+        self.set_extension(kind, data);
+        self
+    }
+
+    pub fn remove_extension(&mut self, kind: u8) -> Option<Bytes> {
+        let idx = self.extensions.iter().position(|ext| ext.kind == kind)?;
+        Some(self.extensions.remove(idx).data)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use super::*;
+
+    #[test]
+    fn test_bare_payload_passthrough() {
+        let payload = Bytes::from_str("0x0000000000000000000000000000000000000000000000000000000000000020").unwrap();
+        let envelope = ConfidentialEnvelope::decode(&payload).unwrap();
+        assert_eq!(envelope, ConfidentialEnvelope::new(payload.clone()));
+        assert_eq!(envelope.encode(), payload);
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let mut envelope = ConfidentialEnvelope::new(Bytes::from_static(b"order"))
+            .with_extension(EnvelopeExtension::FEE_VOUCHER, Bytes::from_static(b"voucher"))
+            .with_extension(0x7f, Bytes::from_static(b"other"));
+        envelope.set_extension(0x7f, Bytes::from_static(b"replaced"));
+
+        let encoded = envelope.encode();
+        assert!(ConfidentialEnvelope::is_envelope(&encoded));
+        let decoded = ConfidentialEnvelope::decode(&encoded).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(decoded.extensions.len(), 2);
+        assert_eq!(decoded.extension(0x7f), Some(&Bytes::from_static(b"replaced")));
+
+        let mut trailing = encoded.to_vec();
+        trailing.push(0);
+        assert!(ConfidentialEnvelope::decode(&trailing).is_err());
+    }
+
+    #[test]
+    fn test_magic_payload_is_wrapped() {
+        let payload = Bytes::from([&ConfidentialEnvelope::MAGIC[..], b"not an envelope"].concat());
+        let envelope = ConfidentialEnvelope::new(payload.clone());
+        let encoded = envelope.encode();
+        assert_ne!(encoded, payload);
+        assert_eq!(ConfidentialEnvelope::decode(&encoded).unwrap(), envelope);
+        // Nested envelopes survive as payloads too
+        let nested = ConfidentialEnvelope::new(Bytes::from_static(b"order"))
+            .with_extension(EnvelopeExtension::SALT, Bytes::from_static(b"salt"))
+            .encode();
+        let outer = ConfidentialEnvelope::new(nested.clone());
+        assert_eq!(ConfidentialEnvelope::decode(&outer.encode()).unwrap().payload, nested);
+    }
+
+}
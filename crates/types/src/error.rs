@@ -0,0 +1,55 @@
+/// Failure converting, encoding or hashing a CCR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CcrError {
+    /// A field required for encoding or signing is not set.
+    MissingField(&'static str),
+    MissingSignature,
+    /// Gas not fitting the encoded type (uint64 in EIP-712 typed data).
+    GasOverflow(u128),
+    InvalidSignature,
+    Rlp(alloy_rlp::Error),
+    Json(String),
+    /// Value rejected by `DecodeMode::Strict`.
+    NonCanonical(&'static str),
+    /// Field set that the encoding (eg. EIP-712 typed data) can't express.
+    Unsupported(&'static str),
+    /// Value not fitting the field it is converted to.
+    Overflow(&'static str),
+    InvalidKey,
+    /// Call that can't be ABI encoded against the given ABI.
+    Abi(String),
+    Io(String),
+}
+
+impl std::fmt::Display for CcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CcrError::MissingField(field) => write!(f, "Missing {field} field"),
+            CcrError::MissingSignature => write!(f, "Missing signature field"),
+            CcrError::GasOverflow(gas) => write!(f, "Gas {gas} exceeds uint64"),
+            CcrError::InvalidSignature => write!(f, "Invalid signature"),
+            CcrError::Rlp(err) => write!(f, "RLP error: {err}"),
+            CcrError::Json(err) => write!(f, "JSON error: {err}"),
+            CcrError::NonCanonical(reason) => write!(f, "Non-canonical request: {reason}"),
+            CcrError::Unsupported(field) => write!(f, "Unsupported {field}"),
+            CcrError::Overflow(field) => write!(f, "{field} overflows"),
+            CcrError::InvalidKey => write!(f, "Invalid wallet key"),
+            CcrError::Abi(err) => write!(f, "ABI error: {err}"),
+            CcrError::Io(err) => write!(f, "I/O error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CcrError {}
+
+impl From<alloy_rlp::Error> for CcrError {
+    fn from(err: alloy_rlp::Error) -> Self {
+        CcrError::Rlp(err)
+    }
+}
+
+impl From<serde_json::Error> for CcrError {
+    fn from(err: serde_json::Error) -> Self {
+        CcrError::Json(err.to_string())
+    }
+}
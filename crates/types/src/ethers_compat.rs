@@ -0,0 +1,131 @@
+//! Conversions from ethers-rs types, for bots that haven't migrated to alloy.
+
+use alloy::{
+    primitives::{Address, Bytes, Signature, TxKind, U256},
+    signers::wallet::LocalWallet,
+};
+use ethers_core::types::{
+    NameOrAddress,
+    Signature as EthersSignature,
+    TransactionRequest as EthersTransactionRequest,
+    H160, U256 as EthersU256,
+};
+use ethers_signers::LocalWallet as EthersLocalWallet;
+use super::{CcrError, ConfidentialComputeRecord};
+
+
+pub fn address_from_ethers(address: H160) -> Address {
+    Address::from(address.0)
+}
+
+pub fn u256_from_ethers(value: EthersU256) -> U256 {
+    let mut buf = [0u8; 32];
+    value.to_big_endian(&mut buf);
+    U256::from_be_bytes(buf)
+}
+
+/// `value` narrowed to `T`, failing instead of panicking on overflow.
+fn narrow<T: TryFrom<EthersU256>>(value: EthersU256, field: &'static str) -> Result<T, CcrError> {
+    T::try_from(value).map_err(|_| CcrError::Overflow(field))
+}
+
+pub fn signature_from_ethers(sig: &EthersSignature) -> Result<Signature, CcrError> {
+    Signature::from_rs_and_parity(u256_from_ethers(sig.r), u256_from_ethers(sig.s), sig.v)
+        .map_err(|_| CcrError::InvalidSignature)
+}
+
+/// Re-create an ethers wallet as an alloy wallet (same private key).
+pub fn wallet_from_ethers(wallet: &EthersLocalWallet) -> Result<LocalWallet, CcrError> {
+    let key = wallet.signer().to_bytes();
+    LocalWallet::from_slice(key.as_slice())
+        .map_err(|_| CcrError::InvalidKey)
+}
+
+impl ConfidentialComputeRecord {
+
+    pub fn from_ethers_tx_request(
+        tx_req: &EthersTransactionRequest,
+        kettle_address: Address,
+    ) -> Result<Self, CcrError> {
+        let to = match &tx_req.to {
+            Some(NameOrAddress::Address(address)) => TxKind::Call(address_from_ethers(*address)),
+            Some(NameOrAddress::Name(_)) => return Err(CcrError::Unsupported("unresolved ENS name")),
+            None => TxKind::Create,
+        };
+        Ok(Self {
+            input: tx_req.data.as_ref().map(|d| Bytes::copy_from_slice(d)).unwrap_or_default(),
+            gas_price: tx_req.gas_price.map(|p| narrow(p, "Gas price")).transpose()?,
+            value: tx_req.value.map(u256_from_ethers).unwrap_or(U256::ZERO),
+            to,
+            nonce: tx_req.nonce.map(|n| narrow(n, "Nonce")).transpose()?,
+            kettle_address: Some(kettle_address),
+            chain_id: tx_req.chain_id.map(|id| id.as_u64()),
+            gas: tx_req.gas.map(|g| narrow(g, "Gas")).transpose()?,
+            from: tx_req.from.map(address_from_ethers),
+            ..Default::default()
+        })
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::signers::Signer;
+    use ethers_signers::Signer as EthersSigner;
+    use super::*;
+
+    #[test]
+    fn test_from_ethers_tx_request() {
+        let kettle_address = Address::from_str("0x7d83e42b214b75bf1f3e57adc3415da573d97bff").unwrap();
+        let to = H160::from_str("0x780675d71ebe3d3ef05fae379063071147dd3aee").unwrap();
+        let tx = EthersTransactionRequest::new()
+            .to(to)
+            .gas(0x0f4240)
+            .gas_price(0x3b9aca00)
+            .nonce(0x22)
+            .value(0x2233)
+            .data(vec![0x23, 0x6e, 0xb5, 0xa7])
+            .chain_id(0x067932);
+
+        let record = ConfidentialComputeRecord::from_ethers_tx_request(&tx, kettle_address).unwrap();
+        assert_eq!(record.to, TxKind::Call(address_from_ethers(to)));
+        assert_eq!(record.gas, Some(0x0f4240));
+        assert_eq!(record.gas_price, Some(0x3b9aca00));
+        assert_eq!(record.nonce, Some(0x22));
+        assert_eq!(record.value, U256::from(0x2233));
+        assert_eq!(record.input, Bytes::from_static(&[0x23, 0x6e, 0xb5, 0xa7]));
+        assert_eq!(record.chain_id, Some(0x067932));
+        assert_eq!(record.kettle_address, Some(kettle_address));
+
+        let ens = EthersTransactionRequest::new().to("vitalik.eth");
+        let err = ConfidentialComputeRecord::from_ethers_tx_request(&ens, kettle_address).unwrap_err();
+        assert!(matches!(err, CcrError::Unsupported(_)));
+
+        // Out of range values are errors, not panics
+        let overflowing = tx.clone().nonce(EthersU256::from(u64::MAX) + 1);
+        assert!(ConfidentialComputeRecord::from_ethers_tx_request(&overflowing, kettle_address).is_err());
+        let overflowing = tx.gas_price(EthersU256::MAX);
+        let err = ConfidentialComputeRecord::from_ethers_tx_request(&overflowing, kettle_address).unwrap_err();
+        assert!(matches!(err, CcrError::Overflow("Gas price")));
+    }
+
+    #[test]
+    fn test_wallet_and_signature() {
+        let pk = "1111111111111111111111111111111111111111111111111111111111111111";
+        let ethers_wallet: EthersLocalWallet = pk.parse().unwrap();
+        let wallet = wallet_from_ethers(&ethers_wallet).unwrap();
+        assert_eq!(wallet.address(), address_from_ethers(ethers_wallet.address()));
+
+        let sig = EthersSignature {
+            r: EthersU256::from_str_radix("1567c31c4bebcd1061edbaf22dd73fd40ff30f9a3ba4525037f23b2dc61e3473", 16).unwrap(),
+            s: EthersU256::from_str_radix("2dce69262794a499d525c5d58edde33e06a5847b4d321d396b743700a2fd71a8", 16).unwrap(),
+            v: 27,
+        };
+        let converted = signature_from_ethers(&sig).unwrap();
+        assert_eq!(converted.r(), U256::from_str("0x1567c31c4bebcd1061edbaf22dd73fd40ff30f9a3ba4525037f23b2dc61e3473").unwrap());
+        assert_eq!(converted.v().recid().to_byte(), 0);
+    }
+
+}
@@ -0,0 +1,73 @@
+use alloy::primitives::{Address, B256};
+use super::{ChainProfile, ConfidentialCallResponse, ConfidentialComputeRequest, SuaveRpcTransaction};
+
+
+/// Base URL of the explorer for `chain_id`, if it's a known chain (see
+/// `ChainProfile`).
+pub fn explorer_base_url(chain_id: u64) -> Option<&'static str> {
+    ChainProfile::by_chain_id(chain_id).map(|profile| profile.explorer_url)
+}
+
+/// Explorer link to a transaction (eg. a CCR by its `tx_hash`).
+pub fn explorer_url(chain_id: u64, tx_hash: B256) -> Option<String> {
+    explorer_base_url(chain_id).map(|base| format!("{base}/tx/{tx_hash}"))
+}
+
+/// Explorer link to a kettle's address page.
+pub fn kettle_explorer_url(chain_id: u64, kettle_address: Address) -> Option<String> {
+    explorer_base_url(chain_id).map(|base| format!("{base}/address/{kettle_address}"))
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Explorer link to this (signed) CCR on its chain.
+    pub fn explorer_url(&self) -> Option<String> {
+        let chain_id = self.confidential_compute_record.chain_id?;
+        explorer_url(chain_id, self.tx_hash().ok()?)
+    }
+
+}
+
+impl ConfidentialCallResponse {
+
+    /// Explorer link to the included CCR.
+    pub fn explorer_url(&self) -> Option<String> {
+        explorer_url(self.request_record.chain_id?, self.transaction.hash)
+    }
+
+}
+
+impl SuaveRpcTransaction {
+
+    /// Explorer link to the kettle result.
+    pub fn explorer_url(&self) -> Option<String> {
+        explorer_url(self.chain_id, self.hash)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use super::*;
+    use super::super::{RIGIL, TOLIMAN};
+
+    #[test]
+    fn test_explorer_urls() {
+        let tx_hash = B256::from_str("0x34fd0eda0a1a5e3bb5aea1c0b1ab7d36e6e1e9f4d9e0b1d2d34c7e2bb0bcf3d7").unwrap();
+        assert_eq!(
+            explorer_url(RIGIL.chain_id, tx_hash).unwrap(),
+            format!("https://explorer.rigil.suave.flashbots.net/tx/{tx_hash}"),
+        );
+        let kettle = Address::from_str("0x03493869959c866713c33669ca118e774a30a0e5").unwrap();
+        assert!(kettle_explorer_url(TOLIMAN.chain_id, kettle).unwrap()
+            .starts_with("https://explorer.toliman.suave.flashbots.net/address/0x"));
+        assert_eq!(explorer_url(1, tx_hash), None);
+
+        let fixture = super::super::fixtures::load_fixture("empty_inputs").unwrap();
+        let url = fixture.request.explorer_url().unwrap();
+        assert!(url.ends_with(&format!("/tx/{}", fixture.request.tx_hash().unwrap())));
+    }
+
+}
@@ -0,0 +1,259 @@
+//! Tabular export of decoded CCRs for analytics. Every row has the same
+//! columns in the same order (`COLUMNS`); new columns are only ever
+//! appended. Big numbers (gas price, value) are written as decimal strings.
+
+use std::io::{self, Write};
+use alloy::primitives::Address;
+use super::{ConfidentialComputeRequest, Fees};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Utf8,
+    UInt64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub ty: ColumnType,
+    pub nullable: bool,
+}
+
+const fn column(name: &'static str, ty: ColumnType, nullable: bool) -> Column {
+    Column { name, ty, nullable }
+}
+
+/// Stable column schema of exported CCRs.
+pub const COLUMNS: &[Column] = &[
+    column("tx_hash", ColumnType::Utf8, true),
+    column("sender", ColumnType::Utf8, true),
+    column("chain_id", ColumnType::UInt64, true),
+    column("nonce", ColumnType::UInt64, true),
+    column("gas", ColumnType::Utf8, true),
+    column("gas_price", ColumnType::Utf8, true),
+    column("to", ColumnType::Utf8, true),
+    column("value", ColumnType::Utf8, false),
+    column("kettle_address", ColumnType::Utf8, true),
+    column("confidential_inputs_hash", ColumnType::Utf8, true),
+    column("input_len", ColumnType::UInt64, false),
+    column("confidential_inputs_len", ColumnType::UInt64, false),
+    column("max_priority_fee_per_gas", ColumnType::Utf8, true),
+    column("max_fee_per_gas", ColumnType::Utf8, true),
+];
+
+/// One exported CCR, fields in `COLUMNS` order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CcrRow {
+    pub tx_hash: Option<String>,
+    pub sender: Option<Address>,
+    pub chain_id: Option<u64>,
+    pub nonce: Option<u64>,
+    pub gas: Option<u128>,
+    /// Legacy gas price, `None` for dynamic fee records.
+    pub gas_price: Option<u128>,
+    /// `None` for contract creation.
+    pub to: Option<Address>,
+    pub value: String,
+    pub kettle_address: Option<Address>,
+    pub confidential_inputs_hash: Option<String>,
+    pub input_len: u64,
+    pub confidential_inputs_len: u64,
+    pub max_priority_fee_per_gas: Option<u128>,
+    pub max_fee_per_gas: Option<u128>,
+}
+
+impl From<&ConfidentialComputeRequest> for CcrRow {
+    fn from(ccr: &ConfidentialComputeRequest) -> Self {
+        let record = &ccr.confidential_compute_record;
+        let sender = ccr.recover_signer().ok();
+        let (gas_price, max_priority_fee_per_gas, max_fee_per_gas) = match record.fees() {
+            Some(Fees::Legacy { gas_price }) => (Some(gas_price), None, None),
+            Some(Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas }) =>
+                (None, Some(max_priority_fee_per_gas), Some(max_fee_per_gas)),
+            None => (None, None, None),
+        };
+        Self {
+            tx_hash: ccr.tx_hash().ok().map(|hash| hash.to_string()),
+            sender,
+            chain_id: record.chain_id,
+            nonce: record.nonce,
+            gas: record.gas,
+            gas_price,
+            to: record.target(),
+            value: record.value.to_string(),
+            kettle_address: record.kettle_address,
+            confidential_inputs_hash: record.confidential_inputs_hash.map(|hash| hash.to_string()),
+            input_len: record.input.len() as u64,
+            confidential_inputs_len: ccr.confidential_inputs.len() as u64,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+        }
+    }
+}
+
+impl CcrRow {
+
+    /// Values as text in `COLUMNS` order; `None` for nulls.
+    pub fn values(&self) -> Vec<Option<String>> {
+        vec![
+            self.tx_hash.clone(),
+            self.sender.map(|a| a.to_string()),
+            self.chain_id.map(|v| v.to_string()),
+            self.nonce.map(|v| v.to_string()),
+            self.gas.map(|v| v.to_string()),
+            self.gas_price.map(|v| v.to_string()),
+            self.to.map(|a| a.to_string()),
+            Some(self.value.clone()),
+            self.kettle_address.map(|a| a.to_string()),
+            self.confidential_inputs_hash.clone(),
+            Some(self.input_len.to_string()),
+            Some(self.confidential_inputs_len.to_string()),
+            self.max_priority_fee_per_gas.map(|v| v.to_string()),
+            self.max_fee_per_gas.map(|v| v.to_string()),
+        ]
+    }
+
+}
+
+/// Streaming CSV writer; the header is written before the first row.
+pub struct CsvExporter<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl<W: Write> CsvExporter<W> {
+
+    pub fn new(writer: W) -> Self {
+        Self { writer, header_written: false }
+    }
+
+    pub fn write(&mut self, ccr: &ConfidentialComputeRequest) -> io::Result<()> {
+        self.write_row(&CcrRow::from(ccr))
+    }
+
+    pub fn write_row(&mut self, row: &CcrRow) -> io::Result<()> {
+        if !self.header_written {
+            let header = COLUMNS.iter().map(|c| c.name).collect::<Vec<_>>().join(",");
+            writeln!(self.writer, "{header}")?;
+            self.header_written = true;
+        }
+        let line = row.values().into_iter()
+            .map(|value| value.map(|v| csv_escape(&v)).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(self.writer, "{line}")
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(feature = "parquet")]
+pub use self::parquet_export::{arrow_schema, to_record_batch, write_parquet};
+
+#[cfg(feature = "parquet")]
+mod parquet_export {
+    use std::{io::Write, sync::Arc};
+    use arrow::{
+        array::{ArrayRef, StringArray, UInt64Array},
+        datatypes::{DataType, Field, Schema},
+        error::ArrowError,
+        record_batch::RecordBatch,
+    };
+    use parquet::{arrow::ArrowWriter, errors::ParquetError};
+    use super::{CcrRow, ColumnType, COLUMNS};
+
+    pub fn arrow_schema() -> Schema {
+        let fields = COLUMNS.iter().map(|c| {
+            let ty = match c.ty {
+                ColumnType::Utf8 => DataType::Utf8,
+                ColumnType::UInt64 => DataType::UInt64,
+            };
+            Field::new(c.name, ty, c.nullable)
+        });
+        Schema::new(fields.collect::<Vec<_>>())
+    }
+
+    pub fn to_record_batch(rows: &[CcrRow]) -> Result<RecordBatch, ArrowError> {
+        let values = rows.iter().map(CcrRow::values).collect::<Vec<_>>();
+        let arrays = COLUMNS.iter().enumerate().map(|(idx, c)| {
+            let column = values.iter().map(|row| row[idx].as_deref());
+            match c.ty {
+                ColumnType::Utf8 => Arc::new(column.collect::<StringArray>()) as ArrayRef,
+                ColumnType::UInt64 => Arc::new(
+                    column.map(|v| v.and_then(|v| v.parse::<u64>().ok())).collect::<UInt64Array>()
+                ) as ArrayRef,
+            }
+        });
+        RecordBatch::try_new(Arc::new(arrow_schema()), arrays.collect())
+    }
+
+    /// Write the rows as a single row group Parquet file.
+    pub fn write_parquet<W: Write + Send>(rows: &[CcrRow], writer: W) -> Result<(), ParquetError> {
+        let batch = to_record_batch(rows)?;
+        let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_csv_export() {
+        let mut exporter = CsvExporter::new(Vec::new());
+        let fixtures = fixtures::load_fixtures();
+        for fixture in &fixtures {
+            exporter.write(&fixture.request).unwrap();
+        }
+        let csv = String::from_utf8(exporter.into_inner()).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), fixtures.len() + 1);
+        assert!(lines[0].starts_with("tx_hash,sender,chain_id,nonce"));
+        for (line, fixture) in lines[1..].iter().zip(&fixtures) {
+            assert_eq!(line.split(',').count(), COLUMNS.len());
+            assert!(line.starts_with(&fixture.request.tx_hash().unwrap().to_string()));
+        }
+        assert_eq!(csv_escape("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn test_row_sender() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let row = CcrRow::from(&fixture.request);
+        assert_eq!(row.sender, Some(fixture.signer));
+        assert_eq!(row.values().len(), COLUMNS.len());
+
+        let unsigned = CcrRow::from(&ConfidentialComputeRequest::default());
+        assert_eq!(unsigned.sender, None);
+        assert_eq!(unsigned.tx_hash, None);
+    }
+
+    #[test]
+    fn test_row_dynamic_fees() {
+        let mut request = fixtures::load_fixture("empty_inputs").unwrap().request;
+        request.confidential_compute_record.set_fees(Fees::Dynamic { max_priority_fee_per_gas: 1, max_fee_per_gas: 2 });
+        let row = CcrRow::from(&request);
+        assert_eq!((row.gas_price, row.max_priority_fee_per_gas, row.max_fee_per_gas), (None, Some(1), Some(2)));
+        let values = row.values();
+        assert_eq!(values[values.len() - 2..], [Some("1".to_string()), Some("2".to_string())]);
+    }
+
+}
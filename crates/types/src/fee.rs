@@ -0,0 +1,88 @@
+use alloy::primitives::{
+    utils::{format_ether, parse_ether, UnitsError},
+    U256,
+};
+
+
+/// Symbol of the SUAVE gas token; amounts are in wei (18 decimals) like ETH.
+pub const GAS_TOKEN_SYMBOL: &str = "SETH";
+
+/// USD price of one SETH, eg. fetched from an exchange API; `None` if it
+/// isn't known. Implemented for closures.
+pub trait UsdOracle {
+    fn seth_usd(&self) -> Option<f64>;
+}
+
+impl<F> UsdOracle for F
+    where F: Fn() -> Option<f64>
+{
+    fn seth_usd(&self) -> Option<f64> {
+        self()
+    }
+}
+
+/// `wei` in SETH without trailing zeros, eg. `0.021 SETH`.
+pub fn format_fee(wei: U256) -> String {
+    let seth = format_ether(wei);
+    let seth = match seth.split_once('.') {
+        Some((int, frac)) if frac.trim_end_matches('0').is_empty() => int.to_string(),
+        Some((int, frac)) => format!("{int}.{}", frac.trim_end_matches('0')),
+        None => seth,
+    };
+    format!("{seth} {GAS_TOKEN_SYMBOL}")
+}
+
+/// `format_fee` followed by the USD value if the oracle has a price, eg.
+/// `0.021 SETH (~$52.50)`.
+pub fn format_fee_usd(wei: U256, oracle: &dyn UsdOracle) -> String {
+    match fee_to_usd(wei, oracle) {
+        Some(usd) => format!("{} (~${usd:.2})", format_fee(wei)),
+        None => format_fee(wei),
+    }
+}
+
+/// Wei in a decimal SETH amount, eg. `"0.5"` (an optional `SETH` suffix is
+/// accepted).
+pub fn parse_fee(seth: &str) -> Result<U256, UnitsError> {
+    let seth = seth.trim();
+    parse_ether(seth.strip_suffix(GAS_TOKEN_SYMBOL).unwrap_or(seth).trim_end())
+}
+
+/// `wei` in SETH, rounded to an `f64`; for display and pricing only.
+pub fn fee_to_seth(wei: U256) -> f64 {
+    format_ether(wei).parse().unwrap_or(f64::INFINITY)
+}
+
+pub fn fee_to_usd(wei: U256, oracle: &dyn UsdOracle) -> Option<f64> {
+    oracle.seth_usd().map(|price| fee_to_seth(wei) * price)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_and_parse_fee() {
+        let fee = U256::from(0x0f4240u64) * U256::from(0x3b9aca00u64);
+        assert_eq!(format_fee(fee), "0.001 SETH");
+        assert_eq!(format_fee(U256::ZERO), "0 SETH");
+        assert_eq!(format_fee(U256::from(2) * U256::from(10).pow(U256::from(18))), "2 SETH");
+        assert_eq!(format_fee(U256::from(1)), "0.000000000000000001 SETH");
+
+        assert_eq!(parse_fee("0.001 SETH").unwrap(), fee);
+        assert_eq!(parse_fee("0.001").unwrap(), fee);
+        assert!(parse_fee("a lot").is_err());
+        assert_eq!(fee_to_seth(fee), 0.001);
+    }
+
+    #[test]
+    fn test_usd_pricing() {
+        let fee = parse_fee("0.021").unwrap();
+        let oracle = || Some(2500.0);
+        assert!((fee_to_usd(fee, &oracle).unwrap() - 52.5).abs() < 1e-9);
+        assert_eq!(format_fee_usd(fee, &oracle), "0.021 SETH (~$52.50)");
+        assert_eq!(format_fee_usd(fee, &|| None::<f64>), "0.021 SETH");
+    }
+
+}
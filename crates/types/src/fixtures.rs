@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use alloy::primitives::{Address, Bytes, B256};
+use super::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+
+pub(crate) const FIXTURES: &[(&str, &str)] = &[
+    ("bundle_inputs", include_str!("../fixtures/bundle_inputs.json")),
+    ("empty_inputs", include_str!("../fixtures/empty_inputs.json")),
+    ("value_transfer", include_str!("../fixtures/value_transfer.json")),
+];
+
+/// Golden CCR test vector: raw envelope together with its JSON form and
+/// the hashes derived from it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CcrFixture {
+    pub name: String,
+    pub description: String,
+    /// 2718 encoded signed request (type 0x43).
+    pub raw: Bytes,
+    pub request: ConfidentialComputeRequest,
+    pub confidential_inputs_hash: B256,
+    /// Keccak of the 0x42 prefixed record that is signed.
+    pub signing_hash: B256,
+    /// Hash reported by the node: keccak of the 0x42 prefixed signed record.
+    pub tx_hash: B256,
+    pub signer: Address,
+}
+
+/// Load all bundled fixtures, eg. to check a downstream implementation
+/// against the same vectors this crate is tested with.
+pub fn load_fixtures() -> Vec<CcrFixture> {
+    FIXTURES.iter()
+        .map(|(name, json)| parse_fixture(name, json))
+        .collect()
+}
+
+pub fn load_fixture(name: &str) -> Option<CcrFixture> {
+    FIXTURES.iter()
+        .find(|(fixture_name, _)| *fixture_name == name)
+        .map(|(name, json)| parse_fixture(name, json))
+}
+
+/// Unsigned record with every field signing needs set: nonce 0, 1M gas at
+/// 1 gwei on Rigil, kettle `0x0303..03`. For tests and benchmarks building
+/// requests of their own.
+pub fn unsigned_record() -> ConfidentialComputeRecord {
+    let mut record = ConfidentialComputeRecord::default();
+    record.nonce = Some(0);
+    record.gas = Some(0x0f4240);
+    record.gas_price = Some(0x3b9aca00);
+    record.chain_id = Some(0x1008c45);
+    record.kettle_address = Some(Address::repeat_byte(0x03));
+    record
+}
+
+/// Path of the directory holding the raw fixture files.
+pub fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+fn parse_fixture(name: &str, json: &str) -> CcrFixture {
+    serde_json::from_str(json)
+        .unwrap_or_else(|e| panic!("Invalid fixture {name}: {e}"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives,
+        consensus::SignableTransaction,
+        eips::eip2718::{Decodable2718, Encodable2718},
+    };
+    use super::*;
+
+    #[test]
+    fn test_fixtures_dir_matches_bundled() {
+        let mut on_disk = std::fs::read_dir(fixtures_dir()).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .map(|path| path.file_stem().unwrap().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        on_disk.sort();
+        let bundled = FIXTURES.iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>();
+        assert_eq!(on_disk, bundled);
+        for fixture in load_fixtures() {
+            assert_eq!(load_fixture(&fixture.name), Some(fixture));
+        }
+    }
+
+    #[test]
+    fn test_fixtures_decode() {
+        for fixture in load_fixtures() {
+            let decoded = ConfidentialComputeRequest::decode_2718(&mut fixture.raw.as_ref())
+                .unwrap_or_else(|e| panic!("{}: {e}", fixture.name));
+            assert_eq!(decoded, fixture.request, "{}", fixture.name);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_encode() {
+        for fixture in load_fixtures() {
+            let mut encoded = Vec::new();
+            fixture.request.encode_2718(&mut encoded);
+            assert_eq!(Bytes::from(encoded), fixture.raw, "{}", fixture.name);
+            assert_eq!(fixture.request.rlp_encode().unwrap(), fixture.raw, "{}", fixture.name);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_hashes() {
+        for fixture in load_fixtures() {
+            let record = &fixture.request.confidential_compute_record;
+            let inputs_hash = primitives::keccak256(&fixture.request.confidential_inputs);
+            assert_eq!(inputs_hash, fixture.confidential_inputs_hash, "{}", fixture.name);
+            assert_eq!(record.confidential_inputs_hash, Some(inputs_hash), "{}", fixture.name);
+            assert_eq!(fixture.request.signature_hash(), fixture.signing_hash, "{}", fixture.name);
+            assert_eq!(fixture.request.tx_hash().unwrap(), fixture.tx_hash, "{}", fixture.name);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_signer() {
+        for fixture in load_fixtures() {
+            let sig = fixture.request.confidential_compute_record.signature.unwrap();
+            let signer = sig.recover_address_from_prehash(&fixture.signing_hash).unwrap();
+            assert_eq!(signer, fixture.signer, "{}", fixture.name);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_json_roundtrip() {
+        for fixture in load_fixtures() {
+            let json = serde_json::to_value(&fixture.request).unwrap();
+            let parsed: ConfidentialComputeRequest = serde_json::from_value(json).unwrap();
+            assert_eq!(parsed, fixture.request, "{}", fixture.name);
+        }
+    }
+
+}
@@ -0,0 +1,235 @@
+//! Generators of CCR records and requests for property tests and fuzz
+//! targets: `arbitrary::Arbitrary` impls (feature `arbitrary`) and
+//! proptest strategies (feature `proptest`).
+//!
+//! Records get well-formed but random signatures; requests are signed
+//! with a generated key, so their signature recovers. Extra fields and
+//! signing domains other than the default are left out.
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn roundtrip(request in fuzzing::any_request()) {
+//!         let raw = request.encoded_2718();
+//!         prop_assert_eq!(ConfidentialComputeRequest::decode_2718(&mut raw.as_slice())?, request);
+//!     }
+//! }
+//! ```
+
+use alloy::{
+    primitives::{Address, B256},
+    signers::{wallet::LocalWallet, SignerSync},
+};
+use super::ConfidentialComputeRequest;
+
+
+/// Key used where the generated one is not a valid secp256k1 scalar (eg.
+/// all zeros from exhausted fuzzer input).
+const FALLBACK_KEY: [u8; 32] = [0x11; 32];
+
+/// Wallet for a generated key.
+pub fn wallet(key: [u8; 32]) -> LocalWallet {
+    LocalWallet::from_bytes(&B256::from(key))
+        .unwrap_or_else(|_| LocalWallet::from_bytes(&B256::from(FALLBACK_KEY)).expect("Valid key"))
+}
+
+/// `request` with its record signed by `wallet`.
+pub fn signed(mut request: ConfidentialComputeRequest, wallet: &LocalWallet) -> ConfidentialComputeRequest {
+    let hash = request.signing_hash().expect("Complete generated record");
+    let signature = wallet.sign_hash_sync(&hash).expect("Signing a hash");
+    request.confidential_compute_record.set_sig(signature.with_parity_bool());
+    request
+}
+
+fn address(bytes: [u8; 20]) -> Address {
+    Address::from(bytes)
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use arbitrary::{Arbitrary, Unstructured};
+    use alloy::{
+        eips::eip2930::{AccessList, AccessListItem},
+        primitives::{Bytes, Signature, TxKind, B256, U256},
+    };
+    use suave_alloy_core::Fees;
+    use super::{address, signed, wallet};
+    use super::super::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+    impl<'a> Arbitrary<'a> for ConfidentialComputeRecord {
+        fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+            let to = match u.ratio(1, 10)? {
+                true => TxKind::Create,
+                false => TxKind::Call(address(u.arbitrary()?)),
+            };
+            let access_list = match u.ratio(1, 5)? {
+                true => Some(AccessList(
+                    u.arbitrary_iter::<([u8; 20], Vec<[u8; 32]>)>()?
+                        .map(|item| item.map(|(item_address, keys)| AccessListItem {
+                            address: address(item_address),
+                            storage_keys: keys.into_iter().map(B256::from).collect(),
+                        }))
+                        .collect::<arbitrary::Result<_>>()?,
+                )),
+                false => None,
+            };
+            let fees = match u.arbitrary()? {
+                true => Fees::Legacy { gas_price: u.arbitrary()? },
+                false => Fees::Dynamic { max_priority_fee_per_gas: u.arbitrary()?, max_fee_per_gas: u.arbitrary()? },
+            };
+            let signature = Signature::from_rs_and_parity(
+                U256::from_limbs(u.arbitrary()?),
+                U256::from_limbs(u.arbitrary()?),
+                u.int_in_range(0u64..=1)?,
+            ).map_err(|_| arbitrary::Error::IncorrectFormat)?;
+            let mut record = ConfidentialComputeRecord {
+                nonce: Some(u.arbitrary()?),
+                to,
+                gas: Some(u.arbitrary()?),
+                value: U256::from_limbs(u.arbitrary()?),
+                input: Bytes::from(u.arbitrary::<Vec<u8>>()?),
+                access_list,
+                kettle_address: Some(address(u.arbitrary()?)),
+                chain_id: Some(u.arbitrary()?),
+                confidential_inputs_hash: Some(B256::from(u.arbitrary::<[u8; 32]>()?)),
+                signature: Some(signature),
+                ..Default::default()
+            };
+            record.set_fees(fees);
+            Ok(record)
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for ConfidentialComputeRequest {
+        fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+            let record = ConfidentialComputeRecord::arbitrary(u)?;
+            let inputs = Bytes::from(u.arbitrary::<Vec<u8>>()?);
+            let wallet = wallet(u.arbitrary()?);
+            Ok(signed(ConfidentialComputeRequest::new(record, Some(inputs)), &wallet))
+        }
+    }
+
+}
+
+#[cfg(feature = "proptest")]
+pub use strategies::{any_record, any_request};
+
+#[cfg(feature = "proptest")]
+mod strategies {
+    use proptest::{collection::vec, option, prelude::*};
+    use alloy::{
+        eips::eip2930::{AccessList, AccessListItem},
+        primitives::{Bytes, Signature, TxKind, B256, U256},
+    };
+    use suave_alloy_core::Fees;
+    use super::{address, signed, wallet};
+    use super::super::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+    fn fees() -> impl Strategy<Value = Fees> {
+        prop_oneof![
+            any::<u128>().prop_map(|gas_price| Fees::Legacy { gas_price }),
+            (any::<u128>(), any::<u128>()).prop_map(|(max_priority_fee_per_gas, max_fee_per_gas)| {
+                Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas }
+            }),
+        ]
+    }
+
+    fn access_list() -> impl Strategy<Value = AccessList> {
+        vec((any::<[u8; 20]>(), vec(any::<[u8; 32]>(), 0..3)), 0..3).prop_map(|items| {
+            AccessList(items.into_iter().map(|(item_address, keys)| AccessListItem {
+                address: address(item_address),
+                storage_keys: keys.into_iter().map(B256::from).collect(),
+            }).collect())
+        })
+    }
+
+    fn signature() -> impl Strategy<Value = Signature> {
+        (any::<[u64; 4]>(), any::<[u64; 4]>(), 0u64..=1).prop_map(|(r, s, parity)| {
+            Signature::from_rs_and_parity(U256::from_limbs(r), U256::from_limbs(s), parity).expect("Parity 0 or 1")
+        })
+    }
+
+    /// Complete records with a random (not recovering) signature.
+    pub fn any_record() -> impl Strategy<Value = ConfidentialComputeRecord> {
+        (
+            any::<u64>(),
+            fees(),
+            any::<u128>(),
+            option::weighted(0.9, any::<[u8; 20]>()),
+            any::<[u64; 4]>(),
+            vec(any::<u8>(), 0..1024),
+            option::weighted(0.2, access_list()),
+            any::<[u8; 20]>(),
+            any::<u64>(),
+            any::<[u8; 32]>(),
+            signature(),
+        ).prop_map(|(nonce, fees, gas, to, value, input, access_list, kettle, chain_id, inputs_hash, signature)| {
+            let mut record = ConfidentialComputeRecord {
+                nonce: Some(nonce),
+                to: to.map_or(TxKind::Create, |to| TxKind::Call(address(to))),
+                gas: Some(gas),
+                value: U256::from_limbs(value),
+                input: Bytes::from(input),
+                access_list,
+                kettle_address: Some(address(kettle)),
+                chain_id: Some(chain_id),
+                confidential_inputs_hash: Some(B256::from(inputs_hash)),
+                signature: Some(signature),
+                ..Default::default()
+            };
+            record.set_fees(fees);
+            record
+        })
+    }
+
+    /// Requests signed by a generated key, with the inputs hash matching.
+    pub fn any_request() -> impl Strategy<Value = ConfidentialComputeRequest> {
+        (any_record(), vec(any::<u8>(), 0..1024), any::<[u8; 32]>()).prop_map(|(record, inputs, key)| {
+            signed(ConfidentialComputeRequest::new(record, Some(inputs.into())), &wallet(key))
+        })
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::eips::eip2718::{Decodable2718, Encodable2718};
+    use super::*;
+
+    #[test]
+    fn test_fallback_key() {
+        let fallback = wallet(FALLBACK_KEY).address();
+        assert_eq!(wallet([0; 32]).address(), fallback);
+        assert_eq!(wallet([0xff; 32]).address(), fallback);
+        assert_ne!(wallet([0x22; 32]).address(), fallback);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_roundtrip() {
+        use arbitrary::{Arbitrary, Unstructured};
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0x43);
+        for _ in 0..64 {
+            let mut data = vec![0u8; rng.gen_range(0..4096)];
+            rng.fill(&mut data[..]);
+            let request = ConfidentialComputeRequest::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            let decoded = ConfidentialComputeRequest::decode_2718(&mut request.encoded_2718().as_slice()).unwrap();
+            assert_eq!(decoded, request);
+            assert!(request.recover_signer().is_ok());
+            request.validate_inputs_hash().unwrap();
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn test_proptest_roundtrip(request in any_request()) {
+            let decoded = ConfidentialComputeRequest::decode_2718(&mut request.encoded_2718().as_slice()).unwrap();
+            proptest::prop_assert_eq!(&decoded, &request);
+            proptest::prop_assert!(request.recover_signer().is_ok());
+        }
+    }
+
+}
@@ -0,0 +1,209 @@
+//! Import of the CCR test vectors exported by suave-geth's Go tests, so the
+//! Rust and Go encodings are checked against each other.
+//!
+//! A vector holds the transaction in the JSON form go-ethereum marshals it
+//! to, along with the outputs the Go implementation derived from it:
+//! ```json
+//! { "name": "...", "tx": { "type": "0x43", "nonce": "0x0", ... }, "rlp": "0x43...",
+//!   "sigHash": "0x...", "hash": "0x...", "sender": "0x..." }
+//! ```
+
+use std::path::Path;
+use serde::Deserialize;
+use alloy::{
+    primitives::{keccak256, Address, Bytes, Signature, TxKind, B256, U256},
+    serde as alloy_serde,
+};
+use super::{
+    fixtures::CcrFixture,
+    CcrError,
+    ConfidentialComputeRecord,
+    ConfidentialComputeRequest,
+};
+
+
+const VECTORS: &[(&str, &str)] = &[
+    ("empty_inputs", include_str!("../fixtures/suave-geth/empty_inputs.json")),
+    ("value_transfer", include_str!("../fixtures/suave-geth/value_transfer.json")),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethVector {
+    pub name: String,
+    pub tx: GethTx,
+    /// 2718 encoding produced by the Go implementation.
+    pub rlp: Bytes,
+    pub sig_hash: B256,
+    pub hash: B256,
+    pub sender: Address,
+}
+
+/// Signed CCR as marshaled by suave-geth (`txJSON`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethTx {
+    #[serde(rename = "type", with = "alloy_serde::num::u64_hex")]
+    pub tx_type: u64,
+    #[serde(with = "alloy_serde::num::u64_hex")]
+    pub nonce: u64,
+    pub to: Option<Address>,
+    #[serde(with = "alloy_serde::num::u128_hex_or_decimal")]
+    pub gas: u128,
+    #[serde(with = "alloy_serde::num::u128_hex_or_decimal")]
+    pub gas_price: u128,
+    pub value: U256,
+    pub input: Bytes,
+    pub kettle_address: Address,
+    pub confidential_inputs_hash: B256,
+    #[serde(default)]
+    pub confidential_inputs: Bytes,
+    #[serde(default, rename = "isEIP712")]
+    pub is_eip712: bool,
+    #[serde(with = "alloy_serde::num::u64_hex")]
+    pub chain_id: u64,
+    #[serde(with = "alloy_serde::num::u64_hex")]
+    pub v: u64,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl TryFrom<GethVector> for CcrFixture {
+    type Error = CcrError;
+
+    fn try_from(vector: GethVector) -> Result<Self, CcrError> {
+        let tx = vector.tx;
+        if tx.tx_type != ConfidentialComputeRequest::TYPE as u64 {
+            return Err(CcrError::Unsupported("transaction type"));
+        }
+        // suave-geth encodes the flag in the record, this crate's encoding has
+        // no room for it
+        if tx.is_eip712 {
+            return Err(CcrError::Unsupported("EIP-712 record"));
+        }
+        let signature = Signature::from_rs_and_parity(tx.r, tx.s, tx.v)
+            .map_err(|_| CcrError::InvalidSignature)?;
+        let record = ConfidentialComputeRecord {
+            nonce: Some(tx.nonce),
+            to: tx.to.map_or(TxKind::Create, TxKind::Call),
+            gas: Some(tx.gas),
+            gas_price: Some(tx.gas_price),
+            value: tx.value,
+            input: tx.input,
+            kettle_address: Some(tx.kettle_address),
+            chain_id: Some(tx.chain_id),
+            confidential_inputs_hash: Some(tx.confidential_inputs_hash),
+            signature: Some(signature),
+            ..Default::default()
+        };
+        Ok(CcrFixture {
+            description: format!("suave-geth vector {}", vector.name),
+            name: vector.name,
+            raw: vector.rlp,
+            request: ConfidentialComputeRequest {
+                confidential_compute_record: record,
+                confidential_inputs: tx.confidential_inputs.clone(),
+            },
+            confidential_inputs_hash: keccak256(&tx.confidential_inputs),
+            signing_hash: vector.sig_hash,
+            tx_hash: vector.hash,
+            signer: vector.sender,
+        })
+    }
+}
+
+/// Convert a suave-geth vector to the fixture format of this crate.
+pub fn import(json: &str) -> Result<CcrFixture, CcrError> {
+    let vector: GethVector = serde_json::from_str(json)
+        .map_err(|e| CcrError::Json(e.to_string()))?;
+    vector.try_into()
+}
+
+/// Import every `.json` vector in `dir`, eg. the output of the Go tests.
+pub fn import_dir(dir: impl AsRef<Path>) -> Result<Vec<CcrFixture>, CcrError> {
+    let dir = dir.as_ref();
+    let io_error = |path: &Path, e: std::io::Error| CcrError::Io(format!("{}: {e}", path.display()));
+    let mut paths = std::fs::read_dir(dir)
+        .and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>())
+        .map_err(|e| io_error(dir, e))?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+    paths.iter()
+        .map(|path| import(&std::fs::read_to_string(path).map_err(|e| io_error(path, e))?))
+        .collect()
+}
+
+/// Vectors bundled with the crate, converted to fixtures.
+pub fn load_vectors() -> Vec<CcrFixture> {
+    VECTORS.iter()
+        .map(|(name, json)| import(json).unwrap_or_else(|e| panic!("Invalid vector {name}: {e}")))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::SignableTransaction,
+        eips::eip2718::{Decodable2718, Encodable2718},
+    };
+    use super::*;
+
+    fn check(fixture: &CcrFixture) {
+        let name = &fixture.name;
+        let decoded = ConfidentialComputeRequest::decode_2718(&mut fixture.raw.as_ref())
+            .unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert_eq!(decoded, fixture.request, "{name}");
+
+        let mut encoded = Vec::new();
+        fixture.request.encode_2718(&mut encoded);
+        assert_eq!(Bytes::from(encoded), fixture.raw, "{name}");
+
+        let record = &fixture.request.confidential_compute_record;
+        assert_eq!(record.confidential_inputs_hash, Some(fixture.confidential_inputs_hash), "{name}");
+        assert_eq!(fixture.request.signature_hash(), fixture.signing_hash, "{name}");
+        assert_eq!(fixture.request.tx_hash().unwrap(), fixture.tx_hash, "{name}");
+        let signer = record.signature.unwrap().recover_address_from_prehash(&fixture.signing_hash).unwrap();
+        assert_eq!(signer, fixture.signer, "{name}");
+    }
+
+    #[test]
+    fn test_bundled_vectors() {
+        let vectors = load_vectors();
+        assert_eq!(vectors.len(), VECTORS.len());
+        vectors.iter().for_each(check);
+    }
+
+    #[test]
+    fn test_vectors_match_fixtures() {
+        for vector in load_vectors() {
+            let fixture = crate::fixtures::load_fixture(&vector.name).unwrap();
+            assert_eq!(vector.request, fixture.request, "{}", vector.name);
+            assert_eq!(
+                (vector.raw, vector.signing_hash, vector.tx_hash, vector.signer),
+                (fixture.raw, fixture.signing_hash, fixture.tx_hash, fixture.signer),
+            );
+        }
+    }
+
+    /// Set `SUAVE_GETH_VECTORS` to a directory of vectors exported by the Go
+    /// tests to check those as well.
+    #[test]
+    fn test_external_vectors() {
+        let Ok(dir) = std::env::var("SUAVE_GETH_VECTORS") else { return };
+        let vectors = import_dir(dir).unwrap();
+        assert!(!vectors.is_empty());
+        vectors.iter().for_each(check);
+    }
+
+    #[test]
+    fn test_import_rejects() {
+        let json = VECTORS[0].1;
+        let eip712 = json.replace("\"chainId\"", "\"isEIP712\": true, \"chainId\"");
+        assert!(matches!(import(&eip712), Err(CcrError::Unsupported(_))));
+        let legacy = json.replace("\"type\": \"0x43\"", "\"type\": \"0x0\"");
+        assert!(matches!(import(&legacy), Err(CcrError::Unsupported(_))));
+        assert!(matches!(import("{}"), Err(CcrError::Json(_))));
+    }
+
+}
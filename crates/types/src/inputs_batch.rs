@@ -0,0 +1,196 @@
+use alloy::primitives::Bytes;
+use super::ConfidentialComputeRequest;
+
+
+/// Many small application messages (eg. orders) packed into a single
+/// confidential inputs blob: magic, an index header of LEB128 varints
+/// (message count, then every message length) and the concatenated
+/// messages. Messages under 128 bytes cost a single byte of overhead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputsBatch {
+    pub messages: Vec<Bytes>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputsBatchError {
+    NotABatch,
+    /// Header varint longer than 64 bits or cut off.
+    InvalidHeader,
+    /// Lengths in the header exceed the carried data.
+    Truncated { expected: usize, available: usize },
+    TrailingBytes(usize),
+}
+
+impl std::fmt::Display for InputsBatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputsBatchError::NotABatch => write!(f, "Inputs are not a batch"),
+            InputsBatchError::InvalidHeader => write!(f, "Invalid batch index header"),
+            InputsBatchError::Truncated { expected, available } =>
+                write!(f, "Batch index expects {expected} bytes of messages, {available} available"),
+            InputsBatchError::TrailingBytes(len) => write!(f, "{len} trailing bytes after the batch messages"),
+        }
+    }
+}
+
+impl std::error::Error for InputsBatchError {}
+
+impl InputsBatch {
+    /// "SCB" followed by the format version.
+    pub const MAGIC: [u8; 4] = [0x53, 0x43, 0x42, 0x01];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, message: impl Into<Bytes>) {
+        self.messages.push(message.into());
+    }
+
+    pub fn with_message(mut self, message: impl Into<Bytes>) -> Self {
+        self.push(message);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn is_batch(confidential_inputs: &[u8]) -> bool {
+        confidential_inputs.starts_with(&Self::MAGIC)
+    }
+
+    pub fn encoded_len(&self) -> usize {
+        let header = varint_len(self.messages.len() as u64)
+            + self.messages.iter().map(|m| varint_len(m.len() as u64)).sum::<usize>();
+        Self::MAGIC.len() + header + self.messages.iter().map(Bytes::len).sum::<usize>()
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut out = Vec::with_capacity(self.encoded_len());
+        out.extend_from_slice(&Self::MAGIC);
+        put_varint(&mut out, self.messages.len() as u64);
+        for message in &self.messages {
+            put_varint(&mut out, message.len() as u64);
+        }
+        for message in &self.messages {
+            out.extend_from_slice(message);
+        }
+        out.into()
+    }
+
+    pub fn decode(confidential_inputs: &[u8]) -> Result<Self, InputsBatchError> {
+        if !Self::is_batch(confidential_inputs) {
+            return Err(InputsBatchError::NotABatch);
+        }
+        let mut buf = &confidential_inputs[Self::MAGIC.len()..];
+        let count = get_varint(&mut buf)?;
+        // Every length takes at least a byte, bounding the allocation
+        if count > buf.len() as u64 {
+            return Err(InputsBatchError::InvalidHeader);
+        }
+        let lengths = (0..count)
+            .map(|_| get_varint(&mut buf).map(|len| len as usize))
+            .collect::<Result<Vec<_>, _>>()?;
+        let expected = lengths.iter().try_fold(0usize, |acc, len| acc.checked_add(*len))
+            .ok_or(InputsBatchError::InvalidHeader)?;
+        if expected > buf.len() {
+            return Err(InputsBatchError::Truncated { expected, available: buf.len() });
+        }
+        if expected < buf.len() {
+            return Err(InputsBatchError::TrailingBytes(buf.len() - expected));
+        }
+        let messages = lengths.into_iter()
+            .map(|len| {
+                let (message, rest) = buf.split_at(len);
+                buf = rest;
+                Bytes::copy_from_slice(message)
+            })
+            .collect();
+        Ok(Self { messages })
+    }
+
+}
+
+fn varint_len(mut value: u64) -> usize {
+    let mut len = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn get_varint(buf: &mut &[u8]) -> Result<u64, InputsBatchError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first().ok_or(InputsBatchError::InvalidHeader)?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(InputsBatchError::InvalidHeader)
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Set the encoded batch as confidential inputs (updating their hash).
+    pub fn with_inputs_batch(self, batch: &InputsBatch) -> Self {
+        self.with_confidential_inputs(batch.encode())
+    }
+
+    pub fn inputs_batch(&self) -> Result<InputsBatch, InputsBatchError> {
+        InputsBatch::decode(&self.confidential_inputs)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inputs_batch_roundtrip() {
+        let orders = (0..200u8).map(|i| vec![i; (i % 7) as usize]).collect::<Vec<_>>();
+        let batch = orders.iter().fold(InputsBatch::new(), |batch, order| batch.with_message(order.clone()))
+            .with_message(vec![0xab; 300]);
+        let encoded = batch.encode();
+        assert_eq!(encoded.len(), batch.encoded_len());
+        // magic + 2 byte count + 1 byte per small message + 2 for the large one
+        let data_len = orders.iter().map(Vec::len).sum::<usize>() + 300;
+        assert_eq!(encoded.len(), 4 + 2 + 200 + 2 + data_len);
+
+        let ccr = ConfidentialComputeRequest::default().with_inputs_batch(&batch);
+        assert_eq!(ccr.inputs_batch().unwrap(), batch);
+        assert_eq!(InputsBatch::decode(&InputsBatch::new().encode()).unwrap(), InputsBatch::new());
+    }
+
+    #[test]
+    fn test_malformed_batch() {
+        let encoded = InputsBatch::new().with_message(vec![1, 2, 3]).encode();
+        assert_eq!(InputsBatch::decode(b"plain"), Err(InputsBatchError::NotABatch));
+        assert_eq!(
+            InputsBatch::decode(&encoded[..encoded.len() - 1]),
+            Err(InputsBatchError::Truncated { expected: 3, available: 2 })
+        );
+        assert_eq!(InputsBatch::decode(&[&encoded[..], &[0]].concat()), Err(InputsBatchError::TrailingBytes(1)));
+        // Count far beyond the data
+        assert_eq!(InputsBatch::decode(&[&InputsBatch::MAGIC[..], &[0xff, 0x7f]].concat()), Err(InputsBatchError::InvalidHeader));
+    }
+
+}
@@ -0,0 +1,117 @@
+use std::collections::{HashMap, HashSet};
+use alloy::primitives::{keccak256, Bytes, FixedBytes};
+use super::ConfidentialComputeRequest;
+
+
+/// Cache of confidential inputs keyed by their keccak hash.
+///
+/// Identical inputs share one `Bytes` allocation across all requests built
+/// from the cache. With content-addressed submission enabled (relays that
+/// accept inputs by hash), inputs already uploaded are reported as not
+/// needing another upload.
+#[derive(Debug, Clone, Default)]
+pub struct InputsCache {
+    entries: HashMap<FixedBytes<32>, Bytes>,
+    uploaded: HashSet<FixedBytes<32>>,
+    content_addressed: bool,
+}
+
+impl InputsCache {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_content_addressed(mut self, content_addressed: bool) -> Self {
+        self.content_addressed = content_addressed;
+        self
+    }
+
+    /// Return the cached copy of `inputs` (inserting it if new) and its hash.
+    pub fn intern(&mut self, inputs: Bytes) -> (FixedBytes<32>, Bytes) {
+        let hash = keccak256(&inputs);
+        let shared = self.entries.entry(hash).or_insert(inputs).clone();
+        (hash, shared)
+    }
+
+    pub fn get(&self, hash: &FixedBytes<32>) -> Option<Bytes> {
+        self.entries.get(hash).cloned()
+    }
+
+    pub fn contains(&self, hash: &FixedBytes<32>) -> bool {
+        self.entries.contains_key(hash)
+    }
+
+    pub fn remove(&mut self, hash: &FixedBytes<32>) -> Option<Bytes> {
+        self.uploaded.remove(hash);
+        self.entries.remove(hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn mark_uploaded(&mut self, hash: FixedBytes<32>) {
+        self.uploaded.insert(hash);
+    }
+
+    /// Whether the inputs must be sent along the request. Always true
+    /// unless content-addressed submission is enabled and they were
+    /// already uploaded.
+    pub fn needs_upload(&self, hash: &FixedBytes<32>) -> bool {
+        !(self.content_addressed && self.uploaded.contains(hash))
+    }
+
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Set confidential inputs (and their hash) through `cache`, sharing
+    /// the allocation with other requests using the same inputs.
+    pub fn set_cached_confidential_inputs(&mut self, cache: &mut InputsCache, inputs: Bytes) {
+        let (hash, shared) = cache.intern(inputs);
+        self.confidential_compute_record.set_confidential_inputs_hash(hash);
+        self.confidential_inputs = shared;
+    }
+
+    pub fn with_cached_confidential_inputs(mut self, cache: &mut InputsCache, inputs: Bytes) -> Self {
+        self.set_cached_confidential_inputs(cache, inputs);
+        self
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inputs_cache() {
+        let mut cache = InputsCache::new().with_content_addressed(true);
+        let inputs = Bytes::from(vec![0xab; 4096]);
+
+        let first = ConfidentialComputeRequest::default()
+            .with_cached_confidential_inputs(&mut cache, inputs.clone());
+        let second = ConfidentialComputeRequest::default()
+            .with_cached_confidential_inputs(&mut cache, Bytes::from(vec![0xab; 4096]));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(first.confidential_inputs.as_ptr(), second.confidential_inputs.as_ptr());
+        assert_eq!(first.validate_inputs_hash(), Ok(()));
+
+        let hash = keccak256(&inputs);
+        assert!(cache.needs_upload(&hash));
+        cache.mark_uploaded(hash);
+        assert!(!cache.needs_upload(&hash));
+        assert!(InputsCache::new().needs_upload(&hash));
+
+        assert_eq!(cache.remove(&hash), Some(inputs));
+        assert!(cache.is_empty());
+        assert!(cache.needs_upload(&hash));
+    }
+
+}
@@ -0,0 +1,208 @@
+use std::str::FromStr;
+use serde_json::{Map, Value};
+use alloy::primitives::U256;
+use super::{consts::KettleFieldName, ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+
+/// Record fields rendered with `JsonFormat::quantity`.
+const QUANTITY_FIELDS: [&str; 3] = ["nonce", "value", "chainId"];
+/// Record fields rendered with `JsonFormat::gas`.
+const GAS_FIELDS: [&str; 2] = ["gas", "gasPrice"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuantityFormat {
+    /// `0x` prefixed without leading zeros, as in Ethereum JSON-RPC.
+    #[default]
+    MinimalHex,
+    /// `0x` prefixed, zero padded to the given number of hex digits.
+    PaddedHex(usize),
+    /// JSON number. Values above `u64::MAX` are written as decimal strings.
+    Number,
+    DecimalString,
+}
+
+impl QuantityFormat {
+
+    pub fn format(&self, value: U256) -> Value {
+        match self {
+            QuantityFormat::MinimalHex => Value::String(format!("0x{value:x}")),
+            QuantityFormat::PaddedHex(width) => Value::String(format!("0x{:0>width$}", format!("{value:x}"))),
+            QuantityFormat::Number => match u64::try_from(value) {
+                Ok(value) => Value::from(value),
+                Err(_) => Value::String(value.to_string()),
+            },
+            QuantityFormat::DecimalString => Value::String(value.to_string()),
+        }
+    }
+
+}
+
+/// Rendering of record quantities in JSON, for consumers expecting
+/// something else than minimal hex (the default, and the only format the
+/// `Deserialize` impls accept), and of the kettle address field name. Use
+/// `from_json_with` to read JSON in any of the formats back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonFormat {
+    /// Nonce, value and chain id.
+    pub quantity: QuantityFormat,
+    /// Gas limit and gas price.
+    pub gas: QuantityFormat,
+    /// Name of the kettle address (see `ProtocolConfig::kettle_field`).
+    pub kettle_field: KettleFieldName,
+}
+
+impl JsonFormat {
+
+    pub fn new(quantity: QuantityFormat, gas: QuantityFormat) -> Self {
+        Self { quantity, gas, kettle_field: KettleFieldName::default() }
+    }
+
+    pub fn with_kettle_field(mut self, kettle_field: KettleFieldName) -> Self {
+        self.kettle_field = kettle_field;
+        self
+    }
+
+    /// Re-render the quantities of a serialized record in this format.
+    pub fn apply(&self, record: &mut Map<String, Value>) {
+        reformat(record, &QUANTITY_FIELDS, self.quantity);
+        reformat(record, &GAS_FIELDS, self.gas);
+        if let Some(kettle_address) = record.remove(KettleFieldName::KettleAddress.as_str()) {
+            record.insert(self.kettle_field.as_str().to_string(), kettle_address);
+        }
+    }
+
+    /// Bring the quantities of a serialized record back to minimal hex.
+    pub fn normalize(record: &mut Map<String, Value>) {
+        reformat(record, &QUANTITY_FIELDS, QuantityFormat::MinimalHex);
+        reformat(record, &GAS_FIELDS, QuantityFormat::MinimalHex);
+    }
+
+}
+
+fn reformat(record: &mut Map<String, Value>, fields: &[&str], format: QuantityFormat) {
+    for field in fields {
+        if let Some(value) = record.get_mut(*field) {
+            if let Some(quantity) = parse_quantity(value) {
+                *value = format.format(quantity);
+            }
+        }
+    }
+}
+
+/// Quantity in any of the `QuantityFormat`s, `None` for nulls and
+/// unparsable values (left for serde to report).
+fn parse_quantity(value: &Value) -> Option<U256> {
+    match value {
+        Value::Number(number) => number.as_u64().map(U256::from),
+        Value::String(s) => U256::from_str(s).ok(),
+        _ => None,
+    }
+}
+
+fn record_object(value: &mut Value) -> serde_json::Result<&mut Map<String, Value>> {
+    value.as_object_mut().ok_or_else(|| serde::de::Error::custom("Expected a JSON object"))
+}
+
+impl ConfidentialComputeRecord {
+
+    pub fn to_json_with(&self, format: &JsonFormat) -> serde_json::Result<Value> {
+        let mut json = serde_json::to_value(self)?;
+        format.apply(record_object(&mut json)?);
+        Ok(json)
+    }
+
+    /// Deserialize a record with quantities in any `QuantityFormat`.
+    pub fn from_json_with(mut json: Value) -> serde_json::Result<Self> {
+        JsonFormat::normalize(record_object(&mut json)?);
+        serde_json::from_value(json)
+    }
+
+}
+
+impl ConfidentialComputeRequest {
+
+    pub fn to_json_with(&self, format: &JsonFormat) -> serde_json::Result<Value> {
+        let mut json = serde_json::to_value(self)?;
+        if let Some(record) = json.get_mut("confidentialComputeRecord") {
+            format.apply(record_object(record)?);
+        }
+        Ok(json)
+    }
+
+    /// Deserialize a request with quantities in any `QuantityFormat`.
+    pub fn from_json_with(mut json: Value) -> serde_json::Result<Self> {
+        if let Some(record) = json.get_mut("confidentialComputeRecord") {
+            JsonFormat::normalize(record_object(record)?);
+        }
+        serde_json::from_value(json)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, TxKind};
+    use super::*;
+
+    fn record() -> ConfidentialComputeRecord {
+        let mut record = ConfidentialComputeRecord::default();
+        record.nonce = Some(0x45);
+        record.to = TxKind::Call(Address::repeat_byte(0xc8));
+        record.gas = Some(0x0f4240);
+        record.gas_price = Some(0x3b9aca00);
+        record.chain_id = Some(0x1008c45);
+        record
+    }
+
+    #[test]
+    fn test_quantity_formats() {
+        let value = U256::from(0x45);
+        assert_eq!(QuantityFormat::MinimalHex.format(value), "0x45");
+        assert_eq!(QuantityFormat::PaddedHex(16).format(value), "0x0000000000000045");
+        assert_eq!(QuantityFormat::Number.format(value), 69);
+        assert_eq!(QuantityFormat::DecimalString.format(value), "69");
+        assert_eq!(QuantityFormat::Number.format(U256::MAX), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn test_record_json_formats() {
+        let record = record();
+        let default = record.to_json_with(&JsonFormat::default()).unwrap();
+        assert_eq!(default, serde_json::to_value(&record).unwrap());
+
+        let format = JsonFormat::new(QuantityFormat::PaddedHex(16), QuantityFormat::Number);
+        let json = record.to_json_with(&format).unwrap();
+        assert_eq!(json["nonce"], "0x0000000000000045");
+        assert_eq!(json["chainId"], "0x0000000001008c45");
+        assert_eq!(json["gas"], 0x0f4240);
+        assert_eq!(json["gasPrice"], 0x3b9aca00);
+        assert_eq!(ConfidentialComputeRecord::from_json_with(json).unwrap(), record);
+
+        let ccr = ConfidentialComputeRequest::new(record, None);
+        let format = JsonFormat::new(QuantityFormat::DecimalString, QuantityFormat::DecimalString);
+        let json = ccr.to_json_with(&format).unwrap();
+        assert_eq!(json["confidentialComputeRecord"]["nonce"], "69");
+        assert_eq!(ConfidentialComputeRequest::from_json_with(json).unwrap(), ccr);
+    }
+
+    #[test]
+    fn test_kettle_field_name() {
+        let mut record = record();
+        record.kettle_address = Some(Address::repeat_byte(0x03));
+        let format = JsonFormat::default().with_kettle_field(KettleFieldName::ExecutionNode);
+        let json = record.to_json_with(&format).unwrap();
+        assert!(json.get("kettleAddress").is_none());
+        assert_eq!(json["executionNode"], serde_json::to_value(record.kettle_address).unwrap());
+        assert_eq!(ConfidentialComputeRecord::from_json_with(json).unwrap(), record);
+
+        let fixture = super::super::fixtures::load_fixture("value_transfer").unwrap();
+        let json = fixture.request.to_json_with(&format).unwrap();
+        assert_eq!(
+            json["confidentialComputeRecord"]["executionNode"],
+            serde_json::to_value(fixture.request.confidential_compute_record.kettle_address).unwrap(),
+        );
+        assert_eq!(serde_json::from_value::<ConfidentialComputeRequest>(json).unwrap(), fixture.request);
+    }
+
+}
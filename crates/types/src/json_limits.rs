@@ -0,0 +1,180 @@
+use serde::de::DeserializeOwned;
+use super::{consts::MAX_TX_SIZE, Bundle, ConfidentialComputeRequest};
+
+
+/// Bounds on JSON taken from untrusted sources (eg. relay APIs). Checked in
+/// a single pass (allocating only the nesting stack) before serde sees the
+/// input, so oversized arrays or strings are rejected instead of allocated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonLimits {
+    /// Size of the whole document in bytes.
+    pub max_size: usize,
+    pub max_depth: usize,
+    pub max_array_len: usize,
+    pub max_object_fields: usize,
+    /// Length of a string in bytes (as written, incl. escapes).
+    pub max_string_len: usize,
+}
+
+impl Default for JsonLimits {
+    /// Room for a hex encoded max size transaction in a single field.
+    fn default() -> Self {
+        Self {
+            max_size: 4 * MAX_TX_SIZE,
+            max_depth: 32,
+            max_array_len: 1024,
+            max_object_fields: 64,
+            max_string_len: 2 * MAX_TX_SIZE + 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonLimitError {
+    TooLarge { size: usize, max: usize },
+    /// `offset` is the byte position at which the limit got exceeded.
+    TooDeep { max: usize, offset: usize },
+    ArrayTooLong { max: usize, offset: usize },
+    TooManyFields { max: usize, offset: usize },
+    StringTooLong { max: usize, offset: usize },
+    /// Within limits but not a valid document.
+    Json(String),
+}
+
+impl std::fmt::Display for JsonLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonLimitError::TooLarge { size, max } => write!(f, "JSON of {size} bytes exceeds the limit of {max} bytes"),
+            JsonLimitError::TooDeep { max, offset } => write!(f, "JSON nested deeper than {max} levels at byte {offset}"),
+            JsonLimitError::ArrayTooLong { max, offset } => write!(f, "JSON array longer than {max} items at byte {offset}"),
+            JsonLimitError::TooManyFields { max, offset } => write!(f, "JSON object with more than {max} fields at byte {offset}"),
+            JsonLimitError::StringTooLong { max, offset } => write!(f, "JSON string longer than {max} bytes at byte {offset}"),
+            JsonLimitError::Json(err) => write!(f, "Invalid JSON: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonLimitError {}
+
+#[derive(Clone, Copy)]
+struct Container {
+    is_array: bool,
+    items: usize,
+}
+
+impl JsonLimits {
+
+    /// Check `json` against the limits without parsing it. Malformed input
+    /// within the limits passes; it's left for the parser to reject.
+    pub fn check(&self, json: &str) -> Result<(), JsonLimitError> {
+        if json.len() > self.max_size {
+            return Err(JsonLimitError::TooLarge { size: json.len(), max: self.max_size });
+        }
+        let bytes = json.as_bytes();
+        // Bounded by `max_depth`
+        let mut stack: Vec<Container> = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let byte = bytes[offset];
+            if byte.is_ascii_whitespace() {
+                offset += 1;
+                continue;
+            }
+            if let Some(top) = stack.last_mut() {
+                if top.items == 0 && byte != b']' && byte != b'}' {
+                    top.items = 1;
+                }
+            }
+            match byte {
+                b'[' | b'{' => {
+                    if stack.len() >= self.max_depth {
+                        return Err(JsonLimitError::TooDeep { max: self.max_depth, offset });
+                    }
+                    stack.push(Container { is_array: byte == b'[', items: 0 });
+                }
+                b']' | b'}' => { stack.pop(); }
+                b',' => {
+                    if let Some(top) = stack.last_mut() {
+                        top.items += 1;
+                        if top.is_array && top.items > self.max_array_len {
+                            return Err(JsonLimitError::ArrayTooLong { max: self.max_array_len, offset });
+                        }
+                        if !top.is_array && top.items > self.max_object_fields {
+                            return Err(JsonLimitError::TooManyFields { max: self.max_object_fields, offset });
+                        }
+                    }
+                }
+                b'"' => {
+                    let start = offset;
+                    offset += 1;
+                    while offset < bytes.len() && bytes[offset] != b'"' {
+                        offset += if bytes[offset] == b'\\' { 2 } else { 1 };
+                    }
+                    if offset - start - 1 > self.max_string_len {
+                        return Err(JsonLimitError::StringTooLong { max: self.max_string_len, offset: start });
+                    }
+                }
+                _ => {}
+            }
+            offset += 1;
+        }
+        Ok(())
+    }
+
+    /// Check the limits, then deserialize.
+    pub fn parse<T: DeserializeOwned>(&self, json: &str) -> Result<T, JsonLimitError> {
+        self.check(json)?;
+        serde_json::from_str(json).map_err(|e| JsonLimitError::Json(e.to_string()))
+    }
+
+}
+
+impl ConfidentialComputeRequest {
+
+    /// `from_json` for untrusted input.
+    pub fn from_json_limited(json: &str, limits: &JsonLimits) -> Result<Self, JsonLimitError> {
+        limits.parse(json)
+    }
+
+}
+
+impl Bundle {
+
+    /// `from_json` for untrusted input.
+    pub fn from_json_limited(json: &str, limits: &JsonLimits) -> Result<Self, JsonLimitError> {
+        limits.parse(json)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_fixtures_within_default_limits() {
+        let limits = JsonLimits::default();
+        for fixture in fixtures::load_fixtures() {
+            let json = serde_json::to_string(&fixture.request).unwrap();
+            assert_eq!(ConfidentialComputeRequest::from_json_limited(&json, &limits).unwrap(), fixture.request);
+        }
+    }
+
+    #[test]
+    fn test_limits_exceeded() {
+        let limits = JsonLimits { max_size: 64, max_depth: 3, max_array_len: 4, max_object_fields: 2, max_string_len: 8 };
+        assert_eq!(limits.check(r#"{"a":[1,2,[3,"4"]],"b":"12345678"}"#), Ok(()));
+        assert_eq!(limits.check("[[[[]]]]"), Err(JsonLimitError::TooDeep { max: 3, offset: 3 }));
+        assert_eq!(limits.check("[1,2,3,4,5]"), Err(JsonLimitError::ArrayTooLong { max: 4, offset: 8 }));
+        assert_eq!(limits.check(r#"{"a":1,"b":2,"c":3}"#), Err(JsonLimitError::TooManyFields { max: 2, offset: 12 }));
+        // Escaped quotes don't end the string
+        assert_eq!(limits.check(r#"["\"\"\"\"\""]"#), Err(JsonLimitError::StringTooLong { max: 8, offset: 1 }));
+        assert_eq!(limits.check(&" ".repeat(65)), Err(JsonLimitError::TooLarge { size: 65, max: 64 }));
+        // Commas inside strings and nested arrays count for their own container
+        assert_eq!(limits.check(r#"[",,,,,",[1,2,3],[4]]"#), Ok(()));
+        assert!(matches!(Bundle::from_json_limited("[]", &limits), Err(JsonLimitError::Json(_))));
+    }
+
+}
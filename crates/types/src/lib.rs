@@ -1,7 +1,93 @@
+mod error;
 mod crecord;
 mod crequest;
 mod cresponse;
+mod cresult;
+mod batch;
+mod envelope;
+mod sponsor;
+mod timestamp;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod validation;
+mod ordering;
+mod decode;
+mod abi_call;
+mod views;
+mod inputs_cache;
+mod encode_map;
+mod client_tag;
+mod builder;
+mod commitment;
+mod preview;
+mod bundle;
+mod json_format;
+mod mempool;
+mod inputs_batch;
+mod scheme;
+mod linkage;
+mod template;
+mod data_id;
+mod json_limits;
+mod profiles;
+mod eip712;
+mod abi_json;
+mod verified;
+mod fee;
+mod partial;
+mod permit;
+pub mod fixtures;
+pub mod geth_vectors;
+pub mod consts;
+pub mod schema;
+pub mod explorer;
+pub mod export;
+pub mod wire_compat;
+#[cfg(feature = "ethers-compat")]
+pub mod ethers_compat;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub mod fuzzing;
 
+pub use error::CcrError;
 pub use crecord::ConfidentialComputeRecord;
-pub use crequest::ConfidentialComputeRequest;
-pub use cresponse::ConfidentialCallResponse;
\ No newline at end of file
+pub use suave_alloy_core::Fees;
+pub use crequest::{ConfidentialComputeRequest, InputsHashMismatch, SignedRequestExt};
+pub use cresponse::{ConfidentialCallResponse, ResponseError, SuaveRpcTransaction};
+pub use cresult::{ConfidentialComputeResult, SuaveTransaction, SuaveTxEnvelope};
+pub use batch::{decode_batch, BatchDecodeReport, BatchDecodeError, DecodeErrorKind};
+pub use envelope::{ConfidentialEnvelope, EnvelopeExtension};
+pub use sponsor::{FeeVoucher, FeeVoucherParams, VoucherError, VOUCHER_DOMAIN};
+pub use validation::LimitError;
+pub use ordering::{sort_by_priority, PriorityKey};
+pub use decode::{DecodeMode, SECP256K1N_HALF};
+pub use abi_call::FunctionCall;
+pub use views::{FullView, PublicView};
+pub use inputs_cache::InputsCache;
+pub use encode_map::{EncodeMap, FieldRange};
+pub use client_tag::{ClientTag, CLIENT_NAME};
+pub use builder::{BuilderError, ConfidentialComputeRecordBuilder, ConfidentialComputeRequestBuilder};
+pub use commitment::salted_commitment;
+pub use preview::{CcrPreview, DecodedCall};
+pub use bundle::{Bundle, BundleError, BundleTx};
+pub use json_format::{JsonFormat, QuantityFormat};
+pub use mempool::{CcrMempool, MempoolConfig, MempoolError, PooledCcr};
+pub use inputs_batch::{InputsBatch, InputsBatchError};
+pub use scheme::{Secp256k1, SignatureScheme, SignatureSchemeError};
+pub use linkage::{CallbackLog, CallbackProof, CallbackTx, LinkageError};
+pub use template::{CcrTemplate, TemplateError, TemplateValue};
+pub use data_id::{BidId, DataId};
+pub use json_limits::{JsonLimitError, JsonLimits};
+pub use eip712::{eip712_domain, ConfidentialRecord, EIP712_DOMAIN_NAME};
+pub use abi_json::AbiRegistry;
+pub use verified::{VerificationError, VerifiedCcr};
+pub use partial::{CcrField, PartialCcr, PartialCcrError, PARTIAL_CCR_VERSION};
+pub use permit::{
+    deadline_in, Approvals, IERC20Permit, Permit, PermitBatch, PermitDetails, PermitError, PermitNonces,
+    SignedPermit, SignedPermitBatch, TokenDomain, PERMIT2_ADDRESS,
+};
+pub use fee::{fee_to_seth, fee_to_usd, format_fee, format_fee_usd, parse_fee, UsdOracle, GAS_TOKEN_SYMBOL};
+pub use profiles::{migrate_profile, ChainProfile, MigrationError, KNOWN_PROFILES, RIGIL, TOLIMAN};
+pub use explorer::{explorer_url, kettle_explorer_url};
+pub use timestamp::{unix_now, SignedTimestamp, ClockSkewGuard, ClockSkewError, TIMESTAMP_DOMAIN};
+#[cfg(feature = "encryption")]
+pub use encryption::{EncryptedInputs, EncryptionError, Hpke, InputsEncryption, KettleKey, KettleSecretKey, RecipientStanza};
\ No newline at end of file
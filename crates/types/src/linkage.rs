@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use alloy::primitives::{keccak256, Address, Bytes, TxHash, B256};
+use super::{CcrError, ConfidentialComputeRequest, ConfidentialComputeResult};
+
+
+/// Log of a callback receipt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallbackLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+/// On-chain transaction claimed to be the callback of a CCR, with the logs
+/// of its receipt.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallbackTx {
+    pub hash: TxHash,
+    pub to: Option<Address>,
+    pub input: Bytes,
+    pub block_number: u64,
+    pub logs: Vec<CallbackLog>,
+}
+
+/// Evidence that `callback_hash` executed the result of `request_hash`:
+/// the callback called the request's SUAPP with the result calldata, and
+/// the SUAPP emitted the calldata hash in log `log_index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallbackProof {
+    pub request_hash: TxHash,
+    pub callback_hash: TxHash,
+    pub suapp: Address,
+    pub calldata_hash: B256,
+    pub block_number: u64,
+    pub log_index: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkageError {
+    /// The result belongs to another request.
+    RequestMismatch { expected: TxHash, actual: TxHash },
+    TargetMismatch { expected: Address, actual: Option<Address> },
+    CalldataMismatch { expected: B256, actual: B256 },
+    /// No log of the SUAPP carries the calldata hash.
+    MissingCommitment { calldata_hash: B256 },
+    /// The result record can't be hashed (eg. it's unsigned).
+    InvalidRecord(String),
+}
+
+impl std::fmt::Display for LinkageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkageError::RequestMismatch { expected, actual } =>
+                write!(f, "Result is of request {actual}, expected {expected}"),
+            LinkageError::TargetMismatch { expected, actual } =>
+                write!(f, "Callback calls {actual:?}, expected the SUAPP {expected}"),
+            LinkageError::CalldataMismatch { expected, actual } =>
+                write!(f, "Callback calldata hash {actual} doesn't match the result's {expected}"),
+            LinkageError::MissingCommitment { calldata_hash } =>
+                write!(f, "No SUAPP log emits the calldata hash {calldata_hash}"),
+            LinkageError::InvalidRecord(err) => write!(f, "Invalid request record: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LinkageError {}
+
+impl ConfidentialComputeResult {
+
+    /// Hash of the callback calldata produced by the kettle.
+    pub fn calldata_hash(&self) -> B256 {
+        keccak256(&self.confidential_compute_result)
+    }
+
+    /// Hash of the request this is the result of.
+    pub fn request_hash(&self) -> Result<TxHash, CcrError> {
+        let request = ConfidentialComputeRequest {
+            confidential_compute_record: self.request_record.clone(),
+            confidential_inputs: Bytes::new(),
+        };
+        request.tx_hash()
+    }
+
+    /// Check that `callback` is the on-chain execution of this result for
+    /// the request `request_hash`, eg. for monitors asserting a SUAPP ran
+    /// its full two-phase flow.
+    pub fn verify_callback(&self, request_hash: TxHash, callback: &CallbackTx) -> Result<CallbackProof, LinkageError> {
+        let actual = self.request_hash().map_err(|e| LinkageError::InvalidRecord(e.to_string()))?;
+        if actual != request_hash {
+            return Err(LinkageError::RequestMismatch { expected: request_hash, actual });
+        }
+        let suapp = self.request_record.target()
+            .ok_or_else(|| LinkageError::InvalidRecord("contract creation has no SUAPP to call back".into()))?;
+        if callback.to != Some(suapp) {
+            return Err(LinkageError::TargetMismatch { expected: suapp, actual: callback.to });
+        }
+        let calldata_hash = self.calldata_hash();
+        let actual = keccak256(&callback.input);
+        if actual != calldata_hash {
+            return Err(LinkageError::CalldataMismatch { expected: calldata_hash, actual });
+        }
+        let log_index = callback.logs.iter()
+            .position(|log| log.address == suapp && emits(log, calldata_hash))
+            .ok_or(LinkageError::MissingCommitment { calldata_hash })?;
+        Ok(CallbackProof {
+            request_hash,
+            callback_hash: callback.hash,
+            suapp,
+            calldata_hash,
+            block_number: callback.block_number,
+            log_index,
+        })
+    }
+
+}
+
+/// Whether `hash` is a topic or a 32 byte word of the log data.
+fn emits(log: &CallbackLog, hash: B256) -> bool {
+    log.topics.contains(&hash) || log.data.chunks_exact(32).any(|word| word == hash.as_slice())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::primitives::{Signature, U256};
+    use super::*;
+    use super::super::fixtures;
+
+    fn result() -> (TxHash, ConfidentialComputeResult) {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let signature = Signature::from_rs_and_parity(U256::from(1), U256::from(1), 27).unwrap();
+        let result = ConfidentialComputeResult {
+            request_record: fixture.request.confidential_compute_record,
+            confidential_compute_result: Bytes::from_str("0x0000000000000000000000000000000000000000000000000000000001ccb310").unwrap(),
+            chain_id: 0x1008c45,
+            signature,
+        };
+        (fixture.tx_hash, result)
+    }
+
+    fn callback(result: &ConfidentialComputeResult) -> CallbackTx {
+        let suapp = result.request_record.target().unwrap();
+        CallbackTx {
+            hash: TxHash::repeat_byte(0xcb),
+            to: Some(suapp),
+            input: result.confidential_compute_result.clone(),
+            block_number: 42,
+            logs: vec![
+                CallbackLog { address: Address::repeat_byte(0x01), topics: vec![result.calldata_hash()], data: Bytes::new() },
+                CallbackLog { address: suapp, topics: vec![B256::ZERO], data: Bytes::copy_from_slice(result.calldata_hash().as_slice()) },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_verify_callback() {
+        let (request_hash, result) = result();
+        let callback = callback(&result);
+        let proof = result.verify_callback(request_hash, &callback).unwrap();
+        // The first log carries the hash but isn't emitted by the SUAPP
+        assert_eq!(proof.log_index, 1);
+        assert_eq!((proof.callback_hash, proof.block_number), (callback.hash, 42));
+        assert_eq!(proof.calldata_hash, keccak256(&result.confidential_compute_result));
+    }
+
+    #[test]
+    fn test_unlinked_callback() {
+        let (request_hash, result) = result();
+        assert!(matches!(
+            result.verify_callback(TxHash::ZERO, &callback(&result)),
+            Err(LinkageError::RequestMismatch { .. })
+        ));
+
+        let mut other_calldata = callback(&result);
+        other_calldata.input = Bytes::from_static(&[0x01]);
+        assert!(matches!(
+            result.verify_callback(request_hash, &other_calldata),
+            Err(LinkageError::CalldataMismatch { .. })
+        ));
+
+        let mut no_commitment = callback(&result);
+        no_commitment.logs.truncate(1);
+        assert_eq!(
+            result.verify_callback(request_hash, &no_commitment),
+            Err(LinkageError::MissingCommitment { calldata_hash: result.calldata_hash() })
+        );
+    }
+
+}
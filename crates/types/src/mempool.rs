@@ -0,0 +1,441 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashMap},
+    time::{Duration, Instant},
+};
+use alloy::primitives::{Address, B256};
+use super::{
+    consts::ProtocolLimits,
+    scheme::{Secp256k1, SignatureScheme},
+    CcrError,
+    ConfidentialComputeRequest,
+    InputsHashMismatch,
+    LimitError,
+    PriorityKey,
+};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MempoolConfig {
+    /// Time a request stays in the pool.
+    pub ttl: Duration,
+    pub max_per_sender: usize,
+    pub max_size: usize,
+    /// Minimum gas price increase (in percent) to replace a request with
+    /// the same sender and nonce.
+    pub replacement_bump_percent: u128,
+    pub limits: ProtocolLimits,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            max_per_sender: 16,
+            max_size: 4096,
+            replacement_bump_percent: 10,
+            limits: ProtocolLimits::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolError {
+    Unsigned,
+    InvalidSignature,
+    MissingNonce,
+    /// Signed request missing fields needed to hash it.
+    Incomplete(CcrError),
+    InputsHash(InputsHashMismatch),
+    Limits(LimitError),
+    /// Nonce already consumed by the sender.
+    NonceTooLow { sender: Address, nonce: u64, next: u64 },
+    /// Replacement not paying enough more than the pooled request.
+    Underpriced { nonce: u64, pooled: u128, offered: u128 },
+    SenderLimit { sender: Address, limit: usize },
+    /// Pool is full of requests paying at least as much.
+    PoolFull,
+    AlreadyKnown(B256),
+}
+
+impl std::fmt::Display for MempoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MempoolError::Unsigned => write!(f, "Request is not signed"),
+            MempoolError::InvalidSignature => write!(f, "Invalid request signature"),
+            MempoolError::MissingNonce => write!(f, "Request has no nonce"),
+            MempoolError::Incomplete(err) => write!(f, "Incomplete request: {err}"),
+            MempoolError::InputsHash(err) => write!(f, "{err}"),
+            MempoolError::Limits(err) => write!(f, "{err}"),
+            MempoolError::NonceTooLow { sender, nonce, next } =>
+                write!(f, "Nonce {nonce} of {sender} is too low, next is {next}"),
+            MempoolError::Underpriced { nonce, pooled, offered } =>
+                write!(f, "Replacement of nonce {nonce} underpriced: {offered} vs pooled {pooled}"),
+            MempoolError::SenderLimit { sender, limit } =>
+                write!(f, "Sender {sender} has {limit} pooled requests already"),
+            MempoolError::PoolFull => write!(f, "Pool is full"),
+            MempoolError::AlreadyKnown(hash) => write!(f, "Request {hash} is already pooled"),
+        }
+    }
+}
+
+impl std::error::Error for MempoolError {}
+
+#[derive(Debug, Clone)]
+pub struct PooledCcr {
+    pub ccr: ConfidentialComputeRequest,
+    pub sender: Address,
+    pub hash: B256,
+    pub priority: PriorityKey,
+    pub expires_at: Instant,
+}
+
+impl PooledCcr {
+
+    pub fn nonce(&self) -> u64 {
+        self.priority.nonce
+    }
+
+}
+
+/// Pool of signed CCRs for relays and kettle frontends: validates requests
+/// on insertion, keeps them per sender by nonce (with fee bumped
+/// replacement), enforces per-sender and total limits, expires them after
+/// a TTL and yields executable requests in `PriorityKey` order.
+///
+/// Signatures are verified under `Secp256k1` unless the pool is built
+/// `with_scheme`.
+#[derive(Debug, Clone)]
+pub struct CcrMempool<S = Secp256k1> {
+    config: MempoolConfig,
+    scheme: S,
+    by_sender: HashMap<Address, BTreeMap<u64, PooledCcr>>,
+    by_hash: HashMap<B256, (Address, u64)>,
+    /// Next nonce per sender, as last reported with `evict_below`.
+    account_nonces: HashMap<Address, u64>,
+}
+
+impl Default for CcrMempool {
+    fn default() -> Self {
+        Self::new(MempoolConfig::default())
+    }
+}
+
+impl CcrMempool {
+
+    pub fn new(config: MempoolConfig) -> Self {
+        Self::with_scheme(config, Secp256k1)
+    }
+
+}
+
+impl<S: SignatureScheme> CcrMempool<S> {
+
+    /// Pool verifying request signatures under `scheme`.
+    pub fn with_scheme(config: MempoolConfig, scheme: S) -> Self {
+        Self {
+            config,
+            scheme,
+            by_sender: HashMap::new(),
+            by_hash: HashMap::new(),
+            account_nonces: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &MempoolConfig {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_hash.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_hash.is_empty()
+    }
+
+    pub fn contains(&self, hash: &B256) -> bool {
+        self.by_hash.contains_key(hash)
+    }
+
+    pub fn get(&self, hash: &B256) -> Option<&PooledCcr> {
+        let (sender, nonce) = self.by_hash.get(hash)?;
+        self.by_sender.get(sender)?.get(nonce)
+    }
+
+    pub fn sender_len(&self, sender: Address) -> usize {
+        self.by_sender.get(&sender).map_or(0, BTreeMap::len)
+    }
+
+    /// Check a request without inserting it; returns its sender.
+    pub fn validate(&self, ccr: &ConfidentialComputeRequest) -> Result<Address, MempoolError> {
+        let record = &ccr.confidential_compute_record;
+        if record.signature.is_none() {
+            return Err(MempoolError::Unsigned);
+        }
+        let nonce = record.nonce.ok_or(MempoolError::MissingNonce)?;
+        let signing_hash = ccr.signing_hash().map_err(MempoolError::Incomplete)?;
+        ccr.validate_inputs_hash().map_err(MempoolError::InputsHash)?;
+        ccr.validate_limits(&self.config.limits).map_err(MempoolError::Limits)?;
+        let sender = ccr.scheme_signature(&self.scheme)
+            .and_then(|sig| self.scheme.signer(&sig, &signing_hash))
+            .map_err(|_| MempoolError::InvalidSignature)?;
+        if let Some(&next) = self.account_nonces.get(&sender) {
+            if nonce < next {
+                return Err(MempoolError::NonceTooLow { sender, nonce, next });
+            }
+        }
+        Ok(sender)
+    }
+
+    pub fn insert(&mut self, ccr: ConfidentialComputeRequest) -> Result<Option<PooledCcr>, MempoolError> {
+        self.insert_at(ccr, Instant::now())
+    }
+
+    /// Insert a request, returning the one it replaced (same sender and
+    /// nonce) or evicted to make room.
+    pub fn insert_at(&mut self, ccr: ConfidentialComputeRequest, now: Instant) -> Result<Option<PooledCcr>, MempoolError> {
+        let sender = self.validate(&ccr)?;
+        let hash = ccr.tx_hash().map_err(MempoolError::Incomplete)?;
+        if self.contains(&hash) {
+            return Err(MempoolError::AlreadyKnown(hash));
+        }
+        let priority = ccr.priority_key().map_err(MempoolError::Incomplete)?;
+        let nonce = priority.nonce;
+        let entry = PooledCcr { ccr, sender, hash, priority, expires_at: now + self.config.ttl };
+
+        if let Some(pooled) = self.by_sender.get(&sender).and_then(|queue| queue.get(&nonce)) {
+            let min_price = pooled.priority.gas_price
+                .saturating_mul(100 + self.config.replacement_bump_percent) / 100;
+            if entry.priority.gas_price < min_price || entry.priority.gas_price <= pooled.priority.gas_price {
+                return Err(MempoolError::Underpriced {
+                    nonce,
+                    pooled: pooled.priority.gas_price,
+                    offered: entry.priority.gas_price,
+                });
+            }
+            let replaced = self.remove_entry(sender, nonce);
+            self.push(entry);
+            return Ok(replaced);
+        }
+
+        if self.sender_len(sender) >= self.config.max_per_sender {
+            return Err(MempoolError::SenderLimit { sender, limit: self.config.max_per_sender });
+        }
+        let mut evicted = None;
+        if self.len() >= self.config.max_size {
+            // Only the last nonce of a sender can go without leaving a gap
+            let (victim_sender, victim_nonce, victim_priority) = self.by_sender.iter()
+                .filter_map(|(sender, queue)| queue.last_key_value().map(|(nonce, e)| (*sender, *nonce, e.priority)))
+                .max_by_key(|(_, _, priority)| *priority)
+                .ok_or(MempoolError::PoolFull)?;
+            if entry.priority.gas_price <= victim_priority.gas_price {
+                return Err(MempoolError::PoolFull);
+            }
+            evicted = self.remove_entry(victim_sender, victim_nonce);
+        }
+        self.push(entry);
+        Ok(evicted)
+    }
+
+    pub fn remove(&mut self, hash: &B256) -> Option<PooledCcr> {
+        let (sender, nonce) = *self.by_hash.get(hash)?;
+        self.remove_entry(sender, nonce)
+    }
+
+    /// Drop requests of `sender` below `next_nonce` (eg. after inclusion)
+    /// and reject such nonces from now on.
+    pub fn evict_below(&mut self, sender: Address, next_nonce: u64) -> Vec<PooledCcr> {
+        self.account_nonces.insert(sender, next_nonce);
+        let Some(queue) = self.by_sender.get_mut(&sender) else {
+            return Vec::new();
+        };
+        let kept = queue.split_off(&next_nonce);
+        let evicted = std::mem::replace(queue, kept).into_values().collect::<Vec<_>>();
+        if queue.is_empty() {
+            self.by_sender.remove(&sender);
+        }
+        for entry in &evicted {
+            self.by_hash.remove(&entry.hash);
+        }
+        evicted
+    }
+
+    pub fn prune_expired(&mut self) -> Vec<PooledCcr> {
+        self.prune_expired_at(Instant::now())
+    }
+
+    pub fn prune_expired_at(&mut self, now: Instant) -> Vec<PooledCcr> {
+        let expired = self.by_sender.iter()
+            .flat_map(|(sender, queue)| queue.iter().map(move |(nonce, entry)| (*sender, *nonce, entry.expires_at)))
+            .filter(|(_, _, expires_at)| *expires_at <= now)
+            .map(|(sender, nonce, _)| (sender, nonce))
+            .collect::<Vec<_>>();
+        expired.into_iter()
+            .filter_map(|(sender, nonce)| self.remove_entry(sender, nonce))
+            .collect()
+    }
+
+    /// Executable requests in inclusion order: per sender only the run of
+    /// consecutive nonces starting at its next nonce (or its lowest pooled
+    /// one if unknown), interleaved across senders by `PriorityKey`.
+    pub fn ready(&self) -> Vec<&PooledCcr> {
+        let runs = self.by_sender.iter()
+            .map(|(sender, queue)| {
+                let mut expected = self.account_nonces.get(sender).copied()
+                    .or_else(|| queue.keys().next().copied())
+                    .unwrap_or_default();
+                queue.range(expected..)
+                    .take_while(|(nonce, _)| {
+                        let consecutive = **nonce == expected;
+                        expected += 1;
+                        consecutive
+                    })
+                    .map(|(_, entry)| entry)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|run| !run.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut heads = runs.iter().enumerate()
+            .map(|(idx, run)| Reverse((run[0].priority, idx, 0)))
+            .collect::<BinaryHeap<_>>();
+        let mut ordered = Vec::with_capacity(runs.iter().map(Vec::len).sum());
+        while let Some(Reverse((_, idx, pos))) = heads.pop() {
+            ordered.push(runs[idx][pos]);
+            if let Some(next) = runs[idx].get(pos + 1) {
+                heads.push(Reverse((next.priority, idx, pos + 1)));
+            }
+        }
+        ordered
+    }
+
+    fn push(&mut self, entry: PooledCcr) {
+        self.by_hash.insert(entry.hash, (entry.sender, entry.nonce()));
+        self.by_sender.entry(entry.sender).or_default().insert(entry.nonce(), entry);
+    }
+
+    fn remove_entry(&mut self, sender: Address, nonce: u64) -> Option<PooledCcr> {
+        let queue = self.by_sender.get_mut(&sender)?;
+        let entry = queue.remove(&nonce)?;
+        if queue.is_empty() {
+            self.by_sender.remove(&sender);
+        }
+        self.by_hash.remove(&entry.hash);
+        Some(entry)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{network::TxSigner, signers::wallet::LocalWallet};
+    use super::*;
+    use super::super::{fixtures, Fees};
+
+    async fn signed(wallet: &LocalWallet, nonce: u64, gas_price: u128) -> ConfidentialComputeRequest {
+        signed_with_fees(wallet, nonce, Fees::Legacy { gas_price }).await
+    }
+
+    async fn signed_with_fees(wallet: &LocalWallet, nonce: u64, fees: Fees) -> ConfidentialComputeRequest {
+        let mut record = fixtures::unsigned_record();
+        record.nonce = Some(nonce);
+        record.set_fees(fees);
+        let mut ccr = ConfidentialComputeRequest::new(record, None);
+        let sig = wallet.sign_transaction(&mut ccr).await.unwrap();
+        ccr.confidential_compute_record.set_sig(sig);
+        ccr
+    }
+
+    fn wallet(key: u8) -> LocalWallet {
+        LocalWallet::from_bytes(&B256::repeat_byte(key)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mempool_ordering_and_replacement() {
+        let (alice, bob) = (wallet(0x11), wallet(0x22));
+        let mut pool = CcrMempool::default();
+        pool.insert(signed(&alice, 0, 100).await).unwrap();
+        pool.insert(signed(&alice, 1, 300).await).unwrap();
+        // Nonce 3 is queued behind the missing nonce 2
+        pool.insert(signed(&alice, 3, 500).await).unwrap();
+        pool.insert(signed(&bob, 0, 200).await).unwrap();
+        assert_eq!(pool.len(), 4);
+
+        let order = |pool: &CcrMempool| pool.ready().iter()
+            .map(|e| (e.sender, e.nonce()))
+            .collect::<Vec<_>>();
+        // Alice's nonce 1 pays the most but waits for her nonce 0
+        assert_eq!(order(&pool), vec![(bob.address(), 0), (alice.address(), 0), (alice.address(), 1)]);
+
+        assert!(matches!(
+            pool.insert(signed(&alice, 0, 105).await),
+            Err(MempoolError::Underpriced { nonce: 0, pooled: 100, offered: 105 })
+        ));
+        let replaced = pool.insert(signed(&alice, 0, 250).await).unwrap().unwrap();
+        assert_eq!(replaced.priority.gas_price, 100);
+        assert!(!pool.contains(&replaced.hash));
+        assert_eq!(order(&pool)[0], (alice.address(), 0));
+
+        let included = pool.evict_below(alice.address(), 2);
+        assert_eq!(included.len(), 2);
+        assert!(matches!(pool.insert(signed(&alice, 1, 900).await), Err(MempoolError::NonceTooLow { next: 2, .. })));
+        pool.insert(signed(&alice, 2, 100).await).unwrap();
+        assert_eq!(order(&pool), vec![(bob.address(), 0), (alice.address(), 2), (alice.address(), 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_mempool_limits_and_expiry() {
+        let alice = wallet(0x11);
+        let config = MempoolConfig { max_per_sender: 2, max_size: 3, ..Default::default() };
+        let mut pool = CcrMempool::new(config);
+        let start = Instant::now();
+
+        let mut unsigned = signed(&alice, 0, 100).await;
+        unsigned.confidential_compute_record.signature = None;
+        assert_eq!(pool.insert_at(unsigned, start).unwrap_err(), MempoolError::Unsigned);
+        // Signed, but the fees were dropped afterwards
+        let mut incomplete = signed(&alice, 0, 100).await;
+        incomplete.confidential_compute_record.gas_price = None;
+        assert_eq!(
+            pool.insert_at(incomplete, start).unwrap_err(),
+            MempoolError::Incomplete(CcrError::MissingField("gas price"))
+        );
+
+        pool.insert_at(signed(&alice, 0, 100).await, start).unwrap();
+        pool.insert_at(signed(&alice, 1, 100).await, start).unwrap();
+        assert!(matches!(pool.insert_at(signed(&alice, 2, 100).await, start), Err(MempoolError::SenderLimit { limit: 2, .. })));
+
+        // Full: a better paying request evicts the worst tail request
+        pool.insert_at(signed(&wallet(0x22), 0, 50).await, start).unwrap();
+        assert_eq!(pool.insert_at(signed(&wallet(0x33), 0, 50).await, start).unwrap_err(), MempoolError::PoolFull);
+        let evicted = pool.insert_at(signed(&wallet(0x33), 0, 70).await, start).unwrap().unwrap();
+        assert_eq!(evicted.sender, wallet(0x22).address());
+        assert_eq!(pool.len(), 3);
+
+        let later = start + config.ttl;
+        assert_eq!(pool.prune_expired_at(later).len(), 3);
+        assert!(pool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mempool_dynamic_fees() {
+        let alice = wallet(0x11);
+        let dynamic = |max_fee_per_gas| Fees::Dynamic { max_priority_fee_per_gas: 1, max_fee_per_gas };
+        let config = MempoolConfig { max_size: 1, ..Default::default() };
+        let mut pool = CcrMempool::new(config);
+
+        // Priced by the max fee, not as zero: a lower legacy price can't evict it
+        pool.insert(signed_with_fees(&alice, 0, dynamic(100)).await).unwrap();
+        assert_eq!(pool.insert(signed(&wallet(0x22), 0, 50).await).unwrap_err(), MempoolError::PoolFull);
+
+        assert!(matches!(
+            pool.insert(signed_with_fees(&alice, 0, dynamic(105)).await),
+            Err(MempoolError::Underpriced { nonce: 0, pooled: 100, offered: 105 })
+        ));
+        let replaced = pool.insert(signed_with_fees(&alice, 0, dynamic(200)).await).unwrap().unwrap();
+        assert_eq!(replaced.priority.gas_price, 100);
+    }
+
+}
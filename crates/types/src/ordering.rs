@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+use alloy::primitives::B256;
+use super::{CcrError, ConfidentialComputeRequest};
+
+
+/// Canonical inclusion ordering of CCRs: higher gas price (the max fee for
+/// dynamic fee records) first, then lower nonce, then signing hash as a
+/// deterministic tie-breaker.
+/// Sorting keys ascending yields the order a kettle would include them in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PriorityKey {
+    pub gas_price: u128,
+    pub nonce: u64,
+    pub hash: B256,
+}
+
+impl Ord for PriorityKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.gas_price.cmp(&self.gas_price)
+            .then_with(|| self.nonce.cmp(&other.nonce))
+            .then_with(|| self.hash.cmp(&other.hash))
+    }
+}
+
+impl PartialOrd for PriorityKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Fails if the signing hash can't be computed, eg. for missing fees
+    /// or nonce.
+    pub fn priority_key(&self) -> Result<PriorityKey, CcrError> {
+        let record = &self.confidential_compute_record;
+        Ok(PriorityKey {
+            hash: self.signing_hash()?,
+            gas_price: record.max_gas_price().unwrap_or_default(),
+            nonce: record.nonce.unwrap_or_default(),
+        })
+    }
+
+}
+
+/// Sort requests in canonical inclusion order. Leaves `ccrs` as is if
+/// the key of any can't be computed.
+pub fn sort_by_priority(ccrs: &mut [ConfidentialComputeRequest]) -> Result<(), CcrError> {
+    let keys = ccrs.iter().map(ConfidentialComputeRequest::priority_key).collect::<Result<Vec<_>, _>>()?;
+    let mut keyed = keys.into_iter().zip(ccrs.iter_mut().map(std::mem::take)).collect::<Vec<_>>();
+    keyed.sort_by_key(|(key, _)| *key);
+    for (slot, (_, ccr)) in ccrs.iter_mut().zip(keyed) {
+        *slot = ccr;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{fixtures, Fees};
+
+    #[test]
+    fn test_priority_ordering() {
+        let key = |gas_price, nonce, hash: u8| PriorityKey { gas_price, nonce, hash: B256::repeat_byte(hash) };
+        let mut keys = vec![key(1, 0, 0), key(2, 5, 0), key(2, 1, 9), key(2, 1, 3)];
+        keys.sort();
+        assert_eq!(keys, vec![key(2, 1, 3), key(2, 1, 9), key(2, 5, 0), key(1, 0, 0)]);
+    }
+
+    #[test]
+    fn test_sort_by_priority() {
+        let mut ccrs = fixtures::load_fixtures().into_iter().map(|f| f.request).collect::<Vec<_>>();
+        sort_by_priority(&mut ccrs).unwrap();
+        let gas_prices = ccrs.iter()
+            .map(|ccr| ccr.confidential_compute_record.gas_price.unwrap())
+            .collect::<Vec<_>>();
+        assert!(gas_prices.windows(2).all(|w| w[0] >= w[1]));
+        let mut resorted = ccrs.clone();
+        resorted.reverse();
+        sort_by_priority(&mut resorted).unwrap();
+        assert_eq!(resorted, ccrs);
+
+        let mut incomplete = ccrs.clone();
+        incomplete[1].confidential_compute_record.gas = None;
+        let before = incomplete.clone();
+        assert_eq!(sort_by_priority(&mut incomplete), Err(CcrError::MissingField("gas")));
+        assert_eq!(incomplete, before);
+    }
+
+    #[test]
+    fn test_priority_key_dynamic_fees() {
+        let mut ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        ccr.confidential_compute_record.set_fees(Fees::Dynamic { max_priority_fee_per_gas: 1, max_fee_per_gas: 7 });
+        assert_eq!(ccr.priority_key().unwrap().gas_price, 7);
+    }
+
+}
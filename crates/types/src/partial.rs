@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use alloy::primitives::Address;
+use super::{ConfidentialComputeRequest, InputsHashMismatch};
+
+
+/// Version of the `PartialCcr` format written by this crate.
+pub const PARTIAL_CCR_VERSION: u64 = 1;
+
+/// Record field a `PartialCcr` can be missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CcrField {
+    Nonce,
+    Gas,
+    /// Gas price, or both dynamic fee caps.
+    Fees,
+    ChainId,
+    KettleAddress,
+    ConfidentialInputsHash,
+    Signature,
+}
+
+impl std::fmt::Display for CcrField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CcrField::Nonce => "nonce",
+            CcrField::Gas => "gas",
+            CcrField::Fees => "fees",
+            CcrField::ChainId => "chain id",
+            CcrField::KettleAddress => "kettle address",
+            CcrField::ConfidentialInputsHash => "confidential inputs hash",
+            CcrField::Signature => "signature",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartialCcrError {
+    UnsupportedVersion(u64),
+    /// Fields still missing when completeness was required.
+    Incomplete(Vec<CcrField>),
+    /// The declared missing fields don't match the request, eg. a wallet
+    /// filled a field without refreshing them.
+    StaleFlags { declared: Vec<CcrField>, actual: Vec<CcrField> },
+    InputsHash(InputsHashMismatch),
+    /// Signed by another key than the required signer.
+    WrongSigner { expected: Address, actual: Address },
+    InvalidSignature,
+    Json(String),
+}
+
+impl std::fmt::Display for PartialCcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PartialCcrError::UnsupportedVersion(version) => write!(f, "Unsupported partial CCR version {version}"),
+            PartialCcrError::Incomplete(missing) => write!(f, "Partial CCR is missing {}", join(missing)),
+            PartialCcrError::StaleFlags { declared, actual } =>
+                write!(f, "Partial CCR declares missing [{}] but is missing [{}]", join(declared), join(actual)),
+            PartialCcrError::InputsHash(mismatch) =>
+                write!(f, "Confidential inputs hash {} doesn't match the inputs ({})", mismatch.committed, mismatch.computed),
+            PartialCcrError::WrongSigner { expected, actual } => write!(f, "Signed by {actual} instead of {expected}"),
+            PartialCcrError::InvalidSignature => write!(f, "Invalid signature"),
+            PartialCcrError::Json(err) => write!(f, "JSON error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PartialCcrError {}
+
+fn join(fields: &[CcrField]) -> String {
+    fields.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Partially constructed CCR exchanged with wallet software, like a PSBT:
+/// the request with the fields set so far, which ones are still missing,
+/// who has to sign it and a summary to show before signing. A wallet
+/// fills what it owns (eg. nonce and fees), calls `refresh` and hands it
+/// back or signs it; `finalize` checks the result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialCcr {
+    pub version: u64,
+    pub request: ConfidentialComputeRequest,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_signer: Option<Address>,
+    pub missing: Vec<CcrField>,
+    /// `CcrPreview` of the request (without ABI decoding).
+    pub summary: String,
+}
+
+impl PartialCcr {
+
+    pub fn new(request: ConfidentialComputeRequest, required_signer: Option<Address>) -> Self {
+        let mut partial = Self {
+            version: PARTIAL_CCR_VERSION,
+            request,
+            required_signer,
+            missing: Vec::new(),
+            summary: String::new(),
+        };
+        partial.refresh();
+        partial
+    }
+
+    /// Recompute `missing` and `summary` after changing the request.
+    pub fn refresh(&mut self) {
+        self.missing = missing_fields(&self.request);
+        self.summary = self.request.preview(None).to_string();
+    }
+
+    /// All fields but the signature are set.
+    pub fn is_ready_to_sign(&self) -> bool {
+        self.missing.iter().all(|field| *field == CcrField::Signature)
+    }
+
+    /// Check the format version, the missing flags and the inputs hash.
+    pub fn validate(&self) -> Result<(), PartialCcrError> {
+        if self.version != PARTIAL_CCR_VERSION {
+            return Err(PartialCcrError::UnsupportedVersion(self.version));
+        }
+        let actual = missing_fields(&self.request);
+        if actual != self.missing {
+            return Err(PartialCcrError::StaleFlags { declared: self.missing.clone(), actual });
+        }
+        if self.request.confidential_compute_record.confidential_inputs_hash.is_some() {
+            self.request.validate_inputs_hash().map_err(PartialCcrError::InputsHash)?;
+        }
+        Ok(())
+    }
+
+    /// The signed request, once nothing is missing and the signature is
+    /// from the required signer.
+    pub fn finalize(self) -> Result<ConfidentialComputeRequest, PartialCcrError> {
+        self.validate()?;
+        if !self.missing.is_empty() {
+            return Err(PartialCcrError::Incomplete(self.missing));
+        }
+        let signer = self.request.recover_signer().map_err(|_| PartialCcrError::InvalidSignature)?;
+        match self.required_signer {
+            Some(expected) if expected != signer => Err(PartialCcrError::WrongSigner { expected, actual: signer }),
+            _ => Ok(self.request),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Serializable partial CCR")
+    }
+
+    /// Parse and `validate`.
+    pub fn from_json(json: &str) -> Result<Self, PartialCcrError> {
+        let partial: Self = serde_json::from_str(json).map_err(|e| PartialCcrError::Json(e.to_string()))?;
+        partial.validate()?;
+        Ok(partial)
+    }
+
+}
+
+fn missing_fields(request: &ConfidentialComputeRequest) -> Vec<CcrField> {
+    let record = &request.confidential_compute_record;
+    [
+        (CcrField::Nonce, record.nonce.is_some()),
+        (CcrField::Gas, record.gas.is_some()),
+        (CcrField::Fees, record.fees().is_some()),
+        (CcrField::ChainId, record.chain_id.is_some()),
+        (CcrField::KettleAddress, record.kettle_address.is_some()),
+        (CcrField::ConfidentialInputsHash, record.confidential_inputs_hash.is_some()),
+        (CcrField::Signature, record.signature.is_some()),
+    ]
+        .into_iter()
+        .filter(|(_, set)| !set)
+        .map(|(field, _)| field)
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        network::TxSigner,
+        primitives::Bytes,
+        signers::{wallet::LocalWallet, Signer},
+    };
+    use super::*;
+    use super::super::fixtures;
+
+    fn unsigned() -> ConfidentialComputeRequest {
+        let mut request = fixtures::load_fixture("empty_inputs").unwrap().request;
+        let record = &mut request.confidential_compute_record;
+        (record.nonce, record.gas_price, record.signature) = (None, None, None);
+        request
+    }
+
+    #[tokio::test]
+    async fn test_complete_and_finalize() {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let mut partial = PartialCcr::new(unsigned(), Some(wallet.address()));
+        assert_eq!(partial.missing, vec![CcrField::Nonce, CcrField::Fees, CcrField::Signature]);
+        assert!(!partial.is_ready_to_sign());
+        assert!(partial.summary.contains("nonce:               <unset>"));
+
+        // Round trip through the wallet
+        let mut partial = PartialCcr::from_json(&partial.to_json()).unwrap();
+        let record = &mut partial.request.confidential_compute_record;
+        (record.nonce, record.gas_price) = (Some(0), Some(0x3b9aca00));
+        assert!(matches!(partial.validate(), Err(PartialCcrError::StaleFlags { .. })));
+        partial.refresh();
+        assert!(partial.is_ready_to_sign());
+        assert_eq!(partial.clone().finalize(), Err(PartialCcrError::Incomplete(vec![CcrField::Signature])));
+
+        let sig = wallet.sign_transaction(&mut partial.request).await.unwrap();
+        partial.request.confidential_compute_record.set_sig(sig);
+        partial.refresh();
+        let request = partial.clone().finalize().unwrap();
+        assert_eq!(request.recover_signer().unwrap(), wallet.address());
+
+        let other = Address::repeat_byte(0x01);
+        partial.required_signer = Some(other);
+        assert_eq!(partial.finalize(), Err(PartialCcrError::WrongSigner { expected: other, actual: wallet.address() }));
+    }
+
+    #[test]
+    fn test_invalid_partials() {
+        let mut partial = PartialCcr::new(unsigned(), None);
+        partial.version = 2;
+        assert_eq!(partial.validate(), Err(PartialCcrError::UnsupportedVersion(2)));
+
+        let mut partial = PartialCcr::new(unsigned(), None);
+        partial.request.confidential_inputs = Bytes::from_static(b"tampered");
+        assert!(matches!(partial.validate(), Err(PartialCcrError::InputsHash(_))));
+        assert!(matches!(PartialCcr::from_json("{}"), Err(PartialCcrError::Json(_))));
+    }
+
+}
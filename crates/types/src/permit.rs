@@ -0,0 +1,500 @@
+use std::{collections::HashMap, time::Duration};
+use alloy::{
+    primitives::{address, keccak256, Address, Bytes, Signature, B256, U256},
+    signers::{Signer, Error as SignerError, Result as SignerResult},
+    sol,
+    sol_types::{Eip712Domain, SolStruct, SolValue},
+};
+use super::{
+    crecord::signature_to_vrs,
+    envelope::{ConfidentialEnvelope, EnvelopeExtension},
+    timestamp::unix_now,
+    ConfidentialComputeRequest,
+};
+
+
+/// Canonical Permit2 deployment, the same on every chain.
+pub const PERMIT2_ADDRESS: Address = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
+
+const PERMIT_DETAILS_TYPE: &str = "PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)";
+const PERMIT_BATCH_TYPE: &str = "PermitBatch(PermitDetails[] details,address spender,uint256 sigDeadline)";
+
+const U48_MAX: u64 = (1 << 48) - 1;
+
+sol! {
+    /// ERC-2612 `Permit` typed data.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    /// ERC-2612 permit as carried in the inputs: the token and the
+    /// arguments of its `permit(owner, spender, value, deadline, v, r, s)`.
+    #[derive(Debug, PartialEq, Eq)]
+    struct SignedPermit {
+        address token;
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+        uint8 v;
+        bytes32 r;
+        bytes32 s;
+    }
+
+    /// Permit2 `PermitDetails`. Amount, expiration and nonce are `uint160`,
+    /// `uint48` and `uint48` on chain, which ABI encode the same.
+    #[derive(Debug, PartialEq, Eq)]
+    struct PermitDetails {
+        address token;
+        uint256 amount;
+        uint256 expiration;
+        uint256 nonce;
+    }
+
+    /// Permit2 `PermitBatch` (allowance transfer) as carried in the inputs:
+    /// the arguments of `permit(owner, permitBatch, signature)`.
+    #[derive(Debug, PartialEq, Eq)]
+    struct SignedPermitBatch {
+        address owner;
+        PermitDetails[] details;
+        address spender;
+        uint256 sigDeadline;
+        bytes signature;
+    }
+
+    /// Token approvals carried next to the intent, ABI encoded so SUAPPs
+    /// can `abi.decode` them.
+    #[derive(Debug, PartialEq, Eq, Default)]
+    struct Approvals {
+        SignedPermit[] permits;
+        SignedPermitBatch[] batches;
+    }
+
+    /// Reads needed to build an ERC-2612 permit.
+    interface IERC20Permit {
+        function nonces(address owner) external view returns (uint256);
+        function DOMAIN_SEPARATOR() external view returns (bytes32);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermitError {
+    /// Deadline (or Permit2 expiration) before `now`.
+    Expired { deadline: U256, now: u64 },
+    WrongSigner { expected: Address, actual: Address },
+    InvalidSignature,
+    /// Verified against the domain of another token.
+    DomainMismatch { expected: Address, actual: Address },
+    /// Permit2 field out of its on-chain range.
+    OutOfRange(&'static str),
+}
+
+impl std::fmt::Display for PermitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermitError::Expired { deadline, now } => write!(f, "Permit expired at {deadline} (now {now})"),
+            PermitError::WrongSigner { expected, actual } => write!(f, "Permit signed by {actual} instead of {expected}"),
+            PermitError::InvalidSignature => write!(f, "Invalid permit signature"),
+            PermitError::DomainMismatch { expected, actual } => write!(f, "Permit for token {actual}, expected {expected}"),
+            PermitError::OutOfRange(field) => write!(f, "Permit2 {field} out of range"),
+        }
+    }
+}
+
+impl std::error::Error for PermitError {}
+
+/// EIP-712 domain of an ERC-2612 token: its `name()`, the permit version
+/// (eg. "1", USDC uses "2") and where it is deployed. Tokens with their
+/// own permit (eg. DAI) are not covered; compare `separator` with the
+/// token's `DOMAIN_SEPARATOR()` to catch a wrong name or version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenDomain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub token: Address,
+}
+
+impl TokenDomain {
+
+    pub fn new(name: impl Into<String>, version: impl Into<String>, chain_id: u64, token: Address) -> Self {
+        Self { name: name.into(), version: version.into(), chain_id, token }
+    }
+
+    pub fn eip712_domain(&self) -> Eip712Domain {
+        Eip712Domain {
+            name: Some(self.name.clone().into()),
+            version: Some(self.version.clone().into()),
+            chain_id: Some(U256::from(self.chain_id)),
+            verifying_contract: Some(self.token),
+            ..Default::default()
+        }
+    }
+
+    pub fn separator(&self) -> B256 {
+        self.eip712_domain().separator()
+    }
+
+}
+
+/// Deadline `ttl` from now, in unix seconds.
+pub fn deadline_in(ttl: Duration) -> U256 {
+    U256::from(unix_now() + ttl.as_secs())
+}
+
+fn check_deadline(deadline: U256, now: u64) -> Result<(), PermitError> {
+    match deadline < U256::from(now) {
+        true => Err(PermitError::Expired { deadline, now }),
+        false => Ok(()),
+    }
+}
+
+fn check_signer(expected: Address, actual: Address) -> Result<(), PermitError> {
+    match expected == actual {
+        true => Ok(()),
+        false => Err(PermitError::WrongSigner { expected, actual }),
+    }
+}
+
+impl Permit {
+
+    pub fn signing_hash(&self, domain: &TokenDomain) -> B256 {
+        self.eip712_signing_hash(&domain.eip712_domain())
+    }
+
+    pub async fn sign<S: Signer>(self, signer: &S, domain: &TokenDomain) -> SignerResult<SignedPermit> {
+        let signature = signer.sign_hash(&self.signing_hash(domain)).await?;
+        let (v, r, s) = signature_to_vrs(signature);
+        Ok(SignedPermit {
+            token: domain.token,
+            owner: self.owner,
+            spender: self.spender,
+            value: self.value,
+            nonce: self.nonce,
+            deadline: self.deadline,
+            v: 27 + v,
+            r: B256::from(r.to_be_bytes::<32>()),
+            s: B256::from(s.to_be_bytes::<32>()),
+        })
+    }
+
+}
+
+impl SignedPermit {
+
+    pub fn permit(&self) -> Permit {
+        Permit { owner: self.owner, spender: self.spender, value: self.value, nonce: self.nonce, deadline: self.deadline }
+    }
+
+    pub fn recover_signer(&self, domain: &TokenDomain) -> Result<Address, PermitError> {
+        let (r, s) = (U256::from_be_bytes(self.r.0), U256::from_be_bytes(self.s.0));
+        let signature = Signature::from_rs_and_parity(r, s, self.v as u64)
+            .map_err(|_| PermitError::InvalidSignature)?;
+        signature.recover_address_from_prehash(&self.permit().signing_hash(domain))
+            .map_err(|_| PermitError::InvalidSignature)
+    }
+
+    /// Check the permit is for `domain`'s token, not past its deadline at
+    /// `now` (unix seconds) and signed by the owner, ie. that `permit`
+    /// won't revert on those grounds.
+    pub fn verify(&self, domain: &TokenDomain, now: u64) -> Result<(), PermitError> {
+        if domain.token != self.token {
+            return Err(PermitError::DomainMismatch { expected: domain.token, actual: self.token });
+        }
+        check_deadline(self.deadline, now)?;
+        check_signer(self.owner, self.recover_signer(domain)?)
+    }
+
+}
+
+/// Permit2 `PermitBatch`, the allowances a single signature grants
+/// `spender`. Permit2 nonces are per (owner, token, spender), read from
+/// its `allowance(owner, token, spender)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermitBatch {
+    pub details: Vec<PermitDetails>,
+    pub spender: Address,
+    pub sig_deadline: U256,
+}
+
+impl PermitDetails {
+
+    fn check_range(&self) -> Result<(), PermitError> {
+        if self.amount.bit_len() > 160 {
+            return Err(PermitError::OutOfRange("amount"));
+        }
+        if self.expiration > U256::from(U48_MAX) {
+            return Err(PermitError::OutOfRange("expiration"));
+        }
+        if self.nonce > U256::from(U48_MAX) {
+            return Err(PermitError::OutOfRange("nonce"));
+        }
+        Ok(())
+    }
+
+    fn struct_hash(&self) -> B256 {
+        keccak256((keccak256(PERMIT_DETAILS_TYPE), self.token, self.amount, self.expiration, self.nonce).abi_encode())
+    }
+
+}
+
+impl PermitBatch {
+
+    pub fn eip712_domain(chain_id: u64) -> Eip712Domain {
+        Eip712Domain {
+            name: Some("Permit2".into()),
+            chain_id: Some(U256::from(chain_id)),
+            verifying_contract: Some(PERMIT2_ADDRESS),
+            ..Default::default()
+        }
+    }
+
+    /// Typed data hash, with the details hashed at their on-chain widths.
+    pub fn signing_hash(&self, chain_id: u64) -> Result<B256, PermitError> {
+        let mut details = Vec::with_capacity(32 * self.details.len());
+        for detail in &self.details {
+            detail.check_range()?;
+            details.extend_from_slice(detail.struct_hash().as_slice());
+        }
+        let type_hash = keccak256([PERMIT_BATCH_TYPE, PERMIT_DETAILS_TYPE].concat());
+        let struct_hash = keccak256((type_hash, keccak256(details), self.spender, self.sig_deadline).abi_encode());
+        let domain = Self::eip712_domain(chain_id);
+        Ok(keccak256([&[0x19u8, 0x01][..], domain.separator().as_slice(), struct_hash.as_slice()].concat()))
+    }
+
+    /// Sign the batch; details out of their on-chain range fail with the
+    /// `PermitError` as `SignerError::Other`.
+    pub async fn sign<S: Signer>(self, signer: &S, chain_id: u64) -> SignerResult<SignedPermitBatch> {
+        let hash = self.signing_hash(chain_id).map_err(SignerError::other)?;
+        let signature = signer.sign_hash(&hash).await?;
+        let (v, r, s) = signature_to_vrs(signature);
+        // r || s || v, as Permit2's `SignatureVerification` expects
+        let mut bytes = Vec::with_capacity(65);
+        bytes.extend_from_slice(&r.to_be_bytes::<32>());
+        bytes.extend_from_slice(&s.to_be_bytes::<32>());
+        bytes.push(27 + v);
+        Ok(SignedPermitBatch {
+            owner: signer.address(),
+            details: self.details,
+            spender: self.spender,
+            sigDeadline: self.sig_deadline,
+            signature: bytes.into(),
+        })
+    }
+
+}
+
+impl SignedPermitBatch {
+
+    pub fn batch(&self) -> PermitBatch {
+        PermitBatch { details: self.details.clone(), spender: self.spender, sig_deadline: self.sigDeadline }
+    }
+
+    pub fn recover_signer(&self, chain_id: u64) -> Result<Address, PermitError> {
+        let [sig @ .., v] = self.signature.as_ref() else {
+            return Err(PermitError::InvalidSignature);
+        };
+        if sig.len() != 64 {
+            return Err(PermitError::InvalidSignature);
+        }
+        let signature = Signature::from_rs_and_parity(
+            U256::from_be_slice(&sig[..32]),
+            U256::from_be_slice(&sig[32..]),
+            *v as u64,
+        ).map_err(|_| PermitError::InvalidSignature)?;
+        signature.recover_address_from_prehash(&self.batch().signing_hash(chain_id)?)
+            .map_err(|_| PermitError::InvalidSignature)
+    }
+
+    /// Check the fields fit their on-chain types, the signature deadline
+    /// and expirations (0 being "at the block it's used in") are not
+    /// past `now` and the owner signed it.
+    pub fn verify(&self, chain_id: u64, now: u64) -> Result<(), PermitError> {
+        check_deadline(self.sigDeadline, now)?;
+        for detail in &self.details {
+            if detail.expiration != U256::ZERO {
+                check_deadline(detail.expiration, now)?;
+            }
+        }
+        check_signer(self.owner, self.recover_signer(chain_id)?)
+    }
+
+}
+
+impl Approvals {
+
+    pub fn is_empty(&self) -> bool {
+        self.permits.is_empty() && self.batches.is_empty()
+    }
+
+    pub fn with_permit(mut self, permit: SignedPermit) -> Self {
+        self.permits.push(permit);
+        self
+    }
+
+    pub fn with_batch(mut self, batch: SignedPermitBatch) -> Self {
+        self.batches.push(batch);
+        self
+    }
+
+    pub fn encode(&self) -> Bytes {
+        self.abi_encode().into()
+    }
+
+    pub fn decode(data: &[u8]) -> alloy::sol_types::Result<Self> {
+        Self::abi_decode(data, true)
+    }
+
+}
+
+/// Nonces handed out for permits not yet used on chain, so several
+/// permits signed before any lands don't reuse a nonce. The on-chain
+/// nonce passed in wins once it catches up.
+#[derive(Debug, Clone, Default)]
+pub struct PermitNonces {
+    erc2612: HashMap<(Address, Address), U256>,
+    permit2: HashMap<(Address, Address, Address), U256>,
+}
+
+impl PermitNonces {
+
+    /// Next ERC-2612 nonce of `owner` on `token`, given its current
+    /// `nonces(owner)`.
+    pub fn next_erc2612(&mut self, token: Address, owner: Address, onchain: U256) -> U256 {
+        reserve(self.erc2612.entry((token, owner)).or_default(), onchain)
+    }
+
+    /// Next Permit2 nonce of (`owner`, `token`, `spender`), given the one
+    /// of its current allowance.
+    pub fn next_permit2(&mut self, owner: Address, token: Address, spender: Address, onchain: U256) -> U256 {
+        reserve(self.permit2.entry((owner, token, spender)).or_default(), onchain)
+    }
+
+}
+
+fn reserve(next: &mut U256, onchain: U256) -> U256 {
+    let nonce = (*next).max(onchain);
+    *next = nonce + U256::from(1);
+    nonce
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Carry `approvals` in the inputs envelope, next to the intent in the
+    /// payload.
+    pub fn with_approvals(mut self, approvals: &Approvals) -> Self {
+        self.set_approvals(approvals);
+        self
+    }
+
+    pub fn set_approvals(&mut self, approvals: &Approvals) {
+        let mut envelope = ConfidentialEnvelope::decode(&self.confidential_inputs)
+            .unwrap_or_else(|_| ConfidentialEnvelope::new(self.confidential_inputs.clone()));
+        envelope.set_extension(EnvelopeExtension::APPROVALS, approvals.encode());
+        self.set_confidential_inputs(envelope.encode());
+    }
+
+    pub fn approvals(&self) -> Option<Approvals> {
+        let envelope = ConfidentialEnvelope::decode(&self.confidential_inputs).ok()?;
+        Approvals::decode(envelope.extension(EnvelopeExtension::APPROVALS)?).ok()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::wallet::LocalWallet;
+    use super::*;
+
+    fn wallet() -> LocalWallet {
+        "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap()
+    }
+
+    fn usdc() -> TokenDomain {
+        TokenDomain::new("USD Coin", "2", 1, address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"))
+    }
+
+    #[test]
+    fn test_token_domain_separator() {
+        let domain = usdc();
+        let type_hash = keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)");
+        let expected = (type_hash, keccak256(&domain.name), keccak256(&domain.version), U256::from(domain.chain_id), domain.token);
+        assert_eq!(domain.separator(), keccak256(expected.abi_encode()));
+    }
+
+    #[tokio::test]
+    async fn test_erc2612_permit() {
+        let wallet = wallet();
+        let spender = Address::repeat_byte(0x22);
+        let mut nonces = PermitNonces::default();
+        let nonce = nonces.next_erc2612(usdc().token, wallet.address(), U256::from(3));
+        assert_eq!(nonces.next_erc2612(usdc().token, wallet.address(), U256::from(3)), U256::from(4));
+
+        let permit = Permit { owner: wallet.address(), spender, value: U256::from(1_000_000), nonce, deadline: U256::from(2_000) };
+        let signed = permit.sign(&wallet, &usdc()).await.unwrap();
+        assert_eq!(signed.nonce, U256::from(3));
+        assert!(signed.v == 27 || signed.v == 28);
+        assert_eq!(signed.verify(&usdc(), 1_000), Ok(()));
+        assert_eq!(signed.verify(&usdc(), 2_001), Err(PermitError::Expired { deadline: U256::from(2_000), now: 2_001 }));
+
+        let other_chain = TokenDomain { chain_id: 10, ..usdc() };
+        assert!(matches!(signed.verify(&other_chain, 1_000), Err(PermitError::WrongSigner { .. })));
+        let other_token = TokenDomain { token: spender, ..usdc() };
+        assert!(matches!(signed.verify(&other_token, 1_000), Err(PermitError::DomainMismatch { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_permit2_batch() {
+        let wallet = wallet();
+        let detail = PermitDetails {
+            token: usdc().token,
+            amount: U256::from(1_000_000),
+            expiration: U256::ZERO,
+            nonce: U256::from(7),
+        };
+        let batch = PermitBatch { details: vec![detail], spender: Address::repeat_byte(0x22), sig_deadline: U256::from(2_000) };
+        let signed = batch.clone().sign(&wallet, 1).await.unwrap();
+        assert_eq!(signed.signature.len(), 65);
+        assert_eq!(signed.owner, wallet.address());
+        assert_eq!(signed.verify(1, 1_000), Ok(()));
+        assert!(matches!(signed.verify(5, 1_000), Err(PermitError::WrongSigner { .. })));
+        assert!(matches!(signed.verify(1, 2_001), Err(PermitError::Expired { .. })));
+
+        let mut too_large = batch;
+        too_large.details[0].amount = U256::from(1) << 160;
+        assert_eq!(too_large.signing_hash(1), Err(PermitError::OutOfRange("amount")));
+        let err = too_large.sign(&wallet, 1).await.unwrap_err();
+        assert!(err.to_string().contains("Permit2 amount out of range"));
+    }
+
+    #[tokio::test]
+    async fn test_approvals_in_inputs() {
+        let wallet = wallet();
+        let permit = Permit {
+            owner: wallet.address(),
+            spender: Address::repeat_byte(0x22),
+            value: U256::MAX,
+            nonce: U256::ZERO,
+            deadline: deadline_in(Duration::from_secs(600)),
+        };
+        let approvals = Approvals::default().with_permit(permit.sign(&wallet, &usdc()).await.unwrap());
+        assert!(!approvals.is_empty());
+        assert_eq!(Approvals::decode(&approvals.encode()).unwrap(), approvals);
+
+        let ccr = ConfidentialComputeRequest::default()
+            .with_confidential_inputs(Bytes::from_static(b"order"))
+            .with_approvals(&approvals);
+        assert_eq!(ccr.approvals(), Some(approvals));
+        let envelope = ConfidentialEnvelope::decode(&ccr.confidential_inputs).unwrap();
+        assert_eq!(envelope.payload, Bytes::from_static(b"order"));
+        ccr.validate_inputs_hash().unwrap();
+    }
+
+}
@@ -0,0 +1,175 @@
+use alloy::{
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    json_abi::JsonAbi,
+    primitives::{hex, keccak256, Address, FixedBytes, B256, U256},
+};
+use super::{format_fee, ConfidentialComputeRequest, Fees};
+
+
+/// Calldata decoded against an ABI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedCall {
+    /// Full signature, e.g. `submitBid(uint64,bytes)`.
+    pub signature: String,
+    pub args: Vec<DynSolValue>,
+}
+
+/// What the kettle will execute for a CCR, for human approval steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CcrPreview {
+    /// `None` for contract creation.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub chain_id: Option<u64>,
+    pub nonce: Option<u64>,
+    pub kettle_address: Option<Address>,
+    pub selector: Option<FixedBytes<4>>,
+    /// Decoded calldata; `None` without an ABI or if no function matches.
+    pub call: Option<DecodedCall>,
+    pub calldata_len: usize,
+    pub confidential_inputs_len: usize,
+    pub confidential_inputs_hash: B256,
+    pub gas: Option<u128>,
+    pub fees: Option<Fees>,
+    /// `gas * gas_price` (or max fee per gas), the most the sender can be charged.
+    pub max_fee: Option<U256>,
+    /// Sender recovered from the signature; `None` if unsigned.
+    pub sender: Option<Address>,
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Preview of the request, decoding the calldata with `abi` if given.
+    pub fn preview(&self, abi: Option<&JsonAbi>) -> CcrPreview {
+        let record = &self.confidential_compute_record;
+        let selector = record.input.get(..4).map(FixedBytes::<4>::from_slice);
+        let call = abi.zip(selector).and_then(|(abi, selector)| decode_call(abi, selector, &record.input[4..]));
+        let sender = self.recover_signer().ok();
+        let max_fee = record.gas.zip(record.max_gas_price())
+            .map(|(gas, gas_price)| U256::from(gas) * U256::from(gas_price));
+        CcrPreview {
+            to: record.target(),
+            value: record.value,
+            chain_id: record.chain_id,
+            nonce: record.nonce,
+            kettle_address: record.kettle_address,
+            selector,
+            call,
+            calldata_len: record.input.len(),
+            confidential_inputs_len: self.confidential_inputs.len(),
+            confidential_inputs_hash: keccak256(&self.confidential_inputs),
+            gas: record.gas,
+            fees: record.fees(),
+            max_fee,
+            sender,
+        }
+    }
+
+}
+
+fn decode_call(abi: &JsonAbi, selector: FixedBytes<4>, data: &[u8]) -> Option<DecodedCall> {
+    let function = abi.functions().find(|f| f.selector() == selector)?;
+    let args = function.abi_decode_input(data, false).ok()?;
+    Some(DecodedCall { signature: function.signature(), args })
+}
+
+fn format_value(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Int(i, _) => i.to_string(),
+        DynSolValue::Uint(u, _) => u.to_string(),
+        DynSolValue::Address(a) => a.to_string(),
+        DynSolValue::FixedBytes(word, size) => hex::encode_prefixed(&word[..*size]),
+        DynSolValue::Bytes(b) => hex::encode_prefixed(b),
+        DynSolValue::String(s) => format!("{s:?}"),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
+            format!("[{}]", items.iter().map(format_value).collect::<Vec<_>>().join(", "))
+        }
+        DynSolValue::Tuple(items) => {
+            format!("({})", items.iter().map(format_value).collect::<Vec<_>>().join(", "))
+        }
+        other => format!("{other:?}"),
+    }
+}
+
+fn or_unset<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| "<unset>".into())
+}
+
+impl std::fmt::Display for CcrPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "sender:              {}", or_unset(self.sender))?;
+        match self.to {
+            Some(to) => writeln!(f, "to:                  {to}")?,
+            None => writeln!(f, "to:                  <contract creation>")?,
+        }
+        writeln!(f, "value:               {}", format_fee(self.value))?;
+        writeln!(f, "kettle:              {}", or_unset(self.kettle_address))?;
+        writeln!(f, "chain id:            {}", or_unset(self.chain_id))?;
+        writeln!(f, "nonce:               {}", or_unset(self.nonce))?;
+        match &self.call {
+            Some(call) => {
+                let args = call.args.iter().map(format_value).collect::<Vec<_>>().join(", ");
+                writeln!(f, "call:                {} ({args})", call.signature)?;
+            }
+            None => writeln!(f, "call:                {} ({} bytes)", or_unset(self.selector), self.calldata_len)?,
+        }
+        writeln!(f, "confidential inputs: {} bytes, hash {}", self.confidential_inputs_len, self.confidential_inputs_hash)?;
+        let price = match self.fees {
+            Some(Fees::Legacy { gas_price }) => format!("{gas_price} wei"),
+            Some(Fees::Dynamic { max_priority_fee_per_gas, max_fee_per_gas }) =>
+                format!("max {max_fee_per_gas} wei (tip {max_priority_fee_per_gas} wei)"),
+            None => "<unset>".into(),
+        };
+        write!(
+            f, "gas:                 {} at {price} (max fee {})",
+            or_unset(self.gas), or_unset(self.max_fee.map(format_fee)),
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_preview() {
+        let fixture = fixtures::load_fixture("empty_inputs").unwrap();
+        let abi: JsonAbi = serde_json::from_str(r#"[{
+            "type": "function",
+            "name": "queryLatestPrice",
+            "inputs": [{"name": "ticker", "type": "string"}],
+            "outputs": [{"name": "price", "type": "uint256"}],
+            "stateMutability": "view"
+        }]"#).unwrap();
+
+        let preview = fixture.request.preview(Some(&abi));
+        assert_eq!(preview.sender, Some(fixture.signer));
+        assert_eq!(preview.confidential_inputs_len, 0);
+        let call = preview.call.as_ref().unwrap();
+        assert_eq!(call.signature, "queryLatestPrice(string)");
+        assert_eq!(call.args, vec![DynSolValue::String("ETHUSDT".into())]);
+        assert!(preview.to_string().contains(r#"queryLatestPrice(string) ("ETHUSDT")"#));
+
+        let raw = fixture.request.preview(None);
+        assert_eq!(raw.call, None);
+        assert_eq!(raw.selector, preview.selector);
+        assert_eq!(raw.max_fee, Some(U256::from(0x0f4240u64) * U256::from(0x3b9aca00u64)));
+        assert!(raw.to_string().ends_with("(max fee 0.001 SETH)"));
+        assert!(ConfidentialComputeRequest::default().preview(None).to_string().contains("sender:              <unset>"));
+    }
+
+    #[test]
+    fn test_preview_dynamic_fees() {
+        let mut request = fixtures::load_fixture("empty_inputs").unwrap().request;
+        let fees = Fees::Dynamic { max_priority_fee_per_gas: 1_000, max_fee_per_gas: 0x77359400 };
+        request.confidential_compute_record.set_fees(fees);
+        let preview = request.preview(None);
+        assert_eq!(preview.fees, Some(fees));
+        assert_eq!(preview.max_fee, Some(U256::from(0x0f4240u64) * U256::from(0x77359400u64)));
+        assert!(preview.to_string().contains("at max 2000000000 wei (tip 1000 wei) (max fee 0.002 SETH)"));
+    }
+
+}
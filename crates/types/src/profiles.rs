@@ -0,0 +1,129 @@
+use alloy::primitives::{address, Address};
+use super::ConfidentialComputeRequest;
+
+
+/// Public endpoints and kettles of a SUAVE chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainProfile {
+    pub name: &'static str,
+    pub chain_id: u64,
+    pub rpc_url: &'static str,
+    /// Kettles serving the public RPC, the first one being the default.
+    pub kettle_addresses: &'static [Address],
+    pub explorer_url: &'static str,
+}
+
+/// Rigil testnet, being retired in favour of Toliman.
+pub const RIGIL: ChainProfile = ChainProfile {
+    name: "rigil",
+    chain_id: 0x1008c45,
+    rpc_url: "https://rpc.rigil.suave.flashbots.net",
+    kettle_addresses: &[address!("03493869959c866713c33669ca118e774a30a0e5")],
+    explorer_url: "https://explorer.rigil.suave.flashbots.net",
+};
+
+pub const TOLIMAN: ChainProfile = ChainProfile {
+    name: "toliman",
+    chain_id: 0x201188a,
+    rpc_url: "https://rpc.toliman.suave.flashbots.net",
+    kettle_addresses: &[address!("f579de142d98f8379c54105ac944fe133b7a17fe")],
+    explorer_url: "https://explorer.toliman.suave.flashbots.net",
+};
+
+pub const KNOWN_PROFILES: &[ChainProfile] = &[RIGIL, TOLIMAN];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationError {
+    /// The request is of another chain than the one migrated from.
+    UnexpectedChain { expected: u64, actual: u64 },
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::UnexpectedChain { expected, actual } =>
+                write!(f, "Request is for chain {actual}, expected chain {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl ChainProfile {
+
+    pub fn by_chain_id(chain_id: u64) -> Option<&'static ChainProfile> {
+        KNOWN_PROFILES.iter().find(|profile| profile.chain_id == chain_id)
+    }
+
+    /// Case-insensitive lookup, eg. from a CLI flag or env var.
+    pub fn by_name(name: &str) -> Option<&'static ChainProfile> {
+        KNOWN_PROFILES.iter().find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn kettle_address(&self) -> Address {
+        self.kettle_addresses[0]
+    }
+
+    /// Unsigned copy of a saved request of `from` for this chain: chain id
+    /// and kettle are replaced, nonce and gas price cleared for the fillers
+    /// as neither carries over. Requests without a chain id are accepted.
+    pub fn migrate_from(
+        &self,
+        from: &ChainProfile,
+        ccr: &ConfidentialComputeRequest,
+    ) -> Result<ConfidentialComputeRequest, MigrationError> {
+        if let Some(actual) = ccr.confidential_compute_record.chain_id {
+            if actual != from.chain_id {
+                return Err(MigrationError::UnexpectedChain { expected: from.chain_id, actual });
+            }
+        }
+        let mut migrated = ccr.retarget(self.chain_id, self.kettle_address());
+        let record = &mut migrated.confidential_compute_record;
+        record.nonce = None;
+        record.gas_price = None;
+        Ok(migrated)
+    }
+
+}
+
+/// Retarget a request saved on Rigil to Toliman (see `ChainProfile::migrate_from`).
+pub fn migrate_profile(rigil_ccr: &ConfidentialComputeRequest) -> Result<ConfidentialComputeRequest, MigrationError> {
+    TOLIMAN.migrate_from(&RIGIL, rigil_ccr)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_profiles() {
+        assert_eq!(ChainProfile::by_chain_id(0x201188a), Some(&TOLIMAN));
+        assert_eq!(ChainProfile::by_name("Rigil"), Some(&RIGIL));
+        assert_eq!(ChainProfile::by_name("holesky"), None);
+        for profile in KNOWN_PROFILES {
+            assert_eq!(super::super::explorer_base_url(profile.chain_id), Some(profile.explorer_url));
+        }
+    }
+
+    #[test]
+    fn test_migrate_profile() {
+        let rigil_ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        assert_eq!(rigil_ccr.confidential_compute_record.chain_id, Some(RIGIL.chain_id));
+
+        let toliman_ccr = migrate_profile(&rigil_ccr).unwrap();
+        let record = &toliman_ccr.confidential_compute_record;
+        assert_eq!(record.chain_id, Some(TOLIMAN.chain_id));
+        assert_eq!(record.kettle_address, Some(TOLIMAN.kettle_address()));
+        assert_eq!((record.nonce, record.gas_price, record.signature), (None, None, None));
+        assert_eq!(record.input, rigil_ccr.confidential_compute_record.input);
+        assert_eq!(toliman_ccr.confidential_inputs, rigil_ccr.confidential_inputs);
+
+        assert_eq!(
+            migrate_profile(&toliman_ccr),
+            Err(MigrationError::UnexpectedChain { expected: RIGIL.chain_id, actual: TOLIMAN.chain_id })
+        );
+    }
+
+}
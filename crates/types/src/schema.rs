@@ -0,0 +1,153 @@
+//! JSON Schemas (draft 2020-12) of the JSON representations produced and
+//! accepted by this crate.
+
+use serde_json::{json, Value};
+
+
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Shared definitions referenced with `#/$defs/<name>`.
+pub fn definitions() -> Value {
+    json!({
+        "address": {
+            "type": "string",
+            "pattern": "^0x[0-9a-fA-F]{40}$"
+        },
+        "hash": {
+            "type": "string",
+            "pattern": "^0x[0-9a-fA-F]{64}$"
+        },
+        "bytes": {
+            "type": "string",
+            "pattern": "^0x([0-9a-fA-F]{2})*$"
+        },
+        "quantity": {
+            "type": "string",
+            "pattern": "^0x(0|[1-9a-fA-F][0-9a-fA-F]*)$"
+        },
+        "quantityOrDecimal": {
+            "oneOf": [
+                { "$ref": "#/$defs/quantity" },
+                { "type": "string", "pattern": "^[0-9]+$" },
+                { "type": "integer", "minimum": 0 }
+            ]
+        },
+        "confidentialComputeRecord": {
+            "type": "object",
+            "properties": {
+                "nonce": { "$ref": "#/$defs/quantity" },
+                "to": { "$ref": "#/$defs/address" },
+                "gas": { "$ref": "#/$defs/quantityOrDecimal" },
+                "gasPrice": { "$ref": "#/$defs/quantityOrDecimal" },
+                "value": { "$ref": "#/$defs/quantity" },
+                "input": { "$ref": "#/$defs/bytes" },
+                "kettleAddress": { "$ref": "#/$defs/address" },
+                "chainId": { "$ref": "#/$defs/quantity" },
+                "confidentialInputsHash": { "$ref": "#/$defs/hash" },
+                "r": { "$ref": "#/$defs/quantity" },
+                "s": { "$ref": "#/$defs/quantity" },
+                "v": { "$ref": "#/$defs/quantity" },
+                "yParity": { "$ref": "#/$defs/quantity" }
+            },
+            "required": ["to", "value", "input"],
+            "dependentRequired": { "r": ["s", "v"], "s": ["r", "v"] }
+        },
+        "confidentialComputeRequest": {
+            "type": "object",
+            "properties": {
+                "confidentialComputeRecord": { "$ref": "#/$defs/confidentialComputeRecord" },
+                "confidentialInputs": { "$ref": "#/$defs/bytes" }
+            },
+            "required": ["confidentialComputeRecord", "confidentialInputs"],
+            "additionalProperties": false
+        },
+        "confidentialCallResponse": {
+            "type": "object",
+            "properties": {
+                "hash": { "$ref": "#/$defs/hash" },
+                "type": { "const": "0x50" },
+                "confidentialComputeResult": { "$ref": "#/$defs/bytes" },
+                "requestRecord": { "$ref": "#/$defs/confidentialComputeRecord" }
+            },
+            "required": ["hash", "confidentialComputeResult", "requestRecord"]
+        }
+    })
+}
+
+/// Same as [`definitions`] with references rebased onto `prefix`,
+/// e.g. `#/components/schemas/` for embedding in OpenRPC documents.
+pub fn definitions_at(prefix: &str) -> Value {
+    let mut defs = definitions();
+    rebase_refs(&mut defs, prefix);
+    defs
+}
+
+fn rebase_refs(value: &mut Value, prefix: &str) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                match v {
+                    Value::String(r) if key == "$ref" => {
+                        if let Some(name) = r.strip_prefix("#/$defs/") {
+                            *r = format!("{prefix}{name}");
+                        }
+                    }
+                    _ => rebase_refs(v, prefix),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| rebase_refs(v, prefix)),
+        _ => {}
+    }
+}
+
+fn schema_for(name: &str, title: &str) -> Value {
+    json!({
+        "$schema": DIALECT,
+        "title": title,
+        "$ref": format!("#/$defs/{name}"),
+        "$defs": definitions(),
+    })
+}
+
+pub fn confidential_compute_record() -> Value {
+    schema_for("confidentialComputeRecord", "ConfidentialComputeRecord")
+}
+
+pub fn confidential_compute_request() -> Value {
+    schema_for("confidentialComputeRequest", "ConfidentialComputeRequest")
+}
+
+/// Transaction returned by `eth_getTransactionByHash` for executed CCRs.
+pub fn confidential_call_response() -> Value {
+    schema_for("confidentialCallResponse", "ConfidentialCallResponse")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_schema_matches_serialization() {
+        let defs = definitions();
+        let record_props = defs["confidentialComputeRecord"]["properties"].as_object().unwrap();
+        let request_schema = &defs["confidentialComputeRequest"];
+
+        for fixture in fixtures::load_fixtures() {
+            let json = serde_json::to_value(&fixture.request).unwrap();
+            for key in request_schema["required"].as_array().unwrap() {
+                assert!(json.get(key.as_str().unwrap()).is_some(), "{} misses {key}", fixture.name);
+            }
+            let record = json["confidentialComputeRecord"].as_object().unwrap();
+            for key in record.keys() {
+                assert!(record_props.contains_key(key), "{key} not in record schema");
+            }
+        }
+        assert_eq!(confidential_compute_request()["$ref"], "#/$defs/confidentialComputeRequest");
+        let rebased = definitions_at("#/components/schemas/");
+        assert_eq!(rebased["confidentialComputeRecord"]["properties"]["to"]["$ref"], "#/components/schemas/address");
+    }
+
+}
@@ -0,0 +1,226 @@
+use alloy::primitives::{Address, Signature, B256, U256};
+use super::{
+    crecord::signature_to_vrs,
+    decode::{SECP256K1N, SECP256K1N_HALF},
+    CcrError,
+    ConfidentialComputeRequest,
+};
+
+
+/// Signature algorithm of CCRs. Signatures travel in the record's
+/// `(v, r, s)` fields whatever the scheme, so the envelope encoding stays
+/// the same: `v` is a single parity bit (0 or 1), `r` and `s` any 256 bit
+/// values. `Secp256k1` is the default and the only scheme SUAVE nodes
+/// accept; forks experimenting with eg. P-256 / WebAuthn signed requests
+/// plug in their own implementation.
+///
+/// Strict decoding (`decode_2718_with_scheme`) and `CcrMempool::with_scheme`
+/// verify requests under a given scheme. `recover_signer`, `SignedRequestExt`
+/// and the network crate's `SuaveSigner` are secp256k1 only; requests of
+/// other schemes are signed outside and attached with `set_scheme_signature`.
+pub trait SignatureScheme {
+    type Signature;
+
+    /// Wire fields of a signature.
+    fn to_vrs(&self, signature: &Self::Signature) -> (u8, U256, U256);
+
+    fn from_vrs(&self, v: u8, r: U256, s: U256) -> Result<Self::Signature, SignatureSchemeError>;
+
+    /// Address that signed `prehash`.
+    fn signer(&self, signature: &Self::Signature, prehash: &B256) -> Result<Address, SignatureSchemeError>;
+
+    /// Encoding rules `DecodeMode::Strict` enforces on top of `signer`
+    /// succeeding (eg. low-s). None by default.
+    fn check_canonical(&self, _signature: &Self::Signature) -> Result<(), &'static str> {
+        Ok(())
+    }
+}
+
+/// Ethereum ECDSA over secp256k1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Secp256k1;
+
+impl SignatureScheme for Secp256k1 {
+    type Signature = Signature;
+
+    fn to_vrs(&self, signature: &Signature) -> (u8, U256, U256) {
+        signature_to_vrs(*signature)
+    }
+
+    fn from_vrs(&self, v: u8, r: U256, s: U256) -> Result<Signature, SignatureSchemeError> {
+        Signature::from_rs_and_parity(r, s, v as u64)
+            .map_err(|e| SignatureSchemeError::Invalid(e.to_string()))
+    }
+
+    fn signer(&self, signature: &Signature, prehash: &B256) -> Result<Address, SignatureSchemeError> {
+        signature.recover_address_from_prehash(prehash)
+            .map_err(|e| SignatureSchemeError::Verification(e.to_string()))
+    }
+
+    fn check_canonical(&self, signature: &Signature) -> Result<(), &'static str> {
+        if signature.r().is_zero() || signature.s().is_zero() || signature.r() >= SECP256K1N {
+            return Err("Signature scalar out of range");
+        }
+        if signature.s() > SECP256K1N_HALF {
+            return Err("High-s signature");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureSchemeError {
+    Unsigned,
+    /// Wire fields not forming a valid signature of the scheme.
+    Invalid(String),
+    Verification(String),
+    /// Request missing fields needed for its signing hash.
+    Request(CcrError),
+}
+
+impl std::fmt::Display for SignatureSchemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureSchemeError::Unsigned => write!(f, "Request is not signed"),
+            SignatureSchemeError::Invalid(err) => write!(f, "Invalid signature: {err}"),
+            SignatureSchemeError::Verification(err) => write!(f, "Signature verification failed: {err}"),
+            SignatureSchemeError::Request(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureSchemeError {}
+
+impl ConfidentialComputeRequest {
+
+    /// Set a signature of `scheme` over `signing_hash`.
+    pub fn set_scheme_signature<S: SignatureScheme>(
+        &mut self,
+        scheme: &S,
+        signature: &S::Signature,
+    ) -> Result<(), SignatureSchemeError> {
+        let (v, r, s) = scheme.to_vrs(signature);
+        if v > 1 {
+            // Stored as a parity bit; anything else wouldn't round-trip
+            return Err(SignatureSchemeError::Invalid(format!("v must be 0 or 1, got {v}")));
+        }
+        let wire = Signature::from_rs_and_parity(r, s, v == 1)
+            .map_err(|e| SignatureSchemeError::Invalid(e.to_string()))?;
+        self.confidential_compute_record.set_sig(wire);
+        Ok(())
+    }
+
+    pub fn scheme_signature<S: SignatureScheme>(&self, scheme: &S) -> Result<S::Signature, SignatureSchemeError> {
+        let wire = self.confidential_compute_record.signature.ok_or(SignatureSchemeError::Unsigned)?;
+        let (v, r, s) = signature_to_vrs(wire);
+        scheme.from_vrs(v, r, s)
+    }
+
+    /// Signer of the request under `scheme`.
+    pub fn recover_signer_with<S: SignatureScheme>(&self, scheme: &S) -> Result<Address, SignatureSchemeError> {
+        let signing_hash = self.signing_hash().map_err(SignatureSchemeError::Request)?;
+        scheme.signer(&self.scheme_signature(scheme)?, &signing_hash)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{eips::eip2718::{Decodable2718, Encodable2718}, primitives::keccak256};
+    use super::*;
+    use super::super::{fixtures, CcrMempool, DecodeMode, MempoolConfig};
+
+    /// Toy scheme: the "signature" is the signer address in `r`, valid for
+    /// any prehash. Stands in for a P-256 verifier.
+    struct AddressInR;
+
+    impl SignatureScheme for AddressInR {
+        type Signature = Address;
+
+        fn to_vrs(&self, signer: &Address) -> (u8, U256, U256) {
+            (0, U256::from_be_slice(signer.as_slice()), U256::from(1))
+        }
+
+        fn from_vrs(&self, _v: u8, r: U256, _s: U256) -> Result<Address, SignatureSchemeError> {
+            let bytes = r.to_be_bytes::<32>();
+            Ok(Address::from_slice(&bytes[12..]))
+        }
+
+        fn signer(&self, signer: &Address, _prehash: &B256) -> Result<Address, SignatureSchemeError> {
+            Ok(*signer)
+        }
+    }
+
+    #[test]
+    fn test_default_scheme_is_wire_compatible() {
+        for fixture in fixtures::load_fixtures() {
+            let ccr = &fixture.request;
+            assert_eq!(ccr.recover_signer_with(&Secp256k1).unwrap(), fixture.signer);
+
+            let sig = ccr.scheme_signature(&Secp256k1).unwrap();
+            let mut resigned = ccr.clone();
+            resigned.set_scheme_signature(&Secp256k1, &sig).unwrap();
+            assert_eq!(resigned.tx_hash().unwrap(), fixture.tx_hash);
+        }
+    }
+
+    #[test]
+    fn test_custom_scheme() {
+        let mut ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        let signer = Address::from_slice(&keccak256(b"p256 key")[12..]);
+        ccr.set_scheme_signature(&AddressInR, &signer).unwrap();
+        assert_eq!(ccr.recover_signer_with(&AddressInR).unwrap(), signer);
+        assert!(ccr.recover_signer_with(&Secp256k1).map_or(true, |recovered| recovered != signer));
+
+        ccr.confidential_compute_record.signature = None;
+        assert_eq!(ccr.recover_signer_with(&AddressInR), Err(SignatureSchemeError::Unsigned));
+    }
+
+    #[test]
+    fn test_custom_scheme_decode_2718() {
+        let mut ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        let signer = Address::from_slice(&keccak256(b"p256 key")[12..]);
+        ccr.set_scheme_signature(&AddressInR, &signer).unwrap();
+        let raw = ccr.encoded_2718();
+
+        let decoded = ConfidentialComputeRequest::decode_2718(&mut raw.as_slice()).unwrap();
+        assert_eq!(decoded.scheme_signature(&AddressInR).unwrap(), signer);
+        let strict = ConfidentialComputeRequest::decode_2718_with_scheme(&raw, DecodeMode::Strict, &AddressInR).unwrap();
+        assert_eq!(strict, decoded);
+        assert_eq!(strict.recover_signer_with(&AddressInR).unwrap(), signer);
+
+        let sender = CcrMempool::with_scheme(MempoolConfig::default(), AddressInR).validate(&strict).unwrap();
+        assert_eq!(sender, signer);
+    }
+
+    #[test]
+    fn test_wire_parity_only() {
+        struct WideV;
+        impl SignatureScheme for WideV {
+            type Signature = ();
+            fn to_vrs(&self, _: &()) -> (u8, U256, U256) {
+                (27, U256::from(1), U256::from(1))
+            }
+            fn from_vrs(&self, _v: u8, _r: U256, _s: U256) -> Result<(), SignatureSchemeError> {
+                Ok(())
+            }
+            fn signer(&self, _: &(), _prehash: &B256) -> Result<Address, SignatureSchemeError> {
+                Ok(Address::ZERO)
+            }
+        }
+        let mut ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        assert!(matches!(ccr.set_scheme_signature(&WideV, &()), Err(SignatureSchemeError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_incomplete_request() {
+        let mut ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        ccr.confidential_compute_record.gas = None;
+        assert_eq!(
+            ccr.recover_signer_with(&Secp256k1),
+            Err(SignatureSchemeError::Request(CcrError::MissingField("gas")))
+        );
+    }
+
+}
@@ -0,0 +1,358 @@
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use alloy::{
+    primitives::{self, Address, Bytes, Signature, TxKind, B256, U256},
+    signers::{Signer, Result as SignerResult},
+};
+use super::{
+    crecord::signature_to_vrs,
+    envelope::{ConfidentialEnvelope, EnvelopeExtension},
+    CcrError,
+    ConfidentialComputeRequest,
+    Fees,
+};
+
+
+/// Domain prefix of the voucher signing payload.
+pub const VOUCHER_DOMAIN: &[u8] = b"SUAVE_FEE_VOUCHER_V1";
+
+/// Terms under which a sponsor agrees to pay for a sender's CCR.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct FeeVoucherParams {
+    pub sponsor: Address,
+    pub sender: Address,
+    pub chain_id: u64,
+    /// Nonce of the sponsored CCR.
+    pub nonce: u64,
+    /// Gas the sponsor covers. Sponsored requests have a zero gas price,
+    /// the sponsor settles their execution outside the CCR.
+    pub max_gas: u128,
+    /// Unix timestamp (seconds) after which the voucher is void.
+    pub expiry: u64,
+    /// Called contract of the sponsored CCR.
+    pub to: TxKind,
+    /// Keccak of the sponsored CCR's calldata.
+    pub input_hash: B256,
+    /// Keccak of the confidential inputs payload, without the envelope the
+    /// voucher itself travels in (see `payload_hash`).
+    pub payload_hash: B256,
+}
+
+impl FeeVoucherParams {
+
+    /// Voucher terms covering `ccr` as it is (gas limit, nonce, chain id,
+    /// target, calldata and confidential inputs).
+    pub fn for_request(
+        ccr: &ConfidentialComputeRequest,
+        sponsor: Address,
+        sender: Address,
+        expiry: u64,
+    ) -> Result<Self, VoucherError> {
+        let record = &ccr.confidential_compute_record;
+        Ok(Self {
+            sponsor,
+            sender,
+            chain_id: record.chain_id.ok_or(VoucherError::MissingField("chain_id"))?,
+            nonce: record.nonce.ok_or(VoucherError::MissingField("nonce"))?,
+            max_gas: record.gas.ok_or(VoucherError::MissingField("gas"))?,
+            expiry,
+            to: record.to,
+            input_hash: primitives::keccak256(&record.input),
+            payload_hash: payload_hash(ccr),
+        })
+    }
+
+    pub fn signing_hash(&self) -> B256 {
+        let mut buf = VOUCHER_DOMAIN.to_vec();
+        self.encode(&mut buf);
+        primitives::keccak256(buf)
+    }
+
+    pub fn into_signed(self, signature: Signature) -> FeeVoucher {
+        FeeVoucher { params: self, signature }
+    }
+
+    pub async fn sign<S: Signer>(self, signer: &S) -> SignerResult<FeeVoucher> {
+        let signature = signer.sign_hash(&self.signing_hash()).await?;
+        Ok(self.into_signed(signature))
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeVoucher {
+    pub params: FeeVoucherParams,
+    pub signature: Signature,
+}
+
+#[derive(RlpEncodable, RlpDecodable)]
+struct FeeVoucherRLP {
+    params: FeeVoucherParams,
+    v: u8,
+    r: U256,
+    s: U256,
+}
+
+impl FeeVoucher {
+
+    pub fn encode(&self) -> Bytes {
+        let (v, r, s) = signature_to_vrs(self.signature);
+        let rlp = FeeVoucherRLP { params: self.params.clone(), v, r, s };
+        let mut out = Vec::new();
+        rlp.encode(&mut out);
+        out.into()
+    }
+
+    pub fn decode(mut buf: &[u8]) -> alloy_rlp::Result<Self> {
+        let rlp = FeeVoucherRLP::decode(&mut buf)?;
+        if !buf.is_empty() {
+            return Err(alloy_rlp::Error::Custom("Trailing bytes after voucher"));
+        }
+        let signature = Signature::from_rs_and_parity(rlp.r, rlp.s, rlp.v as u64)
+            .map_err(|_| alloy_rlp::Error::Custom("Invalid voucher signature"))?;
+        Ok(Self { params: rlp.params, signature })
+    }
+
+    pub fn recover_sponsor(&self) -> Result<Address, VoucherError> {
+        self.signature
+            .recover_address_from_prehash(&self.params.signing_hash())
+            .map_err(|_| VoucherError::InvalidSignature)
+    }
+
+    /// Check that the voucher was signed by its sponsor and covers `ccr`
+    /// at time `now` (unix seconds).
+    pub fn verify(&self, ccr: &ConfidentialComputeRequest, now: u64) -> Result<(), VoucherError> {
+        let params = &self.params;
+        let record = &ccr.confidential_compute_record;
+
+        let sponsor = self.recover_sponsor()?;
+        if sponsor != params.sponsor {
+            return Err(VoucherError::SponsorMismatch { expected: params.sponsor, recovered: sponsor });
+        }
+        if now > params.expiry {
+            return Err(VoucherError::Expired { expiry: params.expiry, now });
+        }
+        let sender = match record.signature {
+            Some(_) => ccr.recover_signer().map_err(|e| match e {
+                CcrError::MissingField(field) => VoucherError::MissingField(field),
+                _ => VoucherError::InvalidSignature,
+            })?,
+            None => record.from.ok_or(VoucherError::MissingField("from"))?,
+        };
+        if sender != params.sender {
+            return Err(VoucherError::SenderMismatch { expected: params.sender, actual: sender });
+        }
+        if record.chain_id != Some(params.chain_id) {
+            return Err(VoucherError::ChainIdMismatch { expected: params.chain_id, actual: record.chain_id });
+        }
+        if record.nonce != Some(params.nonce) {
+            return Err(VoucherError::NonceMismatch { expected: params.nonce, actual: record.nonce });
+        }
+        if record.to != params.to {
+            return Err(VoucherError::RequestMismatch("to"));
+        }
+        if primitives::keccak256(&record.input) != params.input_hash {
+            return Err(VoucherError::RequestMismatch("input"));
+        }
+        if payload_hash(ccr) != params.payload_hash {
+            return Err(VoucherError::RequestMismatch("confidential inputs"));
+        }
+        let gas = record.gas.ok_or(VoucherError::MissingField("gas"))?;
+        if gas > params.max_gas {
+            return Err(VoucherError::GasExceeded { max: params.max_gas, actual: gas });
+        }
+        if record.max_gas_price().unwrap_or_default() != 0 {
+            return Err(VoucherError::NonZeroGasPrice);
+        }
+        Ok(())
+    }
+
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoucherError {
+    MissingField(&'static str),
+    InvalidSignature,
+    SponsorMismatch { expected: Address, recovered: Address },
+    SenderMismatch { expected: Address, actual: Address },
+    ChainIdMismatch { expected: u64, actual: Option<u64> },
+    NonceMismatch { expected: u64, actual: Option<u64> },
+    GasExceeded { max: u128, actual: u128 },
+    NonZeroGasPrice,
+    /// Request field differing from the one the voucher was issued for.
+    RequestMismatch(&'static str),
+    Expired { expiry: u64, now: u64 },
+}
+
+impl std::fmt::Display for VoucherError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoucherError::MissingField(field) => write!(f, "Missing {field} field"),
+            VoucherError::InvalidSignature => write!(f, "Invalid signature"),
+            VoucherError::SponsorMismatch { expected, recovered } =>
+                write!(f, "Voucher signed by {recovered}, expected sponsor {expected}"),
+            VoucherError::SenderMismatch { expected, actual } =>
+                write!(f, "Voucher issued for sender {expected}, request sent by {actual}"),
+            VoucherError::ChainIdMismatch { expected, actual } =>
+                write!(f, "Voucher issued for chain {expected}, request has {actual:?}"),
+            VoucherError::NonceMismatch { expected, actual } =>
+                write!(f, "Voucher issued for nonce {expected}, request has {actual:?}"),
+            VoucherError::GasExceeded { max, actual } =>
+                write!(f, "Request gas {actual} exceeds voucher max gas {max}"),
+            VoucherError::NonZeroGasPrice => write!(f, "Sponsored request must have zero gas price"),
+            VoucherError::RequestMismatch(field) => write!(f, "Request {field} differs from the voucher's"),
+            VoucherError::Expired { expiry, now } => write!(f, "Voucher expired at {expiry} (now {now})"),
+        }
+    }
+}
+
+impl std::error::Error for VoucherError {}
+
+/// Keccak of the confidential inputs payload: the inputs themselves, or
+/// the payload of their envelope, so attaching the voucher (or another
+/// extension) doesn't change it.
+fn payload_hash(ccr: &ConfidentialComputeRequest) -> B256 {
+    match ConfidentialEnvelope::decode(&ccr.confidential_inputs) {
+        Ok(envelope) => primitives::keccak256(&envelope.payload),
+        Err(_) => primitives::keccak256(&ccr.confidential_inputs),
+    }
+}
+
+impl ConfidentialComputeRequest {
+
+    /// Turn the request into a gasless one: zero the gas price and attach
+    /// the sponsor's voucher to the confidential inputs envelope.
+    pub fn with_fee_voucher(mut self, voucher: &FeeVoucher) -> Self {
+        self.set_fee_voucher(voucher);
+        self
+    }
+
+    pub fn set_fee_voucher(&mut self, voucher: &FeeVoucher) {
+        let mut envelope = ConfidentialEnvelope::decode(&self.confidential_inputs)
+            .unwrap_or_else(|_| ConfidentialEnvelope::new(self.confidential_inputs.clone()));
+        envelope.set_extension(EnvelopeExtension::FEE_VOUCHER, voucher.encode());
+        self.confidential_compute_record.set_fees(Fees::Legacy { gas_price: 0 });
+        self.set_confidential_inputs(envelope.encode());
+    }
+
+    pub fn fee_voucher(&self) -> Option<FeeVoucher> {
+        let envelope = ConfidentialEnvelope::decode(&self.confidential_inputs).ok()?;
+        let data = envelope.extension(EnvelopeExtension::FEE_VOUCHER)?;
+        FeeVoucher::decode(data).ok()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use alloy::{
+        network::TxSigner,
+        signers::wallet::LocalWallet,
+    };
+    use super::*;
+    use super::super::ConfidentialComputeRecord;
+
+    fn gasless_request() -> ConfidentialComputeRequest {
+        let record = ConfidentialComputeRecord {
+            nonce: Some(0x22),
+            to: TxKind::Call(Address::from_str("0x780675d71ebe3d3ef05fae379063071147dd3aee").unwrap()),
+            gas: Some(0x0f4240),
+            gas_price: Some(0x3b9aca00),
+            kettle_address: Address::from_str("0x7d83e42b214b75bf1f3e57adc3415da573d97bff").ok(),
+            chain_id: Some(0x067932),
+            ..Default::default()
+        };
+        ConfidentialComputeRequest::new(record, Some(Bytes::from_static(b"intent")))
+    }
+
+    #[tokio::test]
+    async fn test_sponsored_request() {
+        let sender: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let sponsor: LocalWallet = "0x2222222222222222222222222222222222222222222222222222222222222222".parse().unwrap();
+        let expiry = 1_700_000_000;
+
+        let mut ccr = gasless_request();
+        let voucher = FeeVoucherParams::for_request(&ccr, sponsor.address(), sender.address(), expiry)
+            .unwrap()
+            .sign(&sponsor).await.unwrap();
+        ccr.set_fee_voucher(&voucher);
+        assert_eq!(ccr.confidential_compute_record.gas_price, Some(0));
+        assert_eq!(ccr.fee_voucher(), Some(voucher.clone()));
+        let envelope = ConfidentialEnvelope::decode(&ccr.confidential_inputs).unwrap();
+        assert_eq!(envelope.payload, Bytes::from_static(b"intent"));
+
+        let sig = TxSigner::sign_transaction(&sender, &mut ccr).await.unwrap();
+        ccr.confidential_compute_record.set_sig(sig);
+
+        assert_eq!(voucher.recover_sponsor(), Ok(sponsor.address()));
+        assert_eq!(voucher.verify(&ccr, expiry - 1), Ok(()));
+        assert_eq!(
+            voucher.verify(&ccr, expiry + 1),
+            Err(VoucherError::Expired { expiry, now: expiry + 1 })
+        );
+
+        let mut tampered = voucher.clone();
+        tampered.params.max_gas += 1;
+        assert!(matches!(tampered.verify(&ccr, expiry), Err(VoucherError::SponsorMismatch { .. })));
+
+        let mut trailing = voucher.encode().to_vec();
+        trailing.push(0);
+        assert!(FeeVoucher::decode(&trailing).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_voucher_binds_request() {
+        let sender: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let sponsor: LocalWallet = "0x2222222222222222222222222222222222222222222222222222222222222222".parse().unwrap();
+        let expiry = 1_700_000_000;
+        let voucher = FeeVoucherParams::for_request(&gasless_request(), sponsor.address(), sender.address(), expiry)
+            .unwrap()
+            .sign(&sponsor).await.unwrap();
+        let sign = |mut ccr: ConfidentialComputeRequest| {
+            let sender = sender.clone();
+            async move {
+                let sig = TxSigner::sign_transaction(&sender, &mut ccr).await.unwrap();
+                ccr.confidential_compute_record.set_sig(sig);
+                ccr
+            }
+        };
+        let sponsored = gasless_request().with_fee_voucher(&voucher);
+        assert_eq!(voucher.verify(&sign(sponsored.clone()).await, expiry), Ok(()));
+
+        let mut other_input = sponsored.clone();
+        other_input.confidential_compute_record.input = Bytes::from_static(b"drain()");
+        assert_eq!(voucher.verify(&sign(other_input).await, expiry), Err(VoucherError::RequestMismatch("input")));
+
+        let mut other_target = sponsored.clone();
+        other_target.confidential_compute_record.to = TxKind::Create;
+        assert_eq!(voucher.verify(&sign(other_target).await, expiry), Err(VoucherError::RequestMismatch("to")));
+
+        let other_inputs = ConfidentialComputeRequest::new(
+            sponsored.confidential_compute_record.clone(),
+            Some(Bytes::from_static(b"other intent")),
+        ).with_fee_voucher(&voucher);
+        assert_eq!(
+            voucher.verify(&sign(other_inputs).await, expiry),
+            Err(VoucherError::RequestMismatch("confidential inputs"))
+        );
+
+        let mut priced = sponsored;
+        priced.confidential_compute_record.set_fees(Fees::Legacy { gas_price: 1 });
+        assert_eq!(voucher.verify(&sign(priced).await, expiry), Err(VoucherError::NonZeroGasPrice));
+    }
+
+    #[tokio::test]
+    async fn test_voucher_clears_dynamic_fees() {
+        let sponsor: LocalWallet = "0x2222222222222222222222222222222222222222222222222222222222222222".parse().unwrap();
+        let mut ccr = gasless_request();
+        ccr.confidential_compute_record.set_fees(Fees::Dynamic { max_priority_fee_per_gas: 1, max_fee_per_gas: 0x3b9aca00 });
+        let voucher = FeeVoucherParams::for_request(&ccr, sponsor.address(), Address::ZERO, 1_700_000_000)
+            .unwrap()
+            .sign(&sponsor).await.unwrap();
+        ccr.set_fee_voucher(&voucher);
+        assert_eq!(ccr.confidential_compute_record.fees(), Some(Fees::Legacy { gas_price: 0 }));
+        assert_eq!(ccr.confidential_compute_record.max_gas_price(), Some(0));
+    }
+
+}
@@ -0,0 +1,277 @@
+use std::collections::{BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+use alloy::primitives::{hex, Address, Bytes, B256, U256};
+use super::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+
+/// Typed value substituted for a template placeholder. Scalars are ABI
+/// encoded into a 32 byte word, `Bytes` are inserted as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateValue {
+    Uint(U256),
+    Address(Address),
+    Bool(bool),
+    Word(B256),
+    Bytes(Bytes),
+}
+
+impl TemplateValue {
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            TemplateValue::Uint(value) => out.extend_from_slice(&value.to_be_bytes::<32>()),
+            TemplateValue::Address(address) => {
+                out.extend_from_slice(&[0u8; 12]);
+                out.extend_from_slice(address.as_slice());
+            }
+            TemplateValue::Bool(value) => out.extend_from_slice(&U256::from(*value as u8).to_be_bytes::<32>()),
+            TemplateValue::Word(word) => out.extend_from_slice(word.as_slice()),
+            TemplateValue::Bytes(bytes) => out.extend_from_slice(bytes),
+        }
+    }
+
+}
+
+impl From<U256> for TemplateValue {
+    fn from(value: U256) -> Self {
+        TemplateValue::Uint(value)
+    }
+}
+
+impl From<u64> for TemplateValue {
+    fn from(value: u64) -> Self {
+        TemplateValue::Uint(U256::from(value))
+    }
+}
+
+impl From<Address> for TemplateValue {
+    fn from(address: Address) -> Self {
+        TemplateValue::Address(address)
+    }
+}
+
+impl From<bool> for TemplateValue {
+    fn from(value: bool) -> Self {
+        TemplateValue::Bool(value)
+    }
+}
+
+impl From<B256> for TemplateValue {
+    fn from(word: B256) -> Self {
+        TemplateValue::Word(word)
+    }
+}
+
+impl From<Bytes> for TemplateValue {
+    fn from(bytes: Bytes) -> Self {
+        TemplateValue::Bytes(bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// Hex between placeholders not decodable.
+    InvalidHex { field: &'static str, error: String },
+    UnclosedPlaceholder { field: &'static str },
+    MissingValue(String),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateError::InvalidHex { field, error } => write!(f, "Invalid hex in {field} template: {error}"),
+            TemplateError::UnclosedPlaceholder { field } => write!(f, "Unclosed placeholder in {field} template"),
+            TemplateError::MissingValue(name) => write!(f, "No value for placeholder `{name}`"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(Vec<u8>),
+    Placeholder(String),
+}
+
+/// Hex string with `${name}` placeholders, parsed once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HexTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl HexTemplate {
+
+    fn parse(field: &'static str, source: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut rest = source.strip_prefix("0x").unwrap_or(source);
+        while !rest.is_empty() {
+            let (literal, tail) = rest.split_once("${").unwrap_or((rest, ""));
+            if !literal.is_empty() {
+                let bytes = hex::decode(literal)
+                    .map_err(|e| TemplateError::InvalidHex { field, error: e.to_string() })?;
+                segments.push(Segment::Literal(bytes));
+            }
+            if literal.len() == rest.len() {
+                break;
+            }
+            let (name, tail) = tail.split_once('}')
+                .ok_or(TemplateError::UnclosedPlaceholder { field })?;
+            segments.push(Segment::Placeholder(name.trim().to_string()));
+            rest = tail;
+        }
+        Ok(Self { source: source.to_string(), segments })
+    }
+
+    fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name.as_str()),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    fn render(&self, values: &HashMap<&str, TemplateValue>) -> Result<Bytes, TemplateError> {
+        let mut out = Vec::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(bytes) => out.extend_from_slice(bytes),
+                Segment::Placeholder(name) => values.get(name.as_str())
+                    .ok_or_else(|| TemplateError::MissingValue(name.clone()))?
+                    .write(&mut out),
+            }
+        }
+        Ok(out.into())
+    }
+
+}
+
+/// CCR with `${name}` placeholders in the calldata and confidential inputs,
+/// eg. `0x7ed2f2c0${amount}${deadline}`, substituted with typed values on
+/// `instantiate`. Templates are parsed once, so strategies can store them
+/// in their configs and instantiate them on triggers with little work.
+///
+/// ```ignore
+/// let template = CcrTemplate::new(record, "0x7ed2f2c0${amount}${deadline}", "0x")?;
+/// let ccr = template.instantiate([
+///     ("amount", U256::from(10).pow(U256::from(18)).into()),
+///     ("deadline", deadline.into()),
+/// ])?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawTemplate", into = "RawTemplate")]
+pub struct CcrTemplate {
+    /// Every field except the input, which comes from `calldata`.
+    record: ConfidentialComputeRecord,
+    calldata: HexTemplate,
+    confidential_inputs: HexTemplate,
+}
+
+impl CcrTemplate {
+
+    pub fn new(record: ConfidentialComputeRecord, calldata: &str, confidential_inputs: &str) -> Result<Self, TemplateError> {
+        Ok(Self {
+            record,
+            calldata: HexTemplate::parse("calldata", calldata)?,
+            confidential_inputs: HexTemplate::parse("confidential inputs", confidential_inputs)?,
+        })
+    }
+
+    pub fn record(&self) -> &ConfidentialComputeRecord {
+        &self.record
+    }
+
+    /// Names of all placeholders.
+    pub fn placeholders(&self) -> BTreeSet<&str> {
+        self.calldata.placeholders().chain(self.confidential_inputs.placeholders()).collect()
+    }
+
+    /// Unsigned request with every placeholder substituted.
+    pub fn instantiate<'a, I>(&self, values: I) -> Result<ConfidentialComputeRequest, TemplateError>
+        where I: IntoIterator<Item = (&'a str, TemplateValue)>
+    {
+        let values = values.into_iter().collect::<HashMap<_, _>>();
+        let mut record = self.record.clone();
+        record.input = self.calldata.render(&values)?;
+        let confidential_inputs = self.confidential_inputs.render(&values)?;
+        Ok(ConfidentialComputeRequest::new(record, Some(confidential_inputs)))
+    }
+
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTemplate {
+    record: ConfidentialComputeRecord,
+    calldata: String,
+    #[serde(default)]
+    confidential_inputs: String,
+}
+
+impl TryFrom<RawTemplate> for CcrTemplate {
+    type Error = TemplateError;
+
+    fn try_from(raw: RawTemplate) -> Result<Self, Self::Error> {
+        Self::new(raw.record, &raw.calldata, &raw.confidential_inputs)
+    }
+}
+
+impl From<CcrTemplate> for RawTemplate {
+    fn from(template: CcrTemplate) -> Self {
+        Self {
+            record: template.record,
+            calldata: template.calldata.source,
+            confidential_inputs: template.confidential_inputs.source,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use super::*;
+
+    #[test]
+    fn test_instantiate_template() {
+        let to = Address::from_str("0xc803334c79650708Daf3a3462AC4B48296b1352a").unwrap();
+        let record = ConfidentialComputeRecord::builder().to(to).gas(0x0f4240).build();
+        let template = CcrTemplate::new(record, "0x7ed2f2c0${amount}${ deadline }", "${recipient}ff${payload}").unwrap();
+        assert_eq!(template.placeholders().into_iter().collect::<Vec<_>>(), vec!["amount", "deadline", "payload", "recipient"]);
+
+        let ccr = template.instantiate([
+            ("amount", U256::from(5).into()),
+            ("deadline", 1_700_000_000u64.into()),
+            ("recipient", to.into()),
+            ("payload", Bytes::from_static(&[0xab, 0xcd]).into()),
+        ]).unwrap();
+        let record = &ccr.confidential_compute_record;
+        assert_eq!(record.input.len(), 4 + 2 * 32);
+        assert_eq!(record.input[35], 5);
+        assert_eq!(U256::from_be_slice(&record.input[36..]), U256::from(1_700_000_000u64));
+        assert_eq!(&ccr.confidential_inputs[12..32], to.as_slice());
+        assert_eq!(&ccr.confidential_inputs[32..], &[0xff, 0xab, 0xcd]);
+        assert!(ccr.validate_inputs_hash().is_ok());
+        assert_eq!(record.gas, Some(0x0f4240));
+
+        assert_eq!(
+            template.instantiate([("amount", U256::from(5).into())]),
+            Err(TemplateError::MissingValue("deadline".into()))
+        );
+    }
+
+    #[test]
+    fn test_template_serde() {
+        let template = CcrTemplate::new(ConfidentialComputeRecord::default(), "0x01${x}", "").unwrap();
+        let json = serde_json::to_value(&template).unwrap();
+        assert_eq!(json["calldata"], "0x01${x}");
+        assert_eq!(serde_json::from_value::<CcrTemplate>(json).unwrap(), template);
+
+        assert!(matches!(CcrTemplate::new(Default::default(), "0x0${x}", ""), Err(TemplateError::InvalidHex { .. })));
+        assert_eq!(
+            CcrTemplate::new(Default::default(), "0x01${x", ""),
+            Err(TemplateError::UnclosedPlaceholder { field: "calldata" })
+        );
+    }
+
+}
@@ -0,0 +1,195 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use alloy::{
+    primitives::{self, Address, Bytes, Signature, SignatureError, B256, U256},
+    signers::{Signer, Result as SignerResult},
+};
+use super::{
+    crecord::signature_to_vrs,
+    envelope::{ConfidentialEnvelope, EnvelopeExtension},
+    ConfidentialComputeRequest,
+};
+
+
+/// Domain prefix of the timestamp signing payload.
+pub const TIMESTAMP_DOMAIN: &[u8] = b"SUAVE_CCR_TIMESTAMP_V1";
+
+/// Sender-signed construction time of a CCR, carried in the confidential
+/// inputs envelope. Bound to chain id and nonce so it can't be replayed
+/// onto another request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedTimestamp {
+    /// Unix timestamp (seconds).
+    pub timestamp: u64,
+    pub signature: Signature,
+}
+
+#[derive(RlpEncodable)]
+struct TimestampHashParams {
+    timestamp: u64,
+    chain_id: u64,
+    nonce: u64,
+}
+
+#[derive(RlpEncodable, RlpDecodable)]
+struct SignedTimestampRLP {
+    timestamp: u64,
+    v: u8,
+    r: U256,
+    s: U256,
+}
+
+impl SignedTimestamp {
+
+    pub fn signing_hash(timestamp: u64, chain_id: u64, nonce: u64) -> B256 {
+        let mut buf = TIMESTAMP_DOMAIN.to_vec();
+        TimestampHashParams { timestamp, chain_id, nonce }.encode(&mut buf);
+        primitives::keccak256(buf)
+    }
+
+    pub async fn sign<S: Signer>(
+        signer: &S,
+        timestamp: u64,
+        chain_id: u64,
+        nonce: u64,
+    ) -> SignerResult<Self> {
+        let signature = signer.sign_hash(&Self::signing_hash(timestamp, chain_id, nonce)).await?;
+        Ok(Self { timestamp, signature })
+    }
+
+    pub fn recover_signer(&self, chain_id: u64, nonce: u64) -> Result<Address, SignatureError> {
+        let hash = Self::signing_hash(self.timestamp, chain_id, nonce);
+        self.signature.recover_address_from_prehash(&hash)
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let (v, r, s) = signature_to_vrs(self.signature);
+        let mut out = Vec::new();
+        SignedTimestampRLP { timestamp: self.timestamp, v, r, s }.encode(&mut out);
+        out.into()
+    }
+
+    pub fn decode(mut buf: &[u8]) -> alloy_rlp::Result<Self> {
+        let rlp = SignedTimestampRLP::decode(&mut buf)?;
+        let signature = Signature::from_rs_and_parity(rlp.r, rlp.s, rlp.v as u64)
+            .map_err(|_| alloy_rlp::Error::Custom("Invalid timestamp signature"))?;
+        Ok(Self { timestamp: rlp.timestamp, signature })
+    }
+
+}
+
+/// Rejects CCR construction when the local clock drifts too far from the
+/// chain's clock (latest block timestamp).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkewGuard {
+    pub max_skew: Duration,
+}
+
+impl Default for ClockSkewGuard {
+    fn default() -> Self {
+        Self { max_skew: Duration::from_secs(30) }
+    }
+}
+
+impl ClockSkewGuard {
+
+    pub fn new(max_skew: Duration) -> Self {
+        Self { max_skew }
+    }
+
+    /// Check local time against node time (both unix seconds) and return
+    /// the skew in seconds (positive if the local clock is ahead).
+    pub fn check(&self, local_time: u64, node_time: u64) -> Result<i64, ClockSkewError> {
+        let skew = local_time as i64 - node_time as i64;
+        if skew.unsigned_abs() > self.max_skew.as_secs() {
+            return Err(ClockSkewError { local_time, node_time, max_skew: self.max_skew });
+        }
+        Ok(skew)
+    }
+
+    pub fn check_now(&self, node_time: u64) -> Result<i64, ClockSkewError> {
+        self.check(unix_now(), node_time)
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkewError {
+    pub local_time: u64,
+    pub node_time: u64,
+    pub max_skew: Duration,
+}
+
+impl std::fmt::Display for ClockSkewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "Clock skew between local time {} and node time {} exceeds {}s",
+            self.local_time, self.node_time, self.max_skew.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for ClockSkewError {}
+
+/// Current unix time in seconds.
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl ConfidentialComputeRequest {
+
+    pub fn with_signed_timestamp(mut self, timestamp: &SignedTimestamp) -> Self {
+        self.set_signed_timestamp(timestamp);
+        self
+    }
+
+    pub fn set_signed_timestamp(&mut self, timestamp: &SignedTimestamp) {
+        let mut envelope = ConfidentialEnvelope::decode(&self.confidential_inputs)
+            .unwrap_or_else(|_| ConfidentialEnvelope::new(self.confidential_inputs.clone()));
+        envelope.set_extension(EnvelopeExtension::TIMESTAMP, timestamp.encode());
+        self.set_confidential_inputs(envelope.encode());
+    }
+
+    pub fn signed_timestamp(&self) -> Option<SignedTimestamp> {
+        let envelope = ConfidentialEnvelope::decode(&self.confidential_inputs).ok()?;
+        let data = envelope.extension(EnvelopeExtension::TIMESTAMP)?;
+        SignedTimestamp::decode(data).ok()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::wallet::LocalWallet;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_signed_timestamp() {
+        let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse().unwrap();
+        let (chain_id, nonce) = (0x067932, 0x22);
+        let timestamp = SignedTimestamp::sign(&wallet, 1_700_000_000, chain_id, nonce).await.unwrap();
+
+        let ccr = ConfidentialComputeRequest::default()
+            .with_confidential_inputs(Bytes::from_static(b"bid"))
+            .with_signed_timestamp(&timestamp);
+        let extracted = ccr.signed_timestamp().unwrap();
+        assert_eq!(extracted, timestamp);
+        assert_eq!(extracted.recover_signer(chain_id, nonce).unwrap(), wallet.address());
+        assert_ne!(extracted.recover_signer(chain_id, nonce + 1).unwrap(), wallet.address());
+    }
+
+    #[test]
+    fn test_clock_skew_guard() {
+        let guard = ClockSkewGuard::new(Duration::from_secs(10));
+        assert_eq!(guard.check(110, 100), Ok(10));
+        assert_eq!(guard.check(95, 100), Ok(-5));
+        assert_eq!(
+            guard.check(89, 100),
+            Err(ClockSkewError { local_time: 89, node_time: 100, max_skew: Duration::from_secs(10) })
+        );
+    }
+
+}
@@ -0,0 +1,107 @@
+use super::{consts::ProtocolLimits, CcrError, ConfidentialComputeRequest};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    ConfidentialInputsTooLarge { size: usize, max: usize },
+    CalldataTooLarge { size: usize, max: usize },
+    EncodedTooLarge { size: usize, max: usize },
+    IntrinsicGasTooLow { gas: u128, intrinsic: u64 },
+    /// Signed request missing fields needed to encode it.
+    Encoding(CcrError),
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitError::ConfidentialInputsTooLarge { size, max } =>
+                write!(f, "Confidential inputs of {size} bytes exceed the limit of {max} bytes"),
+            LimitError::CalldataTooLarge { size, max } =>
+                write!(f, "Calldata of {size} bytes exceeds the limit of {max} bytes"),
+            LimitError::EncodedTooLarge { size, max } =>
+                write!(f, "Encoded request of {size} bytes exceeds the limit of {max} bytes"),
+            LimitError::IntrinsicGasTooLow { gas, intrinsic } =>
+                write!(f, "Gas limit {gas} is below the intrinsic gas of {intrinsic}"),
+            LimitError::Encoding(err) => write!(f, "Can't size the encoded request: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl ConfidentialComputeRequest {
+
+    /// Check the request against protocol limits. The encoded size is only
+    /// checked once the request is signed.
+    pub fn validate_limits(&self, limits: &ProtocolLimits) -> Result<(), LimitError> {
+        let record = &self.confidential_compute_record;
+        let inputs_size = self.confidential_inputs.len();
+        if inputs_size > limits.max_confidential_inputs_size {
+            return Err(LimitError::ConfidentialInputsTooLarge { size: inputs_size, max: limits.max_confidential_inputs_size });
+        }
+        let calldata_size = record.input.len();
+        if calldata_size > limits.max_calldata_size {
+            return Err(LimitError::CalldataTooLarge { size: calldata_size, max: limits.max_calldata_size });
+        }
+        if let Some(gas) = record.gas {
+            let intrinsic = limits.intrinsic_gas(&record.input);
+            if gas < intrinsic as u128 {
+                return Err(LimitError::IntrinsicGasTooLow { gas, intrinsic });
+            }
+        }
+        if record.signature.is_some() {
+            let size = self.encoded_len().map_err(LimitError::Encoding)?;
+            if size > limits.max_tx_size {
+                return Err(LimitError::EncodedTooLarge { size, max: limits.max_tx_size });
+            }
+        }
+        Ok(())
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Bytes;
+    use super::*;
+    use super::super::{consts::ProtocolVersion, fixtures};
+
+    #[test]
+    fn test_validate_limits() {
+        let limits = ProtocolVersion::V1.limits();
+        for fixture in fixtures::load_fixtures() {
+            assert_eq!(fixture.request.validate_limits(&limits), Ok(()), "{}", fixture.name);
+        }
+
+        let mut ccr = fixtures::load_fixture("empty_inputs").unwrap().request;
+        ccr.confidential_compute_record.signature = None;
+        ccr.set_confidential_inputs(Bytes::from(vec![1; limits.max_confidential_inputs_size + 1]));
+        assert_eq!(
+            ccr.validate_limits(&limits),
+            Err(LimitError::ConfidentialInputsTooLarge { size: limits.max_confidential_inputs_size + 1, max: limits.max_confidential_inputs_size })
+        );
+
+        ccr.set_confidential_inputs(Bytes::new());
+        ccr.confidential_compute_record.gas = Some(21_000);
+        let intrinsic = limits.intrinsic_gas(&ccr.confidential_compute_record.input);
+        assert_eq!(ccr.validate_limits(&limits), Err(LimitError::IntrinsicGasTooLow { gas: 21_000, intrinsic }));
+    }
+
+    #[test]
+    fn test_validate_limits_incomplete() {
+        let limits = ProtocolVersion::V1.limits();
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let size = fixture.raw.len();
+        assert_eq!(
+            fixture.request.validate_limits(&ProtocolLimits { max_tx_size: size - 1, ..limits }),
+            Err(LimitError::EncodedTooLarge { size, max: size - 1 })
+        );
+
+        // Signed but missing a field: an error instead of a panic
+        let mut ccr = fixture.request;
+        ccr.confidential_compute_record.kettle_address = None;
+        assert_eq!(ccr.validate_limits(&limits), Err(LimitError::Encoding(CcrError::MissingField("kettle address"))));
+    }
+
+}
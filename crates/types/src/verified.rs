@@ -0,0 +1,128 @@
+use alloy::primitives::{Address, B256};
+use super::{
+    consts::ProtocolLimits,
+    CcrError,
+    ConfidentialComputeRequest,
+    InputsHashMismatch,
+    LimitError,
+};
+
+
+/// Request received by a kettle that passed every check of `verify`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedCcr {
+    ccr: ConfidentialComputeRequest,
+    sender: Address,
+    tx_hash: B256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    Decode(alloy_rlp::Error),
+    /// Request addressed to another kettle (or to none).
+    WrongKettle { expected: Address, actual: Option<Address> },
+    InputsHash(InputsHashMismatch),
+    Limits(LimitError),
+    Signature(CcrError),
+}
+
+impl std::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerificationError::Decode(err) => write!(f, "Invalid request encoding: {err}"),
+            VerificationError::WrongKettle { expected, actual: Some(actual) } =>
+                write!(f, "Request is for kettle {actual}, not {expected}"),
+            VerificationError::WrongKettle { expected, actual: None } =>
+                write!(f, "Request has no kettle address, expected {expected}"),
+            VerificationError::InputsHash(err) => write!(f, "{err}"),
+            VerificationError::Limits(err) => write!(f, "{err}"),
+            VerificationError::Signature(err) => write!(f, "Invalid signature: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+impl VerifiedCcr {
+
+    /// Decode a raw (0x43 prefixed) request received by `kettle` and check
+    /// it the way the kettle would before executing it: size limits (before
+    /// anything is copied), the kettle address, the confidential inputs
+    /// hash and the signature.
+    pub fn verify(raw: &[u8], kettle: Address, limits: &ProtocolLimits) -> Result<Self, VerificationError> {
+        if raw.len() > limits.max_tx_size {
+            return Err(VerificationError::Limits(LimitError::EncodedTooLarge { size: raw.len(), max: limits.max_tx_size }));
+        }
+        let ccr = ConfidentialComputeRequest::decode_2718_bounded(
+            raw,
+            limits.max_confidential_inputs_size,
+            limits.max_calldata_size,
+        ).map_err(VerificationError::Decode)?;
+        let actual = ccr.kettle_address();
+        if actual != Some(kettle) {
+            return Err(VerificationError::WrongKettle { expected: kettle, actual });
+        }
+        ccr.validate_inputs_hash().map_err(VerificationError::InputsHash)?;
+        ccr.validate_limits(limits).map_err(VerificationError::Limits)?;
+        let sender = ccr.recover_signer().map_err(VerificationError::Signature)?;
+        let tx_hash = ccr.tx_hash().map_err(VerificationError::Signature)?;
+        Ok(Self { ccr, sender, tx_hash })
+    }
+
+    pub fn request(&self) -> &ConfidentialComputeRequest {
+        &self.ccr
+    }
+
+    pub fn sender(&self) -> Address {
+        self.sender
+    }
+
+    pub fn tx_hash(&self) -> B256 {
+        self.tx_hash
+    }
+
+    pub fn into_inner(self) -> ConfidentialComputeRequest {
+        self.ccr
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloy::{eips::eip2718::Encodable2718, primitives::Bytes};
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_verify_received_ccr() {
+        let limits = ProtocolLimits::default();
+        for fixture in fixtures::load_fixtures() {
+            let kettle = fixture.request.kettle_address().unwrap();
+            let verified = VerifiedCcr::verify(&fixture.raw, kettle, &limits).unwrap();
+            assert_eq!(verified.sender(), fixture.signer, "{}", fixture.name);
+            assert_eq!(verified.tx_hash(), fixture.tx_hash, "{}", fixture.name);
+            assert_eq!(verified.request(), &fixture.request, "{}", fixture.name);
+        }
+
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let kettle = fixture.request.kettle_address().unwrap();
+        let other = Address::repeat_byte(0x03);
+        assert_eq!(
+            VerifiedCcr::verify(&fixture.raw, other, &limits),
+            Err(VerificationError::WrongKettle { expected: other, actual: Some(kettle) })
+        );
+
+        // Inputs swapped after signing
+        let mut tampered = fixture.request.clone();
+        tampered.confidential_inputs = Bytes::from_static(b"tampered");
+        let mut raw = Vec::new();
+        tampered.encode_2718(&mut raw);
+        assert!(matches!(VerifiedCcr::verify(&raw, kettle, &limits), Err(VerificationError::InputsHash(_))));
+
+        let small = ProtocolLimits { max_tx_size: fixture.raw.len() - 1, ..limits };
+        assert!(matches!(VerifiedCcr::verify(&fixture.raw, kettle, &small), Err(VerificationError::Limits(_))));
+        assert!(matches!(VerifiedCcr::verify(&fixture.raw[..10], kettle, &limits), Err(VerificationError::Decode(_))));
+    }
+
+}
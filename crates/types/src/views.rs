@@ -0,0 +1,96 @@
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+use alloy::primitives::{keccak256, FixedBytes};
+use super::{ConfidentialComputeRecord, ConfidentialComputeRequest};
+
+
+/// Serializes a request without its confidential inputs; only their hash
+/// (and length) is included. Use it when forwarding CCR metadata to logs,
+/// dashboards or other services that must never see the private payload.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicView<'a>(pub &'a ConfidentialComputeRequest);
+
+/// Serializes a request with everything, including confidential inputs.
+/// Same output as serializing the request itself, but makes the intent
+/// explicit at the call site.
+#[derive(Debug, Clone, Copy)]
+pub struct FullView<'a>(pub &'a ConfidentialComputeRequest);
+
+impl ConfidentialComputeRequest {
+
+    pub fn public_view(&self) -> PublicView<'_> {
+        PublicView(self)
+    }
+
+    pub fn full_view(&self) -> FullView<'_> {
+        FullView(self)
+    }
+
+}
+
+impl<'a> PublicView<'a> {
+
+    pub fn record(&self) -> &'a ConfidentialComputeRecord {
+        &self.0.confidential_compute_record
+    }
+
+    /// Hash of the carried inputs; the record's committed hash if set.
+    pub fn confidential_inputs_hash(&self) -> FixedBytes<32> {
+        self.0.confidential_compute_record.confidential_inputs_hash
+            .unwrap_or_else(|| keccak256(&self.0.confidential_inputs))
+    }
+
+}
+
+impl Serialize for PublicView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PublicView", 3)?;
+        state.serialize_field("confidentialComputeRecord", &self.0.confidential_compute_record)?;
+        state.serialize_field("confidentialInputsHash", &self.confidential_inputs_hash())?;
+        state.serialize_field("confidentialInputsLength", &self.0.confidential_inputs.len())?;
+        state.end()
+    }
+}
+
+impl Serialize for FullView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'a> From<&'a ConfidentialComputeRequest> for PublicView<'a> {
+    fn from(ccr: &'a ConfidentialComputeRequest) -> Self {
+        PublicView(ccr)
+    }
+}
+
+impl<'a> From<&'a ConfidentialComputeRequest> for FullView<'a> {
+    fn from(ccr: &'a ConfidentialComputeRequest) -> Self {
+        FullView(ccr)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::fixtures;
+
+    #[test]
+    fn test_public_view_redacts_inputs() {
+        let fixture = fixtures::load_fixture("bundle_inputs").unwrap();
+        let ccr = &fixture.request;
+        let inputs_hex = alloy::primitives::hex::encode(&ccr.confidential_inputs);
+
+        let public = serde_json::to_string(&ccr.public_view()).unwrap();
+        assert!(!public.contains(&inputs_hex));
+        assert!(!public.contains("\"confidentialInputs\""));
+        let value: serde_json::Value = serde_json::from_str(&public).unwrap();
+        assert_eq!(value["confidentialInputsHash"], serde_json::to_value(fixture.confidential_inputs_hash).unwrap());
+        assert_eq!(value["confidentialInputsLength"], ccr.confidential_inputs.len());
+
+        let full = serde_json::to_string(&ccr.full_view()).unwrap();
+        assert!(full.contains(&inputs_hex));
+        assert_eq!(full, serde_json::to_string(ccr).unwrap());
+    }
+
+}
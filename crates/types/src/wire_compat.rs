@@ -0,0 +1,183 @@
+//! Runtime check of the wire format against the bundled golden vectors.
+//!
+//! A dependency upgrade (alloy, the RLP crates, serde) can change the bytes
+//! a CCR encodes to without any compile error, and the node then rejects
+//! or misattributes the requests. Services can run `check` at startup and
+//! refuse to send traffic if it fails:
+//! ```ignore
+//! let report = wire_compat::check();
+//! if !report.is_ok() {
+//!     eyre::bail!("CCR wire format changed:\n{report}");
+//! }
+//! ```
+
+use alloy::{
+    eips::eip2718::Decodable2718,
+    primitives::keccak256,
+};
+use super::{
+    fixtures::{CcrFixture, FIXTURES},
+    ConfidentialComputeRequest,
+};
+
+
+/// Property of a vector compared against its golden value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireCheck {
+    /// The vector's JSON parses.
+    Json,
+    /// Encoding the request gives the golden raw bytes.
+    Encoding,
+    /// Decoding the golden raw bytes gives the request.
+    Decoding,
+    /// The request survives a JSON round trip.
+    JsonRoundTrip,
+    InputsHash,
+    SigningHash,
+    TxHash,
+    Signer,
+}
+
+impl std::fmt::Display for WireCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WireCheck::Json => "json",
+            WireCheck::Encoding => "encoding",
+            WireCheck::Decoding => "decoding",
+            WireCheck::JsonRoundTrip => "json round trip",
+            WireCheck::InputsHash => "inputs hash",
+            WireCheck::SigningHash => "signing hash",
+            WireCheck::TxHash => "tx hash",
+            WireCheck::Signer => "signer",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireMismatch {
+    pub vector: String,
+    pub check: WireCheck,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl std::fmt::Display for WireMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} mismatch, expected {} got {}", self.vector, self.check, self.expected, self.actual)
+    }
+}
+
+/// Outcome of `check`: the vectors run and what didn't match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WireCompatReport {
+    pub vectors: Vec<String>,
+    pub mismatches: Vec<WireMismatch>,
+}
+
+impl WireCompatReport {
+
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+}
+
+impl std::fmt::Display for WireCompatReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(f, "{} vectors match", self.vectors.len());
+        }
+        write!(f, "{} mismatches over {} vectors", self.mismatches.len(), self.vectors.len())?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n  {mismatch}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for WireCompatReport {}
+
+/// Run every bundled vector through encoding, decoding and hashing and
+/// compare with the golden outputs. Cheap (a few keccaks and signature
+/// recoveries) and never panics.
+pub fn check() -> WireCompatReport {
+    let mut report = WireCompatReport::default();
+    for (name, json) in FIXTURES {
+        report.vectors.push(name.to_string());
+        check_vector(name, json, &mut report.mismatches);
+    }
+    report
+}
+
+fn check_vector(name: &str, json: &str, mismatches: &mut Vec<WireMismatch>) {
+    let mut compare = |check, expected: String, actual: String| {
+        if expected != actual {
+            mismatches.push(WireMismatch { vector: name.to_string(), check, expected, actual });
+        }
+    };
+    let fixture: CcrFixture = match serde_json::from_str(json) {
+        Ok(fixture) => fixture,
+        Err(e) => return compare(WireCheck::Json, "valid vector".to_string(), e.to_string()),
+    };
+    let request = &fixture.request;
+
+    let encoded = request.rlp_encode().map(|encoded| encoded.to_string()).unwrap_or_else(|e| e.to_string());
+    compare(WireCheck::Encoding, fixture.raw.to_string(), encoded);
+
+    let decoded = ConfidentialComputeRequest::decode_2718(&mut fixture.raw.as_ref())
+        .map(|decoded| format!("{decoded:?}"))
+        .unwrap_or_else(|e| e.to_string());
+    compare(WireCheck::Decoding, format!("{request:?}"), decoded);
+
+    let round_trip = serde_json::to_string(request)
+        .and_then(|json| serde_json::from_str::<ConfidentialComputeRequest>(&json))
+        .map(|parsed| format!("{parsed:?}"))
+        .unwrap_or_else(|e| e.to_string());
+    compare(WireCheck::JsonRoundTrip, format!("{request:?}"), round_trip);
+
+    compare(
+        WireCheck::InputsHash,
+        fixture.confidential_inputs_hash.to_string(),
+        keccak256(&request.confidential_inputs).to_string(),
+    );
+    let outputs = [
+        (WireCheck::SigningHash, fixture.signing_hash.to_string(), request.signing_hash().map(|hash| hash.to_string())),
+        (WireCheck::TxHash, fixture.tx_hash.to_string(), request.tx_hash().map(|hash| hash.to_string())),
+        (WireCheck::Signer, fixture.signer.to_string(), request.recover_signer().map(|signer| signer.to_string())),
+    ];
+    for (check, expected, actual) in outputs {
+        compare(check, expected, actual.unwrap_or_else(|e| e.to_string()));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_passes() {
+        let report = check();
+        assert!(report.is_ok(), "{report}");
+        assert_eq!(report.vectors.len(), FIXTURES.len());
+    }
+
+    #[test]
+    fn test_check_reports_mismatches() {
+        let (name, json) = FIXTURES.iter().find(|(name, _)| *name == "empty_inputs").unwrap();
+        let mut mismatches = Vec::new();
+        // Golden signing hash no longer matching what the request hashes to
+        let tampered = json.replace(
+            "0xac5a1d3ae072706a93beb366ce34945c5071725aef4a4ece30a214740c121c06",
+            "0x0000000000000000000000000000000000000000000000000000000000000000",
+        );
+        check_vector(name, &tampered, &mut mismatches);
+        assert_eq!(mismatches.iter().map(|m| m.check).collect::<Vec<_>>(), vec![WireCheck::SigningHash]);
+
+        mismatches.clear();
+        check_vector(name, "{", &mut mismatches);
+        assert_eq!(mismatches[0].check, WireCheck::Json);
+    }
+
+}
@@ -21,7 +21,8 @@ async fn main() -> Result<()> {
     }
     let boracle_add = Address::from_str("0xc803334c79650708Daf3a3462AC4B48296b1352a")?;
     let pk = "0x1111111111111111111111111111111111111111111111111111111111111111";
-    let rpc_url = "https://rpc.rigil.suave.flashbots.net";
+    // The oracle is only deployed on Rigil
+    let rpc_url = suave_alloy::types::RIGIL.rpc_url;
     let ticker = String::from("ETHUSDT");
     let gas = 0x0f4240; // Estimate gas doesn't work well with MEVM
 
@@ -0,0 +1,166 @@
+use std::io::{self, BufRead, Write};
+use eyre::{bail, eyre, Result};
+use alloy::{
+    network::TxSigner,
+    primitives::{Address, Bytes, TxKind, U256},
+    signers::{wallet::LocalWallet, Signer},
+    transports::Transport,
+};
+use suave_alloy::prelude::*;
+
+
+const HELP: &str = "\
+set <field> <value>   nonce, gas, gas_price, to (address or `create`), value, input, kettle, chain_id, inputs
+fill                  nonce, chain id and kettle from the node
+show                  preview and signing hash
+key add <private key> add a signing key
+key <n>               sign with the n-th key
+keys                  list the keys
+sign                  sign and show the raw request and its hash
+send                  sign and submit
+help, quit";
+
+/// Interactive session for building CCRs against a kettle, one field at a
+/// time. Run with `cargo run --example ccr_repl -- [rpc url]` (Rigil by
+/// default).
+#[tokio::main]
+async fn main() -> Result<()> {
+    let rpc_url = std::env::args().nth(1).unwrap_or_else(|| suave_alloy::types::RIGIL.rpc_url.to_string());
+    let mut session = Session {
+        provider: SuaveProvider::try_from(rpc_url.as_str())?,
+        record: ConfidentialComputeRecord { gas: Some(0x0f4240), gas_price: Some(0x3b9aca00), ..Default::default() },
+        inputs: Bytes::new(),
+        keys: Vec::new(),
+        key: 0,
+    };
+    println!("Connected to {rpc_url}, `help` lists the commands");
+
+    let stdin = io::stdin();
+    loop {
+        print!("ccr> ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        let args = line.split_whitespace().collect::<Vec<_>>();
+        match args.as_slice() {
+            [] => continue,
+            ["quit" | "exit"] => break,
+            ["help"] => println!("{HELP}"),
+            args => if let Err(e) = session.run(args).await {
+                println!("error: {e}");
+            },
+        }
+    }
+    Ok(())
+}
+
+struct Session<T> {
+    provider: SuaveProvider<T>,
+    record: ConfidentialComputeRecord,
+    inputs: Bytes,
+    keys: Vec<LocalWallet>,
+    key: usize,
+}
+
+impl<T> Session<T>
+    where T: Transport + Clone
+{
+
+    async fn run(&mut self, args: &[&str]) -> Result<()> {
+        match args {
+            ["set", field, value] => self.set(field, value)?,
+            ["fill"] => {
+                self.record.kettle_address = Some(self.provider.kettle_address().await?);
+                self.record.chain_id = Some(self.provider.cached_chain_id().await?);
+                if let Some(wallet) = self.keys.get(self.key) {
+                    self.record.nonce = Some(self.provider.next_nonce(wallet.address()).await?);
+                }
+                self.show();
+            }
+            ["show"] => self.show(),
+            ["key", "add", key] => {
+                self.keys.push(key.parse()?);
+                self.key = self.keys.len() - 1;
+                println!("Signing with {}", self.keys[self.key].address());
+            }
+            ["key", n] => {
+                let n: usize = n.parse()?;
+                let wallet = self.keys.get(n).ok_or_else(|| eyre!("No key {n}"))?;
+                self.key = n;
+                println!("Signing with {}", wallet.address());
+            }
+            ["keys"] => for (n, wallet) in self.keys.iter().enumerate() {
+                let marker = if n == self.key { "*" } else { " " };
+                println!("{marker} {n}: {}", wallet.address());
+            },
+            ["sign"] => {
+                let signed = self.sign().await?;
+                println!("raw:      {}", signed.rlp_encode()?);
+                println!("tx hash:  {}", signed.tx_hash()?);
+            }
+            ["send"] => {
+                let signed = self.sign().await?;
+                let tx_hash = self.provider.send_raw_ccr(&signed.rlp_encode()?).await?;
+                println!("Sent {tx_hash}");
+                if let Some(url) = signed.explorer_url() {
+                    println!("{url}");
+                }
+                // The node expects the next nonce next time
+                self.record.nonce = self.record.nonce.map(|nonce| nonce + 1);
+            }
+            _ => bail!("Unknown command, see `help`"),
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, field: &str, value: &str) -> Result<()> {
+        let record = &mut self.record;
+        match field {
+            "nonce" => record.nonce = Some(u64::try_from(parse_number(value)?)?),
+            "gas" => record.gas = Some(parse_number(value)?),
+            "gas_price" => record.gas_price = Some(parse_number(value)?),
+            "to" if value == "create" => record.to = TxKind::Create,
+            "to" => record.to = TxKind::Call(value.parse::<Address>()?),
+            "value" => record.value = value.parse::<U256>()?,
+            "input" => record.input = value.parse()?,
+            "kettle" => record.kettle_address = Some(value.parse()?),
+            "chain_id" => record.chain_id = Some(u64::try_from(parse_number(value)?)?),
+            "inputs" => self.inputs = value.parse()?,
+            _ => bail!("Unknown field {field}"),
+        }
+        Ok(())
+    }
+
+    fn request(&self) -> ConfidentialComputeRequest {
+        ConfidentialComputeRequest::new(self.record.clone(), Some(self.inputs.clone()))
+    }
+
+    fn show(&self) {
+        let request = self.request();
+        println!("{}", request.preview(None));
+        // The encoding needs the signature, see `sign`
+        match request.signing_hash() {
+            Ok(signing_hash) => println!("signing hash: {signing_hash}"),
+            Err(e) => println!("incomplete: {e}"),
+        }
+    }
+
+    async fn sign(&self) -> Result<ConfidentialComputeRequest> {
+        let wallet = self.keys.get(self.key).ok_or_else(|| eyre!("No signing key, see `key add`"))?;
+        let mut request = self.request();
+        let signature = wallet.sign_transaction(&mut request).await?;
+        request.confidential_compute_record.set_sig(signature);
+        Ok(request)
+    }
+
+}
+
+/// Decimal or 0x prefixed hex.
+fn parse_number(value: &str) -> Result<u128> {
+    Ok(match value.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16)?,
+        None => value.parse()?,
+    })
+}
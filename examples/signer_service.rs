@@ -0,0 +1,36 @@
+use std::net::SocketAddr;
+use eyre::Result;
+use alloy::{
+    primitives::{Address, U256},
+    signers::wallet::LocalWallet,
+};
+use suave_alloy::prelude::*;
+
+
+/// Run with `cargo run --example signer_service --features service`, then:
+///
+/// curl -X POST localhost:8080/tenants/searcher/sign \
+///     -H 'Authorization: Bearer searcher-key' -H 'Content-Type: application/json' \
+///     -d '{"to":"0xc803334c79650708Daf3a3462AC4B48296b1352a","gas":"0xf4240"}'
+#[tokio::main]
+async fn main() -> Result<()> {
+    let provider = SuaveProvider::try_from("https://rpc.rigil.suave.flashbots.net")?;
+    let searcher: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?;
+    let monitor: LocalWallet = "0x2222222222222222222222222222222222222222222222222222222222222222".parse()?;
+
+    // The searcher may only call its SUAPP; the monitor only sends free calls
+    let suapp: Address = "0xc803334c79650708Daf3a3462AC4B48296b1352a".parse()?;
+    let searcher_policy = TenantPolicy::default()
+        .with_allowed_target(suapp)
+        .with_max_gas(0x0f4240);
+    let monitor_policy = TenantPolicy::default().with_max_value(U256::ZERO);
+
+    let service = SignerService::new(provider)
+        .with_tenant("searcher", "searcher-key", SuaveSigner::new(searcher), searcher_policy)
+        .with_tenant("monitor", "monitor-key", SuaveSigner::new(monitor), monitor_policy);
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
+    println!("Listening on {addr}");
+    service.serve(addr).await?;
+    Ok(())
+}
@@ -19,12 +19,15 @@ async fn main() -> Result<()> {
     let gas = 0x0f4240; // Estimate gas doesn't work well with MEVM
 
     // Create SUAVE signer-provider
-    let rpc_url = "https://rpc.rigil.suave.flashbots.net";
+    // The oracle is only deployed on Rigil
+    let rpc_url = suave_alloy::types::RIGIL.rpc_url;
     let wallet: LocalWallet = "0x1111111111111111111111111111111111111111111111111111111111111111".parse()?; 
+    let signer = SuaveSigner::new(wallet);
     let provider = ProviderBuilder::<_, _, SuaveNetwork>::default()
         .with_recommended_fillers()
         .filler(KettleFiller::default())
-        .signer(SuaveSigner::new(wallet))
+        .filler(SenderFiller::from(&signer))
+        .signer(signer)
         .on_provider(SuaveProvider::try_from(rpc_url)?);
 
     // Create a confidential-compute-request 
@@ -37,6 +40,9 @@ async fn main() -> Result<()> {
     // Send CCR
     let result = provider.send_transaction(ccr).await?;
     let tx_hash = B256::from_slice(&result.tx_hash().to_vec());
+    if let Some(url) = explorer_url(RIGIL.chain_id, tx_hash) {
+        println!("Explorer: {url}");
+    }
 
     // Obtain CCR Response with record and compute-result
     let tx_response = provider.get_transaction_by_hash(tx_hash).await?;